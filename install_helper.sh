@@ -0,0 +1,39 @@
+#!/usr/bin/env bash
+
+# SPDX-FileCopyrightText: 2022 Deren Vural
+# SPDX-License-Identifier: GPL-3.0-or-later
+
+#####
+# Name:
+# install_helper.sh
+#
+# Description:
+# Builds and installs the privileged helper and it's polkit policy to system (needs sudo)
+#
+# Made:
+# 24/01/2023
+#
+# Made by:
+# Deren Vural
+#
+# Notes:
+# HELPER_DIR must match `helper::HELPER_PATH` and the path in the policy file
+#####
+
+# Variables
+HELPER_DIR="/usr/local/bin"
+HELPER_FILE="gtk4-nvidia-monitor-rust-helper"
+POLICY_DIR="/usr/share/polkit-1/actions"
+POLICY_FILE="com.gtk_d.NvidiaMonitorRust.policy"
+
+# Build helper
+echo "Building $HELPER_FILE.."
+cargo build --release --bin "$HELPER_FILE"
+
+# Copy helper across (owned by root, so it can't be swapped out)
+echo "Installing $HELPER_FILE to $HELPER_DIR.."
+sudo install -o root -g root -m 0755 "./target/release/$HELPER_FILE" "$HELPER_DIR/$HELPER_FILE"
+
+# Copy policy across
+echo "Installing $POLICY_FILE to $POLICY_DIR.."
+sudo install -o root -g root -m 0644 "./src/$POLICY_FILE" "$POLICY_DIR/$POLICY_FILE"
@@ -0,0 +1,100 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * gtk4-nvidia-monitor-rust-helper
+ *
+ * Description:
+ * Privileged helper for changing GPU settings that need root (run using pkexec)
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only accepts the operations in `helper::HelperOperation`, anything else is refused before running.
+ * The helper module has no dependencies, keep it that way
+ */
+// Declare module
+// Included directly (std only) rather than linking the app, so no GTK etc. is loaded as root
+#[allow(dead_code)]
+#[path = "../helper/mod.rs"]
+mod helper;
+
+// Imports
+use helper::{parse_power_limit, HelperOperation, NVIDIA_SMI_PATH};
+use std::process::{exit, Command, Output};
+
+/**
+ * Name:
+ * main
+ *
+ * Description:
+ * Validate the requested operation and run it with nvidia-smi
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Exit status is 2 for a refused operation, otherwise that of nvidia-smi
+ * Resetting the power limit looks up the default first, so the app never passes a value for it
+ */
+fn main() {
+    // Parse & validate arguments
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let operation: HelperOperation = match HelperOperation::from_args(&args) {
+        Ok(valid_operation) => valid_operation,
+        Err(err) => {
+            eprintln!("ERROR: {}", err);
+            exit(2);
+        }
+    };
+
+    // Swap a power limit reset for setting the default power limit
+    let operation: HelperOperation = match operation {
+        HelperOperation::ResetPowerLimit { uuid } => {
+            let query: HelperOperation = HelperOperation::ResetPowerLimit { uuid: uuid.clone() };
+            let output: Output = match Command::new(NVIDIA_SMI_PATH)
+                .args(query.to_nvidia_smi_args())
+                .env_clear()
+                .output()
+            {
+                Ok(valid_output) if valid_output.status.success() => valid_output,
+                Ok(valid_output) => exit(valid_output.status.code().unwrap_or(1)),
+                Err(err) => {
+                    eprintln!("ERROR: Cannot run nvidia-smi: {}", err);
+                    exit(1);
+                }
+            };
+
+            match parse_power_limit(&String::from_utf8_lossy(&output.stdout)) {
+                Ok(watts) => HelperOperation::SetPowerLimit { uuid, watts },
+                Err(err) => {
+                    eprintln!("ERROR: {}", err);
+                    exit(1);
+                }
+            }
+        }
+        other => other,
+    };
+
+    // Run with a clean environment
+    match Command::new(NVIDIA_SMI_PATH)
+        .args(operation.to_nvidia_smi_args())
+        .env_clear()
+        .status()
+    {
+        Ok(status) if status.success() => {}
+        Ok(status) => exit(status.code().unwrap_or(1)),
+        Err(err) => {
+            eprintln!("ERROR: Cannot run nvidia-smi: {}", err);
+            exit(1);
+        }
+    }
+}
@@ -22,6 +22,7 @@ extern crate gtk4_nvidia_monitor_rust;
 
 // Imports
 use adwaita::{prelude::ApplicationExtManual, Application};
+use gtk4_nvidia_monitor_rust::headless;
 
 /**
  * Name:
@@ -37,9 +38,15 @@ use adwaita::{prelude::ApplicationExtManual, Application};
  * Deren Vural
  *
  * Notes:
- *
+ * Headless mode (`--headless`) skips GTK entirely, so works without a display
  */
 fn main() {
+    // Run without a window if asked to
+    let args: Vec<String> = std::env::args().collect();
+    if headless::is_headless(&args) {
+        std::process::exit(headless::main(&args));
+    }
+
     // Intialise GTK & Create a new application
     let app: Application = gtk4_nvidia_monitor_rust::create_app();
 
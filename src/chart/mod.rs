@@ -0,0 +1,605 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Layout of recorded history for drawing as a line chart (i.e. scaling, gaps & axis ticks)
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Drawing itself is done by `history_graph`, this only works out where things go
+ */
+// Imports
+use std::collections::VecDeque;
+
+// Modules
+use crate::history::HistorySample;
+
+/// Structure for storing the area (px) a chart is drawn in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartArea {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Structure for storing the time (ms) & value ranges shown by a chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChartRange {
+    pub start: u64,
+    pub end: u64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Structure for storing the times (ms) a chart can be zoomed/panned over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub start: u64,
+    pub end: u64,
+}
+
+/// Steps (ms) between time labels, smallest first
+const TIME_STEPS: [u64; 17] = [
+    1_000, 2_000, 5_000, 10_000, 15_000, 30_000, 60_000, 120_000, 300_000, 600_000, 900_000,
+    1_800_000, 3_600_000, 7_200_000, 10_800_000, 21_600_000, 43_200_000,
+];
+
+/// Structure for storing the lowest & highest sample in a single column (px) of a chart
+struct Bucket {
+    column: u64,
+    low: (u64, f64),
+    high: (u64, f64),
+}
+
+/**
+ * Name:
+ * ChartRange
+ *
+ * Description:
+ * Trait shared by all chart ranges
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ChartRange {
+    /**
+     * Name:
+     * position
+     *
+     * Description:
+     * Convert a timestamp & value to a position (px) in the chart area
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn position(&self, area: &ChartArea, timestamp: u64, value: f64) -> (f64, f64) {
+        let duration: f64 = (self.end.saturating_sub(self.start)).max(1) as f64;
+        let span: f64 = match self.max - self.min {
+            span if span > 0.0 => span,
+            _ => 1.0,
+        };
+
+        (
+            area.x + (timestamp.saturating_sub(self.start)) as f64 / duration * area.width,
+            area.y + area.height - (value - self.min) / span * area.height,
+        )
+    }
+
+    /**
+     * Name:
+     * timestamp_at
+     *
+     * Description:
+     * Convert a position (px) along the chart area to a timestamp
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Positions outside of the chart are clamped to it's edges
+     */
+    pub fn timestamp_at(&self, area: &ChartArea, x: f64) -> u64 {
+        let fraction: f64 = match area.width {
+            width if width > 0.0 => ((x - area.x) / width).clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+
+        self.start + (fraction * (self.end.saturating_sub(self.start)) as f64).round() as u64
+    }
+}
+
+/**
+ * Name:
+ * TimeWindow
+ *
+ * Description:
+ * Trait shared by all time windows
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl TimeWindow {
+    /**
+     * Name:
+     * span
+     *
+     * Description:
+     * Time (ms) covered by this window
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn span(&self) -> u64 {
+        self.end.saturating_sub(self.start)
+    }
+
+    /**
+     * Name:
+     * zoom
+     *
+     * Description:
+     * Scale this window around a timestamp (i.e. under the mouse), a factor below 1 zooming in
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The anchor stays at the same place on the chart. The result never covers less than `min_span` (ms),
+     * or more than (or outside of) the limits
+     */
+    pub fn zoom(&self, anchor: u64, factor: f64, limits: &TimeWindow, min_span: u64) -> TimeWindow {
+        let span: u64 = self.span().max(1);
+        let new_span: u64 = ((span as f64 * factor).round() as u64)
+            .max(min_span)
+            .min(limits.span());
+
+        // Keep anchor at the same fraction of the window
+        let anchor: u64 = anchor.max(self.start).min(self.end);
+        let before: f64 = (anchor - self.start) as f64 / span as f64;
+        let start: u64 = anchor.saturating_sub((before * new_span as f64).round() as u64);
+
+        TimeWindow {
+            start,
+            end: start + new_span,
+        }
+        .within(limits)
+    }
+
+    /**
+     * Name:
+     * pan
+     *
+     * Description:
+     * Move this window by an offset (ms), negative moving back in time
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Stops at the limits, rather than shrinking
+     */
+    pub fn pan(&self, offset: i64, limits: &TimeWindow) -> TimeWindow {
+        let start: u64 = match offset {
+            0.. => self.start.saturating_add(offset as u64),
+            _ => self.start.saturating_sub(offset.unsigned_abs()),
+        };
+
+        TimeWindow {
+            start,
+            end: start + self.span(),
+        }
+        .within(limits)
+    }
+
+    /**
+     * Name:
+     * within
+     *
+     * Description:
+     * Move this window inside the limits, shrinking it only if it's wider than them
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn within(&self, limits: &TimeWindow) -> TimeWindow {
+        let span: u64 = self.span().min(limits.span());
+        let start: u64 = self.start.max(limits.start).min(limits.end - span);
+
+        TimeWindow {
+            start,
+            end: start + span,
+        }
+    }
+}
+
+/**
+ * Name:
+ * Bucket
+ *
+ * Description:
+ * Trait shared by all buckets
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Bucket {
+    /**
+     * Name:
+     * add
+     *
+     * Description:
+     * Add a sample to this column
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn add(&mut self, timestamp: u64, value: f64) {
+        if value < self.low.1 {
+            self.low = (timestamp, value);
+        }
+        if value > self.high.1 {
+            self.high = (timestamp, value);
+        }
+    }
+
+    /**
+     * Name:
+     * points
+     *
+     * Description:
+     * Lowest & highest sample of this column, in the order they were recorded
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn points(&self) -> Vec<(u64, f64)> {
+        if self.low.0 == self.high.0 {
+            vec![self.low]
+        } else if self.low.0 < self.high.0 {
+            vec![self.low, self.high]
+        } else {
+            vec![self.high, self.low]
+        }
+    }
+}
+
+/**
+ * Name:
+ * value_range
+ *
+ * Description:
+ * Find the lowest & highest value recorded between two timestamps
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A flat line is given some room above & below, so it isn't drawn along the edge of the chart.
+ * Samples are expected to be in the order they were recorded
+ */
+pub fn value_range(samples: &VecDeque<HistorySample>, start: u64, end: u64) -> Option<(f64, f64)> {
+    let mut range: Option<(f64, f64)> = None;
+    let first: usize = samples.partition_point(|sample| sample.timestamp < start);
+    for sample in samples
+        .range(first..)
+        .take_while(|sample| sample.timestamp <= end)
+    {
+        if let Some(value) = sample.value {
+            range = match range {
+                Some((min, max)) => Some((min.min(value), max.max(value))),
+                None => Some((value, value)),
+            };
+        }
+    }
+
+    match range {
+        Some((min, max)) if min == max => Some((min - 1.0, max + 1.0)),
+        _ => range,
+    }
+}
+
+/**
+ * Name:
+ * build_segments
+ *
+ * Description:
+ * Convert samples into lines (px) to draw, broken wherever there is a gap in the readings
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A gap is a sample without a value (i.e. unsupported or failed), or no samples for longer than `max_gap` (ms).
+ * Only the lowest & highest sample of each column (px) is kept, so hours of history stay quick to draw
+ */
+pub fn build_segments(
+    samples: &VecDeque<HistorySample>,
+    range: &ChartRange,
+    area: &ChartArea,
+    max_gap: u64,
+) -> Vec<Vec<(f64, f64)>> {
+    let columns: f64 = area.width.max(1.0).floor();
+    let duration: f64 = (range.end.saturating_sub(range.start)).max(1) as f64;
+
+    let mut segments: Vec<Vec<(f64, f64)>> = vec![];
+    let mut current: Vec<(f64, f64)> = vec![];
+    let mut bucket: Option<Bucket> = None;
+    let mut last_timestamp: Option<u64> = None;
+
+    // Add the points of a finished column to the current line
+    let flush = |bucket: &mut Option<Bucket>, current: &mut Vec<(f64, f64)>| {
+        if let Some(finished) = bucket.take() {
+            for (timestamp, value) in finished.points() {
+                current.push(range.position(area, timestamp, value));
+            }
+        }
+    };
+
+    for sample in samples
+        .iter()
+        .filter(|sample| sample.timestamp >= range.start && sample.timestamp <= range.end)
+    {
+        // Check for gaps
+        let gap: bool = match (sample.value, last_timestamp) {
+            (None, _) => true,
+            (Some(_), Some(last)) => sample.timestamp.saturating_sub(last) > max_gap,
+            (Some(_), None) => false,
+        };
+        if gap {
+            flush(&mut bucket, &mut current);
+            if !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+        }
+
+        match sample.value {
+            Some(value) => {
+                let column: u64 =
+                    ((sample.timestamp - range.start) as f64 / duration * columns).floor() as u64;
+                match &mut bucket {
+                    Some(existing) if existing.column == column => {
+                        existing.add(sample.timestamp, value)
+                    }
+                    _ => {
+                        flush(&mut bucket, &mut current);
+                        bucket = Some(Bucket {
+                            column,
+                            low: (sample.timestamp, value),
+                            high: (sample.timestamp, value),
+                        });
+                    }
+                }
+                last_timestamp = Some(sample.timestamp);
+            }
+            None => last_timestamp = None,
+        }
+    }
+    flush(&mut bucket, &mut current);
+    if !current.is_empty() {
+        segments.push(current);
+    }
+
+    segments
+}
+
+/**
+ * Name:
+ * axis_ticks
+ *
+ * Description:
+ * Pick roughly `count` evenly spaced, round values (i.e. 0, 20, 40..) between min & max
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Steps are always 1, 2 or 5 times a power of 10
+ */
+pub fn axis_ticks(min: f64, max: f64, count: u32) -> Vec<f64> {
+    if max <= min || count == 0 {
+        return vec![];
+    }
+
+    // Find a round step size, as a multiple of a power of 10
+    let raw_step: f64 = (max - min) / count as f64;
+    let exponent: i32 = raw_step.log10().floor() as i32;
+    let multiple: f64 = match raw_step / 10_f64.powi(exponent) {
+        normalised if normalised <= 1.0 => 1.0,
+        normalised if normalised <= 2.0 => 2.0,
+        normalised if normalised <= 5.0 => 5.0,
+        _ => 10.0,
+    };
+
+    // Scale by dividing for small steps, to avoid rounding errors (i.e. 0.30000000000000004)
+    let to_value = |index: i64| -> f64 {
+        match exponent {
+            0.. => index as f64 * multiple * 10_f64.powi(exponent),
+            _ => index as f64 * multiple / 10_f64.powi(-exponent),
+        }
+    };
+    let step: f64 = to_value(1);
+
+    let first: i64 = (min / step - 1e-9).ceil() as i64;
+    let last: i64 = (max / step + 1e-9).floor() as i64;
+
+    (first..=last).map(to_value).collect()
+}
+
+/**
+ * Name:
+ * time_ticks
+ *
+ * Description:
+ * Pick roughly `count` evenly spaced, round times (i.e. every 5 minutes) between two timestamps (ms)
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Times are aligned to the step (i.e. on the minute), steps longer than the largest are whole days
+ */
+pub fn time_ticks(start: u64, end: u64, count: u32) -> Vec<u64> {
+    if end <= start || count == 0 {
+        return vec![];
+    }
+
+    let raw_step: u64 = (end - start) / count as u64;
+    let step: u64 = match TIME_STEPS.iter().find(|step| **step >= raw_step) {
+        Some(step) => *step,
+        None => raw_step.div_ceil(86_400_000) * 86_400_000,
+    };
+
+    let first: u64 = start.div_ceil(step) * step;
+    (0..)
+        .map(|index| first + index * step)
+        .take_while(|tick| *tick <= end)
+        .collect()
+}
+
+/**
+ * Name:
+ * nearest_sample
+ *
+ * Description:
+ * Find the sample recorded closest to a timestamp (i.e. under the mouse)
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Samples are expected to be in the order they were recorded
+ */
+pub fn nearest_sample(samples: &VecDeque<HistorySample>, timestamp: u64) -> Option<HistorySample> {
+    let index: usize = samples.partition_point(|sample| sample.timestamp < timestamp);
+    let after: Option<&HistorySample> = samples.get(index);
+    let before: Option<&HistorySample> = match index {
+        0 => None,
+        _ => samples.get(index - 1),
+    };
+
+    match (before, after) {
+        (Some(early), Some(late)) => {
+            if timestamp - early.timestamp <= late.timestamp - timestamp {
+                Some(*early)
+            } else {
+                Some(*late)
+            }
+        }
+        (Some(sample), None) | (None, Some(sample)) => Some(*sample),
+        (None, None) => None,
+    }
+}
+
+/**
+ * Name:
+ * format_age
+ *
+ * Description:
+ * Format how long ago (ms) something was recorded (i.e. `90s` -> `1m 30s`)
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only the two largest units are shown
+ */
+pub fn format_age(age: u64) -> String {
+    let seconds: u64 = age / 1000;
+    match seconds {
+        0 => String::from("now"),
+        1..=59 => format!("{}s", seconds),
+        60..=3599 => match seconds % 60 {
+            0 => format!("{}m", seconds / 60),
+            remainder => format!("{}m {}s", seconds / 60, remainder),
+        },
+        _ => match (seconds % 3600) / 60 {
+            0 => format!("{}h", seconds / 3600),
+            remainder => format!("{}h {}m", seconds / 3600, remainder),
+        },
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Interface for running commands that change GPU state (i.e. fan speed)
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Anything that controls the GPU goes through a `CommandRunner`, so the logic deciding
+ * *what* to run can be tested without any hardware (see `subprocess::SubprocessRunner`)
+ */
+/// Trait for anything able to run a command (given as a list of arguments)
+pub trait CommandRunner {
+    /**
+     * Name:
+     * run
+     *
+     * Description:
+     * Run a command, returning an error message if it failed
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The first argument is the program, i.e. `["nvidia-settings", "-a", "..."]`
+     */
+    fn run(&self, argv: &[String]) -> Result<(), String>;
+}
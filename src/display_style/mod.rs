@@ -0,0 +1,505 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * How a property is displayed in a view (i.e. plain text, level bar, gauge or sparkline) and it's options
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Stored as an optional 5th field of each viewcomponentconfig (UUID:VIEW_TITLE:POSITION:property:STYLE),
+ * where STYLE looks like `bar;min=0;max=100;colour=#3584e4`. Entries without it use the default style
+ */
+// Constants
+/// Every way a property can be displayed, in the order they're offered
+pub const DISPLAY_KINDS: [DisplayKind; 4] = [
+    DisplayKind::Text,
+    DisplayKind::Bar,
+    DisplayKind::Gauge,
+    DisplayKind::Sparkline,
+];
+
+/// Ways a property can be displayed, the reading is always shown as text alongside
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayKind {
+    Text,
+    Bar,
+    Gauge,
+    #[default]
+    Sparkline,
+}
+
+/// Structure for storing how a property is displayed, options not set are worked out from it's readings
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DisplayStyle {
+    pub kind: DisplayKind,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub colour: Option<String>,
+}
+
+/// Structure for storing a single (parsed) viewcomponentconfig
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentConfig {
+    pub uuid: String,
+    pub view_title: String,
+    pub position: usize,
+    pub property: String,
+    pub style: DisplayStyle,
+}
+
+/**
+ * Name:
+ * DisplayKind
+ *
+ * Description:
+ * Trait shared by all display kinds
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl DisplayKind {
+    /**
+     * Name:
+     * name
+     *
+     * Description:
+     * Name this kind is stored as
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayKind::Text => "text",
+            DisplayKind::Bar => "bar",
+            DisplayKind::Gauge => "gauge",
+            DisplayKind::Sparkline => "sparkline",
+        }
+    }
+
+    /**
+     * Name:
+     * label
+     *
+     * Description:
+     * Name this kind is shown as
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn label(&self) -> &'static str {
+        match self {
+            DisplayKind::Text => "Text",
+            DisplayKind::Bar => "Level Bar",
+            DisplayKind::Gauge => "Gauge",
+            DisplayKind::Sparkline => "Sparkline",
+        }
+    }
+
+    /**
+     * Name:
+     * from_name
+     *
+     * Description:
+     * Find a kind by the name it's stored as
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn from_name(name: &str) -> Option<DisplayKind> {
+        DISPLAY_KINDS.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/**
+ * Name:
+ * DisplayStyle
+ *
+ * Description:
+ * Trait shared by all display styles
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl DisplayStyle {
+    /**
+     * Name:
+     * from_setting
+     *
+     * Description:
+     * Parse a style stored in settings (i.e. `gauge;max=90;colour=#e01b24`)
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Unknown options are ignored, so styles saved by newer versions still load
+     */
+    pub fn from_setting(text: &str) -> Result<DisplayStyle, String> {
+        let mut fields = text.split(';');
+
+        let kind: DisplayKind = match fields.next().map(str::trim) {
+            Some("") | None => DisplayKind::default(),
+            Some(name) => match DisplayKind::from_name(name) {
+                Some(kind) => kind,
+                None => return Err(format!("Unknown display style `{}`..", name)),
+            },
+        };
+
+        let mut style: DisplayStyle = DisplayStyle {
+            kind,
+            ..DisplayStyle::default()
+        };
+        for field in fields.filter(|field| !field.trim().is_empty()) {
+            let (key, value): (&str, &str) = match field.split_once('=') {
+                Some((key, value)) => (key.trim(), value.trim()),
+                None => return Err(format!("Malformed display style option `{}`..", field)),
+            };
+
+            match key {
+                "min" | "max" => {
+                    let number: f64 = match value.parse::<f64>() {
+                        Ok(number) if number.is_finite() => number,
+                        _ => {
+                            return Err(format!(
+                                "Invalid `{}` of display style: `{}`..",
+                                key, value
+                            ))
+                        }
+                    };
+                    match key {
+                        "min" => style.min = Some(number),
+                        _ => style.max = Some(number),
+                    }
+                }
+                "colour" => match parse_colour(value) {
+                    Some(_) => style.colour = Some(value.to_lowercase()),
+                    None => return Err(format!("Invalid colour of display style: `{}`..", value)),
+                },
+                _ => {}
+            }
+        }
+
+        Ok(style)
+    }
+
+    /**
+     * Name:
+     * to_setting
+     *
+     * Description:
+     * Format this style for storing in settings
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Never contains a `:`, as that separates the fields of a viewcomponentconfig
+     */
+    pub fn to_setting(&self) -> String {
+        let mut fields: Vec<String> = vec![String::from(self.kind.name())];
+        if let Some(min) = self.min {
+            fields.push(format!("min={}", min));
+        }
+        if let Some(max) = self.max {
+            fields.push(format!("max={}", max));
+        }
+        if let Some(colour) = &self.colour {
+            fields.push(format!("colour={}", colour));
+        }
+
+        fields.join(";")
+    }
+
+    /**
+     * Name:
+     * is_default
+     *
+     * Description:
+     * Check if this style is the same as having no style stored
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_default(&self) -> bool {
+        *self == DisplayStyle::default()
+    }
+
+    /**
+     * Name:
+     * bounds
+     *
+     * Description:
+     * Lowest & highest values shown by a bar or gauge, using the unit & peak of readings for any not set
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Percentages & temperatures (°C) go up to 100 unless told otherwise
+     */
+    pub fn bounds(&self, unit: &str, peak: Option<f64>) -> (f64, f64) {
+        let min: f64 = self.min.unwrap_or(0.0);
+        let max: f64 = match (self.max, unit) {
+            (Some(max), _) => max,
+            (None, "%") | (None, "°C") => 100.0,
+            (None, _) => peak.unwrap_or(min),
+        };
+
+        match max > min {
+            true => (min, max),
+            false => (min, min + 1.0),
+        }
+    }
+
+    /**
+     * Name:
+     * rgb
+     *
+     * Description:
+     * Colour of this style as red, green & blue (0-1), if one is set
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn rgb(&self) -> Option<(f64, f64, f64)> {
+        self.colour.as_deref().and_then(parse_colour)
+    }
+}
+
+/**
+ * Name:
+ * ComponentConfig
+ *
+ * Description:
+ * Trait shared by all component configs
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ComponentConfig {
+    /**
+     * Name:
+     * from_setting
+     *
+     * Description:
+     * Parse a viewcomponentconfig (UUID:VIEW_TITLE:POSITION:property[:STYLE])
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Entries saved before styles existed only have the first 4 fields
+     */
+    pub fn from_setting(text: &str) -> Result<ComponentConfig, String> {
+        let parts: Vec<&str> = text.split(':').collect();
+        if parts.len() != 4 && parts.len() != 5 {
+            return Err(format!("Malformed view component `{}`..", text));
+        }
+
+        let position: usize = match parts[2].parse::<usize>() {
+            Ok(position) => position,
+            Err(_) => return Err(format!("Invalid position of view component `{}`..", text)),
+        };
+        let style: DisplayStyle = match parts.get(4) {
+            Some(style) => DisplayStyle::from_setting(style)?,
+            None => DisplayStyle::default(),
+        };
+
+        Ok(ComponentConfig {
+            uuid: String::from(parts[0]),
+            view_title: String::from(parts[1]),
+            position,
+            property: String::from(parts[3]),
+            style,
+        })
+    }
+
+    /**
+     * Name:
+     * to_setting
+     *
+     * Description:
+     * Format this component for storing in settings
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn to_setting(&self) -> String {
+        format_component(
+            &self.uuid,
+            &self.view_title,
+            self.position,
+            &self.property,
+            &self.style,
+        )
+    }
+}
+
+/**
+ * Name:
+ * format_component
+ *
+ * Description:
+ * Format a viewcomponentconfig (UUID:VIEW_TITLE:POSITION:property[:STYLE])
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The style is left out when it's the default, so older versions can still read the entry
+ */
+pub fn format_component(
+    uuid: &str,
+    view_title: &str,
+    position: usize,
+    property: &str,
+    style: &DisplayStyle,
+) -> String {
+    let component: String = format!("{}:{}:{}:{}", uuid, view_title, position, property);
+    match style.is_default() {
+        true => component,
+        false => component + ":" + &style.to_setting(),
+    }
+}
+
+/**
+ * Name:
+ * fraction
+ *
+ * Description:
+ * How far (0-1) a value is between the lowest & highest values shown
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn fraction(value: f64, min: f64, max: f64) -> f64 {
+    match max > min {
+        true => ((value - min) / (max - min)).clamp(0.0, 1.0),
+        false => 0.0,
+    }
+}
+
+/**
+ * Name:
+ * parse_colour
+ *
+ * Description:
+ * Parse a hex colour (i.e. `#3584e4` or `#38e`) as red, green & blue (0-1)
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn parse_colour(text: &str) -> Option<(f64, f64, f64)> {
+    let digits: &str = text.trim().strip_prefix('#')?;
+    if !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channels: Vec<u8> = match digits.len() {
+        3 => digits
+            .chars()
+            .map(|digit| u8::from_str_radix(&digit.to_string().repeat(2), 16).ok())
+            .collect::<Option<Vec<u8>>>()?,
+        6 => (0..3)
+            .map(|index| u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()?,
+        _ => return None,
+    };
+
+    Some((
+        channels[0] as f64 / 255.0,
+        channels[1] as f64 / 255.0,
+        channels[2] as f64 / 255.0,
+    ))
+}
@@ -0,0 +1,527 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Fan curves (temperature to fan speed), and controlling fans using them
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Curves are stored in the `fancurves` setting, one per fan, i.e.
+ * `UUID:FAN:ENABLED:HYSTERESIS:MIN_CHANGE:40/30,60/50,80/100`
+ */
+// Modules
+use crate::{command::CommandRunner, fan_override::FanControlState};
+
+/// Structure for storing a single point (temperature in °C, speed in %) of a fan curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurvePoint {
+    pub temperature: u32,
+    pub speed: u32,
+}
+
+/// Structure for storing a fan curve and it's smoothing settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanCurve {
+    pub points: Vec<CurvePoint>,
+    pub hysteresis: u32,
+    pub min_change: u32,
+}
+
+/// Structure for storing the fan curve of a single fan (as saved in settings)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanCurveConfig {
+    pub uuid: String,
+    pub fan: u32,
+    pub enabled: bool,
+    pub curve: FanCurve,
+}
+
+/// Structure for controlling a single fan using a fan curve
+#[derive(Debug, Clone)]
+pub struct FanCurveController {
+    pub gpu_index: u32,
+    pub fan: u32,
+    pub curve: FanCurve,
+    last_speed: Option<u32>,
+    last_temperature: f64,
+}
+
+/**
+ * Name:
+ * FanCurve
+ *
+ * Description:
+ * Trait shared by all fan curves
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl FanCurve {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new fan curve, points are sorted by temperature and speeds capped at 100%
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(mut points: Vec<CurvePoint>, hysteresis: u32, min_change: u32) -> Self {
+        for point in points.iter_mut() {
+            point.speed = point.speed.min(100);
+        }
+        points.sort_by_key(|point| point.temperature);
+
+        FanCurve {
+            points,
+            hysteresis,
+            min_change,
+        }
+    }
+
+    /**
+     * Name:
+     * speed_for
+     *
+     * Description:
+     * Find the fan speed for a temperature, interpolating between points
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Below the first point (or above the last) the speed of that point is used
+     */
+    pub fn speed_for(&self, temperature: f64) -> Option<u32> {
+        let first: &CurvePoint = self.points.first()?;
+        let last: &CurvePoint = self.points.last()?;
+
+        // Outside of curve
+        if temperature <= first.temperature as f64 {
+            return Some(first.speed);
+        }
+        if temperature >= last.temperature as f64 {
+            return Some(last.speed);
+        }
+
+        // Find the two points either side and interpolate
+        for pair in self.points.windows(2) {
+            let (lower, upper): (&CurvePoint, &CurvePoint) = (&pair[0], &pair[1]);
+            if temperature <= upper.temperature as f64 {
+                let range: f64 = (upper.temperature - lower.temperature) as f64;
+                if range == 0.0 {
+                    return Some(upper.speed);
+                }
+                let fraction: f64 = (temperature - lower.temperature as f64) / range;
+                let speed: f64 =
+                    lower.speed as f64 + fraction * (upper.speed as f64 - lower.speed as f64);
+
+                return Some(speed.round() as u32);
+            }
+        }
+
+        Some(last.speed)
+    }
+
+    /**
+     * Name:
+     * points_to_string
+     *
+     * Description:
+     * Convert the points of the curve to the format used in settings (i.e. `40/30,60/50`)
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn points_to_string(&self) -> String {
+        self.points
+            .iter()
+            .map(|point| format!("{}/{}", point.temperature, point.speed))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+}
+
+/**
+ * Name:
+ * parse_points
+ *
+ * Description:
+ * Parse a list of curve points from the format used in settings (i.e. `40/30,60/50`)
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn parse_points(input: &str) -> Result<Vec<CurvePoint>, String> {
+    let mut points: Vec<CurvePoint> = vec![];
+
+    for item in input.split(',').filter(|item| !item.trim().is_empty()) {
+        match item.split_once('/') {
+            Some((temperature, speed)) => {
+                match (
+                    temperature.trim().parse::<u32>(),
+                    speed.trim().parse::<u32>(),
+                ) {
+                    (Ok(temperature), Ok(speed)) => points.push(CurvePoint { temperature, speed }),
+                    _ => return Err(format!("Invalid fan curve point `{}`..", item)),
+                }
+            }
+            None => return Err(format!("Invalid fan curve point `{}`..", item)),
+        }
+    }
+
+    Ok(points)
+}
+
+/**
+ * Name:
+ * FanCurveConfig
+ *
+ * Description:
+ * Trait shared by all fan curve configs
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl FanCurveConfig {
+    /**
+     * Name:
+     * from_setting
+     *
+     * Description:
+     * Parse a fan curve from it's entry in the `fancurves` setting
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * UUID:FAN:ENABLED:HYSTERESIS:MIN_CHANGE:POINTS
+     */
+    pub fn from_setting(input: &str) -> Result<Self, String> {
+        // Split into the 6 parts
+        let parts: Vec<&str> = input.split(':').collect();
+        if parts.len() != 6 {
+            return Err(String::from("Malformed fan curve data.."));
+        }
+
+        match (
+            parts[1].parse::<u32>(),
+            parts[2].parse::<bool>(),
+            parts[3].parse::<u32>(),
+            parts[4].parse::<u32>(),
+        ) {
+            (Ok(fan), Ok(enabled), Ok(hysteresis), Ok(min_change)) => Ok(FanCurveConfig {
+                uuid: String::from(parts[0]),
+                fan,
+                enabled,
+                curve: FanCurve::new(parse_points(parts[5])?, hysteresis, min_change),
+            }),
+            _ => Err(String::from("Malformed fan curve data..")),
+        }
+    }
+
+    /**
+     * Name:
+     * to_setting
+     *
+     * Description:
+     * Convert a fan curve to it's entry in the `fancurves` setting
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn to_setting(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            self.uuid,
+            self.fan,
+            self.enabled,
+            self.curve.hysteresis,
+            self.curve.min_change,
+            self.curve.points_to_string()
+        )
+    }
+}
+
+/**
+ * Name:
+ * FanCurveController
+ *
+ * Description:
+ * Trait shared by all fan curve controllers
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl FanCurveController {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new controller for a fan belonging to a gpu (both given by nvidia-settings index)
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(gpu_index: u32, fan: u32, curve: FanCurve) -> Self {
+        FanCurveController {
+            gpu_index,
+            fan,
+            curve,
+            last_speed: None,
+            last_temperature: 0.0,
+        }
+    }
+
+    /**
+     * Name:
+     * last_speed
+     *
+     * Description:
+     * The last speed set by this controller (if any)
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn last_speed(&self) -> Option<u32> {
+        self.last_speed
+    }
+
+    /**
+     * Name:
+     * reset
+     *
+     * Description:
+     * Forget the last speed set, so the next update always applies the curve
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Used when something else (i.e. a manual override or the driver) has changed the fan speed
+     */
+    pub fn reset(&mut self) {
+        self.last_speed = None;
+    }
+
+    /**
+     * Name:
+     * update
+     *
+     * Description:
+     * Decide if the fan speed needs changing for the current temperature, returning the new speed
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Speed is only lowered once the temperature has dropped by the hysteresis amount (avoids
+     * flapping around a point), and changes smaller than the minimum change are ignored unless
+     * they reach either end of the curve
+     */
+    pub fn update(&mut self, temperature: f64) -> Option<u32> {
+        let target: u32 = self.curve.speed_for(temperature)?;
+
+        if let Some(last) = self.last_speed {
+            // No change
+            if target == last {
+                return None;
+            }
+
+            // Hold speed until cooled down enough
+            if target < last && temperature > self.last_temperature - self.curve.hysteresis as f64 {
+                return None;
+            }
+
+            // Ignore tiny changes (unless at either end of the curve)
+            let speeds = self.curve.points.iter().map(|point| point.speed);
+            let at_limit: bool =
+                Some(target) == speeds.clone().min() || Some(target) == speeds.max();
+            if target.abs_diff(last) < self.curve.min_change && !at_limit {
+                return None;
+            }
+        }
+
+        // Store new state
+        self.last_speed = Some(target);
+        self.last_temperature = temperature;
+
+        Some(target)
+    }
+
+    /**
+     * Name:
+     * apply
+     *
+     * Description:
+     * Update the fan speed for the current temperature, running the command if it changed
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The GPU is marked as controlled before running, as control may have been partly taken
+     * even if the command fails. If it fails, the speed is forgotten so it is retried on the
+     * next update
+     */
+    pub fn apply(
+        &mut self,
+        runner: &dyn CommandRunner,
+        fan_control: &mut FanControlState,
+        temperature: f64,
+    ) -> Result<Option<u32>, String> {
+        match self.update(temperature) {
+            Some(speed) => {
+                // Remember before running, so it is restored whatever happens
+                fan_control.mark_controlled(self.gpu_index);
+
+                match runner.run(&create_fan_speed_command(self.gpu_index, self.fan, speed)) {
+                    Ok(_) => Ok(Some(speed)),
+                    Err(err) => {
+                        self.last_speed = None;
+                        Err(err)
+                    }
+                }
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/**
+ * Name:
+ * create_fan_speed_command
+ *
+ * Description:
+ * Create the nvidia-settings command for taking control of a fan and setting it's speed
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn create_fan_speed_command(gpu_index: u32, fan: u32, speed: u32) -> Vec<String> {
+    vec![
+        String::from("nvidia-settings"),
+        String::from("-a"),
+        format!("[gpu:{}]/GPUFanControlState=1", gpu_index),
+        String::from("-a"),
+        format!("[fan:{}]/GPUTargetFanSpeed={}", fan, speed),
+    ]
+}
+
+/**
+ * Name:
+ * parse_temperature
+ *
+ * Description:
+ * Parse a formatted temperature (i.e. `45°C` or `113°F`) back into °C
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Returns None for anything that isn't a temperature (i.e. `N/A`)
+ */
+pub fn parse_temperature(input: &str) -> Option<f64> {
+    // Remove all non-number characters
+    let digits: String = input
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == '-')
+        .collect();
+    let value: f64 = digits.parse::<f64>().ok()?;
+
+    // Convert back from fahrenheit if needed
+    if input.trim_end().ends_with('F') {
+        Some((value - 32.0) * 5.0 / 9.0)
+    } else {
+        Some(value)
+    }
+}
@@ -0,0 +1,481 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Manual (fixed speed) fan control, and handing control of fans back to the driver
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Every GPU we take fan control of (manually or with a fan curve) is tracked in `FAN_CONTROL`,
+ * so control can be handed back on exit, on a crash, or on SIGTERM/SIGINT
+ */
+// Imports
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+// Modules
+use crate::command::CommandRunner;
+
+/// Speed (%) used when a manual override is first switched on
+pub const DEFAULT_OVERRIDE_SPEED: u32 = 50;
+
+/// Every GPU currently under our control (rather than the driver's)
+pub static FAN_CONTROL: Mutex<FanControlState> = Mutex::new(FanControlState::new());
+
+/// Structure for storing the manual override of a GPU page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FanOverride {
+    pub active: bool,
+    pub speed: u32,
+}
+
+/// Structure for tracking which GPUs have fan control taken from the driver
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FanControlState {
+    controlled: Vec<u32>,
+    fixed: Vec<u32>,
+}
+
+/**
+ * Name:
+ * FanOverride
+ *
+ * Description:
+ * Trait for the default state of a manual override
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Default for FanOverride {
+    fn default() -> Self {
+        FanOverride {
+            active: false,
+            speed: DEFAULT_OVERRIDE_SPEED,
+        }
+    }
+}
+
+/**
+ * Name:
+ * FanControlState
+ *
+ * Description:
+ * Trait shared by all fan control states
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl FanControlState {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new state with no GPUs under our control
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub const fn new() -> Self {
+        FanControlState {
+            controlled: vec![],
+            fixed: vec![],
+        }
+    }
+
+    /**
+     * Name:
+     * is_controlled
+     *
+     * Description:
+     * Check if we have taken fan control of a GPU (by nvidia-settings index)
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_controlled(&self, gpu_index: u32) -> bool {
+        self.controlled.contains(&gpu_index)
+    }
+
+    /**
+     * Name:
+     * is_empty
+     *
+     * Description:
+     * Check if we have taken fan control of any GPU
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_empty(&self) -> bool {
+        self.controlled.is_empty()
+    }
+
+    /**
+     * Name:
+     * is_fixed
+     *
+     * Description:
+     * Check if a GPU has a manual override set (fan curves should leave it alone)
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_fixed(&self, gpu_index: u32) -> bool {
+        self.fixed.contains(&gpu_index)
+    }
+
+    /**
+     * Name:
+     * mark_controlled
+     *
+     * Description:
+     * Record that fan control of a GPU was taken elsewhere (i.e. by a fan curve)
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn mark_controlled(&mut self, gpu_index: u32) {
+        if !self.is_controlled(gpu_index) {
+            self.controlled.push(gpu_index);
+        }
+    }
+
+    /**
+     * Name:
+     * set_fixed_speed
+     *
+     * Description:
+     * Take fan control of a GPU and set all of it's fans to a fixed speed
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The GPU is tracked even if the command fails, as control may have been partly taken
+     */
+    pub fn set_fixed_speed(
+        &mut self,
+        runner: &dyn CommandRunner,
+        gpu_index: u32,
+        fans: &[u32],
+        speed: u32,
+    ) -> Result<(), String> {
+        // Remember before running, so it is restored whatever happens
+        self.mark_fixed(gpu_index);
+
+        runner.run(&create_fixed_speed_command(gpu_index, fans, speed))
+    }
+
+    /**
+     * Name:
+     * mark_fixed
+     *
+     * Description:
+     * Record that a GPU has a manual override (and so fan control taken)
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn mark_fixed(&mut self, gpu_index: u32) {
+        self.mark_controlled(gpu_index);
+        if !self.is_fixed(gpu_index) {
+            self.fixed.push(gpu_index);
+        }
+    }
+
+    /**
+     * Name:
+     * mark_released
+     *
+     * Description:
+     * Record that fan control of a GPU was handed back to the driver
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn mark_released(&mut self, gpu_index: u32) {
+        self.controlled.retain(|gpu| *gpu != gpu_index);
+        self.fixed.retain(|gpu| *gpu != gpu_index);
+    }
+
+    /**
+     * Name:
+     * release
+     *
+     * Description:
+     * Hand fan control of a GPU back to the driver
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * If the command fails the GPU is still tracked, so it is retried on exit
+     */
+    pub fn release(&mut self, runner: &dyn CommandRunner, gpu_index: u32) -> Result<(), String> {
+        runner.run(&create_restore_command(gpu_index))?;
+        self.mark_released(gpu_index);
+
+        Ok(())
+    }
+
+    /**
+     * Name:
+     * release_all
+     *
+     * Description:
+     * Hand fan control of every tracked GPU back to the driver, returning any errors
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn release_all(&mut self, runner: &dyn CommandRunner) -> Vec<String> {
+        let mut errors: Vec<String> = vec![];
+
+        for gpu_index in self.controlled.clone() {
+            if let Err(err) = self.release(runner, gpu_index) {
+                errors.push(err);
+            }
+        }
+
+        errors
+    }
+}
+
+/**
+ * Name:
+ * lock_fan_control
+ *
+ * Description:
+ * Lock the shared fan control state
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Ignores poisoning, as this is needed most after a panic
+ */
+pub fn lock_fan_control() -> MutexGuard<'static, FanControlState> {
+    match FAN_CONTROL.lock() {
+        Ok(state) => state,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/**
+ * Name:
+ * apply_fixed_speed
+ *
+ * Description:
+ * Take fan control of a GPU and set all of it's fans to a fixed speed, without holding the lock while the
+ * command runs
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Blocking, so meant to be run off the main thread. The GPU is tracked before running, so it is
+ * restored whatever happens
+ */
+pub fn apply_fixed_speed(
+    runner: &dyn CommandRunner,
+    gpu_index: u32,
+    fans: &[u32],
+    speed: u32,
+) -> Result<(), String> {
+    lock_fan_control().mark_fixed(gpu_index);
+
+    runner.run(&create_fixed_speed_command(gpu_index, fans, speed))
+}
+
+/**
+ * Name:
+ * release_fan_control
+ *
+ * Description:
+ * Hand fan control of a GPU back to the driver, without holding the lock while the command runs
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Blocking, so meant to be run off the main thread. If the command fails the GPU is still tracked,
+ * so it is retried on exit
+ */
+pub fn release_fan_control(runner: &dyn CommandRunner, gpu_index: u32) -> Result<(), String> {
+    runner.run(&create_restore_command(gpu_index))?;
+    lock_fan_control().mark_released(gpu_index);
+
+    Ok(())
+}
+
+/**
+ * Name:
+ * restore_all
+ *
+ * Description:
+ * Hand every fan we control back to the driver (run on exit), returning any errors
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Doesn't wait for the lock, as a panic hook runs while the panicking thread may still hold it.
+ * A held lock is reported as an error, as fans may still be under manual control
+ */
+pub fn restore_all(runner: &dyn CommandRunner) -> Vec<String> {
+    let mut state: MutexGuard<FanControlState> = match FAN_CONTROL.try_lock() {
+        Ok(state) => state,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => {
+            return vec![String::from("fan control state is locked")];
+        }
+    };
+
+    state.release_all(runner)
+}
+
+/**
+ * Name:
+ * create_fixed_speed_command
+ *
+ * Description:
+ * Create the nvidia-settings command for taking control of a GPU and fixing the speed of it's fans
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn create_fixed_speed_command(gpu_index: u32, fans: &[u32], speed: u32) -> Vec<String> {
+    let mut command: Vec<String> = vec![
+        String::from("nvidia-settings"),
+        String::from("-a"),
+        format!("[gpu:{}]/GPUFanControlState=1", gpu_index),
+    ];
+
+    for fan in fans {
+        command.push(String::from("-a"));
+        command.push(format!(
+            "[fan:{}]/GPUTargetFanSpeed={}",
+            fan,
+            speed.min(100)
+        ));
+    }
+
+    command
+}
+
+/**
+ * Name:
+ * create_restore_command
+ *
+ * Description:
+ * Create the nvidia-settings command for handing fan control of a GPU back to the driver
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn create_restore_command(gpu_index: u32) -> Vec<String> {
+    vec![
+        String::from("nvidia-settings"),
+        String::from("-a"),
+        format!("[gpu:{}]/GPUFanControlState=0", gpu_index),
+    ]
+}
@@ -0,0 +1,354 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * imp.rs
+ *
+ * Description:
+ * Implementation of our custom GObject class (FanCurveWindow)
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+// Imports
+use adwaita::{gio, glib, prelude::*, subclass::prelude::*};
+use gio::Settings;
+use glib::{
+    once_cell::sync::Lazy, once_cell::sync::OnceCell, signal::Inhibit,
+    subclass::InitializingObject, FromVariant, ParamSpec, Value,
+};
+use gtk::{
+    subclass::prelude::*, Button, CompositeTemplate, DrawingArea, DropDown, SpinButton, Switch,
+    TemplateChild,
+};
+use std::{cell::Cell, cell::RefCell, rc::Rc};
+
+// Modules
+use crate::{fan_curve::CurvePoint, gpu_page::GpuPage};
+
+/// Structure for storing a GpuPage object and any related information
+#[derive(Default)]
+pub struct ParentContainer {
+    pub window: Option<GpuPage>,
+}
+
+/// Object holding the State and any Template Children
+#[derive(CompositeTemplate, Default)]
+#[template(resource = "/fan-curve-window.ui")]
+pub struct FanCurveWindow {
+    // Public
+    pub settings: OnceCell<Settings>,
+    pub parent_window: Rc<RefCell<ParentContainer>>,
+    pub fans: RefCell<Vec<u32>>,
+    pub points: RefCell<Vec<CurvePoint>>,
+    pub dragged_point: Cell<Option<usize>>,
+
+    // Private
+    uuid: OnceCell<String>,
+
+    // Template Children
+    #[template_child]
+    pub fan_input: TemplateChild<DropDown>,
+    #[template_child]
+    pub enabled_input: TemplateChild<Switch>,
+    #[template_child]
+    pub curve_area: TemplateChild<DrawingArea>,
+    #[template_child]
+    pub hysteresis_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub min_change_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub fan_curve_apply_button: TemplateChild<Button>,
+    #[template_child]
+    pub fan_curve_cancel_button: TemplateChild<Button>,
+    #[template_child]
+    pub fan_curve_reset_button: TemplateChild<Button>,
+}
+
+/// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for FanCurveWindow {
+    // `NAME` needs to match `class` attribute of template
+    const NAME: &'static str = "NvidiaExtensionFanCurveWindow";
+    type Type = super::FanCurveWindow;
+    type ParentType = gtk::ApplicationWindow;
+
+    fn class_init(klass: &mut Self::Class) {
+        klass.bind_template();
+    }
+
+    fn instance_init(obj: &InitializingObject<Self>) {
+        obj.init_template();
+    }
+}
+
+/**
+ * Name:
+ * FanCurveWindow
+ *
+ * Description:
+ * Trait shared by all FanCurveWindow objects
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl FanCurveWindow {
+    /**
+     * Name:
+     * get_setting
+     *
+     * Description:
+     * Generic function for getting setting value
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn get_setting<T: FromVariant>(&self, name: &str) -> T {
+        // Return the value of the property
+        match self.settings.get() {
+            Some(settings) => settings.get::<T>(name),
+            None => panic!("`settings` should be set in `setup_settings`."),
+        }
+    }
+
+    /**
+     * Name:
+     * update_setting
+     *
+     * Description:
+     * Generic function for updating setting values
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn update_setting<T: ToVariant>(&self, name: &str, value: T) {
+        // Fetch settings
+        match self.settings.get() {
+            Some(settings) => match settings.set(name, &value) {
+                Ok(_) => println!("..Setting `{}` updated!", name),
+                Err(err) => panic!("..Cannot update `{}` setting: `{}`", name, err),
+            },
+            None => panic!("..Cannot retrieve settings"),
+        }
+    }
+}
+
+/**
+ * Trait Name:
+ * ObjectImpl
+ *
+ * Description:
+ * Trait shared by all GObjects
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ObjectImpl for FanCurveWindow {
+    /**
+     * Name:
+     * constructed
+     *
+     * Description:
+     * Called during construction, allows calling setup functions
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn constructed(&self, obj: &Self::Type) {
+        // Call "constructed" on parent
+        self.parent_constructed(obj);
+    }
+
+    /**
+     * Name:
+     * properties
+     *
+     * Description:
+     * Create list of custom properties for our GObject
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * beware that you need to use kebab-case (<https://en.wikipedia.org/wiki/Letter_case#Kebab_case>)
+     */
+    fn properties() -> &'static [ParamSpec] {
+        static PROPERTIES: Lazy<Vec<ParamSpec>> =
+            Lazy::new(|| vec![glib::ParamSpecString::builder("uuid").build()]);
+
+        PROPERTIES.as_ref()
+    }
+
+    /**
+     * Name:
+     * set_property
+     *
+     * Description:
+     * Mutator for custom GObject properties
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn set_property(&self, _obj: &Self::Type, _id: usize, value: &Value, pspec: &ParamSpec) {
+        match pspec.name() {
+            "uuid" => match value.get() {
+                Ok(input_uuid) => self
+                    .uuid
+                    .set(input_uuid)
+                    .expect("`uuid` should not be set after calling constructor.."),
+                Err(_) => panic!("The value needs to be of type `String`."),
+            },
+            _ => panic!("Property `{}` does not exist..", pspec.name()),
+        }
+    }
+
+    /**
+     * Name:
+     * property
+     *
+     * Description:
+     * Accessor for custom GObject properties
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "uuid" => match self.uuid.clone().get() {
+                Some(value) => return value.to_value(),
+                None => panic!("Cannot get value of `uuid` property.."),
+            },
+            _ => panic!("Property `{}` does not exist..", pspec.name()),
+        }
+    }
+}
+
+/**
+ * Trait Name:
+ * WidgetImpl
+ *
+ * Description:
+ * Trait shared by all widgets
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl WidgetImpl for FanCurveWindow {}
+
+/**
+ * Trait Name:
+ * WindowImpl
+ *
+ * Description:
+ * Trait shared by all Window's
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl WindowImpl for FanCurveWindow {
+    /**
+     * Name:
+     * close_request
+     *
+     * Description:
+     * Run when window closed
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn close_request(&self, window: &Self::Type) -> Inhibit {
+        // Store state in settings
+        self.update_setting("fancurve-open", false);
+
+        // Pass close request on to the parent
+        self.parent_close_request(window)
+    }
+}
+
+/**
+ * Trait Name:
+ * ApplicationWindowImpl
+ *
+ * Description:
+ * Trait shared by all ApplicationWindow's
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ApplicationWindowImpl for FanCurveWindow {}
@@ -0,0 +1,585 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Public-facing interface/wrapper for our custom GObject (FanCurveWindow)
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The curve editor maps 0-100°C along the x-axis and 0-100% fan speed along the y-axis
+ */
+// Custom GObjects
+mod imp;
+
+// Imports
+use adwaita::{gio, glib, prelude::*, subclass::prelude::*};
+use gio::Settings;
+use glib::{clone, Object};
+use gtk::{cairo, Adjustment, DrawingArea, GestureClick, GestureDrag, StringList};
+use std::cell::{Ref, RefMut};
+
+// Modules
+use crate::{
+    fan_curve::{CurvePoint, FanCurve, FanCurveConfig},
+    fancurvewindow::imp::ParentContainer,
+    gpu_page::GpuPage,
+    APP_ID,
+};
+
+/// Space (px) around the curve inside the drawing area
+const CURVE_MARGIN: f64 = 24.0;
+/// Distance (px) within which a click selects an existing point
+const POINT_RADIUS: f64 = 10.0;
+
+// GObject wrapper for FanCurveWindow
+glib::wrapper! {
+    pub struct FanCurveWindow(ObjectSubclass<imp::FanCurveWindow>)
+        @extends gtk::ApplicationWindow, gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+/**
+ * Name:
+ * default_curve
+ *
+ * Description:
+ * Curve used when a fan has none saved yet
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn default_curve() -> FanCurve {
+    FanCurve::new(
+        vec![
+            CurvePoint {
+                temperature: 30,
+                speed: 30,
+            },
+            CurvePoint {
+                temperature: 60,
+                speed: 50,
+            },
+            CurvePoint {
+                temperature: 80,
+                speed: 100,
+            },
+        ],
+        3,
+        5,
+    )
+}
+
+/**
+ * Name:
+ * point_to_position
+ *
+ * Description:
+ * Convert a curve point to a position (px) in the drawing area
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn point_to_position(point: &CurvePoint, width: f64, height: f64) -> (f64, f64) {
+    (
+        CURVE_MARGIN + point.temperature as f64 / 100.0 * (width - 2.0 * CURVE_MARGIN),
+        height - CURVE_MARGIN - point.speed as f64 / 100.0 * (height - 2.0 * CURVE_MARGIN),
+    )
+}
+
+/**
+ * Name:
+ * position_to_point
+ *
+ * Description:
+ * Convert a position (px) in the drawing area to a curve point
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Positions outside of the graph are clamped to it's edges
+ */
+fn position_to_point(x: f64, y: f64, width: f64, height: f64) -> CurvePoint {
+    let temperature: f64 = (x - CURVE_MARGIN) / (width - 2.0 * CURVE_MARGIN) * 100.0;
+    let speed: f64 = (height - CURVE_MARGIN - y) / (height - 2.0 * CURVE_MARGIN) * 100.0;
+
+    CurvePoint {
+        temperature: temperature.clamp(0.0, 100.0).round() as u32,
+        speed: speed.clamp(0.0, 100.0).round() as u32,
+    }
+}
+
+/**
+ * Name:
+ * find_point_at
+ *
+ * Description:
+ * Find the index of the point (if any) under a position in the drawing area
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn find_point_at(points: &[CurvePoint], x: f64, y: f64, width: f64, height: f64) -> Option<usize> {
+    points.iter().position(|point| {
+        let (point_x, point_y): (f64, f64) = point_to_position(point, width, height);
+        (point_x - x).hypot(point_y - y) <= POINT_RADIUS
+    })
+}
+
+/**
+ * Name:
+ * draw_curve
+ *
+ * Description:
+ * Draw the graph axes, curve and points
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn draw_curve(
+    context: &cairo::Context,
+    points: &[CurvePoint],
+    width: f64,
+    height: f64,
+) -> Result<(), cairo::Error> {
+    // Grid lines every 10°C/10%
+    context.set_source_rgba(0.5, 0.5, 0.5, 0.3);
+    context.set_line_width(1.0);
+    for step in 0..=10 {
+        let (x, y): (f64, f64) = point_to_position(
+            &CurvePoint {
+                temperature: step * 10,
+                speed: step * 10,
+            },
+            width,
+            height,
+        );
+        context.move_to(x, CURVE_MARGIN);
+        context.line_to(x, height - CURVE_MARGIN);
+        context.move_to(CURVE_MARGIN, y);
+        context.line_to(width - CURVE_MARGIN, y);
+    }
+    context.stroke()?;
+
+    // Nothing else to draw
+    if points.is_empty() {
+        return Ok(());
+    }
+
+    // Sort points by temperature
+    let mut sorted_points: Vec<CurvePoint> = points.to_vec();
+    sorted_points.sort_by_key(|point| point.temperature);
+
+    // Curve (flat before the first and after the last point)
+    context.set_source_rgb(0.21, 0.52, 0.89);
+    context.set_line_width(2.0);
+    let (first_x, first_y): (f64, f64) = point_to_position(&sorted_points[0], width, height);
+    context.move_to(CURVE_MARGIN, first_y);
+    context.line_to(first_x, first_y);
+    let mut last_y: f64 = first_y;
+    for point in sorted_points.iter().skip(1) {
+        let (x, y): (f64, f64) = point_to_position(point, width, height);
+        context.line_to(x, y);
+        last_y = y;
+    }
+    context.line_to(width - CURVE_MARGIN, last_y);
+    context.stroke()?;
+
+    // Points
+    for point in sorted_points.iter() {
+        let (x, y): (f64, f64) = point_to_position(point, width, height);
+        context.arc(x, y, POINT_RADIUS / 2.0, 0.0, 2.0 * std::f64::consts::PI);
+        context.fill()?;
+    }
+
+    Ok(())
+}
+
+/**
+ * Trait Name:
+ * FanCurveWindow
+ *
+ * Description:
+ * Trait shared by all fan curve windows
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl FanCurveWindow {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new FanCurveWindow object
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * If no fans could be found, fan 0 is assumed
+     */
+    pub fn new(
+        app: &adwaita::Application,
+        uuid: &str,
+        fans: Vec<u32>,
+        parent_window: &GpuPage,
+    ) -> Self {
+        // Create new window
+        let obj: FanCurveWindow = Object::new(&[("application", app)])
+            .expect("`FanCurveWindow` should be  instantiable.");
+
+        // Set custom properties
+        obj.set_property("uuid", String::from(uuid));
+        if fans.is_empty() {
+            obj.imp().fans.replace(vec![0]);
+        } else {
+            obj.imp().fans.replace(fans);
+        }
+
+        // Set ref to parent
+        {
+            let mut parent_window_container: RefMut<ParentContainer> =
+                obj.imp().parent_window.borrow_mut();
+            parent_window_container.window = Some(parent_window.to_owned());
+        }
+
+        // Apply any setup actions that need the above properties
+        obj.setup_settings();
+        obj.setup_widgets();
+
+        // Return final object
+        obj
+    }
+
+    /**
+     * Name:
+     * setup_settings
+     *
+     * Description:
+     * Load settings for APP_ID
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_settings(&self) {
+        let settings = Settings::new(APP_ID);
+        self.imp()
+            .settings
+            .set(settings)
+            .expect("`settings` should not be set before calling `setup_settings`..");
+    }
+
+    /**
+     * Name:
+     * selected_fan
+     *
+     * Description:
+     * Get the index of the fan currently selected in the dropdown
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn selected_fan(&self) -> u32 {
+        let fans: Vec<u32> = self.imp().fans.borrow().clone();
+        match fans.get(self.imp().fan_input.selected() as usize) {
+            Some(fan) => *fan,
+            None => fans[0],
+        }
+    }
+
+    /**
+     * Name:
+     * load_curve
+     *
+     * Description:
+     * Load the saved curve (or the default curve) of the selected fan into the editor
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn load_curve(&self) {
+        let uuid: String = self.property::<String>("uuid");
+        let fan: u32 = self.selected_fan();
+
+        // Find saved curve for this fan
+        let saved_config: Option<FanCurveConfig> = self
+            .imp()
+            .get_setting::<Vec<String>>("fancurves")
+            .iter()
+            .filter_map(|item| FanCurveConfig::from_setting(item).ok())
+            .find(|config| config.uuid == uuid && config.fan == fan);
+        let (enabled, curve): (bool, FanCurve) = match saved_config {
+            Some(config) => (config.enabled, config.curve),
+            None => (false, default_curve()),
+        };
+
+        // Update widgets
+        self.imp().enabled_input.set_active(enabled);
+        self.imp()
+            .hysteresis_input
+            .set_value(curve.hysteresis as f64);
+        self.imp()
+            .min_change_input
+            .set_value(curve.min_change as f64);
+        self.imp().points.replace(curve.points);
+        self.imp().curve_area.queue_draw();
+    }
+
+    /**
+     * Name:
+     * save_curve
+     *
+     * Description:
+     * Save the curve in the editor for the selected fan
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Replaces any existing curve for the same fan
+     */
+    fn save_curve(&self) {
+        let uuid: String = self.property::<String>("uuid");
+        let fan: u32 = self.selected_fan();
+
+        // Build config from widgets
+        let config: FanCurveConfig = FanCurveConfig {
+            uuid: uuid.clone(),
+            fan,
+            enabled: self.imp().enabled_input.is_active(),
+            curve: FanCurve::new(
+                self.imp().points.borrow().clone(),
+                self.imp().hysteresis_input.value() as u32,
+                self.imp().min_change_input.value() as u32,
+            ),
+        };
+
+        // Replace old entry (if any)
+        let mut fan_curves: Vec<String> = self
+            .imp()
+            .get_setting::<Vec<String>>("fancurves")
+            .into_iter()
+            .filter(|item| match FanCurveConfig::from_setting(item) {
+                Ok(old_config) => !(old_config.uuid == uuid && old_config.fan == fan),
+                Err(_) => true,
+            })
+            .collect();
+        fan_curves.push(config.to_setting());
+        self.imp()
+            .update_setting::<Vec<String>>("fancurves", fan_curves);
+    }
+
+    /**
+     * Name:
+     * setup_widgets
+     *
+     * Description:
+     * Set up all widgets
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_widgets(&self) {
+        // Fill list of fans
+        let fan_names: Vec<String> = self
+            .imp()
+            .fans
+            .borrow()
+            .iter()
+            .map(|fan| String::from("Fan ") + &fan.to_string())
+            .collect();
+        let fan_names_str: Vec<&str> = fan_names.iter().map(|name| name.as_str()).collect();
+        self.imp()
+            .fan_input
+            .set_model(Some(&StringList::new(&fan_names_str)));
+        self.imp()
+            .fan_input
+            .connect_selected_notify(clone!(@weak self as window => move |_| {
+                window.load_curve();
+            }));
+
+        // Create adjustment settings for SpinButtons
+        let adjustment: Adjustment = Adjustment::new(0.0, 0.0, 20.0, 1.0, 5.0, 0.0);
+        self.imp()
+            .hysteresis_input
+            .configure(Some(&adjustment), 1.0, 0);
+        let adjustment: Adjustment = Adjustment::new(0.0, 0.0, 20.0, 1.0, 5.0, 0.0);
+        self.imp()
+            .min_change_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        // Draw curve
+        self.imp().curve_area.set_draw_func(
+            clone!(@weak self as window => move |_area: &DrawingArea, context: &cairo::Context, width: i32, height: i32| {
+                let points: Ref<Vec<CurvePoint>> = window.imp().points.borrow();
+                if let Err(err) = draw_curve(context, &points, width as f64, height as f64) {
+                    println!("..Attempt to draw fan curve failed, returning: {}", err);
+                };
+            }),
+        );
+
+        // Add or move points by dragging
+        let drag_gesture: GestureDrag = GestureDrag::new();
+        drag_gesture.connect_drag_begin(
+            clone!(@weak self as window => move |gesture: &GestureDrag, x: f64, y: f64| {
+                let area: DrawingArea = window.imp().curve_area.get();
+                let (width, height): (f64, f64) = (area.width() as f64, area.height() as f64);
+                let mut points: RefMut<Vec<CurvePoint>> = window.imp().points.borrow_mut();
+
+                // Grab existing point, otherwise add a new one
+                match find_point_at(&points, x, y, width, height) {
+                    Some(index) => window.imp().dragged_point.set(Some(index)),
+                    None => {
+                        points.push(position_to_point(x, y, width, height));
+                        window.imp().dragged_point.set(Some(points.len() - 1));
+                    }
+                }
+                drop(points);
+
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                area.queue_draw();
+            }),
+        );
+        drag_gesture.connect_drag_update(clone!(@weak self as window => move |gesture: &GestureDrag, offset_x: f64, offset_y: f64| {
+            let area: DrawingArea = window.imp().curve_area.get();
+            let (width, height): (f64, f64) = (area.width() as f64, area.height() as f64);
+
+            // Move the grabbed point
+            if let (Some(index), Some((start_x, start_y))) = (window.imp().dragged_point.get(), gesture.start_point()) {
+                if let Some(point) = window.imp().points.borrow_mut().get_mut(index) {
+                    *point = position_to_point(start_x + offset_x, start_y + offset_y, width, height);
+                }
+                area.queue_draw();
+            }
+        }));
+        drag_gesture.connect_drag_end(clone!(@weak self as window => move |_gesture: &GestureDrag, _offset_x: f64, _offset_y: f64| {
+            window.imp().dragged_point.set(None);
+        }));
+        self.imp().curve_area.add_controller(&drag_gesture);
+
+        // Remove points by right-clicking
+        let click_gesture: GestureClick = GestureClick::new();
+        click_gesture.set_button(gdk::BUTTON_SECONDARY);
+        click_gesture.connect_pressed(clone!(@weak self as window => move |_gesture: &GestureClick, _n_press: i32, x: f64, y: f64| {
+            let area: DrawingArea = window.imp().curve_area.get();
+            let (width, height): (f64, f64) = (area.width() as f64, area.height() as f64);
+
+            let mut points: RefMut<Vec<CurvePoint>> = window.imp().points.borrow_mut();
+            if let Some(index) = find_point_at(&points, x, y, width, height) {
+                points.remove(index);
+            }
+            drop(points);
+
+            area.queue_draw();
+        }));
+        self.imp().curve_area.add_controller(&click_gesture);
+
+        // Load curve of first fan
+        self.load_curve();
+
+        // Buttons
+        // Apply
+        self.imp().fan_curve_apply_button.connect_clicked(
+            clone!(@weak self as window => move |_| {
+                // Save any changes to the curve
+                window.save_curve();
+
+                // Emit signal to notify changes made (and thus reload required)
+                let parent_window_container: RefMut<ParentContainer> = window.imp().parent_window.borrow_mut();
+                let _result = parent_window_container.window.as_ref().unwrap().emit_by_name::<i32>("update-views", &[&(-1).to_value()]);
+
+                // Close window
+                window.close();
+            }),
+        );
+        // Cancel
+        self.imp().fan_curve_cancel_button.connect_clicked(
+            clone!(@weak self as window => move |_| {
+                // Close window
+                window.close();
+            }),
+        );
+        // Reset
+        self.imp().fan_curve_reset_button.connect_clicked(
+            clone!(@weak self as window => move |_| {
+                // Replace points with default curve
+                window.imp().points.replace(default_curve().points);
+                window.imp().curve_area.queue_draw();
+            }),
+        );
+    }
+}
@@ -120,6 +120,11 @@ impl Formatter {
     pub fn format(self, value: String, clean: bool) -> Option<String> {
         //println!("FORMATTING");//TEST
 
+        // Catch any values the GPU doesn't support (i.e. `[N/A]` or `[Not Supported]`)
+        if value.contains("N/A") || value.contains("Not Supported") {
+            return Some(String::from("N/A"));
+        }
+
         // Apply cleaning if required
         if clean {
             // Remove all non-number characters
@@ -209,6 +214,215 @@ impl Formatter {
     }
 }
 
+/**
+ * Name:
+ * format_clock_speed
+ *
+ * Description:
+ * Format a clock speed (given in MHz) as either MHz or GHz
+ *
+ * Made:
+ * 14/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Anything at or above 1000MHz is shown in GHz (to 2 decimal places)
+ */
+pub fn format_clock_speed(mhz: f64) -> String {
+    if mhz >= 1000.0 {
+        format!("{:.2} GHz", mhz / 1000.0)
+    } else {
+        format!("{} MHz", mhz.floor())
+    }
+}
+
+/**
+ * Name:
+ * format_power_fraction
+ *
+ * Description:
+ * Format a power draw as a fraction of the enforced limit (given by nvidia-smi as i.e. `123.45 W, 250.00 W`)
+ *
+ * Made:
+ * 18/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Returns None if either value is missing (i.e. `[N/A]`) or the limit is zero
+ */
+pub fn format_power_fraction(line: &str) -> Option<String> {
+    // Grab both values
+    let values: Vec<f64> = line
+        .split(',')
+        .filter_map(|item| {
+            // Remove all non-number characters
+            let cleaned_item: String = item
+                .chars()
+                .filter(|c| c.is_ascii_digit() || c.eq(&'.'))
+                .collect();
+
+            cleaned_item.parse::<f64>().ok()
+        })
+        .collect();
+
+    // Apply formatting
+    match values.as_slice() {
+        [draw, limit] if *limit > 0.0 => Some(format!(
+            "{} / {} W ({}%)",
+            draw.floor(),
+            limit.floor(),
+            (draw / limit * 100.0).round()
+        )),
+        _ => None,
+    }
+}
+
+/**
+ * Name:
+ * format_perf_modes
+ *
+ * Description:
+ * Format the performance levels of a GPU (given by nvidia-settings as i.e. `perf=0, nvclock=300, ...; perf=1, ...`)
+ *
+ * Made:
+ * 18/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Shown as the number of levels & the highest graphics clock of all levels
+ */
+pub fn format_perf_modes(line: &str) -> String {
+    // Grab each performance level
+    let levels: Vec<&str> = line
+        .split(';')
+        .filter(|level| !level.trim().is_empty())
+        .collect();
+
+    // Find the highest graphics clock of all levels
+    let max_clock: f64 = levels
+        .iter()
+        .flat_map(|level| level.split(','))
+        .filter_map(|item| item.trim().strip_prefix("nvclockmax="))
+        .filter_map(|value| value.parse::<f64>().ok())
+        .fold(0.0, f64::max);
+
+    // Apply formatting
+    format!("{} (max {})", levels.len(), format_clock_speed(max_clock))
+}
+
+/**
+ * Name:
+ * pcie_speed_to_generation
+ *
+ * Description:
+ * Convert a PCIe link speed (given in MT/s by nvidia-settings) to the PCIe generation
+ *
+ * Made:
+ * 16/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Returns None for any unknown link speed
+ */
+pub fn pcie_speed_to_generation(speed: f64) -> Option<u32> {
+    match speed as u32 {
+        2500 => Some(1),
+        5000 => Some(2),
+        8000 => Some(3),
+        16000 => Some(4),
+        32000 => Some(5),
+        64000 => Some(6),
+        _ => None,
+    }
+}
+
+/**
+ * Name:
+ * find_utilization_value
+ *
+ * Description:
+ * Grab a single value from the nvidia-settings utilization list by it's key
+ *
+ * Made:
+ * 16/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * i.e. `PCIe` in `graphics=1, memory=2, video=0, PCIe=0`, None if missing
+ */
+pub fn find_utilization_value(line: &str, key: &str) -> Option<String> {
+    line.split(',').find_map(|item: &str| {
+        let (name, value) = item.trim().split_once('=')?;
+        if name == key {
+            Some(String::from(value.trim()))
+        } else {
+            None
+        }
+    })
+}
+
+/**
+ * Name:
+ * parse_pcie_throughput
+ *
+ * Description:
+ * Grab the PCIe RX or TX throughput (in KB/s) from the output of `nvidia-smi -q -d PCIE`
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * `direction` is either `Rx` or `Tx` (i.e. `Rx Throughput : 1500 KB/s`)
+ */
+pub fn parse_pcie_throughput(output: &str, direction: &str) -> Option<f64> {
+    let prefix: String = String::from(direction) + " Throughput";
+    output.lines().find_map(|line: &str| {
+        let (name, value) = line.trim().split_once(':')?;
+        if name.trim() == prefix {
+            value.split_whitespace().next()?.parse::<f64>().ok()
+        } else {
+            None
+        }
+    })
+}
+
+/**
+ * Name:
+ * format_throughput
+ *
+ * Description:
+ * Format a throughput (given in KB/s) as either KB/s or MB/s
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Anything at or above 1000KB/s is shown in MB/s (to 1 decimal place)
+ */
+pub fn format_throughput(kb_per_second: f64) -> String {
+    if kb_per_second >= 1000.0 {
+        format!("{:.1} MB/s", kb_per_second / 1000.0)
+    } else {
+        format!("{} KB/s", kb_per_second.floor())
+    }
+}
+
 /**
  * Trait Name:
  * Default
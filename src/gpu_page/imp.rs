@@ -24,16 +24,43 @@ use gio::Settings;
 use glib::{
     once_cell::sync::Lazy, once_cell::sync::OnceCell, subclass::InitializingObject,
     subclass::Signal, subclass::SignalType, translate::FromGlib, FromVariant, ParamSpec, SourceId,
-    ToValue, Value,
+    ToValue, Value, WeakRef,
 };
 use gtk::{
-    subclass::prelude::*, Align, CompositeTemplate, Grid, Label, LayoutChild, Orientation,
-    TemplateChild,
+    cairo, gdk::Display, gdk::RGBA, subclass::prelude::*, Align, CompositeTemplate, CssProvider,
+    DrawingArea, Grid, Label, LayoutChild, LevelBar, Orientation, StyleContext, TemplateChild,
+};
+use std::{
+    cell::Cell, cell::RefCell, collections::HashMap, collections::HashSet, f64::consts::PI, rc::Rc,
+    sync::Arc, sync::Mutex, sync::MutexGuard,
 };
-use std::{cell::Cell, cell::RefCell, rc::Rc, sync::Arc, sync::Mutex, sync::MutexGuard};
 
 // Modules
-use crate::{modificationwindow::ModificationWindow, provider::Provider};
+use crate::{
+    display_style::{fraction, DisplayKind, DisplayStyle},
+    fan_curve::{parse_temperature, FanCurveConfig, FanCurveController},
+    fan_override::{lock_fan_control, FanControlState, FanOverride},
+    fancurvewindow::FanCurveWindow,
+    history::{format_value, now_millis, with_history, PropertyHistory, STATISTICS_WINDOWS},
+    history_graph::HistoryGraph,
+    modificationwindow::ModificationWindow,
+    overclockwindow::OverclockWindow,
+    property::find_property_description,
+    provider::Provider,
+    subprocess::subprocess::SubprocessRunner,
+    targets::TargetType,
+    watchdog::lock_tuning,
+};
+
+/// Width (px) of the arc of a gauge
+const GAUGE_LINE_WIDTH: f64 = 5.0;
+/// Colour (red, green & blue) of a gauge when none is chosen
+const GAUGE_COLOUR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+thread_local! {
+    /// Colours of level bars already added as styles
+    static LOADED_COLOURS: RefCell<HashSet<String>> = RefCell::new(HashSet::new());
+}
 
 /// Structure for storing a SettingsWindow object and any related information
 #[derive(Default)]
@@ -42,6 +69,20 @@ pub struct ModificationWindowContainer {
     pub open: bool,
 }
 
+/// Structure for storing a FanCurveWindow object and any related information
+#[derive(Default)]
+pub struct FanCurveWindowContainer {
+    pub window: Option<FanCurveWindow>,
+    pub open: bool,
+}
+
+/// Structure for storing a OverclockWindow object and any related information
+#[derive(Default)]
+pub struct OverclockWindowContainer {
+    pub window: Option<OverclockWindow>,
+    pub open: bool,
+}
+
 /// Object holding the State and any Template Children
 #[derive(CompositeTemplate, Default)]
 #[template(resource = "/gpu-page.ui")]
@@ -53,9 +94,15 @@ pub struct GpuPage {
     refreshid: Cell<u32>,
 
     pub modification_window: Rc<RefCell<ModificationWindowContainer>>,
+    pub fan_curve_window: Rc<RefCell<FanCurveWindowContainer>>,
+    pub overclock_window: Rc<RefCell<OverclockWindowContainer>>,
+    pub fan_override: Cell<FanOverride>,
+    pub fan_override_timeout: RefCell<Option<SourceId>>,
 
     #[template_child]
     pub view_switcher: TemplateChild<ViewSwitcherBar>,
+    #[template_child]
+    pub health_banner: TemplateChild<Label>,
 }
 
 /// The central trait for subclassing a GObject
@@ -76,6 +123,245 @@ impl ObjectSubclass for GpuPage {
     }
 }
 
+/**
+ * Name:
+ * is_below_max
+ *
+ * Description:
+ * Compare two formatted values (i.e. `x8` and `x16`), true if the first is lower
+ *
+ * Made:
+ * 16/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Any non-digit characters are ignored
+ */
+fn is_below_max(current: &str, max: &str) -> bool {
+    // Remove all non-number characters
+    let current_digits: String = current.chars().filter(|c| c.is_ascii_digit()).collect();
+    let max_digits: String = max.chars().filter(|c| c.is_ascii_digit()).collect();
+
+    // Compare
+    match (current_digits.parse::<u32>(), max_digits.parse::<u32>()) {
+        (Ok(current_value), Ok(max_value)) => current_value < max_value,
+        _ => false,
+    }
+}
+
+/**
+ * Name:
+ * find_target_property
+ *
+ * Description:
+ * Find the target type (and row label) of a property shown as one row per target (i.e. fans)
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn find_target_property(property: &str) -> Option<(TargetType, &'static str)> {
+    match property {
+        "fans" => Some((TargetType::Fan, "Fan")),
+        "thermal_sensors" => Some((TargetType::ThermalSensor, "Thermal Sensor")),
+        _ => None,
+    }
+}
+
+/**
+ * Name:
+ * get_target_value
+ *
+ * Description:
+ * Grab the value shown in the row of a single fan/thermal sensor
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Fans show speed, RPM and target speed (i.e. `45 % / 1500 RPM (target 50 %)`)
+ */
+fn get_target_value(provider: &Provider, target_type: TargetType, index: u32) -> String {
+    // Grab a single value, anything unsupported is shown as N/A
+    let fetch = |property: &str| -> String {
+        match provider.get_target_data(index, property) {
+            Ok(value) => value,
+            Err(err) => {
+                println!("panicked when fetching target data: `{}`", err);
+                String::from("N/A")
+            }
+        }
+    };
+
+    match target_type {
+        TargetType::Fan => format!(
+            "{} / {} (target {})",
+            fetch("fan_speed"),
+            fetch("fan_speed_rpm"),
+            fetch("fan_target")
+        ),
+        TargetType::ThermalSensor => fetch("thermal_sensor"),
+        TargetType::Gpu => String::from("N/A"),
+    }
+}
+
+/**
+ * Name:
+ * format_statistics
+ *
+ * Description:
+ * Format the peak & average of a property, shown alongside it's current value
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Empty if nothing numeric has been recorded (i.e. N/A)
+ */
+fn format_statistics(history: &PropertyHistory, window: u64) -> String {
+    match (history.peak(), history.statistics(now_millis(), window)) {
+        (Some(peak), Some(statistics)) => format!(
+            "peak {} / avg {}",
+            format_value(peak, history.unit()),
+            format_value(statistics.average, history.unit())
+        ),
+        _ => String::new(),
+    }
+}
+
+/**
+ * Name:
+ * display_fraction
+ *
+ * Description:
+ * How full (0-1) a bar or gauge showing the latest reading of a property is
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * None if the latest reading failed (or isn't a number)
+ */
+fn display_fraction(history: &PropertyHistory, style: &DisplayStyle) -> Option<f64> {
+    let value: f64 = history.latest()?;
+    let (min, max): (f64, f64) = style.bounds(history.unit(), history.peak());
+
+    Some(fraction(value, min, max))
+}
+
+/**
+ * Name:
+ * colour_class
+ *
+ * Description:
+ * Find the css class colouring a level bar, loading it's style the first time it's used
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Styles added to a single widget don't reach the blocks inside a level bar, so these are added for the display
+ */
+fn colour_class(colour: &str) -> String {
+    let class: String = String::from("colour-") + colour.trim_start_matches('#');
+
+    LOADED_COLOURS.with(|loaded| {
+        if loaded.borrow_mut().insert(class.clone()) {
+            let provider: CssProvider = CssProvider::new();
+            provider.load_from_data(
+                format!(
+                    "levelbar.{} block.filled {{ background-color: {}; }}",
+                    class, colour
+                )
+                .as_bytes(),
+            );
+
+            match Display::default() {
+                Some(display) => StyleContext::add_provider_for_display(
+                    &display,
+                    &provider,
+                    gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+                ),
+                None => println!("..Could not connect to a display."),
+            }
+        }
+    });
+
+    class
+}
+
+/**
+ * Name:
+ * draw_gauge
+ *
+ * Description:
+ * Draw a half-circle gauge, filled to the passed fraction (0-1)
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only the track is drawn if there is no reading
+ */
+fn draw_gauge(
+    context: &cairo::Context,
+    width: f64,
+    height: f64,
+    fill: Option<f64>,
+    foreground: &RGBA,
+    colour: (f64, f64, f64),
+) -> Result<(), cairo::Error> {
+    let radius: f64 = (width / 2.0).min(height) - GAUGE_LINE_WIDTH;
+    if radius <= 0.0 {
+        return Ok(());
+    }
+    let (centre_x, centre_y): (f64, f64) = (width / 2.0, height - GAUGE_LINE_WIDTH / 2.0);
+    context.set_line_width(GAUGE_LINE_WIDTH);
+
+    // Track
+    context.set_source_rgba(
+        foreground.red() as f64,
+        foreground.green() as f64,
+        foreground.blue() as f64,
+        foreground.alpha() as f64 * 0.15,
+    );
+    context.arc(centre_x, centre_y, radius, PI, 2.0 * PI);
+    context.stroke()?;
+
+    // Reading
+    if let Some(fill) = fill {
+        if fill > 0.0 {
+            context.set_source_rgb(colour.0, colour.1, colour.2);
+            context.arc(centre_x, centre_y, radius, PI, PI + fill * PI);
+            context.stroke()?;
+        }
+    }
+
+    Ok(())
+}
+
 /**
  * Name:
  * GpuPage
@@ -188,18 +474,63 @@ impl GpuPage {
     pub fn create_properties(
         &self,
         grid: Grid,
-        properties: Vec<String>,
+        properties: Vec<(String, DisplayStyle)>,
         mut labels: Vec<Label>,
     ) -> (Grid, Vec<Label>) {
         // Load properties from struct
-        let properties_store: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(properties));
+        let properties_store: Arc<Mutex<Vec<(String, DisplayStyle)>>> =
+            Arc::new(Mutex::new(properties));
 
         // Grab grid manager
         let grid_manager = grid.layout_manager().unwrap();
 
-        // For each Property
-        for property in Arc::clone(&properties_store).lock().unwrap().iter() {
+        // Build list of rows, fans/thermal sensors get one row each
+        let mut rows: Vec<(String, String, i32, DisplayStyle)> = vec![];
+        for (property, style) in Arc::clone(&properties_store).lock().unwrap().iter() {
+            // Decide on title label & size
+            let (pretty_label, space): (String, i32) = match find_property_description(property) {
+                Some(description) => (String::from(description.label), description.space),
+                None => (property.to_owned(), 5),
+            };
+
+            match find_target_property(property) {
+                Some((target_type, target_label)) => {
+                    // Find all targets belonging to this gpu
+                    let targets: Vec<u32> = match self.provider.get() {
+                        Some(Some(provider)) => {
+                            match provider.get_targets(self.uuid.get().unwrap(), target_type) {
+                                Ok(found) => found,
+                                Err(err) => {
+                                    println!("panicked when fetching targets: `{}`", err);
+                                    vec![]
+                                }
+                            }
+                        }
+                        _ => vec![],
+                    };
+
+                    if targets.is_empty() {
+                        // Show a single (N/A) row
+                        rows.push((property.to_owned(), pretty_label, space, style.clone()));
+                    } else {
+                        for index in targets {
+                            rows.push((
+                                format!("{}:{}", target_type.name(), index),
+                                format!("{} {}", target_label, index),
+                                space,
+                                style.clone(),
+                            ));
+                        }
+                    }
+                }
+                None => rows.push((property.to_owned(), pretty_label, space, style.clone())),
+            }
+        }
+
+        // For each row
+        for (property, pretty_label, space, style) in rows.iter() {
             // println!("BUILDING PROPERTY LABEL: `{}`", property); //TEST
+            let space: i32 = *space;
 
             //==BUILD==
             // Build grid for 2 labels and attach to this page
@@ -229,57 +560,6 @@ impl GpuPage {
             // Fetch layout manager for this (grid) child
             match new_grid.layout_manager() {
                 Some(internal_grid_manager) => {
-                    // Decide on title label size
-                    let space: i32;
-                    let pretty_label: &str;
-                    //TODO: Update to use global list
-                    match property.to_owned().as_str() {
-                        "util" => {
-                            pretty_label = "GPU Utilization";
-                            space = 5
-                        }
-                        "mem_ctrl_util" => {
-                            pretty_label = "Memory Controller Utilization";
-                            space = 5
-                        }
-                        "encoder_util" => {
-                            pretty_label = "Encoder Utilization";
-                            space = 5
-                        }
-                        "decoder_util" => {
-                            pretty_label = "Decoder Utilization";
-                            space = 5
-                        }
-                        "fan_speed" => {
-                            pretty_label = "Fan Speed";
-                            space = 5
-                        }
-                        "temp" => {
-                            pretty_label = "Temperature";
-                            space = 5
-                        }
-                        "memory_usage" => {
-                            pretty_label = "Memory Usage";
-                            space = 8
-                        }
-                        "memory_total" => {
-                            pretty_label = "Memory Total";
-                            space = 8
-                        }
-                        "power_usage" => {
-                            pretty_label = "Power Usage";
-                            space = 8
-                        }
-                        "none" => {
-                            pretty_label = "None";
-                            space = 5
-                        }
-                        _ => {
-                            pretty_label = property;
-                            space = 5
-                        }
-                    }
-
                     // Build title label & add to grid
                     let new_title: String = String::from(property.to_owned()) + "_label";
                     let new_title_label: Label = Label::builder()
@@ -302,14 +582,6 @@ impl GpuPage {
                     title_manager.set_property("row-span", 1);
                     title_manager.set_property("column-span", 1);
 
-                    // Decide on content label size
-                    let space: i32;
-                    match property.to_owned().as_str() {
-                        "util" | "fan_speed" | "temp" | "none" => space = 5,
-                        "memory_usage" | "memory_total" => space = 8,
-                        _ => space = 5,
-                    }
-
                     // Build content label & add to grid
                     let new_content: String = String::from(property.to_owned());
                     let new_content_label: Label = Label::builder()
@@ -327,6 +599,26 @@ impl GpuPage {
                     content_manager.set_property("row-span", 1);
                     title_manager.set_property("column-span", 1);
 
+                    // Build statistics label & add to grid
+                    let new_statistics: String = String::from(property.to_owned()) + "_statistics";
+                    let new_statistics_label: Label = Label::builder()
+                        .label("")
+                        .name(&new_statistics)
+                        .halign(Align::End)
+                        .margin_start(12)
+                        .build();
+                    new_statistics_label.add_css_class("dim-label");
+                    new_grid.attach(&new_statistics_label, 2, 0, 1, 1);
+
+                    // Keep statistics label up to date as readings are recorded
+                    self.subscribe_statistics(&new_statistics_label, property);
+
+                    // Build chosen display (i.e. sparkline) of readings & add to grid
+                    if let Some(new_display) = self.create_display(property, pretty_label, style) {
+                        new_display.set_margin_start(12);
+                        new_grid.attach(&new_display, 3, 0, 1, 1);
+                    }
+
                     // Add to list of content labels, for updating in closure (see below)
                     labels.push(new_content_label);
                 }
@@ -342,6 +634,269 @@ impl GpuPage {
         (grid, labels)
     }
 
+    /**
+     * Name:
+     * create_display
+     *
+     * Description:
+     * Build the widget showing the readings of a property in it's chosen style (i.e. a level bar)
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Plain text needs nothing extra, as the reading is always shown in the content label
+     */
+    fn create_display(
+        &self,
+        property: &str,
+        title: &str,
+        style: &DisplayStyle,
+    ) -> Option<gtk::Widget> {
+        let uuid: &str = self.uuid.get().expect("`uuid` wasn't set properly..");
+
+        match style.kind {
+            DisplayKind::Text => None,
+            DisplayKind::Sparkline => {
+                let new_sparkline: HistoryGraph = HistoryGraph::new(uuid, property, title, false);
+                new_sparkline.set_widget_name(&(String::from(property) + "_sparkline"));
+                new_sparkline.set_display_style(style);
+
+                Some(new_sparkline.upcast::<gtk::Widget>())
+            }
+            DisplayKind::Bar => {
+                let new_bar: LevelBar = LevelBar::builder()
+                    .name(&(String::from(property) + "_bar"))
+                    .min_value(0.0)
+                    .max_value(1.0)
+                    .width_request(80)
+                    .valign(Align::Center)
+                    .build();
+                if let Some(colour) = &style.colour {
+                    new_bar.add_css_class(&colour_class(colour));
+                }
+
+                // Fill with the latest reading
+                let bar_style: DisplayStyle = style.clone();
+                self.subscribe_display(&new_bar, property, move |bar, property_history| {
+                    bar.set_value(display_fraction(property_history, &bar_style).unwrap_or(0.0));
+                });
+
+                Some(new_bar.upcast::<gtk::Widget>())
+            }
+            DisplayKind::Gauge => {
+                let new_gauge: DrawingArea = DrawingArea::builder()
+                    .name(&(String::from(property) + "_gauge"))
+                    .content_width(48)
+                    .content_height(28)
+                    .valign(Align::Center)
+                    .build();
+
+                // Draw the latest reading
+                let gauge_uuid: String = String::from(uuid);
+                let gauge_property: String = String::from(property);
+                let gauge_style: DisplayStyle = style.clone();
+                new_gauge.set_draw_func(
+                    move |area: &DrawingArea, context: &cairo::Context, width: i32, height: i32| {
+                        let fill: Option<f64> = with_history(|history| {
+                            history
+                                .get(&gauge_uuid, &gauge_property)
+                                .and_then(|property_history| {
+                                    display_fraction(property_history, &gauge_style)
+                                })
+                        });
+                        let colour: (f64, f64, f64) = gauge_style.rgb().unwrap_or(GAUGE_COLOUR);
+
+                        if let Err(err) = draw_gauge(
+                            context,
+                            width as f64,
+                            height as f64,
+                            fill,
+                            &area.style_context().color(),
+                            colour,
+                        ) {
+                            println!("..Attempt to draw gauge failed, returning: {}", err);
+                        }
+                    },
+                );
+                self.subscribe_display(&new_gauge, property, |gauge, _| gauge.queue_draw());
+
+                Some(new_gauge.upcast::<gtk::Widget>())
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * subscribe_display
+     *
+     * Description:
+     * Update the passed widget with the readings of a property, whenever it's recorded
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only a weak reference to the widget is kept, so the subscription ends once the view is rebuilt
+     */
+    fn subscribe_display<W: IsA<gtk::Widget>>(
+        &self,
+        widget: &W,
+        property: &str,
+        update: impl Fn(&W, &PropertyHistory) + 'static,
+    ) {
+        // Get stored UUID
+        let uuid: &str = self.uuid.get().expect("`uuid` wasn't set properly..");
+
+        let widget_ref: WeakRef<W> = widget.downgrade();
+        with_history(|history| {
+            // Show anything already recorded (i.e. after editing a view)
+            if let Some(property_history) = history.get(uuid, property) {
+                update(widget, property_history);
+            }
+
+            history.subscribe(
+                uuid,
+                property,
+                Box::new(move |property_history| match widget_ref.upgrade() {
+                    Some(widget) => {
+                        update(&widget, property_history);
+                        true
+                    }
+                    None => false,
+                }),
+            );
+        });
+    }
+
+    /**
+     * Name:
+     * subscribe_statistics
+     *
+     * Description:
+     * Show the peak & average of a property in the passed label, updated whenever it's recorded
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only a weak reference to the label is kept, so the subscription ends once the view is rebuilt
+     */
+    fn subscribe_statistics(&self, label: &Label, property: &str) {
+        // Get stored UUID
+        let uuid: &str = self.uuid.get().expect("`uuid` wasn't set properly..");
+
+        // Load statistics window from settings
+        let window: u64 =
+            match STATISTICS_WINDOWS.get(self.get_setting::<i32>("history-window") as usize) {
+                Some((_, seconds)) => *seconds,
+                None => 0,
+            };
+
+        let label_ref: WeakRef<Label> = label.downgrade();
+        with_history(|history| {
+            // Show anything already recorded (i.e. after editing a view)
+            if let Some(property_history) = history.get(uuid, property) {
+                label.set_label(&format_statistics(property_history, window));
+            }
+
+            history.subscribe(
+                uuid,
+                property,
+                Box::new(move |property_history| match label_ref.upgrade() {
+                    Some(label) => {
+                        label.set_label(&format_statistics(property_history, window));
+                        true
+                    }
+                    None => false,
+                }),
+            );
+        });
+    }
+
+    /**
+     * Name:
+     * create_fan_curve_controllers
+     *
+     * Description:
+     * Load any enabled fan curves for this gpu from settings, creating a controller for each
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Fan curves need the gpu index used by nvidia-settings, so only work with nvidia-settings based providers
+     */
+    fn create_fan_curve_controllers(
+        &self,
+        uuid: &str,
+        provider_store: &Arc<Mutex<Option<Provider>>>,
+    ) -> Vec<FanCurveController> {
+        // Load enabled fan curves for this gpu
+        let configs: Vec<FanCurveConfig> = self
+            .get_setting::<Vec<String>>("fancurves")
+            .iter()
+            .filter_map(|item| match FanCurveConfig::from_setting(item) {
+                Ok(config) => Some(config),
+                Err(err) => {
+                    println!("..Ignoring fan curve: {}", err);
+                    None
+                }
+            })
+            .filter(|config| {
+                config.uuid == uuid && config.enabled && !config.curve.points.is_empty()
+            })
+            .collect();
+        if configs.is_empty() {
+            // Hand fans back to the driver if their curves were disabled (i.e. by a profile)
+            if !lock_fan_control().is_empty() {
+                if let Some(provider) = &*provider_store.lock().unwrap() {
+                    if let Ok(gpu_index) = provider.get_gpu_index(uuid) {
+                        let mut fan_control: MutexGuard<FanControlState> = lock_fan_control();
+                        if fan_control.is_controlled(gpu_index) && !fan_control.is_fixed(gpu_index)
+                        {
+                            if let Err(err) = fan_control.release(&SubprocessRunner, gpu_index) {
+                                println!(
+                                    "..Attempt to restore fan control failed, returning: {}",
+                                    err
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+
+            return vec![];
+        }
+
+        // Find index of gpu
+        match &*provider_store.lock().unwrap() {
+            Some(provider) => match provider.get_gpu_index(uuid) {
+                Ok(gpu_index) => configs
+                    .into_iter()
+                    .map(|config| FanCurveController::new(gpu_index, config.fan, config.curve))
+                    .collect(),
+                Err(err) => {
+                    println!("..Attempt to enable fan curves failed, returning: {}", err);
+                    vec![]
+                }
+            },
+            None => vec![],
+        }
+    }
+
     /**
      * Name:
      * create_updater
@@ -394,6 +949,10 @@ impl GpuPage {
         // Load refresh time (s) from settings
         let refresh_rate: u32 = self.get_setting::<i32>("refreshrate") as u32;
 
+        // Load history retention (minutes) from settings
+        let retention: u64 = self.get_setting::<i32>("history-retention") as u64 * 60;
+        with_history(|history| history.set_retention(retention));
+
         // Create thread safe container for properties
         let properties_store: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(properties));
 
@@ -412,6 +971,16 @@ impl GpuPage {
             .to_owned();
         let provider_store: Arc<Mutex<Option<Provider>>> = Arc::new(Mutex::new(provider));
 
+        // Grab banner for showing any ECC errors
+        let health_banner: Label = self.health_banner.get();
+
+        // Create a controller for each enabled fan curve of this gpu
+        let mut fan_curve_controllers: Vec<FanCurveController> =
+            self.create_fan_curve_controllers(&uuid_store.lock().unwrap(), &provider_store);
+
+        // Max values of any PCIe link properties (i.e. `pcie_gen_max`)
+        let mut max_values: HashMap<String, String> = HashMap::new();
+
         // Async fill the labels
         let id: SourceId = glib::timeout_add_seconds_local(refresh_rate, move || {
             // Grab locked data
@@ -430,6 +999,9 @@ impl GpuPage {
             let labels_container: MutexGuard<Vec<Label>> = labels_lock.lock().unwrap();
             // println!("labels: `{}`", labels_container.len());
 
+            // Timestamp of any readings recorded
+            let now: u64 = now_millis();
+
             // For each Property
             match &mut *provider_container {
                 Some(current_provider) => {
@@ -444,10 +1016,69 @@ impl GpuPage {
                                     // no break here - could be duplicates
                                 }
                             }
+                        } else if let Some((target_type, _)) = find_target_property(property) {
+                            // Fill the row of each fan/thermal sensor (i.e. `fan:0`)
+                            let prefix: String = String::from(target_type.name()) + ":";
+                            for label in labels_container.iter() {
+                                if *property.to_owned() == label.widget_name() {
+                                    // No targets were found for this gpu
+                                    label.set_label("N/A");
+                                } else if let Some(index) = label
+                                    .widget_name()
+                                    .strip_prefix(prefix.as_str())
+                                    .and_then(|index| index.parse::<u32>().ok())
+                                {
+                                    let target_value: String =
+                                        get_target_value(current_provider, target_type, index);
+                                    label.set_label(&target_value);
+
+                                    // Record reading (i.e. `fan:0`)
+                                    with_history(|history| {
+                                        history.record_text(
+                                            &uuid,
+                                            &label.widget_name(),
+                                            now,
+                                            &target_value,
+                                        )
+                                    });
+                                }
+                            }
                         } else {
                             // Grab current Property from provider
                             match current_provider.get_gpu_data(&uuid, property) {
                                 Ok(property_value) => {
+                                    // Record reading, throttle reasons aren't a number
+                                    if property != "throttle_reasons" {
+                                        with_history(|history| {
+                                            history.record_text(
+                                                &uuid,
+                                                property,
+                                                now,
+                                                &property_value,
+                                            )
+                                        });
+                                    }
+
+                                    // Check if PCIe link is running below it's max (i.e. after reseating)
+                                    let mut degraded: bool = false;
+                                    if let "pcie_gen_current" | "pcie_width_current" =
+                                        property.as_str()
+                                    {
+                                        // Max link doesn't change, only query it once
+                                        let max_property: String =
+                                            property.replace("_current", "_max");
+                                        if !max_values.contains_key(&max_property) {
+                                            if let Ok(max_value) =
+                                                current_provider.get_gpu_data(&uuid, &max_property)
+                                            {
+                                                max_values.insert(max_property.clone(), max_value);
+                                            }
+                                        }
+                                        if let Some(max_value) = max_values.get(&max_property) {
+                                            degraded = is_below_max(&property_value, max_value);
+                                        }
+                                    }
+
                                     // For each output label of the page
                                     for label in labels_container.iter() {
                                         // println!("COMPARING AGAINST 1: `{}`", property);
@@ -455,14 +1086,34 @@ impl GpuPage {
 
                                         // Check if correct label
                                         if *property.to_owned() == label.widget_name() {
-                                            label.set_label(&property_value);
+                                            // Throttle reasons are shown as badges (i.e. markup)
+                                            if property == "throttle_reasons" {
+                                                label.set_markup(&property_value);
+                                            } else {
+                                                label.set_label(&property_value);
+                                            }
+
+                                            // Show warning state if required
+                                            if degraded {
+                                                label.add_css_class("warning");
+                                            } else {
+                                                label.remove_css_class("warning");
+                                            }
                                             // no break here - could be duplicates
                                         }
                                     }
                                 }
                                 Err(err) => {
+                                    // Show as unavailable (i.e. not supported by a MIG device) rather than stop refreshing
                                     println!("panicked when fetching gpu data: `{}`", err);
-                                    return Continue(false);
+                                    with_history(|history| {
+                                        history.record(&uuid, property, now, None)
+                                    });
+                                    for label in labels_container.iter() {
+                                        if *property.to_owned() == label.widget_name() {
+                                            label.set_label("N/A");
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -474,6 +1125,66 @@ impl GpuPage {
                 }
             }
 
+            // Apply any fan curves using the current temperature (unless the watchdog has tripped)
+            if !fan_curve_controllers.is_empty() && !lock_tuning().is_tripped() {
+                if let Some(current_provider) = &*provider_container {
+                    match current_provider
+                        .get_gpu_data(&uuid, "temp")
+                        .map(|temp| parse_temperature(&temp))
+                    {
+                        Ok(Some(temperature)) => {
+                            let mut fan_control: MutexGuard<FanControlState> = lock_fan_control();
+                            for controller in fan_curve_controllers.iter_mut() {
+                                // Leave alone while manually overridden
+                                if fan_control.is_fixed(controller.gpu_index) {
+                                    controller.reset();
+                                    continue;
+                                }
+                                // Re-apply once control has been handed back to the driver
+                                if !fan_control.is_controlled(controller.gpu_index) {
+                                    controller.reset();
+                                }
+
+                                match controller.apply(
+                                    &SubprocessRunner,
+                                    &mut fan_control,
+                                    temperature,
+                                ) {
+                                    Ok(Some(speed)) => {
+                                        println!("..fan {} set to {}%", controller.fan, speed)
+                                    }
+                                    Ok(None) => {}
+                                    Err(err) => {
+                                        println!("panicked when applying fan curve: `{}`", err)
+                                    }
+                                }
+                            }
+                        }
+                        _ => println!("panicked when fetching temperature for fan curve.."),
+                    }
+                }
+            }
+
+            // Check for any double-bit ECC errors, whether or not they're in the current view
+            let ecc_errors: bool = match &*provider_container {
+                Some(current_provider) => {
+                    ["ecc_dbe_volatile", "ecc_dbe_aggregate"]
+                        .iter()
+                        .any(|property: &&str| {
+                            matches!(
+                                current_provider
+                                    .get_gpu_data(&uuid, property)
+                                    .map(|count| count.parse::<u64>()),
+                                Ok(Ok(count)) if count > 0
+                            )
+                        })
+                }
+                None => false,
+            };
+
+            // Put page into error state if required
+            health_banner.set_visible(ecc_errors);
+
             Continue(true)
         });
 
@@ -490,7 +1201,7 @@ impl GpuPage {
      * check_properties_for_view
      *
      * Description:
-     * Using passed view title, returns list of saved properties
+     * Using passed view title, returns list of saved properties (& how they're displayed)
      *
      * Made:
      * 03/12/2022
@@ -501,7 +1212,7 @@ impl GpuPage {
      * Notes:
      *
      */
-    pub fn check_properties_for_view(&self, view_name: &str) -> Vec<String> {
+    pub fn check_properties_for_view(&self, view_name: &str) -> Vec<(String, DisplayStyle)> {
         // Load list of Properties for current Page
         let loaded_properties_data: Vec<String> =
             self.get_setting::<Vec<String>>("viewcomponentconfigs");
@@ -523,19 +1234,19 @@ impl GpuPage {
             vec![]
         } else {
             // Create temporary structure for sorting loaded data
-            let mut loaded_properties: Vec<String> =
-                vec![String::from(""); loaded_properties_data.len()];
+            let mut loaded_properties: Vec<(String, DisplayStyle)> =
+                vec![(String::from(""), DisplayStyle::default()); loaded_properties_data.len()];
 
             for index in 0..loaded_properties_data.len() {
                 // println!("item: `{}`", loaded_properties_data[index]); //TEST
 
-                // Split current item into the 4 parts
+                // Split current item into the 4 parts (5 with a display style)
                 let parts: Vec<&str> = loaded_properties_data[index]
                     .split(':')
                     .collect::<Vec<&str>>();
 
                 // Catch any malformed items
-                if parts.len() != 4 {
+                if parts.len() != 4 && parts.len() != 5 {
                     panic!("Malformed gschema data..");
                 }
 
@@ -555,8 +1266,25 @@ impl GpuPage {
                                     // println!("VALID POSITION INDEX"); //TEST
                                     // println!("VALID PROPERTY: `{}`", parts[3]); //TEST
 
+                                    // Load display style, if one was saved
+                                    let style: DisplayStyle = match parts.get(4) {
+                                        Some(saved_style) => {
+                                            match DisplayStyle::from_setting(saved_style) {
+                                                Ok(parsed) => parsed,
+                                                Err(err) => {
+                                                    println!(
+                                                        "..Ignoring saved display style: {}",
+                                                        err
+                                                    );
+                                                    DisplayStyle::default()
+                                                }
+                                            }
+                                        }
+                                        None => DisplayStyle::default(),
+                                    };
+
                                     // Add to final list
-                                    loaded_properties[position] = parts[3].to_owned();
+                                    loaded_properties[position] = (parts[3].to_owned(), style);
                                 }
                             }
                             Err(_) => panic!("Invalid Property position in gschema data.."),
@@ -566,7 +1294,7 @@ impl GpuPage {
             }
 
             // Remove any empty properties
-            loaded_properties.retain(|x| *x != "");
+            loaded_properties.retain(|(x, _)| *x != "");
 
             // Return final list
             loaded_properties.to_owned()
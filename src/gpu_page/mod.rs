@@ -19,17 +19,39 @@
  */
 // Custom GObjects
 mod imp;
-use imp::ModificationWindowContainer;
+use imp::{FanCurveWindowContainer, ModificationWindowContainer, OverclockWindowContainer};
 
 // Imports
 use adwaita::{gio, glib, Application, ViewStack};
 use gio::Settings;
-use glib::{clone, closure, Object};
-use gtk::{prelude::*, subclass::prelude::*, Align, Button, Grid, Label, LayoutChild, Orientation};
-use std::cell::RefMut;
+use glib::{clone, closure, signal::Inhibit, Continue, Object, SourceId};
+use gtk::{
+    prelude::*, subclass::prelude::*, Align, Button, DropDown, Grid, Label, LayoutChild,
+    Orientation, Scale, SpinButton, Switch,
+};
+use std::{cell::RefMut, ffi::OsStr, thread, time::Duration};
 
 // Modules
-use crate::{modificationwindow::ModificationWindow, provider::Provider, APP_ID};
+use crate::{
+    display_style::DisplayStyle,
+    fan_override::{apply_fixed_speed, release_fan_control, FanOverride},
+    fancurvewindow::FanCurveWindow,
+    helper::{create_helper_command, ComputeMode, HelperOperation, MAX_CLOCK, MAX_POWER_LIMIT},
+    history::with_history,
+    modificationwindow::ModificationWindow,
+    overclock::{ClockOffset, OffsetRange},
+    overclockwindow::OverclockWindow,
+    property::HEALTH_VIEW_TEMPLATE,
+    provider::Provider,
+    subprocess::subprocess::{exec_check_callback, SubprocessRunner},
+    targets::TargetType,
+    watchdog::lock_tuning,
+    APP_ID,
+};
+
+// Constants
+/// Delay after the fan override slider stops moving before the speed is applied
+const FAN_OVERRIDE_DELAY: Duration = Duration::from_millis(500);
 
 // GObject wrapper for GpuPage
 glib::wrapper! {
@@ -277,6 +299,81 @@ impl GpuPage {
                     let child_manager: LayoutChild = grid_manager.layout_child(&add_view_button);
                     child_manager.set_property("row-span", 2);
                     child_manager.set_property("column-span", 2);
+
+                    // Create add_health_view_button
+                    let add_health_view_button: Button = Button::builder()
+                        .name("add_health_view_button")
+                        .label("Add Health View")
+                        .margin_start(12)
+                        .margin_end(12)
+                        .margin_top(12)
+                        .margin_bottom(12)
+                        .halign(Align::Center)
+                        .build();
+                    new_grid.attach(&add_health_view_button, 0, 82 as i32, 1, 1);
+                    add_health_view_button.connect_clicked(
+                        clone!(@weak self as gpage => move |_| {
+                            // Add view from template
+                            gpage.add_view_template("Health", HEALTH_VIEW_TEMPLATE);
+                        }),
+                    );
+
+                    // Set layout properties of button
+                    let child_manager: LayoutChild =
+                        grid_manager.layout_child(&add_health_view_button);
+                    child_manager.set_property("row-span", 2);
+                    child_manager.set_property("column-span", 2);
+
+                    // Create fan_curve_button
+                    let fan_curve_button: Button = Button::builder()
+                        .name("fan_curve_button")
+                        .label("Fan Curve")
+                        .margin_start(12)
+                        .margin_end(12)
+                        .margin_top(12)
+                        .margin_bottom(12)
+                        .halign(Align::Center)
+                        .build();
+                    new_grid.attach(&fan_curve_button, 0, 84 as i32, 1, 1);
+                    fan_curve_button.connect_clicked(clone!(@weak self as gpage => move |_| {
+                        // Open fan curve editor
+                        gpage.open_fan_curve_window();
+                    }));
+
+                    // Set layout properties of button
+                    let child_manager: LayoutChild = grid_manager.layout_child(&fan_curve_button);
+                    child_manager.set_property("row-span", 2);
+                    child_manager.set_property("column-span", 2);
+
+                    // Create overclock_button
+                    let overclock_button: Button = Button::builder()
+                        .name("overclock_button")
+                        .label("Overclock")
+                        .margin_start(12)
+                        .margin_end(12)
+                        .margin_top(12)
+                        .margin_bottom(12)
+                        .halign(Align::Center)
+                        .build();
+                    new_grid.attach(&overclock_button, 0, 86 as i32, 1, 1);
+                    overclock_button.connect_clicked(clone!(@weak self as gpage => move |_| {
+                        // Open clock offset editor
+                        gpage.open_overclock_window();
+                    }));
+
+                    // Set layout properties of button
+                    let child_manager: LayoutChild = grid_manager.layout_child(&overclock_button);
+                    child_manager.set_property("row-span", 2);
+                    child_manager.set_property("column-span", 2);
+
+                    // Create manual fan override controls
+                    let fan_override_box: gtk::Box = self.create_fan_override_controls();
+                    new_grid.attach(&fan_override_box, 0, 88 as i32, 1, 1);
+
+                    // Set layout properties of controls
+                    let child_manager: LayoutChild = grid_manager.layout_child(&fan_override_box);
+                    child_manager.set_property("row-span", 2);
+                    child_manager.set_property("column-span", 2);
                 }
                 None => panic!("Cannot fetch layout manager of grid.."),
             }
@@ -291,6 +388,9 @@ impl GpuPage {
             //      function "add_titled_with_icon" not in stable yet
             let new_item = new_stack.page(&new_grid);
             new_item.set_icon_name(Some("package-x-generic-symbolic"));
+
+            // Add tuning view
+            self.add_tuning_view(&content_grid, &new_stack);
             // Replace current view stack
             self.imp().replace_stack(Some(&new_stack));
         } else {
@@ -338,13 +438,13 @@ impl GpuPage {
             for index in 0..loaded_views.len() {
                 // println!("VIEW {}", index);
                 // Grab all saved properties
-                let properties: Vec<String> =
+                let properties: Vec<(String, DisplayStyle)> =
                     self.imp().check_properties_for_view(&loaded_views[index]);
 
                 // println!("GOT {} PROPERTIES FOR VIEW {}", properties.len(), index);
 
                 // Add property items to the final list
-                for prop in &properties {
+                for (prop, _) in &properties {
                     props.push(prop.as_str().clone().to_owned());
                 }
 
@@ -541,6 +641,117 @@ impl GpuPage {
                             grid_manager.layout_child(&add_view_button);
                         child_manager.set_property("row-span", 2);
                         child_manager.set_property("column-span", 2);
+
+                        // Create add_health_view_button
+                        let add_health_view_button: Button = Button::builder()
+                            .name("add_health_view_button")
+                            .label("Add Health View")
+                            .margin_start(12)
+                            .margin_end(12)
+                            .margin_top(12)
+                            .margin_bottom(12)
+                            .halign(Align::Center)
+                            .build();
+                        new_view_grid.attach(&add_health_view_button, 0, 84 as i32, 1, 1);
+                        add_health_view_button.connect_clicked(
+                            clone!(@weak self as gpage => move |_| {
+                                // Add view from template
+                                gpage.add_view_template("Health", HEALTH_VIEW_TEMPLATE);
+                            }),
+                        );
+
+                        // Set layout properties of button
+                        let child_manager: LayoutChild =
+                            grid_manager.layout_child(&add_health_view_button);
+                        child_manager.set_property("row-span", 2);
+                        child_manager.set_property("column-span", 2);
+
+                        // Create fan_curve_button
+                        let fan_curve_button: Button = Button::builder()
+                            .name("fan_curve_button")
+                            .label("Fan Curve")
+                            .margin_start(12)
+                            .margin_end(12)
+                            .margin_top(12)
+                            .margin_bottom(12)
+                            .halign(Align::Center)
+                            .build();
+                        new_view_grid.attach(&fan_curve_button, 0, 86 as i32, 1, 1);
+                        fan_curve_button.connect_clicked(clone!(@weak self as gpage => move |_| {
+                            // Open fan curve editor
+                            gpage.open_fan_curve_window();
+                        }));
+
+                        // Set layout properties of button
+                        let child_manager: LayoutChild =
+                            grid_manager.layout_child(&fan_curve_button);
+                        child_manager.set_property("row-span", 2);
+                        child_manager.set_property("column-span", 2);
+
+                        // Create overclock_button
+                        let overclock_button: Button = Button::builder()
+                            .name("overclock_button")
+                            .label("Overclock")
+                            .margin_start(12)
+                            .margin_end(12)
+                            .margin_top(12)
+                            .margin_bottom(12)
+                            .halign(Align::Center)
+                            .build();
+                        new_view_grid.attach(&overclock_button, 0, 88 as i32, 1, 1);
+                        overclock_button.connect_clicked(clone!(@weak self as gpage => move |_| {
+                            // Open clock offset editor
+                            gpage.open_overclock_window();
+                        }));
+
+                        // Set layout properties of button
+                        let child_manager: LayoutChild =
+                            grid_manager.layout_child(&overclock_button);
+                        child_manager.set_property("row-span", 2);
+                        child_manager.set_property("column-span", 2);
+
+                        // Create manual fan override controls
+                        let fan_override_box: gtk::Box = self.create_fan_override_controls();
+                        new_view_grid.attach(&fan_override_box, 0, 90 as i32, 1, 1);
+
+                        // Set layout properties of controls
+                        let child_manager: LayoutChild =
+                            grid_manager.layout_child(&fan_override_box);
+                        child_manager.set_property("row-span", 2);
+                        child_manager.set_property("column-span", 2);
+
+                        // Create reset_peaks_button
+                        let reset_peaks_button: Button = Button::builder()
+                            .name("reset_peaks_button")
+                            .label("Reset Peaks")
+                            .margin_start(12)
+                            .margin_end(12)
+                            .margin_top(12)
+                            .margin_bottom(12)
+                            .halign(Align::Center)
+                            .build();
+                        new_view_grid.attach(&reset_peaks_button, 0, 92 as i32, 1, 1);
+                        reset_peaks_button.connect_clicked(
+                            clone!(@weak self as gpage => move |_| {
+                                // Start tracking peaks again from current readings
+                                gpage.reset_peaks();
+                            }),
+                        );
+
+                        // Set layout properties of button
+                        let child_manager: LayoutChild =
+                            grid_manager.layout_child(&reset_peaks_button);
+                        child_manager.set_property("row-span", 2);
+                        child_manager.set_property("column-span", 2);
+
+                        // Create log to file controls
+                        let log_box: gtk::Box = self.create_log_controls();
+                        new_view_grid.attach(&log_box, 0, 94 as i32, 1, 1);
+
+                        // Set layout properties of controls
+                        let child_manager: LayoutChild = grid_manager.layout_child(&log_box);
+                        child_manager.set_property("row-span", 2);
+                        child_manager.set_property("column-span", 2);
                     }
                     None => panic!("Cannot fetch layout manager of grid.."),
                 }
@@ -561,6 +772,9 @@ impl GpuPage {
                 new_item.set_icon_name(Some("package-x-generic-symbolic"));
             }
 
+            // Add tuning view
+            self.add_tuning_view(&content_grid, &new_stack);
+
             // if properties exist, call create_updater() function to add time-delayed callback to update appropriate labels
             if props.len() > 0 {
                 self.imp().create_updater(labels, props);
@@ -573,6 +787,740 @@ impl GpuPage {
         }
     }
 
+    /**
+     * Name:
+     * add_tuning_view
+     *
+     * Description:
+     * Add a view for changing settings that need root (power limit, locked clocks, persistence & compute mode)
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Changes are made by the privileged helper, so each one asks for authentication
+     */
+    fn add_tuning_view(&self, content_grid: &Grid, stack: &ViewStack) {
+        // Create grid
+        let tuning_grid: Grid = Grid::builder()
+            .name("tuning_grid")
+            .orientation(Orientation::Horizontal)
+            .row_spacing(12)
+            .column_spacing(12)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+        content_grid.attach(&tuning_grid, 0, 0 as i32, 100, 12);
+
+        // Label for showing the result of changes
+        let status_label: Label = Label::builder()
+            .name("tuning_status")
+            .halign(Align::Start)
+            .wrap(true)
+            .build();
+        tuning_grid.attach(&status_label, 0, 5, 5, 1);
+
+        // Power limit, within what the GPU allows (and the helper accepts)
+        let max_power_limit: f64 = self
+            .read_gpu_number("power_max_limit")
+            .unwrap_or(MAX_POWER_LIMIT as f64)
+            .clamp(1.0, MAX_POWER_LIMIT as f64)
+            .round();
+        let min_power_limit: f64 = self
+            .read_gpu_number("power_min_limit")
+            .unwrap_or(1.0)
+            .clamp(1.0, max_power_limit)
+            .round();
+        let current_power_limit: f64 = self
+            .read_gpu_number("power_limit")
+            .unwrap_or(max_power_limit);
+        let power_limit_input: SpinButton =
+            SpinButton::with_range(min_power_limit, max_power_limit, 1.0);
+        power_limit_input.set_value(current_power_limit.round());
+        let power_limit_button: Button = Button::with_label("Set");
+        power_limit_button.connect_clicked(
+            clone!(@weak self as gpage, @weak power_limit_input, @weak status_label => move |_| {
+                gpage.run_helper(HelperOperation::SetPowerLimit {
+                    uuid: gpage.property::<String>("uuid"),
+                    watts: power_limit_input.value() as u32,
+                }, &status_label);
+            }),
+        );
+        tuning_grid.attach(&Label::new(Some("Power Limit (W)")), 0, 0, 1, 1);
+        tuning_grid.attach(&power_limit_input, 1, 0, 1, 1);
+        tuning_grid.attach(&power_limit_button, 3, 0, 1, 1);
+
+        // Locked clocks (gpu & memory), up to the highest clock the GPU supports, defaulting to current -> highest
+        for (row, title, current_property, max_property) in [
+            (1, "Locked GPU Clocks (MHz)", "gr_clock", "gr_clock_max"),
+            (
+                2,
+                "Locked Memory Clocks (MHz)",
+                "mem_clock",
+                "mem_clock_max",
+            ),
+        ] {
+            let max_clock: f64 = self
+                .read_gpu_number(max_property)
+                .unwrap_or(MAX_CLOCK as f64)
+                .clamp(1.0, MAX_CLOCK as f64)
+                .round();
+            let current_clock: f64 = self.read_gpu_number(current_property).unwrap_or(1.0);
+
+            let min_input: SpinButton = SpinButton::with_range(1.0, max_clock, 1.0);
+            min_input.set_value(current_clock.round());
+            let max_input: SpinButton = SpinButton::with_range(1.0, max_clock, 1.0);
+            max_input.set_value(max_clock);
+
+            let lock_button: Button = Button::with_label("Lock");
+            lock_button.connect_clicked(
+                clone!(@weak self as gpage, @weak min_input, @weak max_input, @weak status_label => move |_| {
+                    let uuid: String = gpage.property::<String>("uuid");
+                    let (min, max): (u32, u32) = (min_input.value() as u32, max_input.value() as u32);
+                    let operation: HelperOperation = match row {
+                        1 => HelperOperation::LockGpuClocks { uuid, min, max },
+                        _ => HelperOperation::LockMemoryClocks { uuid, min, max },
+                    };
+                    gpage.run_helper(operation, &status_label);
+                }),
+            );
+            let reset_button: Button = Button::with_label("Reset");
+            reset_button.connect_clicked(
+                clone!(@weak self as gpage, @weak status_label => move |_| {
+                    let uuid: String = gpage.property::<String>("uuid");
+                    let operation: HelperOperation = match row {
+                        1 => HelperOperation::ResetGpuClocks { uuid },
+                        _ => HelperOperation::ResetMemoryClocks { uuid },
+                    };
+                    gpage.run_helper(operation, &status_label);
+                }),
+            );
+
+            tuning_grid.attach(&Label::new(Some(title)), 0, row, 1, 1);
+            tuning_grid.attach(&min_input, 1, row, 1, 1);
+            tuning_grid.attach(&max_input, 2, row, 1, 1);
+            tuning_grid.attach(&lock_button, 3, row, 1, 1);
+            tuning_grid.attach(&reset_button, 4, row, 1, 1);
+        }
+
+        // Persistence mode
+        let persistence_input: DropDown = DropDown::from_strings(&["Off", "On"]);
+        let persistence_button: Button = Button::with_label("Set");
+        persistence_button.connect_clicked(
+            clone!(@weak self as gpage, @weak persistence_input, @weak status_label => move |_| {
+                gpage.run_helper(HelperOperation::SetPersistenceMode {
+                    uuid: gpage.property::<String>("uuid"),
+                    enabled: persistence_input.selected() == 1,
+                }, &status_label);
+            }),
+        );
+        tuning_grid.attach(&Label::new(Some("Persistence Mode")), 0, 3, 1, 1);
+        tuning_grid.attach(&persistence_input, 1, 3, 1, 1);
+        tuning_grid.attach(&persistence_button, 3, 3, 1, 1);
+
+        // Compute mode
+        let compute_mode_labels: Vec<&str> =
+            ComputeMode::all().iter().map(|mode| mode.label()).collect();
+        let compute_mode_input: DropDown = DropDown::from_strings(&compute_mode_labels);
+        let compute_mode_button: Button = Button::with_label("Set");
+        compute_mode_button.connect_clicked(
+            clone!(@weak self as gpage, @weak compute_mode_input, @weak status_label => move |_| {
+                let mode: ComputeMode = ComputeMode::all()
+                    .get(compute_mode_input.selected() as usize)
+                    .copied()
+                    .unwrap_or(ComputeMode::Default);
+                gpage.run_helper(HelperOperation::SetComputeMode {
+                    uuid: gpage.property::<String>("uuid"),
+                    mode,
+                }, &status_label);
+            }),
+        );
+        tuning_grid.attach(&Label::new(Some("Compute Mode")), 0, 4, 1, 1);
+        tuning_grid.attach(&compute_mode_input, 1, 4, 1, 1);
+        tuning_grid.attach(&compute_mode_button, 3, 4, 1, 1);
+
+        // Save built view
+        stack.add_titled(&tuning_grid, Some("tuning"), "Tuning");
+        let new_item = stack.page(&tuning_grid);
+        new_item.set_icon_name(Some("preferences-system-symbolic"));
+    }
+
+    /**
+     * Name:
+     * read_gpu_number
+     *
+     * Description:
+     * Read a property of this GPU as a number (ignoring any unit)
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * None if the property can't be read (i.e. `[N/A]`)
+     */
+    fn read_gpu_number(&self, property: &str) -> Option<f64> {
+        self.property::<Provider>("provider")
+            .get_gpu_data(&self.property::<String>("uuid"), property)
+            .ok()?
+            .split_whitespace()
+            .next()
+            .and_then(|number| number.parse::<f64>().ok())
+    }
+
+    /**
+     * Name:
+     * run_helper
+     *
+     * Description:
+     * Run an operation using the privileged helper, showing the result
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Runs asynchronously, the result is shown once the helper exits
+     */
+    fn run_helper(&self, operation: HelperOperation, status_label: &Label) {
+        let command: Vec<String> = match create_helper_command(&operation) {
+            Ok(valid_command) => valid_command,
+            Err(err) => {
+                println!("..Attempt to run helper failed, returning: {}", err);
+                status_label.set_label(&err);
+                return;
+            }
+        };
+
+        // Run without blocking the UI while pkexec asks for a password
+        status_label.set_label("Waiting for authentication..");
+        let argv: Vec<&OsStr> = command.iter().map(OsStr::new).collect();
+        exec_check_callback(
+            &argv,
+            None::<&gio::Cancellable>,
+            clone!(@weak self as gpage, @weak status_label => move |result: Result<(), String>| {
+                match result {
+                    Ok(_) => {
+                        // Arm the watchdog
+                        if matches!(
+                            operation,
+                            HelperOperation::SetPowerLimit { .. }
+                                | HelperOperation::LockGpuClocks { .. }
+                                | HelperOperation::LockMemoryClocks { .. }
+                        ) {
+                            lock_tuning().mark_tuned(&gpage.property::<String>("uuid"));
+                        }
+
+                        status_label.set_label("Changes applied")
+                    }
+                    Err(err) => {
+                        println!("..Attempt to run helper failed, returning: {}", err);
+                        status_label.set_label(&err);
+                    }
+                }
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * open_fan_curve_window
+     *
+     * Description:
+     * Open the fan curve editor for this page (if not already open)
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn open_fan_curve_window(&self) {
+        // Borrow (mutable) the window's container
+        let mut fan_curve_window_container: RefMut<FanCurveWindowContainer> =
+            self.imp().fan_curve_window.borrow_mut();
+
+        // Get state from settings
+        fan_curve_window_container.open = self.imp().get_setting::<bool>("fancurve-open");
+
+        // Check if the window is already open
+        if fan_curve_window_container.open && fan_curve_window_container.window.is_some() {
+            println!("....window already open"); //DEBUG
+            return;
+        }
+
+        // Find fans belonging to this gpu
+        let uuid: String = self.property::<String>("uuid");
+        let fans: Vec<u32> = match self
+            .property::<Provider>("provider")
+            .get_targets(&uuid, TargetType::Fan)
+        {
+            Ok(found) => found,
+            Err(err) => {
+                println!("..Attempt to read fans failed, returning: {}", err);
+                vec![]
+            }
+        };
+
+        // Create an app object
+        let app: Application = Application::builder().application_id(APP_ID).build();
+
+        // Create fan curve window
+        let new_fan_curve_window: FanCurveWindow = FanCurveWindow::new(&app, &uuid, fans, self);
+
+        // Show new fan curve window
+        new_fan_curve_window.show();
+
+        // Store object and state back in container
+        fan_curve_window_container.open = true;
+        fan_curve_window_container.window = Some(new_fan_curve_window);
+
+        // Set new state in settings
+        self.imp()
+            .update_setting::<bool>("fancurve-open", fan_curve_window_container.open);
+    }
+
+    /**
+     * Name:
+     * open_overclock_window
+     *
+     * Description:
+     * Open the clock offset editor for this page (if not already open)
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn open_overclock_window(&self) {
+        // Borrow (mutable) the window's container
+        let mut overclock_window_container: RefMut<OverclockWindowContainer> =
+            self.imp().overclock_window.borrow_mut();
+
+        // Get state from settings
+        overclock_window_container.open = self.imp().get_setting::<bool>("overclock-open");
+
+        // Check if the window is already open
+        if overclock_window_container.open && overclock_window_container.window.is_some() {
+            println!("....window already open"); //DEBUG
+            return;
+        }
+
+        // Find current offsets (and their ranges)
+        let uuid: String = self.property::<String>("uuid");
+        let provider: Provider = self.property::<Provider>("provider");
+        let gpu_index: u32 = provider.get_gpu_index(&uuid).unwrap_or(0);
+        let find_offset = |offset: ClockOffset| -> Option<(i32, OffsetRange)> {
+            match provider.get_clock_offset(&uuid, offset) {
+                Ok(found) => Some(found),
+                Err(err) => {
+                    println!(
+                        "..Attempt to read {} failed, returning: {}",
+                        offset.label(),
+                        err
+                    );
+                    None
+                }
+            }
+        };
+        let graphics_offset: Option<(i32, OffsetRange)> = find_offset(ClockOffset::Graphics);
+        let memory_offset: Option<(i32, OffsetRange)> = find_offset(ClockOffset::Memory);
+
+        // Create an app object
+        let app: Application = Application::builder().application_id(APP_ID).build();
+
+        // Create overclock window
+        let new_overclock_window: OverclockWindow =
+            OverclockWindow::new(&app, &uuid, gpu_index, graphics_offset, memory_offset, self);
+
+        // Show new overclock window
+        new_overclock_window.show();
+
+        // Store object and state back in container
+        overclock_window_container.open = true;
+        overclock_window_container.window = Some(new_overclock_window);
+
+        // Set new state in settings
+        self.imp()
+            .update_setting::<bool>("overclock-open", overclock_window_container.open);
+    }
+
+    /**
+     * Name:
+     * create_fan_override_controls
+     *
+     * Description:
+     * Create a toggle and slider for manually fixing the fan speed of this gpu
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * State is kept in the page, so rebuilt views show the current override
+     */
+    fn create_fan_override_controls(&self) -> gtk::Box {
+        let fan_override: FanOverride = self.imp().fan_override.get();
+
+        // Create container
+        let fan_override_box: gtk::Box = gtk::Box::builder()
+            .name("fan_override_box")
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        // Create label, toggle and slider
+        let fan_override_label: Label = Label::builder()
+            .label("Fan Override")
+            .halign(Align::Start)
+            .build();
+        let fan_override_switch: Switch = Switch::builder()
+            .name("fan_override_switch")
+            .active(fan_override.active)
+            .valign(Align::Center)
+            .build();
+        let fan_override_scale: Scale = Scale::with_range(Orientation::Horizontal, 0.0, 100.0, 1.0);
+        fan_override_scale.set_widget_name("fan_override_scale");
+        fan_override_scale.set_value(fan_override.speed as f64);
+        fan_override_scale.set_draw_value(true);
+        fan_override_scale.set_hexpand(true);
+        fan_override_scale.set_width_request(200);
+
+        fan_override_box.append(&fan_override_label);
+        fan_override_box.append(&fan_override_switch);
+        fan_override_box.append(&fan_override_scale);
+
+        // Take or hand back fan control when toggled, only changing state once the command succeeds
+        fan_override_switch.connect_state_set(
+            clone!(@weak self as gpage => @default-return Inhibit(true), move |switch, requested| {
+            let mut fan_override: FanOverride = gpage.imp().fan_override.get();
+            if fan_override.active == requested {
+                return Inhibit(false);
+            }
+            fan_override.active = requested;
+
+            // Any speed change waiting to be applied is included (or no longer wanted)
+            if let Some(pending) = gpage.imp().fan_override_timeout.take() {
+                pending.remove();
+            }
+
+            switch.set_sensitive(false);
+            gpage.apply_fan_override(
+                fan_override,
+                clone!(@weak switch => move |gpage: &GpuPage, result: Result<(), String>| {
+                    switch.set_sensitive(true);
+                    match result {
+                        Ok(_) => {
+                            let current: FanOverride = FanOverride {
+                                speed: gpage.imp().fan_override.get().speed,
+                                ..fan_override
+                            };
+                            gpage.imp().fan_override.set(current);
+                            switch.set_state(requested);
+
+                            // Slider moved while taking control
+                            if current.active && current.speed != fan_override.speed {
+                                gpage.apply_fan_override(current, |_: &GpuPage, result: Result<(), String>| {
+                                    if let Err(err) = result {
+                                        println!("..Attempt to change fan speed failed, returning: {}", err);
+                                    }
+                                });
+                            }
+                        }
+                        Err(err) => {
+                            println!("..Attempt to change fan override failed, returning: {}", err);
+
+                            // Show as it still is
+                            switch.set_active(!requested);
+                        }
+                    }
+                }),
+            );
+
+            Inhibit(true)
+        }));
+
+        // Change speed once the slider stops moving (not on every step of a drag)
+        fan_override_scale.connect_value_changed(clone!(@weak self as gpage => move |scale| {
+            let mut fan_override: FanOverride = gpage.imp().fan_override.get();
+            let speed: u32 = scale.value().round() as u32;
+            if fan_override.speed == speed {
+                return;
+            }
+            fan_override.speed = speed;
+            gpage.imp().fan_override.set(fan_override);
+
+            // Replace any change still waiting to be applied
+            if let Some(pending) = gpage.imp().fan_override_timeout.take() {
+                pending.remove();
+            }
+            if fan_override.active {
+                let id: SourceId = glib::timeout_add_local_once(
+                    FAN_OVERRIDE_DELAY,
+                    clone!(@weak gpage => move || {
+                        gpage.imp().fan_override_timeout.replace(None);
+
+                        gpage.apply_fan_override(
+                            gpage.imp().fan_override.get(),
+                            |_: &GpuPage, result: Result<(), String>| {
+                                if let Err(err) = result {
+                                    println!("..Attempt to change fan speed failed, returning: {}", err);
+                                }
+                            },
+                        );
+                    }),
+                );
+                gpage.imp().fan_override_timeout.replace(Some(id));
+            }
+        }));
+
+        fan_override_box
+    }
+
+    /**
+     * Name:
+     * create_log_controls
+     *
+     * Description:
+     * Create a toggle for logging every reading of this gpu to file
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Logging itself is done by the main window, for every gpu in `logged-gpus`
+     */
+    fn create_log_controls(&self) -> gtk::Box {
+        let uuid: String = self.property::<String>("uuid");
+        let logged: bool = self
+            .imp()
+            .get_setting::<Vec<String>>("logged-gpus")
+            .contains(&uuid);
+
+        // Create container
+        let log_box: gtk::Box = gtk::Box::builder()
+            .name("log_box")
+            .orientation(Orientation::Horizontal)
+            .spacing(12)
+            .margin_start(12)
+            .margin_end(12)
+            .margin_top(12)
+            .margin_bottom(12)
+            .build();
+
+        // Create label and toggle
+        let log_label: Label = Label::builder()
+            .label("Log to File")
+            .halign(Align::Start)
+            .build();
+        let log_switch: Switch = Switch::builder()
+            .name("log_switch")
+            .active(logged)
+            .valign(Align::Center)
+            .build();
+
+        log_box.append(&log_label);
+        log_box.append(&log_switch);
+
+        // Add or remove this gpu from the list of logged gpus when toggled
+        log_switch.connect_active_notify(clone!(@weak self as gpage => move |switch| {
+            let uuid: String = gpage.property::<String>("uuid");
+            let mut logged_gpus: Vec<String> =
+                gpage.imp().get_setting::<Vec<String>>("logged-gpus");
+            if logged_gpus.contains(&uuid) == switch.is_active() {
+                return;
+            }
+
+            if switch.is_active() {
+                logged_gpus.push(uuid);
+            } else {
+                logged_gpus.retain(|logged_uuid| *logged_uuid != uuid);
+            }
+            gpage
+                .imp()
+                .update_setting::<Vec<String>>("logged-gpus", logged_gpus);
+        }));
+
+        log_box
+    }
+
+    /**
+     * Name:
+     * apply_fan_override
+     *
+     * Description:
+     * Set the fixed fan speed of this gpu, or hand fan control back to the driver if switched off,
+     * then run @callback with the result
+     *
+     * Made:
+     * 22/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only works with nvidia-settings based providers (needs the nvidia-settings gpu index).
+     * Refuses to take control once the watchdog has tripped, handing control back is still allowed.
+     * Commands are run on a separate thread, so the UI isn't blocked
+     */
+    fn apply_fan_override<F: FnOnce(&GpuPage, Result<(), String>) + 'static>(
+        &self,
+        fan_override: FanOverride,
+        callback: F,
+    ) {
+        if fan_override.active && lock_tuning().is_tripped() {
+            return callback(self, Err(String::from("Thermal watchdog tripped..")));
+        }
+
+        // Look up everything needed from the provider first
+        let uuid: String = self.property::<String>("uuid");
+        let provider: Provider = self.property::<Provider>("provider");
+        let gpu_index: u32 = match provider.get_gpu_index(&uuid) {
+            Ok(index) => index,
+            Err(err) => return callback(self, Err(err)),
+        };
+        let fans: Vec<u32> = if fan_override.active {
+            match provider.get_targets(&uuid, TargetType::Fan) {
+                Ok(targets) => targets,
+                Err(err) => return callback(self, Err(err)),
+            }
+        } else {
+            vec![]
+        };
+
+        // Run command on a separate thread
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        thread::spawn(move || {
+            let result: Result<(), String> = if fan_override.active {
+                apply_fixed_speed(&SubprocessRunner, gpu_index, &fans, fan_override.speed)
+            } else {
+                release_fan_control(&SubprocessRunner, gpu_index)
+            };
+
+            let _ = sender.send(result);
+        });
+
+        let mut callback: Option<F> = Some(callback);
+        receiver.attach(
+            None,
+            clone!(@weak self as gpage => @default-return Continue(false),
+                move |result: Result<(), String>| {
+                if let Some(callback) = callback.take() {
+                    callback(&gpage, result);
+                }
+
+                Continue(false)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * reset_peaks
+     *
+     * Description:
+     * Reset the peak of every property of this page's GPU
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Peaks are shared by every view of the GPU
+     */
+    pub fn reset_peaks(&self) {
+        let uuid: String = self.property::<String>("uuid");
+        with_history(|history| history.reset_peaks(&uuid));
+    }
+
+    /**
+     * Name:
+     * add_view_template
+     *
+     * Description:
+     * Adds a new view (with a pre-defined list of properties) to this page
+     *
+     * Made:
+     * 17/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Does nothing if a view with the same title already exists
+     */
+    pub fn add_view_template(&self, view_title: &str, properties: &[&str]) {
+        // Get stored UUID
+        let uuid: String = self.property::<String>("uuid");
+
+        // Load list of Views
+        let mut views: Vec<String> = self.imp().get_setting::<Vec<String>>("viewconfigs");
+
+        // Find views for this page
+        let page_views: Vec<&String> = views
+            .iter()
+            .filter(|view| view.starts_with(&(uuid.clone() + ":")))
+            .collect();
+
+        // Check if view already exists
+        if page_views
+            .iter()
+            .any(|view| view.split(':').nth(2) == Some(view_title))
+        {
+            println!("..view `{}` already exists", view_title);
+            return;
+        }
+
+        // Add new view to the end
+        //UUID:POSITION:VIEW_TITLE
+        let position: usize = page_views.len();
+        views.push(uuid.clone() + ":" + &position.to_string() + ":" + view_title);
+        self.imp()
+            .update_setting::<Vec<String>>("viewconfigs", views);
+
+        // Add properties to new view
+        let mut components: Vec<String> = self
+            .imp()
+            .get_setting::<Vec<String>>("viewcomponentconfigs");
+        for (index, property) in properties.iter().enumerate() {
+            //UUID:VIEW_TITLE:POSITION:name
+            components
+                .push(uuid.clone() + ":" + view_title + ":" + &index.to_string() + ":" + property);
+        }
+        self.imp()
+            .update_setting::<Vec<String>>("viewcomponentconfigs", components);
+
+        // Reload views
+        self.load_views();
+    }
+
     /**
      * Name:
      * setup_widgets
@@ -0,0 +1,498 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Import fan and overclock profiles from GreenWithEnvy (GWE)
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * GWE keeps it's profiles in an SQLite database (`~/.local/share/gwe/gwe.db`), in the tables
+ * `fan_profile`, `speed_step` and `overclock_profile`. GWE profiles aren't tied to a GPU,
+ * so each is converted into a profile entry for every GPU
+ */
+// Imports
+use rusqlite::{Connection, OpenFlags, Statement};
+use std::path::{Path, PathBuf};
+
+// Modules
+use crate::{
+    fan_curve::{CurvePoint, FanCurve},
+    profile::{validate_profile_name, Profile},
+};
+
+/// Name of the GWE database file
+pub const DATABASE_NAME: &str = "gwe.db";
+/// Highest temperature (in celcius) accepted for a speed step
+const MAX_STEP_TEMPERATURE: i64 = 120;
+
+/// Structure for storing a GWE fan profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GweFanProfile {
+    pub id: i64,
+    pub name: String,
+    pub vbios_controlled: bool,
+    pub steps: Vec<GweSpeedStep>,
+}
+
+/// Structure for storing a step (point) of a GWE fan profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GweSpeedStep {
+    pub temperature: i64,
+    pub duty: i64,
+}
+
+/// Structure for storing a GWE overclock profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GweOverclockProfile {
+    pub id: i64,
+    pub name: String,
+    pub gpu: i64,
+    pub memory: i64,
+}
+
+/// Structure for storing everything read from a GWE database
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GweData {
+    pub fan_profiles: Vec<GweFanProfile>,
+    pub overclock_profiles: Vec<GweOverclockProfile>,
+}
+
+/// Structure for storing the result of converting GWE profiles
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GweImport {
+    pub profiles: Vec<Profile>,
+    pub problems: Vec<String>,
+}
+
+/// Structure for storing a converted GWE profile, before it's split by GPU
+struct ConvertedProfile {
+    name: String,
+    curve: Option<FanCurve>,
+    offsets: Option<(i32, i32)>,
+}
+
+/**
+ * Name:
+ * database_paths
+ *
+ * Description:
+ * List the places GWE keeps it's database, in order of preference
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Covers both native installs (XDG data directory) and the Flatpak
+ */
+pub fn database_paths(home: Option<&Path>, data_home: Option<&Path>) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = vec![];
+
+    if let Some(valid_data_home) = data_home {
+        paths.push(valid_data_home.join("gwe").join(DATABASE_NAME));
+    }
+    if let Some(valid_home) = home {
+        let native: PathBuf = valid_home.join(".local/share/gwe").join(DATABASE_NAME);
+        if !paths.contains(&native) {
+            paths.push(native);
+        }
+        paths.push(
+            valid_home
+                .join(".var/app/com.leinardi.gwe/data/gwe")
+                .join(DATABASE_NAME),
+        );
+    }
+
+    paths
+}
+
+/**
+ * Name:
+ * find_database
+ *
+ * Description:
+ * Find the GWE database of the current user (if there is one)
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn find_database() -> Option<PathBuf> {
+    let home: Option<PathBuf> = std::env::var_os("HOME").map(PathBuf::from);
+    let data_home: Option<PathBuf> = std::env::var_os("XDG_DATA_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+
+    database_paths(home.as_deref(), data_home.as_deref())
+        .into_iter()
+        .find(|path| path.is_file())
+}
+
+/**
+ * Name:
+ * sql_error
+ *
+ * Description:
+ * Convert a database error into the error type used here
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn sql_error(err: rusqlite::Error) -> String {
+    format!("Cannot read GreenWithEnvy database: {}..", err)
+}
+
+/**
+ * Name:
+ * table_columns
+ *
+ * Description:
+ * List the columns of a table (empty if the table doesn't exist)
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Used to cope with older/newer versions of GWE adding or dropping columns
+ */
+fn table_columns(connection: &Connection, table: &str) -> Result<Vec<String>, String> {
+    let mut statement: Statement = connection
+        .prepare(&format!("PRAGMA table_info({})", table))
+        .map_err(sql_error)?;
+    let columns: Vec<String> = statement
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(sql_error)?
+        .collect::<Result<Vec<String>, rusqlite::Error>>()
+        .map_err(sql_error)?;
+
+    Ok(columns)
+}
+
+/**
+ * Name:
+ * read_database
+ *
+ * Description:
+ * Read every fan & overclock profile from a GWE database
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Opened read-only, so it's safe to run while GWE is open
+ */
+pub fn read_database(path: &Path) -> Result<GweData, String> {
+    let connection: Connection =
+        Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY).map_err(|err| {
+            format!(
+                "Cannot open GreenWithEnvy database `{}`: {}..",
+                path.display(),
+                err
+            )
+        })?;
+
+    let fan_columns: Vec<String> = table_columns(&connection, "fan_profile")?;
+    let step_columns: Vec<String> = table_columns(&connection, "speed_step")?;
+    let overclock_columns: Vec<String> = table_columns(&connection, "overclock_profile")?;
+    if fan_columns.is_empty() && overclock_columns.is_empty() {
+        return Err(format!(
+            "`{}` is not a GreenWithEnvy database..",
+            path.display()
+        ));
+    }
+
+    let mut data: GweData = GweData::default();
+
+    // Fan profiles
+    if !fan_columns.is_empty() {
+        let vbios_column: &str = if fan_columns
+            .iter()
+            .any(|column| column == "vbios_silent_mode")
+        {
+            "vbios_silent_mode"
+        } else {
+            "0"
+        };
+        let order: &str = if fan_columns.iter().any(|column| column == "sort_index") {
+            "sort_index, id"
+        } else {
+            "id"
+        };
+
+        let mut statement: Statement = connection
+            .prepare(&format!(
+                "SELECT id, name, {} FROM fan_profile ORDER BY {}",
+                vbios_column, order
+            ))
+            .map_err(sql_error)?;
+        data.fan_profiles = statement
+            .query_map([], |row| {
+                Ok(GweFanProfile {
+                    id: row.get::<_, i64>(0)?,
+                    name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    vbios_controlled: row.get::<_, Option<bool>>(2)?.unwrap_or(false),
+                    steps: vec![],
+                })
+            })
+            .map_err(sql_error)?
+            .collect::<Result<Vec<GweFanProfile>, rusqlite::Error>>()
+            .map_err(sql_error)?;
+    }
+
+    // Speed steps of each fan profile
+    if !step_columns.is_empty() {
+        let mut statement: Statement = connection
+            .prepare(
+                "SELECT profile_id, temperature, duty FROM speed_step ORDER BY profile_id, temperature",
+            )
+            .map_err(sql_error)?;
+        let steps: Vec<(i64, GweSpeedStep)> = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    GweSpeedStep {
+                        temperature: row.get::<_, i64>(1)?,
+                        duty: row.get::<_, i64>(2)?,
+                    },
+                ))
+            })
+            .map_err(sql_error)?
+            .collect::<Result<Vec<(i64, GweSpeedStep)>, rusqlite::Error>>()
+            .map_err(sql_error)?;
+
+        for (profile_id, step) in steps {
+            if let Some(profile) = data
+                .fan_profiles
+                .iter_mut()
+                .find(|profile| profile.id == profile_id)
+            {
+                profile.steps.push(step);
+            }
+        }
+    }
+
+    // Overclock profiles
+    if !overclock_columns.is_empty() {
+        let mut statement: Statement = connection
+            .prepare("SELECT id, name, gpu, memory FROM overclock_profile ORDER BY id")
+            .map_err(sql_error)?;
+        data.overclock_profiles = statement
+            .query_map([], |row| {
+                Ok(GweOverclockProfile {
+                    id: row.get::<_, i64>(0)?,
+                    name: row.get::<_, Option<String>>(1)?.unwrap_or_default(),
+                    gpu: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+                    memory: row.get::<_, Option<i64>>(3)?.unwrap_or(0),
+                })
+            })
+            .map_err(sql_error)?
+            .collect::<Result<Vec<GweOverclockProfile>, rusqlite::Error>>()
+            .map_err(sql_error)?;
+    }
+
+    Ok(data)
+}
+
+/**
+ * Name:
+ * convert_fan_profile
+ *
+ * Description:
+ * Convert a GWE fan profile into a fan curve
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * GWE's hysteresis is an app-wide setting (not stored with the profile), so none is used
+ */
+pub fn convert_fan_profile(profile: &GweFanProfile) -> Result<FanCurve, String> {
+    if profile.vbios_controlled || profile.steps.is_empty() {
+        return Err(String::from(
+            "Controlled by the VBIOS, nothing to convert..",
+        ));
+    }
+
+    let mut points: Vec<CurvePoint> = vec![];
+    for step in &profile.steps {
+        if !(0..=MAX_STEP_TEMPERATURE).contains(&step.temperature) {
+            return Err(format!(
+                "Speed step temperature `{}` is out of range..",
+                step.temperature
+            ));
+        }
+        if !(0..=100).contains(&step.duty) {
+            return Err(format!("Speed step duty `{}` is out of range..", step.duty));
+        }
+
+        points.push(CurvePoint {
+            temperature: step.temperature as u32,
+            speed: step.duty as u32,
+        });
+    }
+
+    Ok(FanCurve::new(points, 0, 0))
+}
+
+/**
+ * Name:
+ * convert_overclock_profile
+ *
+ * Description:
+ * Convert a GWE overclock profile into graphics & memory clock offsets
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * GWE stores the same offsets given to nvidia-settings, so no scaling is needed
+ */
+pub fn convert_overclock_profile(profile: &GweOverclockProfile) -> Result<(i32, i32), String> {
+    match (i32::try_from(profile.gpu), i32::try_from(profile.memory)) {
+        (Ok(graphics), Ok(memory)) => Ok((graphics, memory)),
+        _ => Err(String::from("Clock offset is out of range..")),
+    }
+}
+
+/**
+ * Name:
+ * convert_gwe
+ *
+ * Description:
+ * Convert GWE profiles into profiles for every GPU, listing anything that could not be converted
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Fan & overclock profiles with the same name are merged into one profile,
+ * names of existing profiles are skipped rather than overwritten
+ */
+pub fn convert_gwe(data: &GweData, gpus: &[(String, Vec<u32>)], existing: &[String]) -> GweImport {
+    let mut import: GweImport = GweImport::default();
+
+    // Convert (merging by name)
+    let mut converted: Vec<ConvertedProfile> = vec![];
+    for fan_profile in &data.fan_profiles {
+        match convert_fan_profile(fan_profile) {
+            Ok(curve) => match converted
+                .iter_mut()
+                .find(|entry| entry.name == fan_profile.name)
+            {
+                Some(entry) => entry.curve = Some(curve),
+                None => converted.push(ConvertedProfile {
+                    name: fan_profile.name.clone(),
+                    curve: Some(curve),
+                    offsets: None,
+                }),
+            },
+            Err(err) => import
+                .problems
+                .push(format!("Fan profile `{}`: {}", fan_profile.name, err)),
+        }
+    }
+    for overclock_profile in &data.overclock_profiles {
+        match convert_overclock_profile(overclock_profile) {
+            Ok(offsets) => match converted
+                .iter_mut()
+                .find(|entry| entry.name == overclock_profile.name)
+            {
+                Some(entry) => entry.offsets = Some(offsets),
+                None => converted.push(ConvertedProfile {
+                    name: overclock_profile.name.clone(),
+                    curve: None,
+                    offsets: Some(offsets),
+                }),
+            },
+            Err(err) => import.problems.push(format!(
+                "Overclock profile `{}`: {}",
+                overclock_profile.name, err
+            )),
+        }
+    }
+
+    // Create a profile for every GPU
+    for ConvertedProfile {
+        name,
+        curve,
+        offsets,
+    } in converted
+    {
+        if let Err(err) = validate_profile_name(&name) {
+            import.problems.push(format!("Profile `{}`: {}", name, err));
+            continue;
+        }
+        if existing.contains(&name) {
+            import
+                .problems
+                .push(format!("Profile `{}` already exists, skipped..", name));
+            continue;
+        }
+
+        for (uuid, fans) in gpus {
+            let fan_curves: Vec<(u32, FanCurve)> = match &curve {
+                Some(valid_curve) => {
+                    if fans.is_empty() {
+                        import.problems.push(format!(
+                            "Profile `{}`: no fans found on GPU {}, fan curve not converted..",
+                            name, uuid
+                        ));
+                    }
+                    fans.iter().map(|fan| (*fan, valid_curve.clone())).collect()
+                }
+                None => vec![],
+            };
+            if fan_curves.is_empty() && offsets.is_none() {
+                continue;
+            }
+
+            import.profiles.push(Profile {
+                name: name.clone(),
+                uuid: uuid.clone(),
+                power_limit: None,
+                graphics_offset: offsets.map(|(graphics, _)| graphics),
+                memory_offset: offsets.map(|(_, memory)| memory),
+                fan_curves,
+            });
+        }
+    }
+
+    import
+}
@@ -0,0 +1,424 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Run without a window (i.e. on a server), exporting readings only
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Doesn't touch GTK (or need a display), everything is set from the command line
+ */
+// Imports
+use std::{
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+// Modules
+use crate::{
+    history::now_millis,
+    influx::{parse_target, InfluxConfig, InfluxWriter},
+    metrics_server::{provider_label, MetricsServer, DEFAULT_PORT},
+    metrics_store::{stored_samples, MetricsStore, RetentionPolicy},
+    mqtt::{parse_broker_url, Backoff, MqttConfig, MqttPublisher},
+    sampler::{sampler_command, Sampler},
+    secret::{read_secret, INFLUX_TOKEN_VARIABLE, MQTT_PASSWORD_VARIABLE},
+};
+
+/// Time between maintaining the metrics store (rolling up & removing old readings)
+const STORE_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Command line usage when headless
+pub const USAGE: &str = "Usage: gtk4-nvidia-monitor-rust --headless [OPTIONS]
+
+Options:
+  --provider <0-3>          How GPUs are read (same numbers as settings, default 2 - Nvidia SMI)
+  --refresh-rate <SECS>     Time between readings when storing/publishing/writing (default 5)
+  --metrics-address <ADDR>  Serve OpenMetrics at http://ADDR/metrics (i.e. 0.0.0.0:9400)
+  --metrics-store <PATH>    Store readings in a metrics store (i.e. the app's, at
+                            ~/.local/share/gtk4-nvidia-monitor-rust/metrics.db)
+  --mqtt-broker <URL>       Publish to an MQTT broker (i.e. mqtt://localhost:1883)
+  --mqtt-username <NAME>    Username to log in to the broker with
+  --mqtt-password-file <F>  File holding the password to log in to the broker with (chmod 600),
+                            otherwise read from NVIDIA_MONITOR_MQTT_PASSWORD
+  --mqtt-topic-prefix <P>   Prefix of every topic (default nvidia-monitor)
+  --mqtt-qos <0-2>          Quality of service of every message (default 0)
+  --influx-target <TARGET>  Write InfluxDB line protocol to stdout, udp://HOST:PORT,
+                            tcp://HOST:PORT or http://HOST:PORT/PATH?QUERY
+  --influx-token-file <F>   File holding the token to write to a HTTP target with (chmod 600),
+                            otherwise read from NVIDIA_MONITOR_INFLUX_TOKEN
+  --influx-measurement <M>  Measurement of every line (default nvidia_gpu)
+  --influx-uuid-tag <TAG>   Name of the tag holding each GPU's UUID (default uuid, empty for none)
+  --influx-name-tag <TAG>   Name of the tag holding each GPU's name (default name, empty for none)
+  --help                    Show this message";
+
+/// Structure for everything set from the command line when headless
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadlessConfig {
+    pub provider_type: i32,
+    pub refresh_rate: u64,
+    pub metrics_address: Option<String>,
+    pub metrics_store: Option<PathBuf>,
+    pub mqtt: Option<MqttConfig>,
+    pub influx: Option<InfluxConfig>,
+}
+
+/**
+ * Name:
+ * HeadlessConfig
+ *
+ * Description:
+ * Default config, reading with nvidia-smi & nothing exported
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Default for HeadlessConfig {
+    fn default() -> Self {
+        HeadlessConfig {
+            provider_type: 2,
+            refresh_rate: 5,
+            metrics_address: None,
+            metrics_store: None,
+            mqtt: None,
+            influx: None,
+        }
+    }
+}
+
+/**
+ * Name:
+ * is_headless
+ *
+ * Description:
+ * Check if headless mode was asked for on the command line
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Checked before GTK is initialised, so works without a display
+ */
+pub fn is_headless(args: &[String]) -> bool {
+    args.iter().skip(1).any(|arg| arg == "--headless")
+}
+
+/**
+ * Name:
+ * parse_arguments
+ *
+ * Description:
+ * Parse the command line (including the program name) into a config
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A metrics address without a port uses the default port (i.e. `0.0.0.0` is `0.0.0.0:9400`).
+ * MQTT/InfluxDB options other than the broker/target are only allowed with a broker/target.
+ * Secrets are only read from a file or the environment, never from the command line (where `ps` shows them)
+ */
+pub fn parse_arguments(args: &[String]) -> Result<HeadlessConfig, String> {
+    let mut config: HeadlessConfig = HeadlessConfig::default();
+    let mut mqtt: MqttConfig = MqttConfig::default();
+    let mut mqtt_broker: Option<String> = None;
+    let mut mqtt_password_file: Option<PathBuf> = None;
+    let mut mqtt_options: Vec<String> = vec![];
+    let mut influx: InfluxConfig = InfluxConfig::default();
+    let mut influx_target: bool = false;
+    let mut influx_token_file: Option<PathBuf> = None;
+    let mut influx_options: Vec<String> = vec![];
+
+    let mut arguments = args.iter().skip(1);
+    while let Some(argument) = arguments.next() {
+        // Split `--name=value`
+        let (name, inline_value): (&str, Option<&str>) = match argument.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (argument.as_str(), None),
+        };
+        let mut value = || -> Result<String, String> {
+            match inline_value {
+                Some(valid_value) => Ok(String::from(valid_value)),
+                None => arguments
+                    .next()
+                    .cloned()
+                    .ok_or(format!("Missing value for `{}`..", name)),
+            }
+        };
+
+        match name {
+            "--headless" => {}
+            "--provider" => {
+                config.provider_type = match value()?.parse::<i32>() {
+                    Ok(provider_type @ 0..=3) => provider_type,
+                    _ => return Err(String::from("Provider must be between 0 and 3..")),
+                }
+            }
+            "--refresh-rate" => {
+                config.refresh_rate = match value()?.parse::<u64>() {
+                    Ok(refresh_rate @ 1..=3600) => refresh_rate,
+                    _ => return Err(String::from("Refresh rate must be between 1 and 3600..")),
+                }
+            }
+            "--metrics-address" => {
+                let address: String = value()?;
+                config.metrics_address = Some(match address.rsplit_once(':') {
+                    Some((_, port)) if port.parse::<u16>().is_ok() => address,
+                    _ => format!("{}:{}", address, DEFAULT_PORT),
+                });
+            }
+            "--metrics-store" => {
+                config.metrics_store = Some(match value()? {
+                    path if !path.is_empty() => PathBuf::from(path),
+                    _ => return Err(String::from("Metrics store path can't be empty..")),
+                });
+            }
+            "--mqtt-broker" => {
+                let broker: String = value()?;
+                parse_broker_url(&broker)?;
+                mqtt_broker = Some(broker);
+            }
+            "--mqtt-username" => {
+                mqtt.username = value()?;
+                mqtt_options.push(String::from(name));
+            }
+            "--mqtt-password-file" => {
+                mqtt_password_file = Some(PathBuf::from(value()?));
+                mqtt_options.push(String::from(name));
+            }
+            "--mqtt-topic-prefix" => {
+                mqtt.topic_prefix = value()?.trim_matches('/').to_string();
+                mqtt_options.push(String::from(name));
+            }
+            "--mqtt-qos" => {
+                mqtt.qos = match value()?.parse::<u8>() {
+                    Ok(qos @ 0..=2) => qos,
+                    _ => return Err(String::from("MQTT QoS must be 0, 1 or 2..")),
+                };
+                mqtt_options.push(String::from(name));
+            }
+            "--influx-target" => {
+                influx.target = parse_target(&value()?)?;
+                influx_target = true;
+            }
+            "--influx-token-file" => {
+                influx_token_file = Some(PathBuf::from(value()?));
+                influx_options.push(String::from(name));
+            }
+            "--influx-measurement" => {
+                influx.measurement = match value()? {
+                    measurement if !measurement.is_empty() => measurement,
+                    _ => return Err(String::from("InfluxDB measurement can't be empty..")),
+                };
+                influx_options.push(String::from(name));
+            }
+            "--influx-uuid-tag" => {
+                influx.uuid_tag = value()?;
+                influx_options.push(String::from(name));
+            }
+            "--influx-name-tag" => {
+                influx.name_tag = value()?;
+                influx_options.push(String::from(name));
+            }
+            _ => return Err(format!("Unknown option `{}`..", argument)),
+        }
+    }
+
+    // MQTT, if a broker was given
+    match mqtt_broker {
+        Some(broker) => {
+            config.mqtt = Some(MqttConfig {
+                broker,
+                password: read_secret(MQTT_PASSWORD_VARIABLE, mqtt_password_file.as_deref())?,
+                keep_alive: (config.refresh_rate as u16 * 3).max(60),
+                ..mqtt
+            })
+        }
+        None => {
+            if let Some(option) = mqtt_options.first() {
+                return Err(format!("`{}` needs `--mqtt-broker`..", option));
+            }
+        }
+    }
+
+    // InfluxDB, if a target was given
+    match influx_target {
+        true => {
+            config.influx = Some(InfluxConfig {
+                token: read_secret(INFLUX_TOKEN_VARIABLE, influx_token_file.as_deref())?,
+                ..influx
+            })
+        }
+        false => {
+            if let Some(option) = influx_options.first() {
+                return Err(format!("`{}` needs `--influx-target`..", option));
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+/**
+ * Name:
+ * run
+ *
+ * Description:
+ * Start every exporter in the config, then serve/publish/write until killed
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Fails if there is nothing to do
+ */
+pub fn run(config: HeadlessConfig) -> Result<(), String> {
+    let server: Option<MetricsServer> = match &config.metrics_address {
+        Some(address) => Some(MetricsServer::start(
+            address,
+            Sampler::new(sampler_command(config.provider_type)),
+            &provider_label(config.provider_type),
+        )?),
+        None => None,
+    };
+
+    let store: Option<MetricsStore> = match &config.metrics_store {
+        Some(path) => Some(MetricsStore::open(path, RetentionPolicy::default())?),
+        None => None,
+    };
+
+    match server {
+        _ if store.is_some() || config.mqtt.is_some() || config.influx.is_some() => {
+            export(&config, store);
+            Ok(())
+        }
+        Some(server) => {
+            server.wait();
+            Ok(())
+        }
+        None => Err(String::from("Nothing to export, see `--help`..")),
+    }
+}
+
+/**
+ * Name:
+ * export
+ *
+ * Description:
+ * Read every GPU each refresh and store/publish/write the readings to the metrics store, MQTT & InfluxDB, forever
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A lost broker is reconnected to with backoff, readings are dropped in the meantime.
+ * Errors go to stderr, as stdout may be read by Telegraf
+ */
+fn export(config: &HeadlessConfig, mut store: Option<MetricsStore>) {
+    let sampler: Sampler = Sampler::new(sampler_command(config.provider_type)).probe();
+    let mut publisher: Option<MqttPublisher> = config
+        .mqtt
+        .clone()
+        .map(|mqtt| MqttPublisher::new(mqtt, Backoff::default()));
+    let mut writer: Option<InfluxWriter> = config.influx.clone().map(InfluxWriter::new);
+    let refresh: Duration = Duration::from_secs(config.refresh_rate);
+    let mut last_maintenance: Instant = Instant::now();
+
+    loop {
+        let start: Instant = Instant::now();
+        match sampler.sample() {
+            Ok(samples) => {
+                if let Some(store) = store.as_mut() {
+                    if let Err(err) = store.record(&stored_samples(&samples)) {
+                        eprintln!("..Attempt to store readings failed, returning: {}", err);
+                    }
+                }
+                if let Some(publisher) = publisher.as_mut() {
+                    if let Err(err) = publisher.publish_samples(&samples, start) {
+                        eprintln!("..Attempt to publish to broker failed, returning: {}", err);
+                    }
+                }
+                if let Some(writer) = writer.as_mut() {
+                    if let Err(err) = writer.write(&samples) {
+                        eprintln!("..Attempt to write to InfluxDB failed, returning: {}", err);
+                    }
+                }
+            }
+            Err(err) => eprintln!("..Attempt to read GPUs failed, returning: {}", err),
+        }
+
+        // Roll up & remove old readings every few minutes
+        if let Some(store) = store.as_mut() {
+            if last_maintenance.elapsed() >= STORE_MAINTENANCE_INTERVAL {
+                last_maintenance = Instant::now();
+                if let Err(err) = store.maintain(now_millis()) {
+                    eprintln!(
+                        "..Attempt to maintain metrics store failed, returning: {}",
+                        err
+                    );
+                }
+            }
+        }
+
+        thread::sleep(refresh.saturating_sub(start.elapsed()));
+    }
+}
+
+/**
+ * Name:
+ * main
+ *
+ * Description:
+ * Entry point when headless, returns the exit code
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Errors go to stderr, as stdout may be read by Telegraf
+ */
+pub fn main(args: &[String]) -> i32 {
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return 0;
+    }
+
+    match parse_arguments(args).and_then(run) {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            1
+        }
+    }
+}
@@ -0,0 +1,578 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Operations run by the privileged helper (power limits, locked clocks, persistence & compute mode)
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The app runs the helper through pkexec, i.e. `pkexec <HELPER_PATH> power-limit GPU-... 250`,
+ * the helper only accepts the operations below and validates every argument before running nvidia-smi
+ *
+ * Resets have their own polkit actions (no password), so the watchdog can revert tuning unattended
+ *
+ * This module is also compiled straight into the helper binary, so must only use std
+ */
+/// Where the helper is installed, must match the polkit policy (see `install_helper.sh`)
+pub const HELPER_PATH: &str = "/usr/local/bin/gtk4-nvidia-monitor-rust-helper";
+/// nvidia-smi is run by absolute path, as the helper runs as root
+pub const NVIDIA_SMI_PATH: &str = "/usr/bin/nvidia-smi";
+/// Highest power limit (W) accepted
+pub const MAX_POWER_LIMIT: u32 = 1000;
+/// Highest clock speed (MHz) accepted
+pub const MAX_CLOCK: u32 = 10000;
+
+/// Compute modes that can be set (see `nvidia-smi -c`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeMode {
+    Default,
+    Prohibited,
+    ExclusiveProcess,
+}
+
+/// Every operation the helper will run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HelperOperation {
+    SetPowerLimit { uuid: String, watts: u32 },
+    LockGpuClocks { uuid: String, min: u32, max: u32 },
+    ResetGpuClocks { uuid: String },
+    LockMemoryClocks { uuid: String, min: u32, max: u32 },
+    ResetMemoryClocks { uuid: String },
+    ResetPowerLimit { uuid: String },
+    SetPersistenceMode { uuid: String, enabled: bool },
+    SetComputeMode { uuid: String, mode: ComputeMode },
+}
+
+/**
+ * Name:
+ * ComputeMode
+ *
+ * Description:
+ * Trait shared by all compute modes
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ComputeMode {
+    /**
+     * Name:
+     * all
+     *
+     * Description:
+     * List of every compute mode (in the order shown to the user)
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn all() -> [ComputeMode; 3] {
+        [
+            ComputeMode::Default,
+            ComputeMode::Prohibited,
+            ComputeMode::ExclusiveProcess,
+        ]
+    }
+
+    /**
+     * Name:
+     * name
+     *
+     * Description:
+     * Name of the mode, as passed to the helper
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn name(&self) -> &'static str {
+        match self {
+            ComputeMode::Default => "default",
+            ComputeMode::Prohibited => "prohibited",
+            ComputeMode::ExclusiveProcess => "exclusive-process",
+        }
+    }
+
+    /**
+     * Name:
+     * label
+     *
+     * Description:
+     * Human readable name of the mode
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn label(&self) -> &'static str {
+        match self {
+            ComputeMode::Default => "Default",
+            ComputeMode::Prohibited => "Prohibited",
+            ComputeMode::ExclusiveProcess => "Exclusive Process",
+        }
+    }
+
+    /**
+     * Name:
+     * from_name
+     *
+     * Description:
+     * Find a mode from it's name
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn from_name(name: &str) -> Option<ComputeMode> {
+        ComputeMode::all()
+            .into_iter()
+            .find(|mode| mode.name() == name)
+    }
+
+    /**
+     * Name:
+     * value
+     *
+     * Description:
+     * Value of the mode used by nvidia-smi
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * 1 (exclusive thread) is deprecated so isn't offered
+     */
+    pub fn value(&self) -> &'static str {
+        match self {
+            ComputeMode::Default => "0",
+            ComputeMode::Prohibited => "2",
+            ComputeMode::ExclusiveProcess => "3",
+        }
+    }
+}
+
+/**
+ * Name:
+ * validate_uuid
+ *
+ * Description:
+ * Check a GPU uuid looks like one (i.e. `GPU-` followed by hex digits and dashes)
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn validate_uuid(uuid: &str) -> Result<(), String> {
+    match uuid.strip_prefix("GPU-") {
+        Some(rest)
+            if !rest.is_empty()
+                && uuid.len() <= 64
+                && rest.chars().all(|c| c.is_ascii_hexdigit() || c == '-') =>
+        {
+            Ok(())
+        }
+        _ => Err(format!("Invalid GPU uuid `{}`..", uuid)),
+    }
+}
+
+/**
+ * Name:
+ * parse_number
+ *
+ * Description:
+ * Strictly parse a whole number (digits only, no signs or spaces)
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn parse_number(input: &str) -> Result<u32, String> {
+    if input.is_empty() || input.len() > 9 || !input.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("Invalid number `{}`..", input));
+    }
+
+    input
+        .parse::<u32>()
+        .map_err(|_| format!("Invalid number `{}`..", input))
+}
+
+/**
+ * Name:
+ * validate_clocks
+ *
+ * Description:
+ * Check a pair of locked clocks (MHz) are sensible
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn validate_clocks(min: u32, max: u32) -> Result<(), String> {
+    if min == 0 || max > MAX_CLOCK || min > max {
+        Err(format!(
+            "Clocks must be between 1 and {} MHz, with minimum below maximum..",
+            MAX_CLOCK
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/**
+ * Name:
+ * HelperOperation
+ *
+ * Description:
+ * Trait shared by all helper operations
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl HelperOperation {
+    /**
+     * Name:
+     * uuid
+     *
+     * Description:
+     * The GPU this operation applies to
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn uuid(&self) -> &str {
+        match self {
+            HelperOperation::SetPowerLimit { uuid, .. }
+            | HelperOperation::LockGpuClocks { uuid, .. }
+            | HelperOperation::ResetGpuClocks { uuid }
+            | HelperOperation::LockMemoryClocks { uuid, .. }
+            | HelperOperation::ResetMemoryClocks { uuid }
+            | HelperOperation::ResetPowerLimit { uuid }
+            | HelperOperation::SetPersistenceMode { uuid, .. }
+            | HelperOperation::SetComputeMode { uuid, .. } => uuid,
+        }
+    }
+
+    /**
+     * Name:
+     * validate
+     *
+     * Description:
+     * Check every argument of the operation
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn validate(&self) -> Result<(), String> {
+        validate_uuid(self.uuid())?;
+
+        match self {
+            HelperOperation::SetPowerLimit { watts, .. } => {
+                if *watts == 0 || *watts > MAX_POWER_LIMIT {
+                    return Err(format!(
+                        "Power limit must be between 1 and {} W..",
+                        MAX_POWER_LIMIT
+                    ));
+                }
+                Ok(())
+            }
+            HelperOperation::LockGpuClocks { min, max, .. }
+            | HelperOperation::LockMemoryClocks { min, max, .. } => validate_clocks(*min, *max),
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * Name:
+     * to_args
+     *
+     * Description:
+     * Convert to the arguments passed to the helper
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            HelperOperation::SetPowerLimit { watts, .. } => {
+                vec![String::from("power-limit"), watts.to_string()]
+            }
+            HelperOperation::LockGpuClocks { min, max, .. } => vec![
+                String::from("lock-gpu-clocks"),
+                min.to_string(),
+                max.to_string(),
+            ],
+            HelperOperation::ResetGpuClocks { .. } => vec![String::from("reset-gpu-clocks")],
+            HelperOperation::LockMemoryClocks { min, max, .. } => vec![
+                String::from("lock-memory-clocks"),
+                min.to_string(),
+                max.to_string(),
+            ],
+            HelperOperation::ResetMemoryClocks { .. } => vec![String::from("reset-memory-clocks")],
+            HelperOperation::ResetPowerLimit { .. } => vec![String::from("reset-power-limit")],
+            HelperOperation::SetPersistenceMode { enabled, .. } => vec![
+                String::from("persistence-mode"),
+                String::from(if *enabled { "on" } else { "off" }),
+            ],
+            HelperOperation::SetComputeMode { mode, .. } => {
+                vec![String::from("compute-mode"), String::from(mode.name())]
+            }
+        };
+
+        // uuid always comes second
+        args.insert(1, String::from(self.uuid()));
+
+        args
+    }
+
+    /**
+     * Name:
+     * from_args
+     *
+     * Description:
+     * Parse (and validate) the arguments given to the helper
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Anything not exactly matching a known operation is rejected
+     */
+    pub fn from_args(args: &[String]) -> Result<Self, String> {
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+
+        let operation: HelperOperation = match args.as_slice() {
+            ["power-limit", uuid, watts] => HelperOperation::SetPowerLimit {
+                uuid: String::from(*uuid),
+                watts: parse_number(watts)?,
+            },
+            ["lock-gpu-clocks", uuid, min, max] => HelperOperation::LockGpuClocks {
+                uuid: String::from(*uuid),
+                min: parse_number(min)?,
+                max: parse_number(max)?,
+            },
+            ["reset-gpu-clocks", uuid] => HelperOperation::ResetGpuClocks {
+                uuid: String::from(*uuid),
+            },
+            ["lock-memory-clocks", uuid, min, max] => HelperOperation::LockMemoryClocks {
+                uuid: String::from(*uuid),
+                min: parse_number(min)?,
+                max: parse_number(max)?,
+            },
+            ["reset-memory-clocks", uuid] => HelperOperation::ResetMemoryClocks {
+                uuid: String::from(*uuid),
+            },
+            ["reset-power-limit", uuid] => HelperOperation::ResetPowerLimit {
+                uuid: String::from(*uuid),
+            },
+            ["persistence-mode", uuid, state] => HelperOperation::SetPersistenceMode {
+                uuid: String::from(*uuid),
+                enabled: match *state {
+                    "on" => true,
+                    "off" => false,
+                    _ => return Err(format!("Invalid persistence mode `{}`..", state)),
+                },
+            },
+            ["compute-mode", uuid, mode] => HelperOperation::SetComputeMode {
+                uuid: String::from(*uuid),
+                mode: match ComputeMode::from_name(mode) {
+                    Some(valid_mode) => valid_mode,
+                    None => return Err(format!("Invalid compute mode `{}`..", mode)),
+                },
+            },
+            _ => return Err(String::from("Unknown operation..")),
+        };
+
+        operation.validate()?;
+
+        Ok(operation)
+    }
+
+    /**
+     * Name:
+     * to_nvidia_smi_args
+     *
+     * Description:
+     * Convert to the arguments passed to nvidia-smi
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * `ResetPowerLimit` only queries the default power limit, which is then set with `SetPowerLimit`
+     */
+    pub fn to_nvidia_smi_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = vec![String::from("-i"), String::from(self.uuid())];
+
+        match self {
+            HelperOperation::SetPowerLimit { watts, .. } => {
+                args.push(String::from("-pl"));
+                args.push(watts.to_string());
+            }
+            HelperOperation::LockGpuClocks { min, max, .. } => {
+                args.push(String::from("-lgc"));
+                args.push(format!("{},{}", min, max));
+            }
+            HelperOperation::ResetGpuClocks { .. } => args.push(String::from("-rgc")),
+            HelperOperation::LockMemoryClocks { min, max, .. } => {
+                args.push(String::from("-lmc"));
+                args.push(format!("{},{}", min, max));
+            }
+            HelperOperation::ResetMemoryClocks { .. } => args.push(String::from("-rmc")),
+            HelperOperation::ResetPowerLimit { .. } => {
+                args.push(String::from("--query-gpu=power.default_limit"));
+                args.push(String::from("--format=csv,noheader,nounits"));
+            }
+            HelperOperation::SetPersistenceMode { enabled, .. } => {
+                args.push(String::from("-pm"));
+                args.push(String::from(if *enabled { "1" } else { "0" }));
+            }
+            HelperOperation::SetComputeMode { mode, .. } => {
+                args.push(String::from("-c"));
+                args.push(String::from(mode.value()));
+            }
+        }
+
+        args
+    }
+}
+
+/**
+ * Name:
+ * create_helper_command
+ *
+ * Description:
+ * Create the pkexec command for running an operation with the helper
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Validated here too, so nothing invalid asks the user for a password
+ */
+pub fn create_helper_command(operation: &HelperOperation) -> Result<Vec<String>, String> {
+    operation.validate()?;
+
+    let mut command: Vec<String> = vec![String::from("pkexec"), String::from(HELPER_PATH)];
+    command.append(&mut operation.to_args());
+
+    Ok(command)
+}
+
+/**
+ * Name:
+ * parse_power_limit
+ *
+ * Description:
+ * Parse the default power limit queried for `ResetPowerLimit`, i.e. `250.00`
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Rounded to whole watts, as that's all `-pl` accepts here
+ */
+pub fn parse_power_limit(output: &str) -> Result<u32, String> {
+    let watts: u32 = match output.trim().parse::<f64>() {
+        Ok(valid_watts) if valid_watts.is_finite() && valid_watts > 0.0 => {
+            valid_watts.round() as u32
+        }
+        _ => return Err(format!("Invalid default power limit `{}`..", output.trim())),
+    };
+
+    if watts > MAX_POWER_LIMIT {
+        return Err(format!(
+            "Power limit must be between 1 and {} W..",
+            MAX_POWER_LIMIT
+        ));
+    }
+
+    Ok(watts)
+}
@@ -0,0 +1,1103 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * In-memory history of every property shown, with rolling statistics
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Samples are kept per GPU (uuid) & property, and pruned once older than the retention time.
+ * Widgets subscribe to a property to be told whenever a sample is recorded (or it's peak reset)
+ */
+// Imports
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Default time (in minutes) samples are kept for
+pub const DEFAULT_RETENTION_MINUTES: u32 = 10;
+/// Most samples kept for any one property, regardless of retention
+pub const MAX_SAMPLES: usize = 86_400;
+/// Windows statistics can be calculated over (in seconds), 0 being every sample kept
+pub const STATISTICS_WINDOWS: [(&str, u64); 4] = [
+    ("1 Minute", 60),
+    ("5 Minutes", 300),
+    ("15 Minutes", 900),
+    ("Everything Kept", 0),
+];
+
+/// Callback of a subscriber, returning false to unsubscribe
+pub type HistoryCallback = Box<dyn FnMut(&PropertyHistory) -> bool>;
+/// ID of a subscription, for unsubscribing
+pub type SubscriptionId = u64;
+/// Callback given every sample recorded, with the UUID, property & unit it was recorded for
+pub type HistoryRecorder = Box<dyn FnMut(&str, &str, &str, HistorySample)>;
+
+thread_local! {
+    /// History shown by the UI, only used from the main (GTK) thread as subscribers hold widgets
+    static HISTORY: RefCell<MetricHistory> = RefCell::new(MetricHistory::default());
+}
+
+/// Structure for storing a single timestamped sample, no value meaning it couldn't be read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistorySample {
+    pub timestamp: u64,
+    pub value: Option<f64>,
+}
+
+/// Structure for storing statistics calculated over a window of samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Statistics {
+    pub min: f64,
+    pub max: f64,
+    pub average: f64,
+    pub p95: f64,
+    pub count: usize,
+}
+
+/// Structure for storing the samples of a single property
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyHistory {
+    samples: VecDeque<HistorySample>,
+    retention: u64,
+    unit: String,
+    peak: Option<f64>,
+}
+
+/// Structure for storing a subscriber to a property
+struct Subscription {
+    id: SubscriptionId,
+    uuid: String,
+    property: String,
+    callback: HistoryCallback,
+}
+
+/// Structure for storing the history of every GPU & property
+pub struct MetricHistory {
+    retention: u64,
+    histories: HashMap<(String, String), PropertyHistory>,
+    subscriptions: Vec<Subscription>,
+    next_id: SubscriptionId,
+    recorder: Option<HistoryRecorder>,
+}
+
+/**
+ * Name:
+ * PropertyHistory
+ *
+ * Description:
+ * Trait shared by all property histories
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Timestamps are in milliseconds, retention in seconds
+ */
+impl PropertyHistory {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create an empty history, keeping samples for the given number of seconds
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(retention: u64) -> Self {
+        PropertyHistory {
+            samples: VecDeque::new(),
+            retention,
+            unit: String::new(),
+            peak: None,
+        }
+    }
+
+    /**
+     * Name:
+     * push
+     *
+     * Description:
+     * Add a sample, pruning any that are now too old
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The peak is kept even once the sample it came from has been pruned
+     */
+    pub fn push(&mut self, sample: HistorySample) {
+        if let Some(value) = sample.value {
+            self.peak = match self.peak {
+                Some(peak) if peak >= value => Some(peak),
+                _ => Some(value),
+            };
+        }
+        self.samples.push_back(sample);
+        self.prune(sample.timestamp);
+    }
+
+    /**
+     * Name:
+     * prune
+     *
+     * Description:
+     * Remove samples older than the retention time (or over the sample limit)
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn prune(&mut self, now: u64) {
+        let oldest: u64 = now.saturating_sub(self.retention * 1000);
+        while let Some(sample) = self.samples.front() {
+            if sample.timestamp < oldest || self.samples.len() > MAX_SAMPLES {
+                self.samples.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * set_retention
+     *
+     * Description:
+     * Change how long (in seconds) samples are kept for
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn set_retention(&mut self, retention: u64) {
+        self.retention = retention;
+        if let Some(newest) = self.samples.back() {
+            let now: u64 = newest.timestamp;
+            self.prune(now);
+        }
+    }
+
+    /**
+     * Name:
+     * samples
+     *
+     * Description:
+     * Every sample kept, oldest first
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn samples(&self) -> &VecDeque<HistorySample> {
+        &self.samples
+    }
+
+    /**
+     * Name:
+     * latest
+     *
+     * Description:
+     * Value of the newest sample, if it could be read
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn latest(&self) -> Option<f64> {
+        self.samples.back().and_then(|sample| sample.value)
+    }
+
+    /**
+     * Name:
+     * unit
+     *
+     * Description:
+     * Unit of the values (i.e. `MHz`), as last shown
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    /**
+     * Name:
+     * set_unit
+     *
+     * Description:
+     * Change the unit of the values
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn set_unit(&mut self, unit: &str) {
+        if self.unit != unit {
+            self.unit = String::from(unit);
+        }
+    }
+
+    /**
+     * Name:
+     * peak
+     *
+     * Description:
+     * Highest value seen since the last reset
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn peak(&self) -> Option<f64> {
+        self.peak
+    }
+
+    /**
+     * Name:
+     * reset_peak
+     *
+     * Description:
+     * Start tracking the peak again from the current value
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn reset_peak(&mut self) {
+        self.peak = self.latest();
+    }
+
+    /**
+     * Name:
+     * statistics
+     *
+     * Description:
+     * Calculate min/max/average/p95 of the samples within the window (in seconds) ending now
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * A window of 0 uses every sample kept, samples without a value are ignored.
+     * p95 uses the nearest rank method
+     */
+    pub fn statistics(&self, now: u64, window: u64) -> Option<Statistics> {
+        let oldest: u64 = match window {
+            0 => 0,
+            _ => now.saturating_sub(window * 1000),
+        };
+        let mut values: Vec<f64> = self
+            .samples
+            .iter()
+            .filter(|sample| sample.timestamp >= oldest && sample.timestamp <= now)
+            .filter_map(|sample| sample.value)
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(|a, b| a.total_cmp(b));
+
+        let count: usize = values.len();
+        let rank: usize = ((count as f64) * 0.95).ceil() as usize;
+
+        Some(Statistics {
+            min: values[0],
+            max: values[count - 1],
+            average: values.iter().sum::<f64>() / count as f64,
+            p95: values[rank.clamp(1, count) - 1],
+            count,
+        })
+    }
+}
+
+/**
+ * Name:
+ * MetricHistory
+ *
+ * Description:
+ * Trait shared by all metric histories
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Subscribers are handed the history of their property, and must not use the store from inside
+ * their callback
+ */
+impl MetricHistory {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create an empty store, keeping samples for the given number of seconds
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(retention: u64) -> Self {
+        MetricHistory {
+            retention,
+            histories: HashMap::new(),
+            subscriptions: vec![],
+            next_id: 1,
+            recorder: None,
+        }
+    }
+
+    /**
+     * Name:
+     * retention
+     *
+     * Description:
+     * How long (in seconds) samples are kept for
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn retention(&self) -> u64 {
+        self.retention
+    }
+
+    /**
+     * Name:
+     * set_retention
+     *
+     * Description:
+     * Change how long (in seconds) samples are kept for, for every property
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn set_retention(&mut self, retention: u64) {
+        if self.retention != retention {
+            self.retention = retention;
+            for history in self.histories.values_mut() {
+                history.set_retention(retention);
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * record
+     *
+     * Description:
+     * Add a sample for a property of a GPU, notifying any subscribers
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn record(&mut self, uuid: &str, property: &str, timestamp: u64, value: Option<f64>) {
+        let retention: u64 = self.retention;
+        let history: &mut PropertyHistory = self
+            .histories
+            .entry((String::from(uuid), String::from(property)))
+            .or_insert_with(|| PropertyHistory::new(retention));
+        let sample: HistorySample = HistorySample { timestamp, value };
+        history.push(sample);
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder(uuid, property, history.unit(), sample);
+        }
+
+        self.notify(uuid, property);
+    }
+
+    /**
+     * Name:
+     * record_text
+     *
+     * Description:
+     * Add a sample for a property of a GPU from it's formatted value (i.e. `1.50 GHz`), returning false if rejected
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Anything that isn't a number is recorded as a missing value.
+     * Values in a different unit to those already recorded are rejected, so a series never mixes units
+     */
+    pub fn record_text(&mut self, uuid: &str, property: &str, timestamp: u64, text: &str) -> bool {
+        match parse_sample(text) {
+            Some((value, unit)) => {
+                let retention: u64 = self.retention;
+                let history: &mut PropertyHistory = self
+                    .histories
+                    .entry((String::from(uuid), String::from(property)))
+                    .or_insert_with(|| PropertyHistory::new(retention));
+                let recorded: bool = history
+                    .samples()
+                    .iter()
+                    .any(|sample| sample.value.is_some());
+                if recorded && history.unit() != unit {
+                    println!(
+                        "..Ignoring `{}` for {}, which is recorded in `{}`",
+                        text,
+                        property,
+                        history.unit()
+                    );
+                    return false;
+                }
+                history.set_unit(&unit);
+                self.record(uuid, property, timestamp, Some(value));
+            }
+            None => self.record(uuid, property, timestamp, None),
+        }
+
+        true
+    }
+
+    /**
+     * Name:
+     * restore
+     *
+     * Description:
+     * Add samples recorded earlier (i.e. loaded from disk) for a property of a GPU
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Subscribers & the recorder aren't told, as nothing new was read. Samples are expected to be older than
+     * anything already recorded
+     */
+    pub fn restore(&mut self, uuid: &str, property: &str, unit: &str, samples: &[HistorySample]) {
+        let retention: u64 = self.retention;
+        let history: &mut PropertyHistory = self
+            .histories
+            .entry((String::from(uuid), String::from(property)))
+            .or_insert_with(|| PropertyHistory::new(retention));
+        if !unit.is_empty() {
+            history.set_unit(unit);
+        }
+        for sample in samples {
+            history.push(*sample);
+        }
+    }
+
+    /**
+     * Name:
+     * set_recorder
+     *
+     * Description:
+     * Give every sample recorded from now on to a callback (i.e. to store them on disk), or stop
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn set_recorder(&mut self, recorder: Option<HistoryRecorder>) {
+        self.recorder = recorder;
+    }
+
+    /**
+     * Name:
+     * get
+     *
+     * Description:
+     * History of a property of a GPU, if anything has been recorded
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn get(&self, uuid: &str, property: &str) -> Option<&PropertyHistory> {
+        self.histories
+            .get(&(String::from(uuid), String::from(property)))
+    }
+
+    /**
+     * Name:
+     * properties
+     *
+     * Description:
+     * Names of every property recorded for a GPU, sorted
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn properties(&self, uuid: &str) -> Vec<String> {
+        let mut properties: Vec<String> = self
+            .histories
+            .keys()
+            .filter(|(key_uuid, _)| key_uuid == uuid)
+            .map(|(_, property)| property.to_owned())
+            .collect();
+        properties.sort();
+
+        properties
+    }
+
+    /**
+     * Name:
+     * uuids
+     *
+     * Description:
+     * UUIDs of every GPU with anything recorded, sorted
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn uuids(&self) -> Vec<String> {
+        let mut uuids: Vec<String> = self
+            .histories
+            .keys()
+            .map(|(uuid, _)| uuid.to_owned())
+            .collect();
+        uuids.sort();
+        uuids.dedup();
+
+        uuids
+    }
+
+    /**
+     * Name:
+     * reset_peaks
+     *
+     * Description:
+     * Reset the peak of every property of a GPU, notifying any subscribers
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn reset_peaks(&mut self, uuid: &str) {
+        for property in self.properties(uuid) {
+            self.reset_peak(uuid, &property);
+        }
+    }
+
+    /**
+     * Name:
+     * reset_peak
+     *
+     * Description:
+     * Reset the peak of a property of a GPU, notifying any subscribers
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn reset_peak(&mut self, uuid: &str, property: &str) {
+        if let Some(history) = self
+            .histories
+            .get_mut(&(String::from(uuid), String::from(property)))
+        {
+            history.reset_peak();
+            self.notify(uuid, property);
+        }
+    }
+
+    /**
+     * Name:
+     * subscribe
+     *
+     * Description:
+     * Call the given callback whenever a property of a GPU changes
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The callback is dropped once it returns false (i.e. it's widget is gone)
+     */
+    pub fn subscribe(
+        &mut self,
+        uuid: &str,
+        property: &str,
+        callback: HistoryCallback,
+    ) -> SubscriptionId {
+        let id: SubscriptionId = self.next_id;
+        self.next_id += 1;
+        self.subscriptions.push(Subscription {
+            id,
+            uuid: String::from(uuid),
+            property: String::from(property),
+            callback,
+        });
+
+        id
+    }
+
+    /**
+     * Name:
+     * unsubscribe
+     *
+     * Description:
+     * Remove a subscription, returning if it existed
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let before: usize = self.subscriptions.len();
+        self.subscriptions
+            .retain(|subscription| subscription.id != id);
+
+        self.subscriptions.len() != before
+    }
+
+    /**
+     * Name:
+     * subscriptions
+     *
+     * Description:
+     * Number of current subscriptions
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn subscriptions(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    /**
+     * Name:
+     * notify
+     *
+     * Description:
+     * Call every subscriber of a property, dropping any that have finished
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn notify(&mut self, uuid: &str, property: &str) {
+        if let Some(history) = self
+            .histories
+            .get(&(String::from(uuid), String::from(property)))
+        {
+            self.subscriptions.retain_mut(|subscription| {
+                if subscription.uuid == uuid && subscription.property == property {
+                    (subscription.callback)(history)
+                } else {
+                    true
+                }
+            });
+        }
+    }
+}
+
+/**
+ * Name:
+ * MetricHistory
+ *
+ * Description:
+ * Default trait for metric histories
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Default for MetricHistory {
+    fn default() -> Self {
+        MetricHistory::new(DEFAULT_RETENTION_MINUTES as u64 * 60)
+    }
+}
+
+/**
+ * Name:
+ * with_history
+ *
+ * Description:
+ * Use the history shown by the UI
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Must not be called from inside a subscriber's callback
+ */
+pub fn with_history<T>(func: impl FnOnce(&mut MetricHistory) -> T) -> T {
+    HISTORY.with(|history| func(&mut history.borrow_mut()))
+}
+
+/**
+ * Name:
+ * now_millis
+ *
+ * Description:
+ * Current time, in milliseconds since the unix epoch
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn now_millis() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as u64,
+        Err(_) => 0,
+    }
+}
+
+/**
+ * Name:
+ * parse_sample
+ *
+ * Description:
+ * Split a formatted value (i.e. `55°C`) into it's number & unit
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only the first value is used (i.e. the speed of `45 % / 1500 RPM`).
+ * Values are always kept in one unit, whichever the settings show them in (MHz, KB/s & °C).
+ * PCIe links & performance states are kept as their number (i.e. `Gen 3`, `x16` & `P2`)
+ */
+pub fn parse_sample(text: &str) -> Option<(f64, String)> {
+    let text: &str = text.trim();
+    let text: &str = ["Gen ", "x", "P"]
+        .iter()
+        .find_map(|prefix| {
+            text.strip_prefix(prefix)
+                .filter(|rest| rest.starts_with(|c: char| c.is_ascii_digit()))
+        })
+        .unwrap_or(text);
+    let end: usize = text
+        .char_indices()
+        .find(|(index, c)| !(c.is_ascii_digit() || *c == '.' || (*index == 0 && *c == '-')))
+        .map(|(index, _)| index)
+        .unwrap_or(text.len());
+    let value: f64 = text[..end].parse::<f64>().ok()?;
+    let unit: &str = text[end..].split_whitespace().next().unwrap_or("");
+
+    match unit {
+        "GHz" => Some((value * 1000.0, String::from("MHz"))),
+        "MB/s" => Some((value * 1000.0, String::from("KB/s"))),
+        "°F" => Some(((value - 32.0) * 5.0 / 9.0, String::from("°C"))),
+        _ => Some((value, String::from(unit))),
+    }
+}
+
+/**
+ * Name:
+ * format_value
+ *
+ * Description:
+ * Format a value & unit for showing alongside the current value
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Whole numbers are shown without decimal places, units are spaced as in `mainwindow` (i.e. `45 %`, `55°C`)
+ */
+pub fn format_value(value: f64, unit: &str) -> String {
+    let number: String = if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.2}", value)
+    };
+
+    if unit.is_empty() || unit.starts_with('°') {
+        number + unit
+    } else {
+        format!("{} {}", number, unit)
+    }
+}
+
+/**
+ * Name:
+ * format_timestamp
+ *
+ * Description:
+ * Format a timestamp (ms since the unix epoch) as an ISO 8601 date & time (i.e. `2023-02-02T10:15:30.250Z`)
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Always UTC, days are converted to a date using the proleptic Gregorian calendar
+ */
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days: i64 = (timestamp / 86_400_000) as i64;
+    let milliseconds: u64 = timestamp % 86_400_000;
+
+    // Convert days since 1970-01-01 to a date (see <https://howardhinnant.github.io/date_algorithms.html>)
+    let shifted: i64 = days + 719_468;
+    let era: i64 = shifted.div_euclid(146_097);
+    let day_of_era: i64 = shifted.rem_euclid(146_097);
+    let year_of_era: i64 =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month: i64 = match month_index {
+        0..=9 => month_index + 3,
+        _ => month_index - 9,
+    };
+    let year: i64 = year_of_era
+        + era * 400
+        + match month {
+            1 | 2 => 1,
+            _ => 0,
+        };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        milliseconds / 3_600_000,
+        (milliseconds / 60_000) % 60,
+        (milliseconds / 1000) % 60,
+        milliseconds % 1000
+    )
+}
+
+/**
+ * Name:
+ * escape_csv
+ *
+ * Description:
+ * Quote a field of a CSV file, if needed
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn escape_csv(field: &str) -> String {
+    match field.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", field.replace('"', "\"\"")),
+        false => String::from(field),
+    }
+}
+
+/**
+ * Name:
+ * export_csv
+ *
+ * Description:
+ * Export the samples of several properties between two timestamps as CSV, one row per timestamp
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Each series is given as a column title & it's history. Cells are left empty where a property has no
+ * reading at that time (i.e. it failed, or was recorded at a different time)
+ */
+pub fn export_csv(series: &[(&str, &PropertyHistory)], start: u64, end: u64) -> String {
+    // Header
+    let mut header: Vec<String> = vec![String::from("timestamp"), String::from("time")];
+    for (title, history) in series {
+        header.push(escape_csv(&match history.unit() {
+            "" => String::from(*title),
+            unit => format!("{} ({})", title, unit),
+        }));
+    }
+
+    // Collect readings of every series by time
+    let mut rows: BTreeMap<u64, Vec<Option<f64>>> = BTreeMap::new();
+    for (index, (_, history)) in series.iter().enumerate() {
+        let samples: &VecDeque<HistorySample> = history.samples();
+        let first: usize = samples.partition_point(|sample| sample.timestamp < start);
+        for sample in samples
+            .range(first..)
+            .take_while(|sample| sample.timestamp <= end)
+        {
+            let row: &mut Vec<Option<f64>> = rows
+                .entry(sample.timestamp)
+                .or_insert_with(|| vec![None; series.len()]);
+            row[index] = sample.value;
+        }
+    }
+
+    let mut output: String = header.join(",") + "\n";
+    for (timestamp, values) in rows {
+        let mut fields: Vec<String> = vec![timestamp.to_string(), format_timestamp(timestamp)];
+        fields.extend(values.iter().map(|value| match value {
+            Some(value) => value.to_string(),
+            None => String::new(),
+        }));
+        output += &(fields.join(",") + "\n");
+    }
+
+    output
+}
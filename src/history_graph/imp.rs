@@ -0,0 +1,168 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * imp.rs
+ *
+ * Description:
+ * Implementation of our custom GObject class (HistoryGraph)
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+// Imports
+use glib::{once_cell::sync::Lazy, once_cell::sync::OnceCell, ParamSpec, ToValue, Value};
+use gtk::{glib, subclass::prelude::*};
+use std::cell::Cell;
+
+/// Object holding the State
+#[derive(Default)]
+pub struct HistoryGraph {
+    uuid: OnceCell<String>,
+    property: OnceCell<String>,
+    title: OnceCell<String>,
+    detailed: Cell<bool>,
+
+    pub max_gap: Cell<u64>,
+    pub hover: Cell<Option<f64>>,
+    pub line_colour: Cell<Option<(f64, f64, f64)>>,
+    pub value_bounds: Cell<(Option<f64>, Option<f64>)>,
+}
+
+/// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for HistoryGraph {
+    //Crate+Obj to avoid collisions
+    const NAME: &'static str = "NvidiaMonitorRustHistoryGraph";
+    // the actual GObject that will be created
+    type Type = super::HistoryGraph;
+    // Parent GObject we inherit from
+    type ParentType = gtk::DrawingArea;
+}
+
+// Trait shared by all GObjects
+impl ObjectImpl for HistoryGraph {
+    /**
+     * Name:
+     * properties
+     *
+     * Description:
+     * Create list of custom properties for our GObject
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * beware that you need to use kebab-case (<https://en.wikipedia.org/wiki/Letter_case#Kebab_case>)
+     */
+    fn properties() -> &'static [ParamSpec] {
+        static PROPERTIES: Lazy<Vec<ParamSpec>> = Lazy::new(|| {
+            vec![
+                glib::ParamSpecString::builder("uuid").build(),
+                glib::ParamSpecString::builder("property").build(),
+                glib::ParamSpecString::builder("title").build(),
+                glib::ParamSpecBoolean::builder("detailed").build(),
+            ]
+        });
+
+        PROPERTIES.as_ref()
+    }
+
+    /**
+     * Name:
+     * set_property
+     *
+     * Description:
+     * Mutator for custom GObject properties
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn set_property(&self, _obj: &Self::Type, _id: usize, value: &Value, pspec: &ParamSpec) {
+        match pspec.name() {
+            "uuid" => match value.get() {
+                Ok(input_uuid) => self
+                    .uuid
+                    .set(input_uuid)
+                    .expect("`uuid` should not be set after calling constructor.."),
+                Err(_) => panic!("The value needs to be of type `String`."),
+            },
+            "property" => match value.get() {
+                Ok(input_property) => self
+                    .property
+                    .set(input_property)
+                    .expect("`property` should not be set after calling constructor.."),
+                Err(_) => panic!("The value needs to be of type `String`."),
+            },
+            "title" => match value.get() {
+                Ok(input_title) => self
+                    .title
+                    .set(input_title)
+                    .expect("`title` should not be set after calling constructor.."),
+                Err(_) => panic!("The value needs to be of type `String`."),
+            },
+            "detailed" => match value.get() {
+                Ok(input_detailed) => self.detailed.set(input_detailed),
+                Err(_) => panic!("The value needs to be of type `bool`."),
+            },
+            _ => panic!("Property `{}` does not exist..", pspec.name()),
+        }
+    }
+
+    /**
+     * Name:
+     * property
+     *
+     * Description:
+     * Accessor for custom GObject properties
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "uuid" => match self.uuid.get() {
+                Some(value) => value.to_value(),
+                None => panic!("Cannot get value of `uuid` property.."),
+            },
+            "property" => match self.property.get() {
+                Some(value) => value.to_value(),
+                None => panic!("Cannot get value of `property` property.."),
+            },
+            "title" => match self.title.get() {
+                Some(value) => value.to_value(),
+                None => panic!("Cannot get value of `title` property.."),
+            },
+            "detailed" => self.detailed.get().to_value(),
+            _ => panic!("Property `{}` does not exist..", pspec.name()),
+        }
+    }
+}
+
+// Trait shared by all widgets
+impl WidgetImpl for HistoryGraph {}
+
+// Trait shared by all drawing areas
+impl DrawingAreaImpl for HistoryGraph {}
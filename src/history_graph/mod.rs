@@ -0,0 +1,685 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Public-facing interface/wrapper for our custom GObject (HistoryGraph)
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Either a small sparkline of the last few minutes (shown next to a property), or a detailed line chart of
+ * everything kept with axes & hover tooltips. Clicking a sparkline opens the detailed chart in a new window
+ */
+// Custom GObjects
+mod imp;
+
+// Imports
+use adwaita::{gio, glib, prelude::*, subclass::prelude::*};
+use gio::Settings;
+use glib::{clone, Object, WeakRef};
+use gtk::{cairo, gdk::RGBA, DrawingArea, EventControllerMotion, GestureClick};
+use std::collections::VecDeque;
+
+// Modules
+use crate::{
+    chart::{
+        axis_ticks, build_segments, format_age, nearest_sample, value_range, ChartArea, ChartRange,
+    },
+    display_style::DisplayStyle,
+    history::{format_value, now_millis, with_history, HistorySample, PropertyHistory},
+    APP_ID,
+};
+
+/// Time (s) shown by a sparkline
+pub const SPARKLINE_WINDOW: u64 = 300;
+/// Space (px) above & right of a detailed chart
+const CHART_MARGIN: f64 = 12.0;
+/// Space (px) left of a detailed chart, for value labels
+const VALUE_AXIS_WIDTH: f64 = 72.0;
+/// Space (px) below a detailed chart, for time labels
+const TIME_AXIS_HEIGHT: f64 = 24.0;
+/// Space (px) around a sparkline
+const SPARKLINE_MARGIN: f64 = 2.0;
+/// Refreshes in a row without readings before the line is broken
+const GAP_REFRESHES: u64 = 3;
+/// Colour (red, green & blue) of lines when none is chosen
+const LINE_COLOUR: (f64, f64, f64) = (0.21, 0.52, 0.89);
+
+// GObject wrapper for HistoryGraph
+glib::wrapper! {
+    pub struct HistoryGraph(ObjectSubclass<imp::HistoryGraph>)
+        @extends gtk::DrawingArea, gtk::Widget,
+        @implements gtk::Accessible, gtk::Buildable, gtk::ConstraintTarget;
+}
+
+/**
+ * Name:
+ * set_colour
+ *
+ * Description:
+ * Use a colour (with extra transparency) for the next thing drawn
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn set_colour(context: &cairo::Context, colour: &RGBA, alpha: f64) {
+    context.set_source_rgba(
+        colour.red() as f64,
+        colour.green() as f64,
+        colour.blue() as f64,
+        colour.alpha() as f64 * alpha,
+    );
+}
+
+/**
+ * Name:
+ * draw_segments
+ *
+ * Description:
+ * Draw each unbroken run of readings as a line
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A lone reading (between two gaps) is drawn as a dot so it isn't lost
+ */
+pub fn draw_segments(
+    context: &cairo::Context,
+    segments: &[Vec<(f64, f64)>],
+    line_width: f64,
+    colour: (f64, f64, f64),
+) -> Result<(), cairo::Error> {
+    context.set_source_rgb(colour.0, colour.1, colour.2);
+    context.set_line_width(line_width);
+    for segment in segments {
+        match segment.as_slice() {
+            [(x, y)] => {
+                context.arc(*x, *y, line_width, 0.0, 2.0 * std::f64::consts::PI);
+                context.fill()?;
+            }
+            [(first_x, first_y), rest @ ..] => {
+                context.move_to(*first_x, *first_y);
+                for (x, y) in rest {
+                    context.line_to(*x, *y);
+                }
+                context.stroke()?;
+            }
+            [] => {}
+        }
+    }
+
+    Ok(())
+}
+
+/**
+ * Name:
+ * draw_text
+ *
+ * Description:
+ * Draw a label, aligned by it's right edge if needed
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * y is the baseline of the text
+ */
+pub fn draw_text(
+    context: &cairo::Context,
+    text: &str,
+    x: f64,
+    y: f64,
+    align_right: bool,
+) -> Result<(), cairo::Error> {
+    let offset: f64 = match align_right {
+        true => context.text_extents(text)?.width,
+        false => 0.0,
+    };
+    context.move_to(x - offset, y);
+    context.show_text(text)
+}
+
+/**
+ * Name:
+ * chart_layout
+ *
+ * Description:
+ * Work out where the readings of a detailed chart are drawn, and the times & values it covers
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Covers everything kept (i.e. the retention time), ending now
+ */
+fn chart_layout(
+    history: &PropertyHistory,
+    retention: u64,
+    width: f64,
+    height: f64,
+) -> Option<(ChartArea, ChartRange)> {
+    let area: ChartArea = ChartArea {
+        x: VALUE_AXIS_WIDTH,
+        y: CHART_MARGIN,
+        width: width - VALUE_AXIS_WIDTH - CHART_MARGIN,
+        height: height - CHART_MARGIN - TIME_AXIS_HEIGHT,
+    };
+    if area.width <= 0.0 || area.height <= 0.0 {
+        return None;
+    }
+
+    let end: u64 = now_millis();
+    let start: u64 = end.saturating_sub(retention * 1000);
+    let (min, max): (f64, f64) = value_range(history.samples(), start, end)?;
+
+    Some((
+        area,
+        ChartRange {
+            start,
+            end,
+            min,
+            max,
+        },
+    ))
+}
+
+/**
+ * Trait Name:
+ * HistoryGraph
+ *
+ * Description:
+ * Trait shared by all history graphs
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl HistoryGraph {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new HistoryGraph object, showing a property of a GPU
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The property is the name it's readings are recorded under (i.e. `temp` or `fan:0`)
+     */
+    pub fn new(uuid: &str, property: &str, title: &str, detailed: bool) -> Self {
+        // Create new graph
+        let obj: HistoryGraph = Object::new(&[]).expect("Failed to create `HistoryGraph`.");
+
+        // Set custom properties
+        obj.set_property("uuid", String::from(uuid));
+        obj.set_property("property", String::from(property));
+        obj.set_property("title", String::from(title));
+        obj.set_property("detailed", detailed);
+
+        // Apply any setup actions that need the above properties
+        obj.setup_widgets();
+
+        // Return final object
+        obj
+    }
+
+    /**
+     * Name:
+     * set_display_style
+     *
+     * Description:
+     * Use the colour & bounds (min/max) chosen for this property
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Bounds only apply to sparklines, detailed charts always fit the readings
+     */
+    pub fn set_display_style(&self, style: &DisplayStyle) {
+        self.imp().line_colour.set(style.rgb());
+        self.imp().value_bounds.set((style.min, style.max));
+        self.queue_draw();
+    }
+
+    /**
+     * Name:
+     * line_colour
+     *
+     * Description:
+     * Colour (red, green & blue) readings are drawn in
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn line_colour(&self) -> (f64, f64, f64) {
+        self.imp().line_colour.get().unwrap_or(LINE_COLOUR)
+    }
+
+    /**
+     * Name:
+     * setup_widgets
+     *
+     * Description:
+     * Size the graph, connect drawing & input, and redraw whenever a reading is recorded
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only a weak reference is subscribed, so the subscription ends with the graph
+     */
+    fn setup_widgets(&self) {
+        // Break lines after a few refreshes without readings
+        let refresh_rate: u64 = Settings::new(APP_ID).int("refreshrate") as u64;
+        self.imp().max_gap.set(refresh_rate * 1000 * GAP_REFRESHES);
+
+        // Size
+        let detailed: bool = self.property::<bool>("detailed");
+        if detailed {
+            self.set_content_width(480);
+            self.set_content_height(240);
+            self.set_hexpand(true);
+            self.set_vexpand(true);
+        } else {
+            self.set_content_width(80);
+            self.set_content_height(24);
+            self.set_valign(gtk::Align::Center);
+        }
+
+        // Drawing
+        self.set_draw_func(
+            clone!(@weak self as graph => move |_area: &DrawingArea, context: &cairo::Context, width: i32, height: i32| {
+                let result: Result<(), cairo::Error> = match graph.property::<bool>("detailed") {
+                    true => graph.draw_chart(context, width as f64, height as f64),
+                    false => graph.draw_sparkline(context, width as f64, height as f64),
+                };
+                if let Err(err) = result {
+                    println!("..Attempt to draw history failed, returning: {}", err);
+                }
+            }),
+        );
+
+        // Input
+        if detailed {
+            // Show the reading under the mouse
+            let motion_controller: EventControllerMotion = EventControllerMotion::new();
+            motion_controller.connect_motion(clone!(@weak self as graph => move |_controller: &EventControllerMotion, x: f64, _y: f64| {
+                graph.imp().hover.set(Some(x));
+                graph.update_tooltip();
+                graph.queue_draw();
+            }));
+            motion_controller.connect_leave(
+                clone!(@weak self as graph => move |_controller: &EventControllerMotion| {
+                    graph.imp().hover.set(None);
+                    graph.set_tooltip_text(None);
+                    graph.queue_draw();
+                }),
+            );
+            self.add_controller(&motion_controller);
+        } else {
+            // Open detailed chart
+            self.set_cursor_from_name(Some("pointer"));
+            self.set_tooltip_text(Some("Show history"));
+            let click_gesture: GestureClick = GestureClick::new();
+            click_gesture.connect_released(clone!(@weak self as graph => move |_gesture: &GestureClick, _n_press: i32, _x: f64, _y: f64| {
+                graph.open_chart_window();
+            }));
+            self.add_controller(&click_gesture);
+        }
+
+        // Redraw whenever a reading is recorded
+        let graph_ref: WeakRef<HistoryGraph> = self.downgrade();
+        let uuid: String = self.property::<String>("uuid");
+        let property: String = self.property::<String>("property");
+        with_history(|history| {
+            history.subscribe(
+                &uuid,
+                &property,
+                Box::new(move |_| match graph_ref.upgrade() {
+                    Some(graph) => {
+                        graph.queue_draw();
+                        true
+                    }
+                    None => false,
+                }),
+            )
+        });
+    }
+
+    /**
+     * Name:
+     * open_chart_window
+     *
+     * Description:
+     * Show a detailed chart of this property in a new window
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn open_chart_window(&self) {
+        let title: String = self.property::<String>("title");
+        let chart: HistoryGraph = HistoryGraph::new(
+            &self.property::<String>("uuid"),
+            &self.property::<String>("property"),
+            &title,
+            true,
+        );
+        chart.imp().line_colour.set(self.imp().line_colour.get());
+
+        let window: gtk::Window = gtk::Window::builder()
+            .title(&title)
+            .default_width(640)
+            .default_height(360)
+            .child(&chart)
+            .build();
+        if let Some(root) = self.root() {
+            if let Ok(parent) = root.downcast::<gtk::Window>() {
+                window.set_transient_for(Some(&parent));
+            }
+        }
+        window.present();
+    }
+
+    /**
+     * Name:
+     * draw_sparkline
+     *
+     * Description:
+     * Draw the last few minutes of readings as a line, without axes
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn draw_sparkline(
+        &self,
+        context: &cairo::Context,
+        width: f64,
+        height: f64,
+    ) -> Result<(), cairo::Error> {
+        let uuid: String = self.property::<String>("uuid");
+        let property: String = self.property::<String>("property");
+        let max_gap: u64 = self.imp().max_gap.get();
+        let colour: (f64, f64, f64) = self.line_colour();
+        let (fixed_min, fixed_max): (Option<f64>, Option<f64>) = self.imp().value_bounds.get();
+
+        with_history(|history| {
+            let samples: &VecDeque<HistorySample> = match history.get(&uuid, &property) {
+                Some(property_history) => property_history.samples(),
+                None => return Ok(()),
+            };
+
+            let end: u64 = now_millis();
+            let start: u64 = end.saturating_sub(SPARKLINE_WINDOW * 1000);
+            let (min, max): (f64, f64) = match value_range(samples, start, end) {
+                Some((found_min, found_max)) => {
+                    // Use any bounds chosen for this property instead
+                    match (
+                        fixed_min.unwrap_or(found_min),
+                        fixed_max.unwrap_or(found_max),
+                    ) {
+                        (min, max) if max > min => (min, max),
+                        _ => (found_min, found_max),
+                    }
+                }
+                None => return Ok(()),
+            };
+            let area: ChartArea = ChartArea {
+                x: SPARKLINE_MARGIN,
+                y: SPARKLINE_MARGIN,
+                width: width - 2.0 * SPARKLINE_MARGIN,
+                height: height - 2.0 * SPARKLINE_MARGIN,
+            };
+            let range: ChartRange = ChartRange {
+                start,
+                end,
+                min,
+                max,
+            };
+
+            draw_segments(
+                context,
+                &build_segments(samples, &range, &area, max_gap),
+                1.5,
+                colour,
+            )
+        })
+    }
+
+    /**
+     * Name:
+     * draw_chart
+     *
+     * Description:
+     * Draw every reading kept, with value & time axes and the reading under the mouse
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn draw_chart(
+        &self,
+        context: &cairo::Context,
+        width: f64,
+        height: f64,
+    ) -> Result<(), cairo::Error> {
+        let uuid: String = self.property::<String>("uuid");
+        let property: String = self.property::<String>("property");
+        let max_gap: u64 = self.imp().max_gap.get();
+        let hover: Option<f64> = self.imp().hover.get();
+        let foreground: RGBA = self.style_context().color();
+
+        with_history(|history| {
+            let retention: u64 = history.retention();
+            let layout: Option<(ChartArea, ChartRange, &PropertyHistory)> =
+                history.get(&uuid, &property).and_then(|property_history| {
+                    chart_layout(property_history, retention, width, height)
+                        .map(|(area, range)| (area, range, property_history))
+                });
+            let (area, range, property_history) = match layout {
+                Some(found) => found,
+                None => {
+                    // Nothing numeric recorded yet (or unsupported)
+                    set_colour(context, &foreground, 0.6);
+                    context.set_font_size(14.0);
+                    let text: &str = "No readings yet";
+                    let extents: cairo::TextExtents = context.text_extents(text)?;
+                    return draw_text(
+                        context,
+                        text,
+                        (width - extents.width) / 2.0,
+                        height / 2.0,
+                        false,
+                    );
+                }
+            };
+            let unit: &str = property_history.unit();
+            context.set_font_size(11.0);
+
+            // Value axis, with grid lines
+            for tick in axis_ticks(range.min, range.max, 5) {
+                let (_, y): (f64, f64) = range.position(&area, range.start, tick);
+                set_colour(context, &foreground, 0.15);
+                context.set_line_width(1.0);
+                context.move_to(area.x, y);
+                context.line_to(area.x + area.width, y);
+                context.stroke()?;
+
+                set_colour(context, &foreground, 0.8);
+                draw_text(
+                    context,
+                    &format_value(tick, unit),
+                    area.x - 6.0,
+                    y + 4.0,
+                    true,
+                )?;
+            }
+
+            // Time axis, as how long ago
+            for step in 0..=4 {
+                let timestamp: u64 = range.start + (range.end - range.start) * step / 4;
+                let (x, _): (f64, f64) = range.position(&area, timestamp, range.min);
+                let label: String = match range.end - timestamp {
+                    0 => String::from("now"),
+                    age => format!("-{}", format_age(age)),
+                };
+
+                set_colour(context, &foreground, 0.15);
+                context.move_to(x, area.y);
+                context.line_to(x, area.y + area.height);
+                context.stroke()?;
+
+                set_colour(context, &foreground, 0.8);
+                let label_width: f64 = context.text_extents(&label)?.width;
+                draw_text(
+                    context,
+                    &label,
+                    (x - label_width / 2.0).min(width - label_width).max(0.0),
+                    height - 6.0,
+                    false,
+                )?;
+            }
+
+            // Readings
+            draw_segments(
+                context,
+                &build_segments(property_history.samples(), &range, &area, max_gap),
+                2.0,
+                self.line_colour(),
+            )?;
+
+            // Reading under the mouse
+            if let Some(x) = hover {
+                let timestamp: u64 = range.timestamp_at(&area, x);
+                if let Some(sample) = nearest_sample(property_history.samples(), timestamp) {
+                    let (sample_x, sample_y): (f64, f64) =
+                        range.position(&area, sample.timestamp, sample.value.unwrap_or(range.min));
+
+                    set_colour(context, &foreground, 0.5);
+                    context.set_line_width(1.0);
+                    context.move_to(sample_x, area.y);
+                    context.line_to(sample_x, area.y + area.height);
+                    context.stroke()?;
+
+                    if sample.value.is_some() {
+                        let colour: (f64, f64, f64) = self.line_colour();
+                        context.set_source_rgb(colour.0, colour.1, colour.2);
+                        context.arc(sample_x, sample_y, 4.0, 0.0, 2.0 * std::f64::consts::PI);
+                        context.fill()?;
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /**
+     * Name:
+     * update_tooltip
+     *
+     * Description:
+     * Show the reading under the mouse (and how long ago it was) as a tooltip
+     *
+     * Made:
+     * 31/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Gaps are shown as N/A
+     */
+    fn update_tooltip(&self) {
+        let uuid: String = self.property::<String>("uuid");
+        let property: String = self.property::<String>("property");
+        let width: f64 = self.width() as f64;
+        let height: f64 = self.height() as f64;
+
+        let text: Option<String> = match self.imp().hover.get() {
+            Some(x) => with_history(|history| {
+                let retention: u64 = history.retention();
+                let property_history: &PropertyHistory = history.get(&uuid, &property)?;
+                let (area, range): (ChartArea, ChartRange) =
+                    chart_layout(property_history, retention, width, height)?;
+                let sample: HistorySample =
+                    nearest_sample(property_history.samples(), range.timestamp_at(&area, x))?;
+
+                let value: String = match sample.value {
+                    Some(value) => format_value(value, property_history.unit()),
+                    None => String::from("N/A"),
+                };
+                Some(match range.end.saturating_sub(sample.timestamp) {
+                    age if age < 1000 => format!("{} (now)", value),
+                    age => format!("{} ({} ago)", value, format_age(age)),
+                })
+            }),
+            None => None,
+        };
+
+        self.set_tooltip_text(text.as_deref());
+    }
+}
@@ -0,0 +1,276 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * imp.rs
+ *
+ * Description:
+ * Implementation of our custom GObject class (HistoryWindow)
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+// Imports
+use adwaita::{gio, glib, prelude::*, subclass::prelude::*};
+use gio::Settings;
+use glib::{once_cell::sync::OnceCell, signal::Inhibit, subclass::InitializingObject, FromVariant};
+use gtk::{
+    subclass::prelude::*, Button, CompositeTemplate, DrawingArea, FileChooserNative, ListBox,
+    TemplateChild,
+};
+use std::{cell::Cell, cell::RefCell};
+
+// Modules
+use crate::chart::{ChartArea, TimeWindow};
+
+/// Structure for storing a single property of a GPU that can be shown in the chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub uuid: String,
+    pub property: String,
+    pub title: String,
+    pub colour: (f64, f64, f64),
+    pub visible: bool,
+}
+
+/// Object holding the State and any Template Children
+#[derive(CompositeTemplate, Default)]
+#[template(resource = "/history-window.ui")]
+pub struct HistoryWindow {
+    // Public
+    pub settings: OnceCell<Settings>,
+    pub gpus: RefCell<Vec<(String, String)>>,
+    pub series: RefCell<Vec<Series>>,
+    pub view: Cell<Option<TimeWindow>>,
+    pub hover: Cell<Option<f64>>,
+    pub drag_origin: Cell<Option<TimeWindow>>,
+    pub plot_area: Cell<Option<ChartArea>>,
+    pub export_dialog: RefCell<Option<FileChooserNative>>,
+    pub max_gap: Cell<u64>,
+
+    // Template Children
+    #[template_child]
+    pub series_listbox: TemplateChild<ListBox>,
+    #[template_child]
+    pub history_area: TemplateChild<DrawingArea>,
+    #[template_child]
+    pub history_zoom_out_button: TemplateChild<Button>,
+    #[template_child]
+    pub history_zoom_in_button: TemplateChild<Button>,
+    #[template_child]
+    pub history_live_button: TemplateChild<Button>,
+    #[template_child]
+    pub history_export_button: TemplateChild<Button>,
+    #[template_child]
+    pub history_reload_button: TemplateChild<Button>,
+}
+
+/// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for HistoryWindow {
+    // `NAME` needs to match `class` attribute of template
+    const NAME: &'static str = "NvidiaExtensionHistoryWindow";
+    type Type = super::HistoryWindow;
+    type ParentType = gtk::ApplicationWindow;
+
+    fn class_init(klass: &mut Self::Class) {
+        klass.bind_template();
+    }
+
+    fn instance_init(obj: &InitializingObject<Self>) {
+        obj.init_template();
+    }
+}
+
+/**
+ * Name:
+ * HistoryWindow
+ *
+ * Description:
+ * Trait shared by all HistoryWindow objects
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl HistoryWindow {
+    /**
+     * Name:
+     * get_setting
+     *
+     * Description:
+     * Generic function for getting setting value
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn get_setting<T: FromVariant>(&self, name: &str) -> T {
+        // Return the value of the property
+        match self.settings.get() {
+            Some(settings) => settings.get::<T>(name),
+            None => panic!("`settings` should be set in `setup_settings`."),
+        }
+    }
+
+    /**
+     * Name:
+     * update_setting
+     *
+     * Description:
+     * Generic function for updating setting values
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn update_setting<T: ToVariant>(&self, name: &str, value: T) {
+        // Fetch settings
+        match self.settings.get() {
+            Some(settings) => match settings.set(name, &value) {
+                Ok(_) => println!("..Setting `{}` updated!", name),
+                Err(err) => panic!("..Cannot update `{}` setting: `{}`", name, err),
+            },
+            None => panic!("..Cannot retrieve settings"),
+        }
+    }
+}
+
+/**
+ * Trait Name:
+ * ObjectImpl
+ *
+ * Description:
+ * Trait shared by all GObjects
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ObjectImpl for HistoryWindow {
+    /**
+     * Name:
+     * constructed
+     *
+     * Description:
+     * Called during construction, allows calling setup functions
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn constructed(&self, obj: &Self::Type) {
+        // Call "constructed" on parent
+        self.parent_constructed(obj);
+    }
+}
+
+/**
+ * Trait Name:
+ * WidgetImpl
+ *
+ * Description:
+ * Trait shared by all widgets
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl WidgetImpl for HistoryWindow {}
+
+/**
+ * Trait Name:
+ * WindowImpl
+ *
+ * Description:
+ * Trait shared by all Window's
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl WindowImpl for HistoryWindow {
+    /**
+     * Name:
+     * close_request
+     *
+     * Description:
+     * Run when window closed
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn close_request(&self, window: &Self::Type) -> Inhibit {
+        // Store state in settings
+        self.update_setting("history-open", false);
+
+        // Pass close request on to the parent
+        self.parent_close_request(window)
+    }
+}
+
+/**
+ * Trait Name:
+ * ApplicationWindowImpl
+ *
+ * Description:
+ * Trait shared by all ApplicationWindow's
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ApplicationWindowImpl for HistoryWindow {}
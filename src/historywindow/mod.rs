@@ -0,0 +1,968 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Public-facing interface/wrapper for our custom GObject (HistoryWindow)
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Plots any combination of recorded properties (of any GPU) on a shared time axis, with a value axis per unit.
+ * Scrolling zooms around the mouse, dragging pans, and the visible range can be exported as CSV
+ */
+// Custom GObjects
+mod imp;
+use imp::Series;
+
+// Imports
+use adwaita::{gio, glib, prelude::*, subclass::prelude::*};
+use gio::Settings;
+use glib::{clone, signal::Inhibit, Object, WeakRef};
+use gtk::{
+    cairo, gdk::RGBA, CheckButton, DrawingArea, EventControllerMotion, EventControllerScroll,
+    EventControllerScrollFlags, FileChooserAction, FileChooserNative, GestureDrag, Label,
+    Orientation, ResponseType,
+};
+use std::path::Path;
+
+// Modules
+use crate::{
+    chart::{
+        axis_ticks, build_segments, format_age, nearest_sample, time_ticks, value_range, ChartArea,
+        ChartRange, TimeWindow,
+    },
+    history::{export_csv, format_value, now_millis, with_history, HistorySample, PropertyHistory},
+    history_graph::{draw_segments, draw_text, set_colour},
+    property::find_property_description,
+    APP_ID,
+};
+
+/// Shortest time (ms) that can be zoomed in to
+const MIN_SPAN: u64 = 10_000;
+/// Zoom factor of a single scroll step or button press
+const ZOOM_STEP: f64 = 1.25;
+/// Width (px) of each value axis, left of the chart
+const VALUE_AXIS_WIDTH: f64 = 72.0;
+/// Space (px) above the chart, for unit labels
+const UNIT_LABEL_HEIGHT: f64 = 28.0;
+/// Space (px) right of the chart
+const CHART_MARGIN: f64 = 12.0;
+/// Space (px) below the chart, for time labels
+const TIME_AXIS_HEIGHT: f64 = 24.0;
+/// Space (px) wanted between time labels
+const TIME_LABEL_SPACING: f64 = 120.0;
+/// Refreshes in a row without readings before a line is broken
+const GAP_REFRESHES: u64 = 3;
+/// Colours (red, green & blue) given to each series in turn
+const SERIES_COLOURS: [(f64, f64, f64); 8] = [
+    (0.21, 0.52, 0.89),
+    (0.88, 0.11, 0.14),
+    (0.20, 0.82, 0.48),
+    (1.00, 0.47, 0.00),
+    (0.57, 0.25, 0.67),
+    (0.96, 0.83, 0.18),
+    (0.60, 0.42, 0.27),
+    (0.13, 0.63, 0.62),
+];
+
+// GObject wrapper for HistoryWindow
+glib::wrapper! {
+    pub struct HistoryWindow(ObjectSubclass<imp::HistoryWindow>)
+        @extends gtk::ApplicationWindow, gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+/**
+ * Name:
+ * property_label
+ *
+ * Description:
+ * Name a recorded property is shown as (i.e. `fan:0` -> `Fan 0`)
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn property_label(property: &str) -> String {
+    if let Some(description) = find_property_description(property) {
+        return String::from(description.label);
+    }
+
+    match property.split_once(':') {
+        Some((target, index)) => {
+            let words: Vec<String> = target
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                        None => String::new(),
+                    }
+                })
+                .collect();
+            format!("{} {}", words.join(" "), index)
+        }
+        None => property.to_owned(),
+    }
+}
+
+/**
+ * Name:
+ * find_axis
+ *
+ * Description:
+ * Find the value axis (unit & value range) used by a unit
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn find_axis<'a>(axes: &'a [(String, f64, f64)], unit: &str) -> Option<&'a (String, f64, f64)> {
+    axes.iter().find(|(axis_unit, _, _)| axis_unit == unit)
+}
+
+/**
+ * Trait Name:
+ * HistoryWindow
+ *
+ * Description:
+ * Trait shared by all history windows
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl HistoryWindow {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new HistoryWindow object
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * GPUs are given as their UUID & name, any GPU recorded but not given is shown by it's UUID
+     */
+    pub fn new(app: &adwaita::Application, gpus: Vec<(String, String)>) -> Self {
+        // Create new window
+        let obj: HistoryWindow =
+            Object::new(&[("application", app)]).expect("`HistoryWindow` should be  instantiable.");
+
+        // Set GPU names
+        obj.imp().gpus.replace(gpus);
+
+        // Apply any setup actions that need the above properties
+        obj.setup_settings();
+        obj.setup_widgets();
+
+        // Return final object
+        obj
+    }
+
+    /**
+     * Name:
+     * setup_settings
+     *
+     * Description:
+     * Load settings for APP_ID
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_settings(&self) {
+        let settings = Settings::new(APP_ID);
+        self.imp()
+            .settings
+            .set(settings)
+            .expect("`settings` should not be set before calling `setup_settings`..");
+    }
+
+    /**
+     * Name:
+     * setup_widgets
+     *
+     * Description:
+     * Load the properties that can be shown, and connect drawing, input & buttons
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_widgets(&self) {
+        // Break lines after a few refreshes without readings
+        let refresh_rate: u64 = self.imp().get_setting::<i32>("refreshrate") as u64;
+        self.imp().max_gap.set(refresh_rate * 1000 * GAP_REFRESHES);
+
+        // Properties
+        self.load_series();
+
+        // Drawing
+        self.imp().history_area.set_draw_func(
+            clone!(@weak self as window => move |_area: &DrawingArea, context: &cairo::Context, width: i32, height: i32| {
+                if let Err(err) = window.draw_history(context, width as f64, height as f64) {
+                    println!("..Attempt to draw history failed, returning: {}", err);
+                }
+            }),
+        );
+
+        // Show the readings under the mouse
+        let motion_controller: EventControllerMotion = EventControllerMotion::new();
+        motion_controller.connect_motion(clone!(@weak self as window => move |_controller: &EventControllerMotion, x: f64, _y: f64| {
+            window.imp().hover.set(Some(x));
+            window.update_tooltip();
+            window.imp().history_area.queue_draw();
+        }));
+        motion_controller.connect_leave(
+            clone!(@weak self as window => move |_controller: &EventControllerMotion| {
+                window.imp().hover.set(None);
+                window.imp().history_area.set_tooltip_text(None);
+                window.imp().history_area.queue_draw();
+            }),
+        );
+        self.imp().history_area.add_controller(&motion_controller);
+
+        // Zoom around the mouse when scrolling
+        let scroll_controller: EventControllerScroll =
+            EventControllerScroll::new(EventControllerScrollFlags::VERTICAL);
+        scroll_controller.connect_scroll(
+            clone!(@weak self as window => @default-return Inhibit(false), move |_controller: &EventControllerScroll, _dx: f64, dy: f64| {
+                window.zoom(ZOOM_STEP.powf(dy));
+                Inhibit(true)
+            }),
+        );
+        self.imp().history_area.add_controller(&scroll_controller);
+
+        // Pan when dragging
+        let drag_gesture: GestureDrag = GestureDrag::new();
+        drag_gesture.connect_drag_begin(
+            clone!(@weak self as window => move |_gesture: &GestureDrag, _x: f64, _y: f64| {
+                window.imp().drag_origin.set(Some(window.visible_window()));
+            }),
+        );
+        drag_gesture.connect_drag_update(clone!(@weak self as window => move |_gesture: &GestureDrag, offset_x: f64, _offset_y: f64| {
+            if let (Some(origin), Some(area)) = (window.imp().drag_origin.get(), window.imp().plot_area.get()) {
+                let offset: i64 = (-offset_x / area.width.max(1.0) * origin.span() as f64) as i64;
+                window.set_view(origin.pan(offset, &window.limits()));
+            }
+        }));
+        drag_gesture.connect_drag_end(clone!(@weak self as window => move |_gesture: &GestureDrag, _offset_x: f64, _offset_y: f64| {
+            window.imp().drag_origin.set(None);
+        }));
+        self.imp().history_area.add_controller(&drag_gesture);
+
+        // Buttons
+        self.imp().history_zoom_in_button.connect_clicked(
+            clone!(@weak self as window => move |_button| {
+                window.zoom(1.0 / (ZOOM_STEP * ZOOM_STEP));
+            }),
+        );
+        self.imp().history_zoom_out_button.connect_clicked(
+            clone!(@weak self as window => move |_button| {
+                window.zoom(ZOOM_STEP * ZOOM_STEP);
+            }),
+        );
+        self.imp().history_live_button.connect_clicked(
+            clone!(@weak self as window => move |_button| {
+                window.imp().view.set(None);
+                window.imp().history_area.queue_draw();
+            }),
+        );
+        self.imp().history_export_button.connect_clicked(
+            clone!(@weak self as window => move |_button| {
+                window.open_export_dialog();
+            }),
+        );
+        self.imp().history_reload_button.connect_clicked(
+            clone!(@weak self as window => move |_button| {
+                window.load_series();
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * load_series
+     *
+     * Description:
+     * List every property recorded (grouped by GPU), each with a check button to show it in the chart
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Properties already listed keep their colour & whether they're shown, so this can be re-run to pick up new ones
+     */
+    fn load_series(&self) {
+        let gpus: Vec<(String, String)> = self.imp().gpus.borrow().clone();
+        let previous: Vec<Series> = self.imp().series.borrow().clone();
+        let recorded: Vec<(String, Vec<String>)> = with_history(|history| {
+            history
+                .uuids()
+                .into_iter()
+                .map(|uuid| {
+                    let properties: Vec<String> = history.properties(&uuid);
+                    (uuid, properties)
+                })
+                .collect()
+        });
+
+        // Clear list
+        let listbox: &gtk::ListBox = &self.imp().series_listbox;
+        while let Some(row) = listbox.first_child() {
+            listbox.remove(&row);
+        }
+
+        if recorded.is_empty() {
+            let empty_label: Label = Label::new(Some("Nothing recorded yet"));
+            empty_label.add_css_class("dim-label");
+            listbox.append(&empty_label);
+        }
+
+        let mut series: Vec<Series> = vec![];
+        for (uuid, properties) in recorded {
+            // GPU heading
+            let name: String = match gpus.iter().find(|(gpu_uuid, _)| *gpu_uuid == uuid) {
+                Some((_, gpu_name)) => gpu_name.to_owned(),
+                None => uuid.to_owned(),
+            };
+            let heading: Label = Label::new(Some(name.as_str()));
+            heading.add_css_class("heading");
+            heading.set_xalign(0.0);
+            heading.set_margin_top(6);
+            listbox.append(&heading);
+
+            for property in properties {
+                let label: String = property_label(&property);
+                let existing: Option<&Series> = previous
+                    .iter()
+                    .find(|item| item.uuid == uuid && item.property == property);
+                let entry: Series = match existing {
+                    Some(item) => Series {
+                        title: format!("{}: {}", name, label),
+                        ..item.clone()
+                    },
+                    None => {
+                        self.subscribe_series(&uuid, &property);
+                        Series {
+                            uuid: uuid.to_owned(),
+                            property: property.to_owned(),
+                            title: format!("{}: {}", name, label),
+                            colour: SERIES_COLOURS[series.len() % SERIES_COLOURS.len()],
+                            visible: false,
+                        }
+                    }
+                };
+
+                // Colour of it's line
+                let colour: (f64, f64, f64) = entry.colour;
+                let swatch: DrawingArea = DrawingArea::new();
+                swatch.set_content_width(12);
+                swatch.set_content_height(12);
+                swatch.set_valign(gtk::Align::Center);
+                swatch.set_draw_func(move |_area, context, width, height| {
+                    context.set_source_rgb(colour.0, colour.1, colour.2);
+                    context.rectangle(0.0, 0.0, width as f64, height as f64);
+                    if let Err(err) = context.fill() {
+                        println!("..Attempt to draw colour failed, returning: {}", err);
+                    }
+                });
+
+                // Show/hide
+                let index: usize = series.len();
+                let check_button: CheckButton = CheckButton::with_label(&label);
+                check_button.set_active(entry.visible);
+                check_button.connect_toggled(
+                    clone!(@weak self as window => move |button: &CheckButton| {
+                        if let Some(item) = window.imp().series.borrow_mut().get_mut(index) {
+                            item.visible = button.is_active();
+                        }
+                        window.update_tooltip();
+                        window.imp().history_area.queue_draw();
+                    }),
+                );
+
+                let row: gtk::Box = gtk::Box::new(Orientation::Horizontal, 6);
+                row.append(&swatch);
+                row.append(&check_button);
+                listbox.append(&row);
+
+                series.push(entry);
+            }
+        }
+
+        self.imp().series.replace(series);
+        self.imp().history_area.queue_draw();
+    }
+
+    /**
+     * Name:
+     * subscribe_series
+     *
+     * Description:
+     * Redraw whenever a reading of a property is recorded, if it's shown
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only a weak reference is subscribed, so the subscription ends with the window
+     */
+    fn subscribe_series(&self, uuid: &str, property: &str) {
+        let window_ref: WeakRef<HistoryWindow> = self.downgrade();
+        let series_uuid: String = String::from(uuid);
+        let series_property: String = String::from(property);
+        with_history(|history| {
+            history.subscribe(
+                uuid,
+                property,
+                Box::new(move |_| match window_ref.upgrade() {
+                    Some(window) => {
+                        let shown: bool = window.imp().series.borrow().iter().any(|item| {
+                            item.visible
+                                && item.uuid == series_uuid
+                                && item.property == series_property
+                        });
+                        if shown {
+                            window.imp().history_area.queue_draw();
+                        }
+                        true
+                    }
+                    None => false,
+                }),
+            )
+        });
+    }
+
+    /**
+     * Name:
+     * limits
+     *
+     * Description:
+     * Times that can be shown, from the oldest reading kept up to now
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn limits(&self) -> TimeWindow {
+        let end: u64 = now_millis();
+        let retention: u64 = with_history(|history| history.retention());
+
+        TimeWindow {
+            start: end.saturating_sub(retention * 1000),
+            end,
+        }
+    }
+
+    /**
+     * Name:
+     * visible_window
+     *
+     * Description:
+     * Times currently shown in the chart
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Everything kept is shown until zoomed/panned
+     */
+    fn visible_window(&self) -> TimeWindow {
+        let limits: TimeWindow = self.limits();
+        match self.imp().view.get() {
+            Some(view) => view.within(&limits),
+            None => limits,
+        }
+    }
+
+    /**
+     * Name:
+     * set_view
+     *
+     * Description:
+     * Show a range of times in the chart
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Showing everything kept goes back to following new readings
+     */
+    fn set_view(&self, window: TimeWindow) {
+        let limits: TimeWindow = self.limits();
+        match window.span() >= limits.span() {
+            true => self.imp().view.set(None),
+            false => self.imp().view.set(Some(window)),
+        }
+
+        self.update_tooltip();
+        self.imp().history_area.queue_draw();
+    }
+
+    /**
+     * Name:
+     * zoom
+     *
+     * Description:
+     * Zoom around the mouse (or the middle of the chart), a factor below 1 zooming in
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn zoom(&self, factor: f64) {
+        let window: TimeWindow = self.visible_window();
+        let anchor: u64 = match (self.imp().hover.get(), self.imp().plot_area.get()) {
+            (Some(x), Some(area)) => ChartRange {
+                start: window.start,
+                end: window.end,
+                min: 0.0,
+                max: 1.0,
+            }
+            .timestamp_at(&area, x),
+            _ => window.start + window.span() / 2,
+        };
+
+        self.set_view(window.zoom(anchor, factor, &self.limits(), MIN_SPAN));
+    }
+
+    /**
+     * Name:
+     * visible_series
+     *
+     * Description:
+     * Every series currently shown in the chart
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn visible_series(&self) -> Vec<Series> {
+        self.imp()
+            .series
+            .borrow()
+            .iter()
+            .filter(|item| item.visible)
+            .cloned()
+            .collect()
+    }
+
+    /**
+     * Name:
+     * draw_history
+     *
+     * Description:
+     * Draw every shown series over the visible times, with a value axis per unit and the readings under the mouse
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Axes are fitted to the readings of the visible times only
+     */
+    fn draw_history(
+        &self,
+        context: &cairo::Context,
+        width: f64,
+        height: f64,
+    ) -> Result<(), cairo::Error> {
+        let series: Vec<Series> = self.visible_series();
+        let window: TimeWindow = self.visible_window();
+        let now: u64 = now_millis();
+        let max_gap: u64 = self.imp().max_gap.get();
+        let hover: Option<f64> = self.imp().hover.get();
+        let foreground: RGBA = self.imp().history_area.style_context().color();
+        context.set_font_size(11.0);
+
+        with_history(|history| {
+            // Value axes, one per unit (in the order first shown)
+            let mut axes: Vec<(String, f64, f64)> = vec![];
+            for item in series.iter() {
+                let property_history: &PropertyHistory =
+                    match history.get(&item.uuid, &item.property) {
+                        Some(found) => found,
+                        None => continue,
+                    };
+                let (min, max): (f64, f64) =
+                    match value_range(property_history.samples(), window.start, window.end) {
+                        Some(range) => range,
+                        None => continue,
+                    };
+                match axes
+                    .iter_mut()
+                    .find(|(unit, _, _)| unit == property_history.unit())
+                {
+                    Some(axis) => {
+                        axis.1 = axis.1.min(min);
+                        axis.2 = axis.2.max(max);
+                    }
+                    None => axes.push((String::from(property_history.unit()), min, max)),
+                }
+            }
+
+            let axes_width: f64 = VALUE_AXIS_WIDTH * axes.len().max(1) as f64;
+            let area: ChartArea = ChartArea {
+                x: axes_width,
+                y: UNIT_LABEL_HEIGHT,
+                width: width - axes_width - CHART_MARGIN,
+                height: height - UNIT_LABEL_HEIGHT - TIME_AXIS_HEIGHT,
+            };
+            if area.width <= 0.0 || area.height <= 0.0 {
+                self.imp().plot_area.set(None);
+                return Ok(());
+            }
+            self.imp().plot_area.set(Some(area));
+
+            // Nothing to show
+            if axes.is_empty() {
+                let text: &str = match series.is_empty() {
+                    true => "Pick properties to show",
+                    false => "No readings in this range",
+                };
+                set_colour(context, &foreground, 0.6);
+                context.set_font_size(14.0);
+                let extents: cairo::TextExtents = context.text_extents(text)?;
+                return draw_text(
+                    context,
+                    text,
+                    (width - extents.width) / 2.0,
+                    height / 2.0,
+                    false,
+                );
+            }
+
+            // Value axes, with grid lines for the first
+            context.set_line_width(1.0);
+            for (index, (unit, min, max)) in axes.iter().enumerate() {
+                let range: ChartRange = ChartRange {
+                    start: window.start,
+                    end: window.end,
+                    min: *min,
+                    max: *max,
+                };
+                let label_x: f64 = VALUE_AXIS_WIDTH * (index + 1) as f64 - 6.0;
+
+                set_colour(context, &foreground, 0.8);
+                let unit_label: &str = match unit.is_empty() {
+                    true => "Value",
+                    false => unit,
+                };
+                draw_text(context, unit_label, label_x, UNIT_LABEL_HEIGHT - 10.0, true)?;
+
+                for tick in axis_ticks(*min, *max, 5) {
+                    let (_, y): (f64, f64) = range.position(&area, window.start, tick);
+                    if index == 0 {
+                        set_colour(context, &foreground, 0.15);
+                        context.move_to(area.x, y);
+                        context.line_to(area.x + area.width, y);
+                        context.stroke()?;
+                    }
+
+                    set_colour(context, &foreground, 0.8);
+                    draw_text(context, &format_value(tick, unit), label_x, y + 4.0, true)?;
+                }
+            }
+
+            // Time axis, as how long ago
+            let time_range: ChartRange = ChartRange {
+                start: window.start,
+                end: window.end,
+                min: 0.0,
+                max: 1.0,
+            };
+            let label_count: u32 = ((area.width / TIME_LABEL_SPACING) as u32).max(1);
+            for timestamp in time_ticks(window.start, window.end, label_count) {
+                let (x, _): (f64, f64) = time_range.position(&area, timestamp, 0.0);
+                let label: String = match now.saturating_sub(timestamp) {
+                    age if age < 1000 => String::from("now"),
+                    age => format!("-{}", format_age(age)),
+                };
+
+                set_colour(context, &foreground, 0.15);
+                context.move_to(x, area.y);
+                context.line_to(x, area.y + area.height);
+                context.stroke()?;
+
+                set_colour(context, &foreground, 0.8);
+                let label_width: f64 = context.text_extents(&label)?.width;
+                draw_text(
+                    context,
+                    &label,
+                    (x - label_width / 2.0).min(width - label_width).max(0.0),
+                    height - 6.0,
+                    false,
+                )?;
+            }
+
+            // Readings
+            let hover_timestamp: Option<u64> = hover.map(|x| time_range.timestamp_at(&area, x));
+            for item in series.iter() {
+                let property_history: &PropertyHistory =
+                    match history.get(&item.uuid, &item.property) {
+                        Some(found) => found,
+                        None => continue,
+                    };
+                let range: ChartRange = match find_axis(&axes, property_history.unit()) {
+                    Some((_, min, max)) => ChartRange {
+                        start: window.start,
+                        end: window.end,
+                        min: *min,
+                        max: *max,
+                    },
+                    None => continue,
+                };
+
+                draw_segments(
+                    context,
+                    &build_segments(property_history.samples(), &range, &area, max_gap),
+                    2.0,
+                    item.colour,
+                )?;
+
+                // Reading under the mouse
+                if let Some(timestamp) = hover_timestamp {
+                    if let Some(HistorySample {
+                        timestamp: sample_timestamp,
+                        value: Some(value),
+                    }) = nearest_sample(property_history.samples(), timestamp)
+                    {
+                        if sample_timestamp >= window.start && sample_timestamp <= window.end {
+                            let (x, y): (f64, f64) = range.position(&area, sample_timestamp, value);
+                            context.set_source_rgb(item.colour.0, item.colour.1, item.colour.2);
+                            context.arc(x, y, 4.0, 0.0, 2.0 * std::f64::consts::PI);
+                            context.fill()?;
+                        }
+                    }
+                }
+            }
+
+            // Line under the mouse
+            if let Some(timestamp) = hover_timestamp {
+                let (x, _): (f64, f64) = time_range.position(&area, timestamp, 0.0);
+                set_colour(context, &foreground, 0.5);
+                context.set_line_width(1.0);
+                context.move_to(x, area.y);
+                context.line_to(x, area.y + area.height);
+                context.stroke()?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /**
+     * Name:
+     * update_tooltip
+     *
+     * Description:
+     * Show the readings of every shown series under the mouse (and how long ago it was) as a tooltip
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Gaps are shown as N/A
+     */
+    fn update_tooltip(&self) {
+        let series: Vec<Series> = self.visible_series();
+        let window: TimeWindow = self.visible_window();
+
+        let text: Option<String> = match (self.imp().hover.get(), self.imp().plot_area.get()) {
+            (Some(x), Some(area)) if !series.is_empty() => {
+                let timestamp: u64 = ChartRange {
+                    start: window.start,
+                    end: window.end,
+                    min: 0.0,
+                    max: 1.0,
+                }
+                .timestamp_at(&area, x);
+
+                let mut lines: Vec<String> = vec![match now_millis().saturating_sub(timestamp) {
+                    age if age < 1000 => String::from("now"),
+                    age => format!("{} ago", format_age(age)),
+                }];
+                with_history(|history| {
+                    for item in series.iter() {
+                        let property_history: &PropertyHistory =
+                            match history.get(&item.uuid, &item.property) {
+                                Some(found) => found,
+                                None => continue,
+                            };
+                        let value: String =
+                            match nearest_sample(property_history.samples(), timestamp) {
+                                Some(HistorySample {
+                                    value: Some(value), ..
+                                }) => format_value(value, property_history.unit()),
+                                _ => String::from("N/A"),
+                            };
+                        lines.push(format!("{}: {}", item.title, value));
+                    }
+                });
+
+                Some(lines.join("\n"))
+            }
+            _ => None,
+        };
+
+        self.imp().history_area.set_tooltip_text(text.as_deref());
+    }
+
+    /**
+     * Name:
+     * open_export_dialog
+     *
+     * Description:
+     * Ask where to save the visible range, then export it
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The dialog is kept in the window until it's answered, otherwise it would be dropped straight away
+     */
+    fn open_export_dialog(&self) {
+        let dialog: FileChooserNative = FileChooserNative::new(
+            Some("Export Visible Range"),
+            Some(self),
+            FileChooserAction::Save,
+            Some("_Export"),
+            Some("_Cancel"),
+        );
+        dialog.set_modal(true);
+        dialog.set_current_name("history.csv");
+        dialog.connect_response(
+            clone!(@weak self as window => move |dialog: &FileChooserNative, response: ResponseType| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = dialog.file().and_then(|file| file.path()) {
+                        match window.export_visible(&path) {
+                            Ok(_) => println!("..History exported to `{}`", path.display()),
+                            Err(err) => println!("..Attempt to export history failed, returning: {}", err),
+                        }
+                    }
+                }
+                window.imp().export_dialog.replace(None);
+            }),
+        );
+        dialog.show();
+
+        self.imp().export_dialog.replace(Some(dialog));
+    }
+
+    /**
+     * Name:
+     * export_visible
+     *
+     * Description:
+     * Save the readings of every shown series over the visible times as CSV
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn export_visible(&self, path: &Path) -> Result<(), String> {
+        let series: Vec<Series> = self.visible_series();
+        if series.is_empty() {
+            return Err(String::from("No properties picked to export.."));
+        }
+        let window: TimeWindow = self.visible_window();
+
+        let csv: String = with_history(|history| {
+            let found: Vec<(&str, &PropertyHistory)> = series
+                .iter()
+                .filter_map(|item| {
+                    history
+                        .get(&item.uuid, &item.property)
+                        .map(|property_history| (item.title.as_str(), property_history))
+                })
+                .collect();
+            export_csv(&found, window.start, window.end)
+        });
+
+        match std::fs::write(path, csv) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(format!("Cannot write `{}`: {}..", path.display(), err)),
+        }
+    }
+}
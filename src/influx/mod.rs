@@ -0,0 +1,598 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Write the readings of every GPU in InfluxDB line protocol, to a socket, HTTP endpoint or stdout
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Every GPU is a line (one field per property), each refresh is written as a single batch.
+ * Stdout is for Telegraf's `execd` input. Doesn't depend on GTK, so also works headless.
+ * <https://docs.influxdata.com/influxdb/v2.6/reference/syntax/line-protocol/>
+ */
+// Imports
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs, UdpSocket},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread,
+    time::Duration,
+};
+
+// Modules
+use crate::sampler::GpuSample;
+
+/// Measurement written if none is set
+pub const DEFAULT_MEASUREMENT: &str = "nvidia_gpu";
+/// Path written to if a HTTP target doesn't give one
+pub const DEFAULT_HTTP_PATH: &str = "/api/v2/write";
+/// Longest datagram sent, whole lines are never split
+const MAX_DATAGRAM: usize = 1400;
+/// Longest time to wait to connect or for a response
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Batches waiting to be written, newer batches are dropped once full
+pub const WORKER_QUEUE_SIZE: usize = 4;
+
+/// Where batches are written to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InfluxTarget {
+    Stdout,
+    Udp(String),
+    Tcp(String),
+    Http { address: String, path: String },
+}
+
+/// Structure for everything needed to write batches
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfluxConfig {
+    pub target: InfluxTarget,
+    pub token: String,
+    pub measurement: String,
+    pub uuid_tag: String,
+    pub name_tag: String,
+}
+
+/**
+ * Name:
+ * InfluxConfig
+ *
+ * Description:
+ * Default config, written to stdout with `uuid` & `name` tags
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Default for InfluxConfig {
+    fn default() -> Self {
+        InfluxConfig {
+            target: InfluxTarget::Stdout,
+            token: String::new(),
+            measurement: String::from(DEFAULT_MEASUREMENT),
+            uuid_tag: String::from("uuid"),
+            name_tag: String::from("name"),
+        }
+    }
+}
+
+/**
+ * Name:
+ * parse_target
+ *
+ * Description:
+ * Parse where to write to, i.e. `stdout`, `udp://host:8089`, `tcp://host:8094` or `http://host:8086/write?db=gpus`
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Sockets need a port, HTTP defaults to port 80 and the InfluxDB 2 write path (the query is kept as is)
+ */
+pub fn parse_target(text: &str) -> Result<InfluxTarget, String> {
+    let text: &str = text.trim();
+    let (scheme, rest): (&str, &str) = match text {
+        "stdout" | "-" => return Ok(InfluxTarget::Stdout),
+        _ => text
+            .split_once("://")
+            .ok_or(format!("Unknown InfluxDB target `{}`..", text))?,
+    };
+
+    match scheme {
+        "udp" | "tcp" => {
+            let address: &str = rest.trim_end_matches('/');
+            match address.rsplit_once(':') {
+                Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => {
+                    match scheme {
+                        "udp" => Ok(InfluxTarget::Udp(String::from(address))),
+                        _ => Ok(InfluxTarget::Tcp(String::from(address))),
+                    }
+                }
+                _ => Err(format!("InfluxDB target `{}` needs a port..", text)),
+            }
+        }
+        "http" => {
+            let (address, path): (&str, String) = match rest.find(['/', '?']) {
+                Some(index) if rest[index..].starts_with('?') => (
+                    &rest[..index],
+                    format!("{}{}", DEFAULT_HTTP_PATH, &rest[index..]),
+                ),
+                Some(index) => (&rest[..index], String::from(&rest[index..])),
+                None => (rest, String::from(DEFAULT_HTTP_PATH)),
+            };
+            let address: String = match address.rsplit_once(':') {
+                _ if address.is_empty() => {
+                    return Err(format!("InfluxDB target `{}` needs a host..", text))
+                }
+                Some((_, port)) if !port.ends_with(']') => match port.parse::<u16>() {
+                    Ok(_) => String::from(address),
+                    Err(_) => return Err(format!("Invalid InfluxDB port `{}`..", port)),
+                },
+                _ => format!("{}:80", address),
+            };
+
+            Ok(InfluxTarget::Http { address, path })
+        }
+        _ => Err(format!("Unsupported InfluxDB scheme `{}`..", scheme)),
+    }
+}
+
+/**
+ * Name:
+ * escape
+ *
+ * Description:
+ * Escape a measurement, tag key/value or field key (`characters` are backslashed)
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Line breaks can't be escaped (they would end the line), so are removed
+ */
+fn escape(text: &str, characters: &[char]) -> String {
+    let mut output: String = String::new();
+    for c in text.chars() {
+        match c {
+            '\n' | '\r' => {}
+            c if characters.contains(&c) => {
+                output.push('\\');
+                output.push(c);
+            }
+            c => output.push(c),
+        }
+    }
+
+    output
+}
+
+/**
+ * Name:
+ * format_line
+ *
+ * Description:
+ * Format the readings of a GPU as a line, i.e. `nvidia_gpu,uuid=GPU-1a2b3c4d util=45,temp=52 1675728000000000000`
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Readings that couldn't be read are left out, a GPU without any readings has no line.
+ * Tags with an empty name are left out, an empty measurement uses the default.
+ * Timestamps are in nanoseconds (the default precision)
+ */
+pub fn format_line(config: &InfluxConfig, sample: &GpuSample) -> Option<String> {
+    let fields: Vec<String> = sample
+        .readings
+        .iter()
+        .filter_map(|reading| {
+            reading.value.map(|value| {
+                format!(
+                    "{}={}",
+                    escape(reading.metric.name, &[',', '=', ' ']),
+                    value
+                )
+            })
+        })
+        .collect();
+    if fields.is_empty() {
+        return None;
+    }
+
+    let measurement: &str = match config.measurement.is_empty() {
+        true => DEFAULT_MEASUREMENT,
+        false => &config.measurement,
+    };
+    let mut line: String = escape(measurement, &[',', ' ']);
+    for (tag, value) in [
+        (&config.uuid_tag, &sample.uuid),
+        (&config.name_tag, &sample.name),
+    ] {
+        if !tag.is_empty() && !value.is_empty() {
+            line += &format!(
+                ",{}={}",
+                escape(tag, &[',', '=', ' ']),
+                escape(value, &[',', '=', ' '])
+            );
+        }
+    }
+
+    Some(format!(
+        "{} {} {}",
+        line,
+        fields.join(","),
+        sample.timestamp * 1_000_000
+    ))
+}
+
+/**
+ * Name:
+ * format_batch
+ *
+ * Description:
+ * Format the readings of every GPU, a line each
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Empty if there is nothing to write
+ */
+pub fn format_batch(config: &InfluxConfig, samples: &[GpuSample]) -> String {
+    samples
+        .iter()
+        .filter_map(|sample| format_line(config, sample))
+        .map(|line| line + "\n")
+        .collect()
+}
+
+/**
+ * Name:
+ * split_datagrams
+ *
+ * Description:
+ * Split a batch into datagrams of whole lines, each as small as needed to fit a single packet
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A single line longer than the limit is sent on it's own
+ */
+pub fn split_datagrams(batch: &str) -> Vec<String> {
+    let mut datagrams: Vec<String> = vec![];
+    let mut current: String = String::new();
+    for line in batch.split_inclusive('\n') {
+        if !current.is_empty() && current.len() + line.len() > MAX_DATAGRAM {
+            datagrams.push(current);
+            current = String::new();
+        }
+        current += line;
+    }
+    if !current.is_empty() {
+        datagrams.push(current);
+    }
+
+    datagrams
+}
+
+/**
+ * Name:
+ * resolve
+ *
+ * Description:
+ * Find the first socket address of a target address
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn resolve(address: &str) -> Result<SocketAddr, String> {
+    address
+        .to_socket_addrs()
+        .map_err(|err| format!("Cannot find `{}`: {}", address, err))?
+        .next()
+        .ok_or(format!("Cannot find `{}`..", address))
+}
+
+/**
+ * Name:
+ * connect
+ *
+ * Description:
+ * Open a TCP connection to a target address
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn connect(address: &str) -> Result<TcpStream, String> {
+    let stream: TcpStream = TcpStream::connect_timeout(&resolve(address)?, TIMEOUT)
+        .map_err(|err| format!("Cannot connect to `{}`: {}", address, err))?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(TIMEOUT)))
+        .map_err(|err| err.to_string())?;
+
+    Ok(stream)
+}
+
+/// Structure for writing batches to a target, keeping any TCP connection open between them
+#[derive(Debug)]
+pub struct InfluxWriter {
+    config: InfluxConfig,
+    stream: Option<TcpStream>,
+}
+
+/**
+ * Name:
+ * InfluxWriter
+ *
+ * Description:
+ * Trait shared by all InfluxWriter objects
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl InfluxWriter {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a writer, nothing is connected until the first batch is written
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(config: InfluxConfig) -> Self {
+        InfluxWriter {
+            config,
+            stream: None,
+        }
+    }
+
+    /**
+     * Name:
+     * write
+     *
+     * Description:
+     * Write the readings of every GPU as a single batch, returns the number of lines written
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * A lost TCP connection is opened again on the next batch, nothing is retried
+     */
+    pub fn write(&mut self, samples: &[GpuSample]) -> Result<usize, String> {
+        let batch: String = format_batch(&self.config, samples);
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        match &self.config.target {
+            InfluxTarget::Stdout => {
+                let mut stdout: io::StdoutLock = io::stdout().lock();
+                stdout
+                    .write_all(batch.as_bytes())
+                    .and_then(|_| stdout.flush())
+                    .map_err(|err| format!("Cannot write to stdout: {}", err))?;
+            }
+            InfluxTarget::Udp(address) => {
+                let target: SocketAddr = resolve(address)?;
+                let local: &str = match target {
+                    SocketAddr::V4(_) => "0.0.0.0:0",
+                    SocketAddr::V6(_) => "[::]:0",
+                };
+                let socket: UdpSocket = UdpSocket::bind(local).map_err(|err| err.to_string())?;
+                for datagram in split_datagrams(&batch) {
+                    socket
+                        .send_to(datagram.as_bytes(), target)
+                        .map_err(|err| format!("Cannot send to `{}`: {}", address, err))?;
+                }
+            }
+            InfluxTarget::Tcp(address) => {
+                if self.stream.is_none() {
+                    self.stream = Some(connect(address)?);
+                }
+                if let Some(stream) = self.stream.as_mut() {
+                    if let Err(err) = stream.write_all(batch.as_bytes()) {
+                        self.stream = None;
+                        return Err(format!("Cannot write to `{}`: {}", address, err));
+                    }
+                }
+            }
+            InfluxTarget::Http { address, path } => {
+                post(address, path, &self.config.token, &batch)?;
+            }
+        }
+
+        Ok(batch.lines().count())
+    }
+}
+
+/**
+ * Name:
+ * post
+ *
+ * Description:
+ * Send a batch to a HTTP write endpoint, failing unless it's accepted (2xx)
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A token is sent as `Authorization: Token TOKEN` (InfluxDB 2, or `user:password` for 1.8+)
+ */
+fn post(address: &str, path: &str, token: &str, batch: &str) -> Result<(), String> {
+    let mut stream: TcpStream = connect(address)?;
+
+    // Send request
+    let mut request: String = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n",
+        path,
+        address,
+        batch.len()
+    );
+    if !token.is_empty() {
+        request += &format!("Authorization: Token {}\r\n", token);
+    }
+    request += "\r\n";
+    request += batch;
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|err| format!("Cannot write to `{}`: {}", address, err))?;
+
+    // Check status
+    let mut reader: BufReader<TcpStream> = BufReader::new(stream);
+    let mut status_line: String = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|err| format!("Cannot read from `{}`: {}", address, err))?;
+    let status: &str = status_line.trim_end();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        Some(_) => Err(format!("InfluxDB refused batch: `{}`..", status)),
+        None => Err(format!("Invalid response from `{}`..", address)),
+    }
+}
+
+/// Structure for a writer running in the background, so a slow target never blocks the caller
+#[derive(Debug)]
+pub struct InfluxWorker {
+    sender: SyncSender<Vec<GpuSample>>,
+}
+
+/**
+ * Name:
+ * InfluxWorker
+ *
+ * Description:
+ * Trait shared by all InfluxWorker objects
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl InfluxWorker {
+    /**
+     * Name:
+     * spawn
+     *
+     * Description:
+     * Start writing in the background
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The thread is never joined, once dropped it writes anything queued then stops
+     */
+    pub fn spawn(config: InfluxConfig) -> Self {
+        let (sender, receiver): (SyncSender<Vec<GpuSample>>, Receiver<Vec<GpuSample>>) =
+            mpsc::sync_channel(WORKER_QUEUE_SIZE);
+
+        thread::spawn(move || {
+            let mut writer: InfluxWriter = InfluxWriter::new(config);
+            for samples in receiver {
+                if let Err(err) = writer.write(&samples) {
+                    eprintln!("..Attempt to write to InfluxDB failed, returning: {}", err);
+                }
+            }
+        });
+
+        InfluxWorker { sender }
+    }
+
+    /**
+     * Name:
+     * send
+     *
+     * Description:
+     * Queue samples to be written, returning false if they were dropped
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Never blocks, samples are dropped if the target can't keep up
+     */
+    pub fn send(&self, samples: Vec<GpuSample>) -> bool {
+        match self.sender.try_send(samples) {
+            Ok(_) => true,
+            Err(TrySendError::Full(_)) => {
+                eprintln!("..InfluxDB is busy, dropping readings");
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
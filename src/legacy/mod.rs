@@ -0,0 +1,245 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Migrate settings from the original gnome-nvidia-extension
+ *
+ * Made:
+ * 29/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The extension stores it's settings under `org.gnome.shell.extensions.nvidiautil`, using the same
+ * provider (0-3) and temperature unit (0-1) values as this app. The properties shown are stored per
+ * provider as a list of booleans, in the order of `LEGACY_PROPERTIES`
+ */
+/// GSettings schema of the extension
+pub const LEGACY_SCHEMA_ID: &str = "org.gnome.shell.extensions.nvidiautil";
+/// Properties the extension could show, in the order stored in it's settings
+pub const LEGACY_PROPERTIES: [&str; 5] =
+    ["util", "temp", "memory_usage", "fan_speed", "power_usage"];
+/// Title of the view created for each GPU
+pub const IMPORTED_VIEW_TITLE: &str = "Overview";
+/// Range of refresh rates accepted by this app
+const REFRESH_RATE_RANGE: (i32, i32) = (1, 20);
+
+/// Structure for storing the settings read from the extension
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacySettings {
+    pub refresh_rate: Option<i32>,
+    pub temp_format: Option<i32>,
+    pub provider: Option<i32>,
+    pub properties: Vec<Vec<bool>>,
+}
+
+/// Structure for storing the settings translated for this app
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LegacyImport {
+    pub refresh_rate: Option<i32>,
+    pub temp_format: Option<i32>,
+    pub provider: Option<i32>,
+    pub viewconfigs: Vec<String>,
+    pub viewcomponentconfigs: Vec<String>,
+    pub problems: Vec<String>,
+}
+
+/**
+ * Name:
+ * LegacySettings
+ *
+ * Description:
+ * Trait shared by all legacy settings
+ *
+ * Made:
+ * 29/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl LegacySettings {
+    /**
+     * Name:
+     * is_empty
+     *
+     * Description:
+     * Check if there is anything to import
+     *
+     * Made:
+     * 29/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_empty(&self) -> bool {
+        self.refresh_rate.is_none()
+            && self.temp_format.is_none()
+            && self.provider.is_none()
+            && self.properties.is_empty()
+    }
+}
+
+/**
+ * Name:
+ * config_key
+ *
+ * Description:
+ * Find the extension setting holding the enabled properties of a provider
+ *
+ * Made:
+ * 29/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Nvidia Settings and Nvidia SMI (0) shares the Nvidia Settings list
+ */
+pub fn config_key(provider: i32) -> Option<&'static str> {
+    match provider {
+        0 | 1 => Some("settingsconfig"),
+        2 => Some("smiconfig"),
+        3 => Some("optimusconfig"),
+        _ => None,
+    }
+}
+
+/**
+ * Name:
+ * enabled_properties
+ *
+ * Description:
+ * Convert the extension's list of booleans into the names of the enabled properties
+ *
+ * Made:
+ * 29/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Anything past the known properties is ignored
+ */
+pub fn enabled_properties(flags: &[bool]) -> Vec<&'static str> {
+    flags
+        .iter()
+        .zip(LEGACY_PROPERTIES.iter())
+        .filter(|(enabled, _)| **enabled)
+        .map(|(_, property)| *property)
+        .collect()
+}
+
+/**
+ * Name:
+ * translate_legacy
+ *
+ * Description:
+ * Translate the extension's settings into this app's, replacing the views of every GPU given
+ *
+ * Made:
+ * 29/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A single list of properties applies to every GPU (as the extension showed the same properties
+ * for all of them), otherwise each list is for the GPU at that index
+ */
+pub fn translate_legacy(
+    legacy: &LegacySettings,
+    gpu_uuids: &[String],
+    viewconfigs: Vec<String>,
+    viewcomponentconfigs: Vec<String>,
+) -> LegacyImport {
+    let mut import: LegacyImport = LegacyImport {
+        viewconfigs,
+        viewcomponentconfigs,
+        ..LegacyImport::default()
+    };
+
+    // Simple values
+    if let Some(refresh_rate) = legacy.refresh_rate {
+        let (min, max): (i32, i32) = REFRESH_RATE_RANGE;
+        let clamped: i32 = refresh_rate.clamp(min, max);
+        if clamped != refresh_rate {
+            import.problems.push(format!(
+                "Refresh rate `{}` is out of range, using `{}`..",
+                refresh_rate, clamped
+            ));
+        }
+        import.refresh_rate = Some(clamped);
+    }
+    match legacy.temp_format {
+        Some(format @ 0..=1) => import.temp_format = Some(format),
+        Some(format) => import
+            .problems
+            .push(format!("Unknown temperature unit `{}`..", format)),
+        None => {}
+    }
+    match legacy.provider {
+        Some(provider @ 0..=3) => import.provider = Some(provider),
+        Some(provider) => import
+            .problems
+            .push(format!("Unknown provider `{}`..", provider)),
+        None => {}
+    }
+
+    // Views
+    if legacy.properties.len() > 1 && legacy.properties.len() != gpu_uuids.len() {
+        import.problems.push(format!(
+            "Properties saved for {} GPUs, but {} found..",
+            legacy.properties.len(),
+            gpu_uuids.len()
+        ));
+    }
+    for (index, uuid) in gpu_uuids.iter().enumerate() {
+        let flags: &Vec<bool> = match legacy.properties.len() {
+            0 => break,
+            1 => &legacy.properties[0],
+            _ => match legacy.properties.get(index) {
+                Some(valid_flags) => valid_flags,
+                None => continue,
+            },
+        };
+        let properties: Vec<&str> = enabled_properties(flags);
+        if properties.is_empty() {
+            import
+                .problems
+                .push(format!("No properties enabled for GPU {}..", uuid));
+            continue;
+        }
+
+        // Replace any existing views of this GPU
+        let prefix: String = uuid.clone() + ":";
+        import.viewconfigs.retain(|view| !view.starts_with(&prefix));
+        import
+            .viewcomponentconfigs
+            .retain(|component| !component.starts_with(&prefix));
+
+        //UUID:POSITION:VIEW_TITLE
+        import
+            .viewconfigs
+            .push(format!("{}:0:{}", uuid, IMPORTED_VIEW_TITLE));
+        //UUID:VIEW_TITLE:POSITION:name
+        for (position, property) in properties.iter().enumerate() {
+            import.viewcomponentconfigs.push(format!(
+                "{}:{}:{}:{}",
+                uuid, IMPORTED_VIEW_TITLE, position, property
+            ));
+        }
+    }
+
+    import
+}
@@ -18,26 +18,59 @@
  *
  */
 // Modules
-mod formatter;
+pub mod chart;
+pub mod command;
+pub mod display_style;
+pub mod fan_curve;
+pub mod fan_override;
+pub mod formatter;
 mod gpu_page;
+pub mod gwe;
+pub mod headless;
+pub mod helper;
+pub mod history;
+pub mod influx;
+pub mod legacy;
 mod mainwindow;
+pub mod metrics_server;
+pub mod metrics_store;
+pub mod mig;
+pub mod mqtt;
+pub mod overclock;
 mod processor;
-mod property;
+pub mod profile;
+pub mod profile_rules;
+pub mod property;
 mod provider;
+pub mod sample_log;
+pub mod sampler;
+pub mod secret;
 mod subprocess;
+pub mod targets;
+pub mod throttle;
+pub mod watchdog;
 use mainwindow::MainWindow;
 mod custom_button;
+mod fancurvewindow;
+mod history_graph;
+mod historywindow;
 mod modificationwindow;
+mod overclockwindow;
 mod settingswindow;
 
 // Imports
-use adwaita::{gio, prelude::*, Application};
+use adwaita::{gio, glib, prelude::*, Application};
 use gdk::Display;
 use gio::resources_register_include;
+use glib::{clone, Continue};
 use gtk::{CssProvider, StyleContext};
+use std::{cell::RefCell, rc::Rc};
+use subprocess::subprocess::SubprocessRunner;
 
 // Constants
 const APP_ID: &str = "com.gtk_d.NvidiaMonitorRust";
+const SIGINT: i32 = 2;
+const SIGTERM: i32 = 15;
 
 /**
  * Name:
@@ -66,15 +99,83 @@ pub fn create_app() -> Application {
     // Create a new application
     let app: Application = Application::builder().application_id(APP_ID).build();
 
+    // Command line options
+    app.add_main_option(
+        "profile",
+        glib::Char::from(b'p'),
+        glib::OptionFlags::NONE,
+        glib::OptionArg::String,
+        "Apply a saved tuning profile at startup",
+        Some("NAME"),
+    );
+    let startup_profile: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    app.connect_handle_local_options(clone!(@strong startup_profile => move |_, options| {
+        if let Some(name) = options
+            .lookup_value("profile", Some(glib::VariantTy::STRING))
+            .and_then(|value| value.get::<String>())
+        {
+            startup_profile.replace(Some(name));
+        }
+
+        // Carry on as normal
+        -1
+    }));
+
     // Connect to signals of `app`
     app.connect_startup(setup_shortcuts);
     app.connect_startup(|_| load_css());
-    app.connect_activate(build_ui);
+    app.connect_activate(move |app| build_ui(app, startup_profile.take()));
+
+    // Make sure fans are handed back to the driver however we exit
+    setup_fan_restore(&app);
 
     // Return the application
     app
 }
 
+/**
+ * Name:
+ * setup_fan_restore
+ *
+ * Description:
+ * Hand any fans we control back to the driver on a crash (panic) or SIGTERM/SIGINT
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Closing the main window is handled in `MainWindow::close_request`
+ */
+fn setup_fan_restore(app: &Application) {
+    // Restore before the usual panic output
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        for err in fan_override::restore_all(&SubprocessRunner) {
+            println!("..Cannot restore fan control: `{}`", err);
+        }
+        default_hook(info);
+    }));
+
+    // Restore then quit on signals
+    for signal in [SIGINT, SIGTERM] {
+        glib::unix_signal_add_local(
+            signal,
+            clone!(@weak app => @default-return Continue(false), move || {
+                println!("..Signal `{}` received, restoring fan control", signal);
+                for err in fan_override::restore_all(&SubprocessRunner) {
+                    println!("..Cannot restore fan control: `{}`", err);
+                }
+                app.quit();
+
+                Continue(false)
+            }),
+        );
+    }
+}
+
 /**
  * Name:
  * setup_shortcuts
@@ -148,9 +249,9 @@ fn load_css() {
  * Deren Vural
  *
  * Notes:
- *
+ * startup_profile is the name of a profile to apply (from the `--profile` option)
  */
-fn build_ui(app: &Application) {
+fn build_ui(app: &Application, startup_profile: Option<String>) {
     // Create a new custom window and show it
     let window: MainWindow = MainWindow::new(app);
 
@@ -167,6 +268,23 @@ fn build_ui(app: &Application) {
     // Present window
     window.show();
 
+    // Offer to bring settings over from the original extension (first run only)
+    window.offer_legacy_import();
+
+    // Apply any profile asked for on the command line
+    if let Some(name) = startup_profile {
+        window.apply_profile(
+            &name.clone(),
+            move |_: &MainWindow, result: Result<(), String>| match result {
+                Ok(_) => println!("..Profile `{}` applied", name),
+                Err(err) => println!(
+                    "..Attempt to apply profile `{}` failed, returning: {}",
+                    name, err
+                ),
+            },
+        );
+    }
+
     /*
     // App Indicator
     //let mut indicator = AppIndicator::new("Nvidia App", "");
@@ -29,12 +29,33 @@ use gtk::{
     subclass::prelude::*, Button, CompositeTemplate, PolicyType, ScrolledWindow, Stack,
     TemplateChild,
 };
-use std::{cell::Cell, cell::RefCell, cell::RefMut, rc::Rc};
+use std::{cell::Cell, cell::RefCell, cell::RefMut, collections::HashMap, rc::Rc};
 
 // Modules
 use crate::{
-    formatter::Formatter, gpu_page::GpuPage, processor::Processor, property::Property,
-    provider::Provider, settingswindow::SettingsWindow,
+    fan_override::restore_all,
+    formatter::{
+        find_utilization_value, format_clock_speed, format_perf_modes, format_power_fraction,
+        pcie_speed_to_generation, Formatter,
+    },
+    gpu_page::GpuPage,
+    historywindow::HistoryWindow,
+    influx::{InfluxConfig, InfluxWorker},
+    metrics_server::MetricsServer,
+    metrics_store::MetricsWorker,
+    mqtt::{MqttConfig, MqttWorker},
+    processor::Processor,
+    profile::Profile,
+    profile_rules::RuleEngine,
+    property::Property,
+    provider::Provider,
+    sample_log::SampleLog,
+    sampler::Sampler,
+    settingswindow::SettingsWindow,
+    subprocess::subprocess::SubprocessRunner,
+    targets::TargetType,
+    throttle::{create_badge_markup, decode_throttle_reasons, parse_throttle_bitmask},
+    watchdog::Watchdog,
 };
 
 /// Structure for storing a SettingsWindow object and any related information
@@ -43,6 +64,12 @@ pub struct SettingsWindowContainer {
     pub window: Option<SettingsWindow>,
     pub open: bool,
 }
+/// Structure for storing a HistoryWindow object and any related information
+#[derive(Default)]
+pub struct HistoryWindowContainer {
+    pub window: Option<HistoryWindow>,
+    pub open: bool,
+}
 /// Enum for choosing temperature units
 #[derive(Debug, PartialEq, Eq)]
 enum TemperatureUnit {
@@ -50,6 +77,609 @@ enum TemperatureUnit {
     FAHRENHEIT = 1,
 }
 
+/**
+ * Name:
+ * create_clock_properties
+ *
+ * Description:
+ * Creates the clock speed (and clock throttle) properties for an nvidia-smi based provider
+ *
+ * Made:
+ * 14/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * base_call/start_call are the same as the other properties of the provider,
+ * i.e. `nvidia-smi` + `--query-gpu=` or `optirun` + `nvidia-smi --query-gpu=`
+ */
+fn create_clock_properties(base_call: &str, start_call: &str) -> Vec<Property> {
+    // List of current, application and max clocks
+    let ids: [&str; 9] = [
+        "clocks.gr",
+        "clocks.sm",
+        "clocks.mem",
+        "clocks.video",
+        "clocks.applications.graphics",
+        "clocks.applications.memory",
+        "clocks.max.graphics",
+        "clocks.max.sm",
+        "clocks.max.memory",
+    ];
+
+    // Create a property for each clock
+    let mut properties: Vec<Property> = vec![];
+    for id in ids {
+        properties.push(Property::new(
+            &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input
+                    let input_str: String = String::from(input.get(0).unwrap());
+
+                    // Convert to float
+                    match input_str.parse::<f64>() {
+                        // Apply formatting and return result
+                        Ok(parsed_value) => Some(format_clock_speed(parsed_value)),
+                        Err(_) => {
+                            //this should catch "" etc
+                            println!("Not a valid number");
+
+                            None
+                        }
+                    }
+                },
+            ),
+            id,
+        ));
+    }
+
+    // Create property for reasons behind any clock throttling
+    properties.push(Property::new(
+        &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+        &Formatter::new(
+            |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                // Grab input
+                let input_str: String = String::from(input.get(0).unwrap());
+
+                // Convert from hex bitmask
+                match parse_throttle_bitmask(&input_str) {
+                    // Decode bitmask and apply formatting
+                    Some(bitmask) => Some(create_badge_markup(&decode_throttle_reasons(bitmask))),
+                    None => {
+                        //this should catch "[N/A]" etc
+                        println!("Not a valid bitmask");
+
+                        None
+                    }
+                }
+            },
+        ),
+        "clocks_throttle_reasons.active",
+    ));
+
+    properties
+}
+
+/**
+ * Name:
+ * create_pcie_properties
+ *
+ * Description:
+ * Creates the PCIe link properties for an nvidia-smi based provider
+ *
+ * Made:
+ * 16/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * see create_clock_properties
+ */
+fn create_pcie_properties(base_call: &str, start_call: &str) -> Vec<Property> {
+    vec![
+        Property::new(
+            &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from("Gen ") + input.get(0).unwrap())
+                },
+            ),
+            "pcie.link.gen.current",
+        ),
+        Property::new(
+            &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from("Gen ") + input.get(0).unwrap())
+                },
+            ),
+            "pcie.link.gen.max",
+        ),
+        Property::new(
+            &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from("x") + input.get(0).unwrap())
+                },
+            ),
+            "pcie.link.width.current",
+        ),
+        Property::new(
+            &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from("x") + input.get(0).unwrap())
+                },
+            ),
+            "pcie.link.width.max",
+        ),
+    ]
+}
+
+/**
+ * Name:
+ * create_nvidia_settings_pcie_properties
+ *
+ * Description:
+ * Creates the PCIe link properties for an nvidia-settings based provider
+ *
+ * Made:
+ * 16/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * nvidia-settings gives link speed (MT/s) rather than generation
+ */
+fn create_nvidia_settings_pcie_properties() -> Vec<Property> {
+    vec![
+        Property::new(
+            &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input
+                    let input_str: String = String::from(input.get(0).unwrap());
+
+                    // Convert link speed to generation and apply formatting
+                    match input_str.parse::<f64>() {
+                        Ok(speed) => pcie_speed_to_generation(speed)
+                            .map(|generation| String::from("Gen ") + &generation.to_string()),
+                        Err(_) => {
+                            //this should catch "" etc
+                            println!("Not a valid number");
+
+                            None
+                        }
+                    }
+                },
+            ),
+            "PCIECurrentLinkSpeed",
+        ),
+        Property::new(
+            &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input
+                    let input_str: String = String::from(input.get(0).unwrap());
+
+                    // Convert link speed to generation and apply formatting
+                    match input_str.parse::<f64>() {
+                        Ok(speed) => pcie_speed_to_generation(speed)
+                            .map(|generation| String::from("Gen ") + &generation.to_string()),
+                        Err(_) => {
+                            //this should catch "" etc
+                            println!("Not a valid number");
+
+                            None
+                        }
+                    }
+                },
+            ),
+            "PCIEMaxLinkSpeed",
+        ),
+        Property::new(
+            &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from("x") + input.get(0).unwrap())
+                },
+            ),
+            "PCIECurrentLinkWidth",
+        ),
+        Property::new(
+            &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from("x") + input.get(0).unwrap())
+                },
+            ),
+            "PCIEMaxLinkWidth",
+        ),
+    ]
+}
+
+/**
+ * Name:
+ * create_pcie_utilization_property
+ *
+ * Description:
+ * Creates the PCIe bandwidth utilization property (only available through nvidia-settings)
+ *
+ * Made:
+ * 16/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn create_pcie_utilization_property() -> Property {
+    Property::new(
+        &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+        &Formatter::new(
+            |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                // Grab item in output that we want (i.e. `PCIe=2`), not every driver lists it
+                match find_utilization_value(input.get(0).unwrap(), "PCIe") {
+                    // Apply formatting
+                    Some(value) => Some(value + " %"),
+                    None => Some(String::from("N/A")),
+                }
+            },
+        ),
+        "GPUUtilization.pcie",
+    )
+}
+
+/**
+ * Name:
+ * create_health_properties
+ *
+ * Description:
+ * Creates the ECC error, retired page and row remapping properties for an nvidia-smi based provider
+ *
+ * Made:
+ * 17/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Row remapping uses a different query (`--query-remapped-rows=`) to the rest
+ */
+fn create_health_properties(base_call: &str, start_call: &str) -> Vec<Property> {
+    // List of all error counts
+    let count_ids: [&str; 6] = [
+        "ecc.errors.corrected.volatile.total",
+        "ecc.errors.uncorrected.volatile.total",
+        "ecc.errors.corrected.aggregate.total",
+        "ecc.errors.uncorrected.aggregate.total",
+        "retired_pages.single_bit_ecc.count",
+        "retired_pages.double_bit.count",
+    ];
+    // List of all row remapping properties
+    let remapped_rows_ids: [&str; 4] = [
+        "remapped_rows.correctable",
+        "remapped_rows.uncorrectable",
+        "remapped_rows.pending",
+        "remapped_rows.failure",
+    ];
+    let remapped_rows_start_call: String =
+        start_call.replace("--query-gpu=", "--query-remapped-rows=");
+
+    // Create ECC mode property
+    let mut properties: Vec<Property> = vec![Property::new(
+        &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+        &Formatter::new(
+            |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                Some(String::from(input.get(0).unwrap()))
+            },
+        ),
+        "ecc.mode.current",
+    )];
+
+    // Create a property for each error count/row remapping state
+    for id in count_ids.iter().chain(remapped_rows_ids.iter()) {
+        // Decide on query
+        let query: &str = if remapped_rows_ids.contains(id) {
+            &remapped_rows_start_call
+        } else {
+            start_call
+        };
+
+        // Decide on formatting
+        let formatter: Formatter = if let "remapped_rows.pending" | "remapped_rows.failure" = *id {
+            Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and convert to yes/no
+                    match input.get(0).unwrap().trim() {
+                        "1" | "Yes" | "yes" => Some(String::from("Yes")),
+                        "0" | "No" | "no" => Some(String::from("No")),
+                        other => Some(String::from(other)),
+                    }
+                },
+            )
+        } else {
+            Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    Some(String::from(input.get(0).unwrap()))
+                },
+            )
+        };
+
+        properties.push(Property::new(
+            &Processor::new(base_call, query, None, " --format=csv,noheader -i "),
+            &formatter,
+            id,
+        ));
+    }
+
+    // Retired pages pending isn't part of the row remapping query
+    properties.push(Property::new(
+        &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+        &Formatter::new(
+            |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                // Grab input and convert to yes/no
+                match input.get(0).unwrap().trim() {
+                    "1" | "Yes" | "yes" => Some(String::from("Yes")),
+                    "0" | "No" | "no" => Some(String::from("No")),
+                    other => Some(String::from(other)),
+                }
+            },
+        ),
+        "retired_pages.pending",
+    ));
+
+    properties
+}
+
+/**
+ * Name:
+ * create_power_properties
+ *
+ * Description:
+ * Creates the power limit and performance state properties for an nvidia-smi based provider
+ *
+ * Made:
+ * 18/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * see create_clock_properties
+ */
+fn create_power_properties(base_call: &str, start_call: &str) -> Vec<Property> {
+    // List of all power limits
+    let limit_ids: [&str; 5] = [
+        "power.limit",
+        "enforced.power.limit",
+        "power.min_limit",
+        "power.max_limit",
+        "power.default_limit",
+    ];
+
+    // Create a property for each power limit
+    let mut properties: Vec<Property> = vec![];
+    for id in limit_ids {
+        properties.push(Property::new(
+            &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input
+                    let input_str: String = String::from(input.get(0).unwrap());
+
+                    // Convert to float
+                    match input_str.parse::<f64>() {
+                        Ok(parsed_value) => {
+                            // Round down to nearest integer and apply formatting
+                            Some(parsed_value.floor().to_string() + " W")
+                        }
+                        Err(_) => {
+                            //this should catch "" etc
+                            println!("Not a valid number");
+
+                            None
+                        }
+                    }
+                },
+            ),
+            id,
+        ));
+    }
+
+    // Create performance state property
+    properties.push(Property::new(
+        &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+        &Formatter::new(
+            |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                // Grab input and add formatting (`P` is removed when cleaning)
+                Some(String::from("P") + input.get(0).unwrap())
+            },
+        ),
+        "pstate",
+    ));
+
+    // Create property for power draw as a fraction of the enforced limit
+    properties.push(Property::new(
+        &Processor::new(base_call, start_call, None, " --format=csv,noheader -i "),
+        &Formatter::new(
+            |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                // Grab input and apply formatting
+                match format_power_fraction(input.get(0).unwrap()) {
+                    Some(fraction) => Some(fraction),
+                    None => {
+                        println!("Not a valid power draw/limit");
+
+                        None
+                    }
+                }
+            },
+        ),
+        "power.draw,enforced.power.limit",
+    ));
+
+    properties
+}
+
+/**
+ * Name:
+ * create_nvidia_settings_perf_properties
+ *
+ * Description:
+ * Creates the performance level properties for an nvidia-settings based provider
+ *
+ * Made:
+ * 18/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn create_nvidia_settings_perf_properties() -> Vec<Property> {
+    vec![
+        Property::new(
+            &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from("Level ") + input.get(0).unwrap())
+                },
+            ),
+            "GPUCurrentPerfLevel",
+        ),
+        Property::new(
+            &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and apply formatting
+                    Some(format_perf_modes(input.get(0).unwrap()))
+                },
+            ),
+            "GPUPerfModes",
+        ),
+    ]
+}
+
+/**
+ * Name:
+ * create_nvidia_settings_target_processor
+ *
+ * Description:
+ * Creates an nvidia-settings processor that queries a fan/thermal sensor target instead of a gpu
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn create_nvidia_settings_target_processor(target_type: TargetType) -> Processor {
+    let processor: Processor = Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t");
+    processor.set_target_type(target_type);
+
+    processor
+}
+
+/**
+ * Name:
+ * create_nvidia_settings_target_properties
+ *
+ * Description:
+ * Creates the per-fan and per-thermal sensor properties for an nvidia-settings based provider
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * These are parsed using the index of the fan/sensor (i.e. `[fan:1]`) rather than the gpu uuid
+ */
+fn create_nvidia_settings_target_properties() -> Vec<Property> {
+    vec![
+        Property::new(
+            &create_nvidia_settings_target_processor(TargetType::Fan),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from(input.get(0).unwrap()) + " %")
+                },
+            ),
+            "GPUCurrentFanSpeed",
+        ),
+        Property::new(
+            &create_nvidia_settings_target_processor(TargetType::Fan),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from(input.get(0).unwrap()) + " RPM")
+                },
+            ),
+            "GPUCurrentFanSpeedRPM",
+        ),
+        Property::new(
+            &create_nvidia_settings_target_processor(TargetType::Fan),
+            &Formatter::new(
+                |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                    // Grab input and add formatting
+                    Some(String::from(input.get(0).unwrap()) + " %")
+                },
+            ),
+            "GPUTargetFanSpeed",
+        ),
+        Property::new(
+            &create_nvidia_settings_target_processor(TargetType::ThermalSensor),
+            &Formatter::new(
+                |input: Vec<String>, params: Option<Vec<(String, String)>>| {
+                    // Grab input
+                    let temp: f64 = match input.get(0).unwrap().parse::<f64>() {
+                        Ok(valid_temp) => valid_temp,
+                        Err(_) => {
+                            //this should catch "" etc
+                            println!("Not a valid number");
+
+                            return None;
+                        }
+                    };
+
+                    // Grab current temperature unit from parameters
+                    let fahrenheit: bool = match params {
+                        Some(valid_params) => valid_params
+                            .iter()
+                            .any(|i| i == &(String::from("tempformat"), String::from("F"))),
+                        None => false,
+                    };
+
+                    // Apply formatting
+                    if fahrenheit {
+                        Some((temp * 9.0 / 5.0 + 32.0).floor().to_string() + "\u{00B0}F")
+                    } else {
+                        Some(temp.to_string() + "\u{00B0}C")
+                    }
+                },
+            ),
+            "ThermalSensorReading",
+        ),
+    ]
+}
+
 /// Object holding the State and any Template Children
 #[derive(CompositeTemplate, Default)]
 #[template(resource = "/main-window.ui")]
@@ -57,7 +687,23 @@ pub struct MainWindow {
     // Public
     pub settings: OnceCell<Settings>,
     pub settings_window: Rc<RefCell<SettingsWindowContainer>>,
+    pub history_window: Rc<RefCell<HistoryWindowContainer>>,
     pub provider: Cell<Option<Provider>>,
+    pub profile_rules: RefCell<RuleEngine>,
+    pub previous_profiles: RefCell<Option<Vec<Profile>>>,
+    pub profile_gpus: RefCell<Vec<String>>,
+    pub profile_scan_running: Cell<bool>,
+    pub profile_scan_error: RefCell<Option<String>>,
+    pub profile_apply_running: Cell<bool>,
+    pub watchdog: RefCell<Watchdog>,
+    pub watchdog_sampling: Cell<bool>,
+    pub metrics_worker: RefCell<Option<MetricsWorker>>,
+    pub sampler: RefCell<Option<(i32, Sampler)>>,
+    pub sampling: Cell<bool>,
+    pub sample_logs: RefCell<HashMap<String, SampleLog>>,
+    pub metrics_server: RefCell<Option<(String, MetricsServer)>>,
+    pub mqtt_worker: RefCell<Option<(MqttConfig, MqttWorker)>>,
+    pub influx_worker: RefCell<Option<(InfluxConfig, InfluxWorker)>>,
 
     // Private
     gpu_pages: RefCell<Vec<GpuPage>>,
@@ -65,6 +711,8 @@ pub struct MainWindow {
     // Template Children
     #[template_child]
     pub gpu_stack: TemplateChild<Stack>,
+    #[template_child]
+    pub profiles_section: TemplateChild<gio::Menu>,
 }
 
 /// The central trait for subclassing a GObject
@@ -158,7 +806,7 @@ impl MainWindow {
      * create_gpu_page
      *
      * Description:
-     * Create a new object of type GpuPage and add to stack
+     * Create a new object of type GpuPage and add to stack, followed by a page for each MIG device
      *
      * Made:
      * 07/11/2022
@@ -167,9 +815,46 @@ impl MainWindow {
      * Deren Vural
      *
      * Notes:
-     *
+     * MIG pages are added directly after their parent GPU so they appear nested underneath it
      */
     fn create_gpu_page(&self, uuid: &str, name: &str, provider: Provider) {
+        // Create page for the GPU itself
+        self.add_gpu_page(uuid, name, provider.clone());
+
+        // Create page for each MIG device (if any)
+        match provider.get_mig_devices(uuid) {
+            Ok(mig_devices) => {
+                for mig_device in mig_devices {
+                    let mig_name: String = format!(
+                        "\u{21B3} MIG {} (Device {})",
+                        mig_device.profile, mig_device.index
+                    );
+                    self.add_gpu_page(&mig_device.uuid, &mig_name, provider.clone());
+                }
+            }
+            Err(err) => {
+                println!("..Attempt to read MIG devices failed, returning: {}", err)
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * add_gpu_page
+     *
+     * Description:
+     * Create a new object of type GpuPage and add to stack
+     *
+     * Made:
+     * 20/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn add_gpu_page(&self, uuid: &str, name: &str, provider: Provider) {
         // Create new GpuPage object
         let new_page: GpuPage = GpuPage::new(uuid, name, provider);
 
@@ -219,7 +904,7 @@ impl MainWindow {
                 // Create new provider
                 Provider::new(
                     || {
-                        vec![
+                        let mut properties: Vec<Property> = vec![
                             Property::new(
                                 &Processor::new(
                                     "nvidia-smi",
@@ -455,7 +1140,30 @@ impl MainWindow {
                                 ),
                                 "power.draw",
                             ),
-                        ]
+                        ];
+
+                        // Add clock speed properties
+                        properties
+                            .append(&mut create_clock_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add PCIe link properties
+                        properties
+                            .append(&mut create_pcie_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add health (ECC/retired pages/row remapping) properties
+                        properties
+                            .append(&mut create_health_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add power limit/performance state properties
+                        properties
+                            .append(&mut create_power_properties("nvidia-smi", "--query-gpu="));
+                        properties.append(&mut create_nvidia_settings_perf_properties());
+                        properties.push(create_pcie_utilization_property());
+
+                        // Add per-fan/thermal sensor properties
+                        properties.append(&mut create_nvidia_settings_target_properties());
+
+                        properties
                     },
                     0,
                 )
@@ -465,9 +1173,9 @@ impl MainWindow {
                 // Create new provider
                 Provider::new(
                     || {
-                        vec![
+                        let mut properties: Vec<Property> = vec![
                             Property::new(
-                                &Processor::new("nvidia-settings", "-q=[gpu:", Some("]/"), " -t"),
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
                                 &Formatter::new(
                                     |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
                                         // Grab input
@@ -489,7 +1197,7 @@ impl MainWindow {
                                 "GPUUtilization.gpu",
                             ),
                             Property::new(
-                                &Processor::new("nvidia-settings", "-q=[gpu:", Some("]/"), " -t"),
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
                                 &Formatter::new(
                                     |input: Vec<String>, params: Option<Vec<(String, String)>>| {
                                         // Grab input
@@ -567,7 +1275,7 @@ impl MainWindow {
                                 "GPUCoreTemp",
                             ),
                             Property::new(
-                                &Processor::new("nvidia-settings", "-q=[gpu:", Some("]/"), " -t"),
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
                                 &Formatter::new(
                                     |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
                                         // Grab input and add formatting
@@ -577,7 +1285,7 @@ impl MainWindow {
                                 "UsedDedicatedGPUMemory",
                             ),
                             Property::new(
-                                &Processor::new("nvidia-settings", "-q=[gpu:", Some("]/"), " -t"),
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
                                 &Formatter::new(
                                     |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
                                         // Grab input and add formatting
@@ -587,7 +1295,7 @@ impl MainWindow {
                                 "TotalDedicatedGPUMemory",
                             ),
                             Property::new(
-                                &Processor::new("nvidia-settings", "-q=[gpu:", Some("]/"), " -t"),
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
                                 &Formatter::new(
                                     |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
                                         // Grab input
@@ -608,9 +1316,59 @@ impl MainWindow {
                                 ),
                                 "GPUUtilization.mem",
                             ),
+                            Property::new(
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+                                &Formatter::new(
+                                    |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                                        // Grab input
+                                        let line: String = String::from(input.get(0).unwrap());
+                                        let list: Vec<&str> = line.split(',').collect(); // This grabs `1500,4000` etc
+
+                                        // Grab item in output that we want (graphics clock)
+                                        match list[0].trim().parse::<f64>() {
+                                            // Apply formatting and return result
+                                            Ok(parsed_value) => {
+                                                Some(format_clock_speed(parsed_value))
+                                            }
+                                            Err(_) => {
+                                                //this should catch "" etc
+                                                println!("Not a valid number");
+
+                                                None
+                                            }
+                                        }
+                                    },
+                                ),
+                                "GPUCurrentClockFreqs.gr",
+                            ),
+                            Property::new(
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
+                                &Formatter::new(
+                                    |input: Vec<String>, _params: Option<Vec<(String, String)>>| {
+                                        // Grab input
+                                        let line: String = String::from(input.get(0).unwrap());
+                                        let list: Vec<&str> = line.split(',').collect(); // This grabs `1500,4000` etc
+
+                                        // Grab item in output that we want (memory clock)
+                                        match list[1].trim().parse::<f64>() {
+                                            // Apply formatting and return result
+                                            Ok(parsed_value) => {
+                                                Some(format_clock_speed(parsed_value))
+                                            }
+                                            Err(_) => {
+                                                //this should catch "" etc
+                                                println!("Not a valid number");
+
+                                                None
+                                            }
+                                        }
+                                    },
+                                ),
+                                "GPUCurrentClockFreqs.mem",
+                            ),
                             /*
                             Property::new(
-                                &Processor::new("nvidia-settings", "-q=[gpu:", Some("]/"), " -t"),
+                                &Processor::new("nvidia-settings", "-q=[", Some("]/"), " -t"),
                                 //&Processor::new("nvidia-settings", "-q=[gpu:]/", " -t"),
                                 &Formatter::new(|input: Vec<String>, _params: Option<Vec<(String, String)>>| {
                                     // Grab input
@@ -626,7 +1384,19 @@ impl MainWindow {
                                 "GPUCurrentFanSpeedRPM",
                             ),
                             */
-                        ]
+                        ];
+
+                        // Add PCIe link properties
+                        properties.append(&mut create_nvidia_settings_pcie_properties());
+                        properties.push(create_pcie_utilization_property());
+
+                        // Add performance level properties
+                        properties.append(&mut create_nvidia_settings_perf_properties());
+
+                        // Add per-fan/thermal sensor properties
+                        properties.append(&mut create_nvidia_settings_target_properties());
+
+                        properties
                     },
                     1,
                 )
@@ -636,7 +1406,7 @@ impl MainWindow {
                 // Create new provider
                 Provider::new(
                     || {
-                        vec![
+                        let mut properties: Vec<Property> = vec![
                             Property::new(
                                 &Processor::new(
                                     "nvidia-smi",
@@ -872,7 +1642,25 @@ impl MainWindow {
                                 ),
                                 "power.draw",
                             ),
-                        ]
+                        ];
+
+                        // Add clock speed properties
+                        properties
+                            .append(&mut create_clock_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add PCIe link properties
+                        properties
+                            .append(&mut create_pcie_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add health (ECC/retired pages/row remapping) properties
+                        properties
+                            .append(&mut create_health_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add power limit/performance state properties
+                        properties
+                            .append(&mut create_power_properties("nvidia-smi", "--query-gpu="));
+
+                        properties
                     },
                     2,
                 )
@@ -882,7 +1670,7 @@ impl MainWindow {
                 // Create new provider
                 Provider::new(
                     || {
-                        vec![
+                        let mut properties: Vec<Property> = vec![
                             Property::new(
                                 &Processor::new(
                                     "optirun",
@@ -1118,7 +1906,33 @@ impl MainWindow {
                                 ),
                                 "power.draw",
                             ),
-                        ]
+                        ];
+
+                        // Add clock speed properties
+                        properties.append(&mut create_clock_properties(
+                            "optirun",
+                            "nvidia-smi --query-gpu=",
+                        ));
+
+                        // Add PCIe link properties
+                        properties.append(&mut create_pcie_properties(
+                            "optirun",
+                            "nvidia-smi --query-gpu=",
+                        ));
+
+                        // Add health (ECC/retired pages/row remapping) properties
+                        properties.append(&mut create_health_properties(
+                            "optirun",
+                            "nvidia-smi --query-gpu=",
+                        ));
+
+                        // Add power limit/performance state properties
+                        properties.append(&mut create_power_properties(
+                            "optirun",
+                            "nvidia-smi --query-gpu=",
+                        ));
+
+                        properties
                     },
                     3,
                 )
@@ -1128,7 +1942,7 @@ impl MainWindow {
                 // Create new provider
                 Provider::new(
                     || {
-                        vec![
+                        let mut properties: Vec<Property> = vec![
                             Property::new(
                                 &Processor::new(
                                     "nvidia-smi",
@@ -1341,7 +2155,25 @@ impl MainWindow {
                                 ),
                                 "power.draw",
                             ),
-                        ]
+                        ];
+
+                        // Add clock speed properties
+                        properties
+                            .append(&mut create_clock_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add PCIe link properties
+                        properties
+                            .append(&mut create_pcie_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add health (ECC/retired pages/row remapping) properties
+                        properties
+                            .append(&mut create_health_properties("nvidia-smi", "--query-gpu="));
+
+                        // Add power limit/performance state properties
+                        properties
+                            .append(&mut create_power_properties("nvidia-smi", "--query-gpu="));
+
+                        properties
                     },
                     0,
                 )
@@ -1349,6 +2181,57 @@ impl MainWindow {
         }
     }
 
+    /**
+     * Name:
+     * gpu_names
+     *
+     * Description:
+     * UUID & name of every GpuPage
+     *
+     * Made:
+     * 02/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn gpu_names(&self) -> Vec<(String, String)> {
+        self.gpu_pages
+            .borrow()
+            .iter()
+            .map(|gpu_page| {
+                (
+                    gpu_page.property::<String>("uuid"),
+                    gpu_page.property::<String>("name"),
+                )
+            })
+            .collect()
+    }
+
+    /**
+     * Name:
+     * reload_views
+     *
+     * Description:
+     * Re-load the views of every GpuPage (i.e. after settings they use have changed)
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn reload_views(&self) {
+        for gpu_page in self.gpu_pages.borrow().iter() {
+            let _result = gpu_page.emit_by_name::<i32>("update-views", &[&(-1).to_value()]);
+        }
+    }
+
     /**
      * Name:
      * refresh_cards
@@ -1851,6 +2734,14 @@ impl WindowImpl for MainWindow {
         self.update_setting("app-settings-open", false);
         self.update_setting("nvidia-settings-open", false);
         self.update_setting("modification-open", false);
+        self.update_setting("fancurve-open", false);
+        self.update_setting("overclock-open", false);
+        self.update_setting("history-open", false);
+
+        // Hand any fans we control back to the driver
+        for err in restore_all(&SubprocessRunner) {
+            println!("..Cannot restore fan control: `{}`", err);
+        }
 
         // Pass close request on to the parent
         self.parent_close_request(window)
@@ -19,16 +19,63 @@
  */
 // Custom GObjects
 mod imp;
-use imp::SettingsWindowContainer;
+use imp::{HistoryWindowContainer, SettingsWindowContainer};
 
 // Imports
 use adwaita::{gio, glib, prelude::*, subclass::prelude::*};
-use gio::{Settings, SimpleAction};
-use glib::{clone, closure, Object};
-use std::cell::RefMut;
+use gio::{
+    MenuItem, Notification, NotificationPriority, Settings, SettingsSchema, SettingsSchemaSource,
+    SimpleAction,
+};
+use glib::{clone, closure, Continue, Object, Variant, VariantTy};
+use gtk::{
+    ButtonsType, Dialog, DialogFlags, Entry, MessageDialog, MessageType, Orientation, ResponseType,
+};
+use std::{
+    cell::RefMut,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::MutexGuard,
+    thread,
+};
 
 // Modules
-use crate::{provider::Provider, settingswindow::SettingsWindow, APP_ID};
+use crate::{
+    fan_override::{lock_fan_control, restore_all},
+    gwe::{convert_gwe, find_database, read_database, GweData, GweImport},
+    history::{now_millis, with_history},
+    historywindow::HistoryWindow,
+    influx::{parse_target, InfluxConfig, InfluxTarget, InfluxWorker},
+    legacy::{config_key, translate_legacy, LegacyImport, LegacySettings, LEGACY_SCHEMA_ID},
+    metrics_server::{provider_label, MetricsServer},
+    metrics_store::{
+        default_store_path, stored_samples, MetricsStore, MetricsWorker, RetentionPolicy,
+    },
+    mqtt::{MqttConfig, MqttWorker},
+    overclock::{ClockOffset, OffsetRange},
+    profile::{
+        apply_plan, load_profiles, plan_profile, profile_names, replace_profile,
+        validate_profile_name, Profile, ProfilePlan,
+    },
+    profile_rules::{
+        gpu_processes_command, is_installed, list_processes, ProcessInfo, ProfileSwitch, RuleEngine,
+    },
+    provider::Provider,
+    sample_log::{default_log_directory, LogFormat, RotationPolicy, SampleLog},
+    sampler::{sampler_command, GpuSample, Sampler},
+    secret::{read_secret, INFLUX_TOKEN_VARIABLE, MQTT_PASSWORD_VARIABLE},
+    settingswindow::SettingsWindow,
+    subprocess::subprocess::{PrivilegedRunner, SubprocessRunner},
+    targets::TargetType,
+    watchdog::{
+        create_revert_plan, lock_tuning, read_watchdog_samples, run_revert_plan, RevertPlan,
+        Sample, TripReason, TuningState, Watchdog, WatchdogConfig, WatchdogEvent,
+    },
+    APP_ID,
+};
+
+/// Time (s) between each maintenance of the metrics store
+const METRICS_MAINTENANCE_INTERVAL: u32 = 300;
 
 // GObject wrapper for MainWindow
 glib::wrapper! {
@@ -38,125 +85,1896 @@ glib::wrapper! {
                     gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
 }
 
-/**
- * Trait Name:
- * MainWindow
- *
- * Description:
- * Trait shared by all main windows
- *
- * Made:
- * 09/10/2022
- *
- * Made by:
- * Deren Vural
- *
- * Notes:
- *
- */
-impl MainWindow {
+/**
+ * Trait Name:
+ * MainWindow
+ *
+ * Description:
+ * Trait shared by all main windows
+ *
+ * Made:
+ * 09/10/2022
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl MainWindow {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new MainWindow object
+     *
+     * Made:
+     * 09/10/2022
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(app: &adwaita::Application) -> Self {
+        // Create new window
+        Object::new(&[("application", app)]).expect("`MainWindow` should be  instantiable.")
+    }
+
+    /**
+     * Name:
+     * setup_settings
+     *
+     * Description:
+     * Load settings for APP_ID
+     *
+     * Made:
+     * 09/10/2022
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_settings(&self) {
+        let settings = Settings::new(APP_ID);
+        self.imp()
+            .settings
+            .set(settings)
+            .expect("`settings` should not be set before calling `setup_settings`.");
+    }
+
+    /**
+     * Name:
+     * settings
+     *
+     * Description:
+     * Get settings for APP_ID
+     *
+     * Made:
+     * 09/10/2022
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn settings(&self) -> &Settings {
+        self.imp()
+            .settings
+            .get()
+            .expect("`settings` should be set in `setup_settings`.")
+    }
+
+    /**
+     * Name:
+     * setup_widgets
+     *
+     * Description:
+     * Set up all widgets
+     *
+     * Made:
+     * 23/10/2022
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_widgets(&self) {
+        // Connect closure to re-load stored views (with different settings) when a settings window is closed
+        //NOTE: expected return value seems to be broken - look at imp.rs:1772
+        self.connect_closure(
+            "update-all-views",
+            false,
+            closure!(move |window: MainWindow| {
+                println!("closure called!"); //TEST
+
+                // Reload views
+                println!("reloading views.."); //TEST
+                window.imp().refresh_cards();
+                println!("views reloaded.."); //TEST
+
+                // Return final value
+                0
+            }),
+        );
+
+        // Keep the list of profiles in the header menu up to date
+        self.load_profiles_menu();
+        self.settings().connect_changed(
+            Some("profiles"),
+            clone!(@weak self as window => move |_, _| {
+                window.load_profiles_menu();
+            }),
+        );
+
+        // Switch profiles automatically
+        self.setup_profile_rules();
+
+        // Revert tuning if a GPU overheats
+        self.setup_watchdog();
+
+        // Keep history on disk
+        self.setup_metrics_store();
+
+        // Log readings of chosen GPUs to file & publish them to MQTT/InfluxDB
+        self.setup_exporters();
+
+        // Serve readings for Prometheus
+        self.update_metrics_server();
+        for key in [
+            "metrics-server",
+            "metrics-server-address",
+            "metrics-server-port",
+            "provider",
+        ] {
+            self.settings().connect_changed(
+                Some(key),
+                clone!(@weak self as window => move |_, _| {
+                    window.update_metrics_server();
+                }),
+            );
+        }
+
+        // Publish readings to MQTT (& Home Assistant)
+        self.update_mqtt();
+        for key in [
+            "mqtt",
+            "mqtt-broker",
+            "mqtt-username",
+            "mqtt-password-file",
+            "mqtt-topic-prefix",
+            "mqtt-qos",
+        ] {
+            self.settings().connect_changed(
+                Some(key),
+                clone!(@weak self as window => move |_, _| {
+                    window.update_mqtt();
+                }),
+            );
+        }
+
+        // Write readings to InfluxDB (or Telegraf)
+        self.update_influx();
+        for key in [
+            "influx",
+            "influx-target",
+            "influx-token-file",
+            "influx-measurement",
+            "influx-uuid-tag",
+            "influx-name-tag",
+        ] {
+            self.settings().connect_changed(
+                Some(key),
+                clone!(@weak self as window => move |_, _| {
+                    window.update_influx();
+                }),
+            );
+        }
+    }
+
+    /**
+     * Name:
+     * current_provider
+     *
+     * Description:
+     * Grab the current provider, creating one if there isn't one yet
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn current_provider(&self) -> Provider {
+        match self.property::<Option<Provider>>("provider") {
+            Some(provider) => provider,
+            None => {
+                // Create new provider of the type in settings
+                let provider_type: i32 = self.imp().get_setting::<i32>("provider");
+                let provider: Provider = self.imp().create_provider(provider_type);
+                self.set_property("provider", Some(provider.clone()));
+
+                provider
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * load_profiles_menu
+     *
+     * Description:
+     * Add an item to the header menu for each saved profile
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn load_profiles_menu(&self) {
+        let section: gio::Menu = self.imp().profiles_section.get();
+        section.remove_all();
+
+        let profiles: Vec<Profile> =
+            load_profiles(&self.imp().get_setting::<Vec<String>>("profiles"));
+        for name in profile_names(&profiles) {
+            let item: MenuItem = MenuItem::new(Some(&name), None);
+            item.set_action_and_target_value(Some("win.apply_profile"), Some(&name.to_variant()));
+            section.append_item(&item);
+        }
+    }
+
+    /**
+     * Name:
+     * capture_profiles
+     *
+     * Description:
+     * Capture the current fan curves, clock offsets and power limit of every GPU
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Anything a GPU doesn't support is left unset
+     */
+    fn capture_profiles(&self, name: &str) -> Result<Vec<Profile>, String> {
+        let provider: Provider = self.current_provider();
+        let fan_curves: Vec<String> = self.imp().get_setting::<Vec<String>>("fancurves");
+        let mut profiles: Vec<Profile> = vec![];
+
+        for uuid in provider.get_gpu_uuids()? {
+            // Power limit, i.e. `150.00 W`
+            let power_limit: Option<u32> = provider
+                .get_gpu_data(&uuid, "power_limit")
+                .ok()
+                .and_then(|value| {
+                    value
+                        .split_whitespace()
+                        .next()
+                        .and_then(|number| number.parse::<f64>().ok())
+                })
+                .map(|watts| watts.round() as u32)
+                .filter(|watts| *watts > 0);
+
+            // Clock offsets
+            let graphics_offset: Option<i32> = provider
+                .get_clock_offset(&uuid, ClockOffset::Graphics)
+                .ok()
+                .map(|(value, _)| value);
+            let memory_offset: Option<i32> = provider
+                .get_clock_offset(&uuid, ClockOffset::Memory)
+                .ok()
+                .map(|(value, _)| value);
+
+            profiles.push(Profile::capture(
+                name,
+                &uuid,
+                power_limit,
+                graphics_offset,
+                memory_offset,
+                &fan_curves,
+            ));
+        }
+
+        Ok(profiles)
+    }
+
+    /**
+     * Name:
+     * save_profile
+     *
+     * Description:
+     * Save the current fan curves, clock offsets and power limit of every GPU as a profile
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Saving over an existing profile replaces it
+     */
+    pub fn save_profile(&self, name: &str) -> Result<(), String> {
+        validate_profile_name(name)?;
+
+        let mut entries: Vec<String> = self.imp().get_setting::<Vec<String>>("profiles");
+        for profile in self.capture_profiles(name)? {
+            entries = replace_profile(entries, &profile);
+        }
+        self.imp()
+            .update_setting::<Vec<String>>("profiles", entries);
+
+        Ok(())
+    }
+
+    /**
+     * Name:
+     * import_gwe
+     *
+     * Description:
+     * Import the fan & overclock profiles of GreenWithEnvy as profiles for every GPU
+     *
+     * Made:
+     * 28/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Existing profiles are never overwritten
+     */
+    pub fn import_gwe(&self) -> Result<GweImport, String> {
+        let path: PathBuf = find_database().ok_or_else(|| {
+            String::from("No GreenWithEnvy database found in `~/.local/share/gwe/`..")
+        })?;
+        let data: GweData = read_database(&path)?;
+
+        // Find fans belonging to each gpu
+        let provider: Provider = self.current_provider();
+        let gpus: Vec<(String, Vec<u32>)> = provider
+            .get_gpu_uuids()?
+            .into_iter()
+            .map(|uuid| {
+                let fans: Vec<u32> =
+                    provider
+                        .get_targets(&uuid, TargetType::Fan)
+                        .unwrap_or_else(|err| {
+                            println!("..Attempt to read fans failed, returning: {}", err);
+                            vec![]
+                        });
+                (uuid, fans)
+            })
+            .collect();
+
+        // Convert & store
+        let mut entries: Vec<String> = self.imp().get_setting::<Vec<String>>("profiles");
+        let existing: Vec<String> = profile_names(&load_profiles(&entries));
+        let import: GweImport = convert_gwe(&data, &gpus, &existing);
+        for profile in &import.profiles {
+            entries = replace_profile(entries, profile);
+        }
+        self.imp()
+            .update_setting::<Vec<String>>("profiles", entries);
+
+        Ok(import)
+    }
+
+    /**
+     * Name:
+     * show_import_report
+     *
+     * Description:
+     * Show which profiles were imported, and anything that could not be converted
+     *
+     * Made:
+     * 28/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn show_import_report(&self, import: &GweImport) {
+        let names: Vec<String> = profile_names(&import.profiles);
+        let mut message: String = if names.is_empty() {
+            String::from("No profiles were imported from GreenWithEnvy.")
+        } else {
+            format!(
+                "Imported {} profile(s) from GreenWithEnvy:\n{}",
+                names.len(),
+                names.join(", ")
+            )
+        };
+        if !import.problems.is_empty() {
+            message.push_str(&format!(
+                "\n\nCould not convert:\n{}",
+                import.problems.join("\n")
+            ));
+        }
+
+        let message_type: MessageType = if import.problems.is_empty() {
+            MessageType::Info
+        } else {
+            MessageType::Warning
+        };
+        let dialog: MessageDialog = MessageDialog::new(
+            Some(self),
+            DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+            message_type,
+            ButtonsType::Close,
+            &message,
+        );
+        dialog.connect_response(|dialog, _| dialog.close());
+        dialog.show();
+    }
+
+    /**
+     * Name:
+     * read_legacy_settings
+     *
+     * Description:
+     * Read any settings changed in the original gnome-nvidia-extension (if installed)
+     *
+     * Made:
+     * 29/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only values set by the user are read, the extension's defaults aren't worth migrating
+     */
+    fn read_legacy_settings(&self) -> Option<LegacySettings> {
+        let schema: SettingsSchema =
+            SettingsSchemaSource::default()?.lookup(LEGACY_SCHEMA_ID, true)?;
+        let legacy_settings: Settings = Settings::new(LEGACY_SCHEMA_ID);
+        let user_value = |key: &str| -> Option<Variant> {
+            if schema.has_key(key) {
+                legacy_settings.user_value(key)
+            } else {
+                None
+            }
+        };
+
+        let provider: Option<i32> = user_value("provider").and_then(|value| value.get::<i32>());
+        // Either one list for every GPU, or one per GPU
+        let properties: Vec<Vec<bool>> = config_key(provider.unwrap_or(0))
+            .and_then(user_value)
+            .and_then(|value| {
+                value
+                    .get::<Vec<bool>>()
+                    .map(|flags| vec![flags])
+                    .or_else(|| value.get::<Vec<Vec<bool>>>())
+            })
+            .unwrap_or_default();
+
+        Some(LegacySettings {
+            refresh_rate: user_value("refreshrate").and_then(|value| value.get::<i32>()),
+            temp_format: user_value("tempformat").and_then(|value| value.get::<i32>()),
+            provider,
+            properties,
+        })
+    }
+
+    /**
+     * Name:
+     * import_legacy_settings
+     *
+     * Description:
+     * Translate settings from the original gnome-nvidia-extension into this app's
+     *
+     * Made:
+     * 29/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Views of every GPU with properties enabled in the extension are replaced
+     */
+    fn import_legacy_settings(&self, legacy: &LegacySettings) -> Result<LegacyImport, String> {
+        let gpu_uuids: Vec<String> = self.current_provider().get_gpu_uuids()?;
+        let import: LegacyImport = translate_legacy(
+            legacy,
+            &gpu_uuids,
+            self.imp().get_setting::<Vec<String>>("viewconfigs"),
+            self.imp()
+                .get_setting::<Vec<String>>("viewcomponentconfigs"),
+        );
+
+        if let Some(refresh_rate) = import.refresh_rate {
+            self.imp()
+                .update_setting::<i32>("refreshrate", refresh_rate);
+        }
+        if let Some(temp_format) = import.temp_format {
+            self.imp().update_setting::<i32>("tempformat", temp_format);
+        }
+        if let Some(provider) = import.provider {
+            self.imp().update_setting::<i32>("provider", provider);
+        }
+        self.imp()
+            .update_setting::<Vec<String>>("viewconfigs", import.viewconfigs.clone());
+        self.imp().update_setting::<Vec<String>>(
+            "viewcomponentconfigs",
+            import.viewcomponentconfigs.clone(),
+        );
+
+        // Re-create pages (with the new provider if changed)
+        self.imp().refresh_cards();
+
+        Ok(import)
+    }
+
+    /**
+     * Name:
+     * offer_legacy_import
+     *
+     * Description:
+     * On first run, offer to import settings from the original gnome-nvidia-extension
+     *
+     * Made:
+     * 29/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only offered once, whatever the answer
+     */
+    pub fn offer_legacy_import(&self) {
+        if self.imp().get_setting::<bool>("legacy-import-offered") {
+            return;
+        }
+        let legacy: LegacySettings = match self.read_legacy_settings() {
+            Some(valid_legacy) if !valid_legacy.is_empty() => valid_legacy,
+            _ => return,
+        };
+
+        let dialog: MessageDialog = MessageDialog::new(
+            Some(self),
+            DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+            MessageType::Question,
+            ButtonsType::YesNo,
+            "Settings from gnome-nvidia-extension were found.\n\nImport the refresh rate, temperature unit, provider and properties shown?",
+        );
+        dialog.connect_response(clone!(@weak self as window => move |dialog, response| {
+            window.imp().update_setting::<bool>("legacy-import-offered", true);
+            dialog.close();
+
+            if response == ResponseType::Yes {
+                match window.import_legacy_settings(&legacy) {
+                    Ok(import) => {
+                        println!("..Settings imported from gnome-nvidia-extension");
+                        if !import.problems.is_empty() {
+                            window.show_error(&format!(
+                                "Some settings could not be imported:\n{}",
+                                import.problems.join("\n")
+                            ));
+                        }
+                    }
+                    Err(err) => {
+                        println!("..Attempt to import legacy settings failed, returning: {}", err);
+                        window.show_error(&err);
+                    }
+                }
+            }
+        }));
+        dialog.show();
+    }
+
+    /**
+     * Name:
+     * apply_profile
+     *
+     * Description:
+     * Apply a saved profile to every GPU it has settings for
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn apply_profile<F: FnOnce(&MainWindow, Result<(), String>) + 'static>(
+        &self,
+        name: &str,
+        callback: F,
+    ) {
+        let profiles: Vec<Profile> =
+            load_profiles(&self.imp().get_setting::<Vec<String>>("profiles"))
+                .into_iter()
+                .filter(|profile| profile.name == name)
+                .collect();
+        if profiles.is_empty() {
+            return callback(self, Err(format!("No profile called `{}`..", name)));
+        }
+
+        self.apply_profiles(profiles, callback)
+    }
+
+    /**
+     * Name:
+     * apply_profiles
+     *
+     * Description:
+     * Apply the settings of each GPU in a profile, then run @callback with the result
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * GPUs that aren't present are skipped, a failure on one GPU doesn't stop the others.
+     * Commands are run on a separate thread, so the UI isn't blocked (i.e. by a pkexec prompt)
+     */
+    fn apply_profiles<F: FnOnce(&MainWindow, Result<(), String>) + 'static>(
+        &self,
+        profiles: Vec<Profile>,
+        callback: F,
+    ) {
+        if self.imp().profile_apply_running.get() {
+            return callback(
+                self,
+                Err(String::from("A profile is still being applied..")),
+            );
+        }
+
+        let provider: Provider = self.current_provider();
+        let gpu_uuids: Vec<String> = match provider.get_gpu_uuids() {
+            Ok(uuids) => uuids,
+            Err(err) => return callback(self, Err(err)),
+        };
+
+        // Look up everything needed from the provider first
+        let mut requests: Vec<(
+            Profile,
+            Option<u32>,
+            Option<OffsetRange>,
+            Option<OffsetRange>,
+        )> = vec![];
+        for profile in profiles {
+            if !gpu_uuids.contains(&profile.uuid) {
+                println!("..Skipping profile for missing GPU `{}`", profile.uuid);
+                continue;
+            }
+
+            // Only look up what's needed for clock offsets if the profile has any
+            let (gpu_index, graphics_range, memory_range) =
+                if profile.graphics_offset.is_some() || profile.memory_offset.is_some() {
+                    (
+                        provider.get_gpu_index(&profile.uuid).ok(),
+                        provider
+                            .get_clock_offset(&profile.uuid, ClockOffset::Graphics)
+                            .ok()
+                            .map(|(_, range)| range),
+                        provider
+                            .get_clock_offset(&profile.uuid, ClockOffset::Memory)
+                            .ok()
+                            .map(|(_, range)| range),
+                    )
+                } else {
+                    (None, None, None)
+                };
+
+            requests.push((profile, gpu_index, graphics_range, memory_range));
+        }
+
+        // Run commands on a separate thread
+        self.imp().profile_apply_running.set(true);
+        let mut fan_curves: Vec<String> = self.imp().get_setting::<Vec<String>>("fancurves");
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        thread::spawn(move || {
+            let mut errors: Vec<String> = vec![];
+            for (profile, gpu_index, graphics_range, memory_range) in requests {
+                // Don't re-apply anything once the watchdog has tripped
+                if lock_tuning().is_tripped() {
+                    errors.push(format!("{}: Thermal watchdog tripped..", profile.uuid));
+                    continue;
+                }
+
+                // Validate everything before running anything
+                let result: Result<ProfilePlan, String> = plan_profile(
+                    &profile,
+                    gpu_index,
+                    graphics_range,
+                    memory_range,
+                    fan_curves.clone(),
+                )
+                .and_then(|plan| {
+                    apply_plan(&plan, &SubprocessRunner, &PrivilegedRunner)?;
+                    Ok(plan)
+                });
+                match result {
+                    Ok(plan) => {
+                        // Arm the watchdog
+                        if !plan.commands.is_empty() || !plan.privileged_commands.is_empty() {
+                            lock_tuning().mark_tuned(&profile.uuid);
+                        }
+                        fan_curves = plan.fan_curves
+                    }
+                    Err(err) => errors.push(format!("{}: {}", profile.uuid, err)),
+                }
+            }
+
+            let _ = sender.send((fan_curves, errors));
+        });
+
+        // Save results once finished
+        let mut callback: Option<F> = Some(callback);
+        receiver.attach(
+            None,
+            clone!(@weak self as window => @default-return Continue(false),
+                move |result: (Vec<String>, Vec<String>)| {
+                let (fan_curves, errors): (Vec<String>, Vec<String>) = result;
+                window.imp().profile_apply_running.set(false);
+
+                // Fan curves are picked up by the GpuPages when their views are re-loaded
+                window
+                    .imp()
+                    .update_setting::<Vec<String>>("fancurves", fan_curves);
+                window.imp().reload_views();
+
+                if let Some(callback) = callback.take() {
+                    if errors.is_empty() {
+                        callback(&window, Ok(()))
+                    } else {
+                        callback(&window, Err(errors.join("\n")))
+                    }
+                }
+
+                Continue(false)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * check_profile_rules
+     *
+     * Description:
+     * Scan the running processes (on a separate thread), then switch profile if required
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Skipped while a scan or profile switch is still running. If the processes using each GPU can't be found,
+     * the user is told once (until they can be found again)
+     */
+    fn check_profile_rules(&self) {
+        // Don't re-apply anything until the watchdog alert is acknowledged
+        if self.imp().profile_rules.borrow().rules().is_empty()
+            || lock_tuning().is_tripped()
+            || self.imp().profile_scan_running.get()
+            || self.imp().profile_apply_running.get()
+        {
+            return;
+        }
+
+        // GPUs (in index order) don't change while running, only look them up once
+        let provider: Provider = self.current_provider();
+        if self.imp().profile_gpus.borrow().is_empty() {
+            match provider.get_gpu_uuids() {
+                Ok(uuids) => {
+                    self.imp().profile_gpus.replace(uuids);
+                }
+                Err(err) => {
+                    println!(
+                        "..Attempt to check profile rules failed, returning: {}",
+                        err
+                    );
+                    return;
+                }
+            }
+        }
+
+        // Grab running processes (and which GPUs they use) on a separate thread
+        self.imp().profile_scan_running.set(true);
+        let provider_type: i32 = provider.property::<i32>("provider-type");
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        thread::spawn(move || {
+            let command: Option<Vec<String>> =
+                gpu_processes_command(provider_type, is_installed("nvidia-smi"));
+            let _ = sender.send(list_processes(Path::new("/proc"), command.as_deref()));
+        });
+        receiver.attach(
+            None,
+            clone!(@weak self as window => @default-return Continue(false),
+                move |result: Result<Vec<ProcessInfo>, String>| {
+                window.imp().profile_scan_running.set(false);
+                match result {
+                    Ok(processes) => {
+                        window.imp().profile_scan_error.take();
+                        window.switch_profile(&processes);
+                    }
+                    Err(err) => {
+                        // Only shown once, not every refresh
+                        if window.imp().profile_scan_error.borrow().as_ref() != Some(&err) {
+                            let message: String = format!(
+                                "Cannot check profile rules, as the processes using each GPU are unknown: {}",
+                                err
+                            );
+                            println!("..{}", message);
+                            window.show_error(&message);
+                            window.imp().profile_scan_error.replace(Some(err));
+                        }
+                    }
+                }
+
+                Continue(false)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * switch_profile
+     *
+     * Description:
+     * Switch profile if the running processes match a different rule (or none)
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The settings from before the first switch are kept in memory, to revert to once no rules match.
+     * A rule only counts as active once it's profile has been applied
+     */
+    fn switch_profile(&self, processes: &[ProcessInfo]) {
+        if lock_tuning().is_tripped() {
+            return;
+        }
+
+        // Check rules
+        let gpu_uuids: Vec<String> = self.imp().profile_gpus.borrow().clone();
+        let switch: Option<ProfileSwitch> = self
+            .imp()
+            .profile_rules
+            .borrow_mut()
+            .update(processes, &gpu_uuids);
+        if let Some(valid_switch) = switch {
+            println!("..{}", valid_switch.describe());
+
+            let report = |_: &MainWindow, result: Result<(), String>| {
+                if let Err(err) = result {
+                    println!("..Attempt to switch profile failed, returning: {}", err);
+                }
+            };
+            match valid_switch {
+                ProfileSwitch::Apply { profile, rule, .. } => {
+                    // Remember current settings, for reverting
+                    if self.imp().previous_profiles.borrow().is_none() {
+                        match self.capture_profiles("Previous") {
+                            Ok(previous) => {
+                                self.imp().previous_profiles.replace(Some(previous));
+                            }
+                            Err(err) => println!(
+                                "..Cannot capture current settings, `{}` can't be reverted: {}",
+                                profile, err
+                            ),
+                        }
+                    }
+
+                    // Only active once applied, otherwise it's tried again next refresh
+                    let applied: String = profile.clone();
+                    self.apply_profile(
+                        &profile,
+                        move |window: &MainWindow, result: Result<(), String>| {
+                            if result.is_ok() {
+                                window
+                                    .imp()
+                                    .profile_rules
+                                    .borrow_mut()
+                                    .confirm(rule, &applied);
+                            }
+                            report(window, result);
+                        },
+                    )
+                }
+                ProfileSwitch::Revert { profile } => match self.imp().previous_profiles.take() {
+                    Some(previous) => self.apply_profiles(previous, report),
+                    None => {
+                        let message: String = format!(
+                            "Cannot revert profile `{}`, the settings from before it was applied weren't captured (or were reset by the thermal watchdog)..",
+                            profile
+                        );
+                        println!("..{}", message);
+                        self.show_error(&message);
+                    }
+                },
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * load_profile_rules
+     *
+     * Description:
+     * Load the profile rules from the `profile-rules` setting, showing why any are ignored
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Rules are edited through the setting, so this is the only place a bad pattern can be pointed out
+     */
+    fn load_profile_rules(&self) {
+        let rules: Vec<String> = self.imp().get_setting::<Vec<String>>("profile-rules");
+        let errors: Vec<String> = RuleEngine::invalid_settings(&rules);
+        if !errors.is_empty() {
+            let message: String = format!(
+                "Some profile rules are invalid and will be ignored:\n{}",
+                errors.join("\n")
+            );
+            println!("..{}", message);
+            self.show_error(&message);
+        }
+
+        self.imp()
+            .profile_rules
+            .replace(RuleEngine::from_settings(&rules));
+    }
+
+    /**
+     * Name:
+     * setup_profile_rules
+     *
+     * Description:
+     * Load the profile rules and check them every refresh
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_profile_rules(&self) {
+        // Load rules
+        self.load_profile_rules();
+        self.settings().connect_changed(
+            Some("profile-rules"),
+            clone!(@weak self as window => move |_, _| {
+                // Any active rule is dropped, so it's profile stays applied until the next match
+                window.load_profile_rules();
+            }),
+        );
+
+        // Check rules
+        let refresh_rate: u32 = self.imp().get_setting::<i32>("refreshrate") as u32;
+        glib::timeout_add_seconds_local(
+            refresh_rate.max(1),
+            clone!(@weak self as window => @default-return Continue(false), move || {
+                window.check_profile_rules();
+
+                Continue(true)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * revert_all_tuning
+     *
+     * Description:
+     * Put every tuned GPU back to driver defaults, then run @callback with any errors
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Marked as tripped first, so fan curves aren't re-applied behind our back.
+     * Commands are run on a separate thread, so the UI isn't blocked
+     */
+    fn revert_all_tuning<F: FnOnce(&MainWindow, Vec<String>) + 'static>(&self, callback: F) {
+        let tuned: Vec<String> = {
+            let mut tuning: MutexGuard<TuningState> = lock_tuning();
+            tuning.set_tripped(true);
+            let tuned: Vec<String> = tuning.tuned().to_vec();
+            tuning.clear();
+            tuned
+        };
+
+        // Look up everything needed from the provider first
+        let provider: Provider = self.current_provider();
+        let mut plans: Vec<(String, Result<RevertPlan, String>)> = vec![];
+        for uuid in tuned {
+            let plan: Result<RevertPlan, String> =
+                create_revert_plan(&uuid, provider.get_gpu_index(&uuid).ok());
+            plans.push((uuid, plan));
+        }
+
+        // Don't switch back to the settings from before a profile rule matched
+        self.imp().previous_profiles.take();
+
+        // Run commands on a separate thread
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        thread::spawn(move || {
+            // Hand any fans we control back to the driver
+            let mut errors: Vec<String> = vec![];
+            for err in restore_all(&SubprocessRunner) {
+                errors.push(format!("Fan control: {}", err));
+            }
+
+            // Reset clocks & power limit
+            for (uuid, plan) in plans {
+                match plan {
+                    Ok(valid_plan) => {
+                        for err in
+                            run_revert_plan(&valid_plan, &SubprocessRunner, &PrivilegedRunner)
+                        {
+                            errors.push(format!("{}: {}", uuid, err));
+                        }
+                    }
+                    Err(err) => errors.push(format!("{}: {}", uuid, err)),
+                }
+            }
+
+            let _ = sender.send(errors);
+        });
+
+        let mut callback: Option<F> = Some(callback);
+        receiver.attach(
+            None,
+            clone!(@weak self as window => @default-return Continue(false),
+                move |errors: Vec<String>| {
+                window.imp().reload_views();
+
+                if let Some(callback) = callback.take() {
+                    callback(&window, errors);
+                }
+
+                Continue(false)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * trip_watchdog
+     *
+     * Description:
+     * Revert all tuning, then raise an alert (and notification) until acknowledged
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The alert is shown straight away, then updated once the revert has finished
+     */
+    fn trip_watchdog(&self, reason: &TripReason) {
+        println!("..Thermal watchdog tripped: {}", reason.describe());
+
+        // Notify, in case the window isn't visible
+        if let Some(app) = self.application() {
+            let notification: Notification = Notification::new("Thermal watchdog tripped");
+            notification.set_body(Some(&reason.describe()));
+            notification.set_priority(NotificationPriority::Urgent);
+            app.send_notification(Some("watchdog"), &notification);
+        }
+
+        // Alert
+        let dialog: MessageDialog = MessageDialog::new(
+            Some(self),
+            DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+            MessageType::Error,
+            ButtonsType::Ok,
+            &format!(
+                "Thermal watchdog tripped!\n\n{}\n\nReverting all tuning to driver defaults..",
+                reason.describe()
+            ),
+        );
+        dialog.connect_response(clone!(@weak self as window => move |dialog, _| {
+            // Fan curves & profile rules can take effect again
+            window.imp().watchdog.borrow_mut().acknowledge();
+            lock_tuning().set_tripped(false);
+            if let Some(app) = window.application() {
+                app.withdraw_notification("watchdog");
+            }
+
+            dialog.close();
+        }));
+        dialog.show();
+
+        // Can't be acknowledged until the revert has finished
+        dialog.set_response_sensitive(ResponseType::Ok, false);
+
+        // Only claim success if every command succeeded
+        let description: String = reason.describe();
+        self.revert_all_tuning(clone!(@weak dialog => move |_window: &MainWindow, errors: Vec<String>| {
+            let message: String = if errors.is_empty() {
+                format!(
+                    "Thermal watchdog tripped!\n\n{}\n\nAll tuning has been reverted to driver defaults.",
+                    description
+                )
+            } else {
+                println!("..Attempt to revert tuning failed, returning: {}", errors.join(", "));
+                format!(
+                    "Thermal watchdog tripped!\n\n{}\n\nSome settings could not be reverted:\n{}\n\n\
+                    Clocks & power limits can only be reset while this session is active, \
+                    check the \"Tuning\" view once unlocked.",
+                    description,
+                    errors.join("\n")
+                )
+            };
+            dialog.set_text(Some(&message));
+            dialog.set_response_sensitive(ResponseType::Ok, true);
+        }));
+    }
+
+    /**
+     * Name:
+     * check_watchdog
+     *
+     * Description:
+     * Read every GPU for the watchdog (while any tuning is active), then feed it the samples
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * GPUs are read on a separate thread, so a slow or hung driver can't block the UI.
+     * A read still running by the next refresh counts as a failure, so a hung driver still trips the watchdog
+     */
+    fn check_watchdog(&self) {
+        let tuning_active: bool = !lock_fan_control().is_empty() || !lock_tuning().is_empty();
+        if !tuning_active {
+            self.feed_watchdog(false, &[]);
+            return;
+        }
+        if self.imp().watchdog_sampling.get() {
+            self.feed_watchdog(
+                true,
+                &[Sample::Failure {
+                    uuid: String::new(),
+                    error: String::from("Still waiting for the last reading.."),
+                }],
+            );
+            return;
+        }
+
+        // Read every gpu on a separate thread
+        let command: Vec<String> = sampler_command(self.imp().get_setting::<i32>("provider"));
+        self.imp().watchdog_sampling.set(true);
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        thread::spawn(move || {
+            let _ = sender.send(read_watchdog_samples(&command));
+        });
+        receiver.attach(
+            None,
+            clone!(@weak self as window => @default-return Continue(false),
+                move |samples: Vec<Sample>| {
+                window.imp().watchdog_sampling.set(false);
+
+                // Tuning may have been reverted while reading
+                let tuning_active: bool =
+                    !lock_fan_control().is_empty() || !lock_tuning().is_empty();
+                window.feed_watchdog(tuning_active, &samples);
+
+                Continue(false)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * feed_watchdog
+     *
+     * Description:
+     * Feed the watchdog a sample of every GPU, then act on whatever it decides
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Limits are read each time, so changes in the settings window take effect straight away
+     */
+    fn feed_watchdog(&self, tuning_active: bool, samples: &[Sample]) {
+        let config: WatchdogConfig = WatchdogConfig {
+            critical_temperature: self
+                .imp()
+                .get_setting::<i32>("watchdog-critical-temp")
+                .into(),
+            max_failures: self
+                .imp()
+                .get_setting::<i32>("watchdog-max-failures")
+                .max(1) as u32,
+        };
+        let event: Option<WatchdogEvent> = {
+            let mut watchdog: RefMut<Watchdog> = self.imp().watchdog.borrow_mut();
+            watchdog.set_config(config);
+            watchdog.tick(tuning_active, samples)
+        };
+
+        match event {
+            Some(WatchdogEvent::Armed) => println!("..Thermal watchdog armed"),
+            Some(WatchdogEvent::Disarmed) => println!("..Thermal watchdog disarmed"),
+            Some(WatchdogEvent::Tripped(reason)) => self.trip_watchdog(&reason),
+            None => {}
+        }
+    }
+
+    /**
+     * Name:
+     * setup_watchdog
+     *
+     * Description:
+     * Check the watchdog every refresh
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_watchdog(&self) {
+        let refresh_rate: u32 = self.imp().get_setting::<i32>("refreshrate") as u32;
+        glib::timeout_add_seconds_local(
+            refresh_rate.max(1),
+            clone!(@weak self as window => @default-return Continue(false), move || {
+                window.check_watchdog();
+
+                Continue(true)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * setup_metrics_store
+     *
+     * Description:
+     * Open the metrics store, restore recent history from it, then hand it to a worker to store every reading
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * History still works (in memory only) if the store can't be opened.
+     * Readings are passed on with the exporters (see `export_samples`)
+     */
+    fn setup_metrics_store(&self) {
+        if !self.imp().get_setting::<bool>("metrics-store") {
+            return;
+        }
+
+        // Open store
+        let path: PathBuf = match default_store_path() {
+            Some(valid_path) => valid_path,
+            None => {
+                println!("..Cannot find the data directory, history won't be kept on disk");
+                return;
+            }
+        };
+        let store: MetricsStore = match MetricsStore::open(&path, self.metrics_policy()) {
+            Ok(valid_store) => valid_store,
+            Err(err) => {
+                println!("..Attempt to open metrics store failed, returning: {}", err);
+                return;
+            }
+        };
+
+        // Restore readings from before the last restart
+        let retention: u64 = self.imp().get_setting::<i32>("history-retention") as u64 * 60;
+        let end: u64 = now_millis();
+        let start: u64 = end.saturating_sub(retention * 1000);
+        match store.series() {
+            Ok(series) => with_history(|history| {
+                history.set_retention(retention);
+                for (uuid, property, unit) in series {
+                    match store.query(&uuid, &property, start, end) {
+                        Ok(samples) => history.restore(&uuid, &property, &unit, &samples),
+                        Err(err) => {
+                            println!("..Attempt to restore history failed, returning: {}", err)
+                        }
+                    }
+                }
+            }),
+            Err(err) => println!("..Attempt to restore history failed, returning: {}", err),
+        }
+
+        // Write & maintain on a separate thread
+        self.imp()
+            .metrics_worker
+            .replace(Some(MetricsWorker::spawn(store)));
+
+        // Average, forget & tidy up old readings
+        glib::timeout_add_seconds_local(
+            METRICS_MAINTENANCE_INTERVAL,
+            clone!(@weak self as window => @default-return Continue(false), move || {
+                window.maintain_metrics();
+
+                Continue(true)
+            }),
+        );
+    }
+
     /**
      * Name:
-     * new
+     * setup_exporters
      *
      * Description:
-     * Create a new MainWindow object
+     * Each refresh, read every GPU once and pass the readings on to each exporter (log files, metrics store,
+     * MQTT & InfluxDB)
      *
      * Made:
-     * 09/10/2022
+     * 04/02/2023
      *
      * Made by:
      * Deren Vural
      *
      * Notes:
+     * Readings come straight from nvidia-smi (not the formatted labels), so are exported whichever provider is used
+     */
+    fn setup_exporters(&self) {
+        let refresh_rate: u32 = self.imp().get_setting::<i32>("refreshrate") as u32;
+        glib::timeout_add_seconds_local(
+            refresh_rate.max(1),
+            clone!(@weak self as window => @default-return Continue(false), move || {
+                window.export_samples();
+
+                Continue(true)
+            }),
+        );
+    }
+
+    /**
+     * Name:
+     * export_samples
+     *
+     * Description:
+     * Read every GPU (on a separate thread), then log, store, publish & write the readings
      *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * GPUs aren't read if nothing is logged, stored, published or written, or the last read hasn't finished.
+     * Supported properties are probed the first time (and whenever the provider changes)
      */
-    pub fn new(app: &adwaita::Application) -> Self {
-        // Create new window
-        Object::new(&[("application", app)]).expect("`MainWindow` should be  instantiable.")
+    fn export_samples(&self) {
+        let logged_gpus: Vec<String> = self.imp().get_setting::<Vec<String>>("logged-gpus");
+        self.imp()
+            .sample_logs
+            .borrow_mut()
+            .retain(|uuid, _| logged_gpus.contains(uuid));
+        let publishing: bool = self.imp().metrics_worker.borrow().is_some()
+            || self.imp().mqtt_worker.borrow().is_some()
+            || self.imp().influx_worker.borrow().is_some();
+        if (logged_gpus.is_empty() && !publishing) || self.imp().sampling.get() {
+            return;
+        }
+
+        // Re-use the probed sampler, unless the provider changed
+        let provider_type: i32 = self.imp().get_setting::<i32>("provider");
+        let sampler: Option<Sampler> = match self.imp().sampler.borrow().as_ref() {
+            Some((sampler_type, sampler)) if *sampler_type == provider_type => {
+                Some(sampler.clone())
+            }
+            _ => None,
+        };
+
+        // Read every gpu on a separate thread
+        self.imp().sampling.set(true);
+        let (sender, receiver) = glib::MainContext::channel(glib::PRIORITY_DEFAULT);
+        thread::spawn(move || {
+            let sampler: Sampler =
+                sampler.unwrap_or_else(|| Sampler::new(sampler_command(provider_type)).probe());
+            let samples: Result<Vec<GpuSample>, String> = sampler.sample();
+            let _ = sender.send((sampler, samples));
+        });
+        receiver.attach(
+            None,
+            clone!(@weak self as window => @default-return Continue(false),
+                move |result: (Sampler, Result<Vec<GpuSample>, String>)| {
+                let (sampler, samples): (Sampler, Result<Vec<GpuSample>, String>) = result;
+                window.imp().sampling.set(false);
+                window.imp().sampler.replace(Some((provider_type, sampler)));
+
+                match samples {
+                    Ok(valid_samples) => window.pass_samples(valid_samples),
+                    Err(err) => println!(
+                        "..Attempt to read GPUs for exporting failed, returning: {}",
+                        err
+                    ),
+                }
+
+                Continue(false)
+            }),
+        );
     }
 
     /**
      * Name:
-     * setup_settings
+     * pass_samples
      *
      * Description:
-     * Load settings for APP_ID
+     * Pass readings of every GPU on to each exporter
      *
      * Made:
-     * 09/10/2022
+     * 08/02/2023
      *
      * Made by:
      * Deren Vural
      *
      * Notes:
+     * Only logging happens here, everything else is queued for a worker
+     */
+    fn pass_samples(&self, samples: Vec<GpuSample>) {
+        let logged_gpus: Vec<String> = self.imp().get_setting::<Vec<String>>("logged-gpus");
+        if !logged_gpus.is_empty() {
+            self.log_samples(&samples, &logged_gpus);
+        }
+        if let Some(worker) = self.imp().metrics_worker.borrow().as_ref() {
+            worker.record(stored_samples(&samples));
+        }
+        if let Some((_, worker)) = self.imp().mqtt_worker.borrow().as_ref() {
+            worker.send(samples.clone());
+        }
+        if let Some((_, worker)) = self.imp().influx_worker.borrow().as_ref() {
+            worker.send(samples);
+        }
+    }
+
+    /**
+     * Name:
+     * log_samples
+     *
+     * Description:
+     * Write a row to the log of every logged GPU
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
      *
+     * Notes:
+     * Logs are closed once their GPU is no longer logged, or re-opened if the format/directory changes
      */
-    fn setup_settings(&self) {
-        let settings = Settings::new(APP_ID);
-        self.imp()
-            .settings
-            .set(settings)
-            .expect("`settings` should not be set before calling `setup_settings`.");
+    fn log_samples(&self, samples: &[GpuSample], logged_gpus: &[String]) {
+        let mut sample_logs: RefMut<HashMap<String, SampleLog>> =
+            self.imp().sample_logs.borrow_mut();
+
+        // Load log settings
+        let format: LogFormat =
+            match LogFormat::from_name(&self.imp().get_setting::<String>("log-format")) {
+                Some(valid_format) => valid_format,
+                None => {
+                    println!("..Unknown log format in settings");
+                    return;
+                }
+            };
+        let directory: PathBuf = match self.imp().get_setting::<String>("log-directory") {
+            directory if !directory.is_empty() => PathBuf::from(directory),
+            _ => match default_log_directory() {
+                Some(valid_directory) => valid_directory,
+                None => {
+                    println!("..Cannot find the data directory, readings won't be logged");
+                    return;
+                }
+            },
+        };
+        let policy: RotationPolicy = RotationPolicy {
+            max_bytes: self.imp().get_setting::<i32>("log-max-size").max(1) as u64 * 1024 * 1024,
+            daily: true,
+        };
+
+        // Write a row for each logged gpu
+        for sample in samples
+            .iter()
+            .filter(|sample| logged_gpus.contains(&sample.uuid))
+        {
+            let log: &mut SampleLog = sample_logs
+                .entry(sample.uuid.clone())
+                .or_insert_with(|| SampleLog::new(&directory, &sample.uuid, format, policy));
+            if log.format() != format || log.directory() != directory.as_path() {
+                *log = SampleLog::new(&directory, &sample.uuid, format, policy);
+            }
+            log.set_policy(policy);
+
+            if let Err(err) = log.write(sample) {
+                println!("..Attempt to log readings failed, returning: {}", err);
+            }
+        }
     }
 
     /**
      * Name:
-     * settings
+     * update_metrics_server
      *
      * Description:
-     * Get settings for APP_ID
+     * Start, stop or move the metrics server to match settings
      *
      * Made:
-     * 09/10/2022
+     * 05/02/2023
      *
      * Made by:
      * Deren Vural
      *
      * Notes:
+     * Left alone if already running with the same settings. If the new server can't start (i.e. it's port is in use),
+     * the user is told
+     */
+    fn update_metrics_server(&self) {
+        let mut metrics_server: RefMut<Option<(String, MetricsServer)>> =
+            self.imp().metrics_server.borrow_mut();
+
+        // Work out what should be running (address & provider), if anything
+        let address: String = format!(
+            "{}:{}",
+            self.imp().get_setting::<String>("metrics-server-address"),
+            self.imp().get_setting::<i32>("metrics-server-port")
+        );
+        let provider_type: i32 = self.imp().get_setting::<i32>("provider");
+        let wanted: Option<String> = match self.imp().get_setting::<bool>("metrics-server") {
+            true => Some(format!("{} ({})", address, provider_label(provider_type))),
+            false => None,
+        };
+        if metrics_server.as_ref().map(|(current, _)| current) == wanted.as_ref() {
+            return;
+        }
+
+        // Stop current server (freeing it's port) before starting another
+        let stop_error: Option<String> = match metrics_server.take() {
+            Some((_, mut server)) => server.stop().err(),
+            None => None,
+        };
+        if let Some(description) = wanted {
+            match MetricsServer::start(
+                &address,
+                Sampler::new(sampler_command(provider_type)),
+                &provider_label(provider_type),
+            ) {
+                Ok(server) => *metrics_server = Some((description, server)),
+                Err(err) => {
+                    // i.e. the port is still in use, by the last server or something else
+                    let message: String = match stop_error {
+                        Some(valid_stop_error) => format!(
+                            "Cannot start metrics server: {} ({})",
+                            err, valid_stop_error
+                        ),
+                        None => format!("Cannot start metrics server: {}", err),
+                    };
+                    println!("..{}", message);
+                    drop(metrics_server);
+                    self.show_error(&message);
+                }
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * read_secret_setting
+     *
+     * Description:
+     * Read a secret from the file set in settings, otherwise from an environment variable
      *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only the path is kept in settings, never the secret itself
      */
-    fn settings(&self) -> &Settings {
-        self.imp()
-            .settings
-            .get()
-            .expect("`settings` should be set in `setup_settings`.")
+    fn read_secret_setting(&self, key: &str, variable: &str) -> Result<String, String> {
+        let path: String = self.imp().get_setting::<String>(key);
+
+        match path.is_empty() {
+            true => read_secret(variable, None),
+            false => read_secret(variable, Some(Path::new(&path))),
+        }
     }
 
     /**
      * Name:
-     * setup_widgets
+     * update_mqtt
      *
      * Description:
-     * Set up all widgets
+     * Start, stop or restart publishing to MQTT to match settings
      *
      * Made:
-     * 23/10/2022
+     * 06/02/2023
      *
      * Made by:
      * Deren Vural
      *
      * Notes:
+     * Left alone if already publishing with the same settings
+     */
+    fn update_mqtt(&self) {
+        let mut mqtt_worker: RefMut<Option<(MqttConfig, MqttWorker)>> =
+            self.imp().mqtt_worker.borrow_mut();
+
+        // Work out what should be running, if anything
+        let refresh_rate: u16 = self.imp().get_setting::<i32>("refreshrate").clamp(1, 3600) as u16;
+        let wanted: Option<MqttConfig> = match self.imp().get_setting::<bool>("mqtt") {
+            true => match self.read_secret_setting("mqtt-password-file", MQTT_PASSWORD_VARIABLE) {
+                Ok(password) => Some(MqttConfig {
+                    broker: self.imp().get_setting::<String>("mqtt-broker"),
+                    username: self.imp().get_setting::<String>("mqtt-username"),
+                    password,
+                    topic_prefix: self.imp().get_setting::<String>("mqtt-topic-prefix"),
+                    qos: self.imp().get_setting::<i32>("mqtt-qos").clamp(0, 2) as u8,
+                    keep_alive: (refresh_rate * 3).max(60),
+                    ..MqttConfig::default()
+                }),
+                Err(err) => {
+                    println!("..Attempt to read MQTT password failed, returning: {}", err);
+                    None
+                }
+            },
+            false => None,
+        };
+        if mqtt_worker.as_ref().map(|(current, _)| current) == wanted.as_ref() {
+            return;
+        }
+
+        // The new worker connects once the old one has disconnected (marking us offline)
+        let previous: Option<MqttWorker> = mqtt_worker.take().map(|(_, worker)| worker);
+        if let Some(config) = wanted {
+            *mqtt_worker = Some((config.clone(), MqttWorker::spawn(config, previous)));
+        }
+    }
+
+    /**
+     * Name:
+     * update_influx
+     *
+     * Description:
+     * Start, stop or restart writing to InfluxDB to match settings
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
      *
+     * Notes:
+     * Left alone if already writing with the same settings.
+     * Never to stdout, which the rest of the app also prints to
      */
-    fn setup_widgets(&self) {
-        // Connect closure to re-load stored views (with different settings) when a settings window is closed
-        //NOTE: expected return value seems to be broken - look at imp.rs:1772
-        self.connect_closure(
-            "update-all-views",
-            false,
-            closure!(move |window: MainWindow| {
-                println!("closure called!"); //TEST
+    fn update_influx(&self) {
+        let mut influx_worker: RefMut<Option<(InfluxConfig, InfluxWorker)>> =
+            self.imp().influx_worker.borrow_mut();
 
-                // Reload views
-                println!("reloading views.."); //TEST
-                window.imp().refresh_cards();
-                println!("views reloaded.."); //TEST
+        // Work out what should be running, if anything
+        let wanted: Option<InfluxConfig> = match self.imp().get_setting::<bool>("influx") {
+            true => match (
+                parse_target(&self.imp().get_setting::<String>("influx-target")).and_then(
+                    |target| match target {
+                        InfluxTarget::Stdout => Err(String::from(
+                            "stdout is only available headless, where it isn't mixed with other output..",
+                        )),
+                        target => Ok(target),
+                    },
+                ),
+                self.read_secret_setting("influx-token-file", INFLUX_TOKEN_VARIABLE),
+            ) {
+                (Ok(target), Ok(token)) => Some(InfluxConfig {
+                    target,
+                    token,
+                    measurement: self.imp().get_setting::<String>("influx-measurement"),
+                    uuid_tag: self.imp().get_setting::<String>("influx-uuid-tag"),
+                    name_tag: self.imp().get_setting::<String>("influx-name-tag"),
+                }),
+                (Err(err), _) | (_, Err(err)) => {
+                    println!(
+                        "..Attempt to read InfluxDB settings failed, returning: {}",
+                        err
+                    );
+                    None
+                }
+            },
+            false => None,
+        };
+        if influx_worker.as_ref().map(|(current, _)| current) == wanted.as_ref() {
+            return;
+        }
 
-                // Return final value
-                0
+        influx_worker.take();
+        if let Some(config) = wanted {
+            *influx_worker = Some((config.clone(), InfluxWorker::spawn(config)));
+        }
+    }
+
+    /**
+     * Name:
+     * metrics_policy
+     *
+     * Description:
+     * How long the metrics store keeps readings, with the size limit from settings
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn metrics_policy(&self) -> RetentionPolicy {
+        let max_size: u64 = self
+            .imp()
+            .get_setting::<i32>("metrics-store-max-size")
+            .max(1) as u64;
+
+        RetentionPolicy {
+            max_bytes: max_size * 1024 * 1024,
+            ..RetentionPolicy::default()
+        }
+    }
+
+    /**
+     * Name:
+     * maintain_metrics
+     *
+     * Description:
+     * Average, forget & tidy up old readings in the metrics store
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Picks up any change to the size limit. Run by the worker, as vacuuming a large store takes a while
+     */
+    fn maintain_metrics(&self) {
+        if let Some(worker) = self.imp().metrics_worker.borrow().as_ref() {
+            worker.maintain(self.metrics_policy(), now_millis());
+        }
+    }
+
+    /**
+     * Name:
+     * show_error
+     *
+     * Description:
+     * Show an error in a pop-up
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn show_error(&self, message: &str) {
+        let dialog: MessageDialog = MessageDialog::new(
+            Some(self),
+            DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+            MessageType::Error,
+            ButtonsType::Close,
+            message,
+        );
+        dialog.connect_response(|dialog, _| dialog.close());
+        dialog.show();
+    }
+
+    /**
+     * Name:
+     * open_save_profile_dialog
+     *
+     * Description:
+     * Ask for a name, then save the current settings of every GPU as a profile
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn open_save_profile_dialog(&self) {
+        let dialog: Dialog = Dialog::with_buttons(
+            Some("Save Profile"),
+            Some(self),
+            DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Save", ResponseType::Accept),
+            ],
+        );
+
+        // Name input
+        let entry: Entry = Entry::builder()
+            .placeholder_text("i.e. Quiet")
+            .activates_default(true)
+            .margin_top(12)
+            .margin_bottom(12)
+            .margin_start(12)
+            .margin_end(12)
+            .build();
+        let content: gtk::Box = dialog.content_area();
+        content.set_orientation(Orientation::Vertical);
+        content.append(&entry);
+        dialog.set_default_response(ResponseType::Accept);
+
+        dialog.connect_response(
+            clone!(@weak self as window, @weak entry => move |dialog, response| {
+                if response == ResponseType::Accept {
+                    match window.save_profile(entry.text().trim()) {
+                        Ok(_) => println!("..Profile `{}` saved", entry.text()),
+                        Err(err) => {
+                            println!("..Attempt to save profile failed, returning: {}", err);
+                            entry.add_css_class("error");
+                            entry.set_tooltip_text(Some(&err));
+                            return;
+                        }
+                    }
+                }
+
+                dialog.close();
             }),
         );
+        dialog.show();
     }
 
     /**
@@ -394,11 +2212,81 @@ impl MainWindow {
         }));
         self.add_action(&open_app_settings);
 
+        let open_history: SimpleAction = SimpleAction::new("open_history", None);
+        open_history.connect_activate(clone!(@weak self as window => move |_, _| {
+            // Borrow (mutable) the window's container
+            let mut history_window_container: RefMut<HistoryWindowContainer> = window.imp().history_window.borrow_mut();
+
+            // Get state from settings
+            history_window_container.open = window.imp().get_setting::<bool>("history-open");
+
+            // Check if the window is already open
+            if history_window_container.open && history_window_container.window.is_some() {
+                println!("....window already open");//DEBUG
+                return;
+            }
+
+            // Create an app object
+            let app: adwaita::Application = adwaita::Application::builder().application_id(APP_ID).build();
+
+            // Create history window
+            let new_history_window: HistoryWindow = HistoryWindow::new(&app, window.imp().gpu_names());
+
+            // Show new history window
+            new_history_window.show();
+
+            // Store object and state back in container
+            history_window_container.open = true;
+            history_window_container.window = Some(new_history_window);
+
+            // Set new state in settings
+            window.imp().update_setting::<bool>("history-open", history_window_container.open);
+        }));
+        self.add_action(&open_history);
+
         let about: SimpleAction = SimpleAction::new("about", None);
         about.connect_activate(clone!(@weak self as window => move |_, _| {
             // Show About info pop-up
             println!("About pop-up not yet implemented..");//TODO
         }));
         self.add_action(&about);
+
+        let save_profile: SimpleAction = SimpleAction::new("save_profile", None);
+        save_profile.connect_activate(clone!(@weak self as window => move |_, _| {
+            window.open_save_profile_dialog();
+        }));
+        self.add_action(&save_profile);
+
+        let apply_profile: SimpleAction =
+            SimpleAction::new("apply_profile", Some(VariantTy::STRING));
+        apply_profile.connect_activate(clone!(@weak self as window => move |_, parameter| {
+            // Grab name of profile
+            let name: String = match parameter.and_then(|value| value.get::<String>()) {
+                Some(valid_name) => valid_name,
+                None => return,
+            };
+
+            let profile_name: String = name.clone();
+            window.apply_profile(&profile_name, move |window, result| match result {
+                Ok(_) => println!("..Profile `{}` applied", name),
+                Err(err) => {
+                    println!("..Attempt to apply profile `{}` failed, returning: {}", name, err);
+                    window.show_error(&err);
+                }
+            });
+        }));
+        self.add_action(&apply_profile);
+
+        let import_gwe: SimpleAction = SimpleAction::new("import_gwe", None);
+        import_gwe.connect_activate(clone!(@weak self as window => move |_, _| {
+            match window.import_gwe() {
+                Ok(import) => window.show_import_report(&import),
+                Err(err) => {
+                    println!("..Attempt to import GreenWithEnvy profiles failed, returning: {}", err);
+                    window.show_error(&err);
+                }
+            }
+        }));
+        self.add_action(&import_gwe);
     }
 }
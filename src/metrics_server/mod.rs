@@ -0,0 +1,501 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Embedded HTTP server exposing the current readings of every GPU for Prometheus (OpenMetrics format)
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * GPUs are read when scraped, at `/metrics`. Doesn't depend on GTK, so also works headless.
+ * Values are converted to base units (i.e. bytes, hertz) as recommended by Prometheus
+ */
+// Imports
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+// Modules
+use crate::sampler::{sampler_command, GpuSample, Metric, Sampler};
+
+/// Content type of an OpenMetrics response
+pub const CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+/// Port used if none is set
+pub const DEFAULT_PORT: u16 = 9400;
+/// Prefix of every metric name
+pub const METRIC_PREFIX: &str = "nvidia_gpu_";
+/// Longest time to wait for a request to be sent
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+/// Longest time to wait for the server thread when stopping
+const STOP_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Structure for a running metrics server
+#[derive(Debug)]
+pub struct MetricsServer {
+    address: SocketAddr,
+    running: Arc<AtomicBool>,
+    stopped: Receiver<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/**
+ * Name:
+ * MetricsServer
+ *
+ * Description:
+ * Trait shared by all MetricsServer objects
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl MetricsServer {
+    /**
+     * Name:
+     * start
+     *
+     * Description:
+     * Bind to an address (i.e. `127.0.0.1:9400`) and serve readings from the sampler in the background
+     *
+     * Made:
+     * 05/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Port 0 picks any free port, see `address`. Supported properties are probed on the server thread
+     */
+    pub fn start(address: &str, sampler: Sampler, provider: &str) -> Result<Self, String> {
+        let listener: TcpListener = TcpListener::bind(address)
+            .map_err(|err| format!("Cannot listen on `{}`: {}", address, err))?;
+        let local_address: SocketAddr = listener
+            .local_addr()
+            .map_err(|err| format!("Cannot listen on `{}`: {}", address, err))?;
+
+        // Serve each request on it's own thread, so a slow client can't stop the server closing
+        let running: Arc<AtomicBool> = Arc::new(AtomicBool::new(true));
+        let thread_running: Arc<AtomicBool> = Arc::clone(&running);
+        let provider: String = String::from(provider);
+        let (stopped_sender, stopped): (Sender<()>, Receiver<()>) = mpsc::channel();
+        let thread: JoinHandle<()> = thread::spawn(move || {
+            // Leave out anything the driver can't read
+            let sampler: Arc<Sampler> = Arc::new(sampler.probe());
+            let provider: Arc<String> = Arc::new(provider);
+
+            for stream in listener.incoming() {
+                if !thread_running.load(Ordering::SeqCst) {
+                    break;
+                }
+                match stream {
+                    Ok(valid_stream) => {
+                        let sampler: Arc<Sampler> = Arc::clone(&sampler);
+                        let provider: Arc<String> = Arc::clone(&provider);
+                        thread::spawn(move || {
+                            if let Err(err) = handle_connection(valid_stream, &sampler, &provider) {
+                                eprintln!("..Attempt to serve metrics failed, returning: {}", err);
+                            }
+                        });
+                    }
+                    Err(err) => {
+                        eprintln!("..Attempt to accept connection failed, returning: {}", err)
+                    }
+                }
+            }
+
+            // Free the port before saying so
+            drop(listener);
+            let _ = stopped_sender.send(());
+        });
+
+        eprintln!("..Serving metrics at http://{}/metrics", local_address);
+
+        Ok(MetricsServer {
+            address: local_address,
+            running,
+            stopped,
+            thread: Some(thread),
+        })
+    }
+
+    /**
+     * Name:
+     * address
+     *
+     * Description:
+     * Address the server is listening on
+     *
+     * Made:
+     * 05/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /**
+     * Name:
+     * wait
+     *
+     * Description:
+     * Block until the server stops
+     *
+     * Made:
+     * 05/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Used when headless, where serving is all there is to do
+     */
+    pub fn wait(mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /**
+     * Name:
+     * stop
+     *
+     * Description:
+     * Stop serving & close the socket
+     *
+     * Made:
+     * 05/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The server thread is woken with a connection of it's own. Never blocks for long (i.e. on the UI thread): if the
+     * server thread hasn't closed the socket after STOP_TIMEOUT (i.e. it's still probing the driver) it's left to
+     * finish on it's own, freeing the port once it does, and an error is returned (as the port can't be reused yet).
+     * Use `wait` to block until the server has stopped
+     */
+    pub fn stop(&mut self) -> Result<(), String> {
+        if let Some(thread) = self.thread.take() {
+            self.running.store(false, Ordering::SeqCst);
+            let _ = TcpStream::connect_timeout(&self.address, STOP_TIMEOUT);
+            let stopped: bool = self.stopped.recv_timeout(STOP_TIMEOUT).is_ok();
+            drop(thread);
+            if !stopped {
+                return Err(format!(
+                    "Metrics server is still closing, `{}` will be freed once it has..",
+                    self.address
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Name:
+ * MetricsServer
+ *
+ * Description:
+ * Stop the server when dropped
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Drop for MetricsServer {
+    fn drop(&mut self) {
+        if let Err(err) = self.stop() {
+            eprintln!("..{}", err);
+        }
+    }
+}
+
+/**
+ * Name:
+ * handle_connection
+ *
+ * Description:
+ * Read a request and send back a response
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only the request line is looked at, the connection is always closed afterwards
+ */
+fn handle_connection(stream: TcpStream, sampler: &Sampler, provider: &str) -> Result<(), String> {
+    stream
+        .set_read_timeout(Some(REQUEST_TIMEOUT))
+        .map_err(|err| err.to_string())?;
+
+    // Read request line & headers
+    let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+    let mut request_line: String = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|err| err.to_string())?;
+    let mut header: String = String::new();
+    while reader
+        .read_line(&mut header)
+        .map_err(|err| err.to_string())?
+        > 2
+    {
+        header.clear();
+    }
+
+    let (status, content_type, body): (&str, &str, String) = match parse_request_line(&request_line)
+    {
+        Some(("GET", "/metrics")) => match sampler.sample() {
+            Ok(samples) => (
+                "200 OK",
+                CONTENT_TYPE,
+                format_openmetrics(&samples, sampler.metrics(), provider),
+            ),
+            Err(err) => (
+                "500 Internal Server Error",
+                "text/plain; charset=utf-8",
+                err + "\n",
+            ),
+        },
+        Some(("GET", "/")) => (
+            "200 OK",
+            "text/html; charset=utf-8",
+            String::from("<html><body><a href=\"/metrics\">Metrics</a></body></html>\n"),
+        ),
+        Some(("GET", _)) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            String::from("Not Found\n"),
+        ),
+        Some(_) => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            String::from("Method Not Allowed\n"),
+        ),
+        None => (
+            "400 Bad Request",
+            "text/plain; charset=utf-8",
+            String::from("Bad Request\n"),
+        ),
+    };
+
+    // Send response
+    let response: String = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    (&stream)
+        .write_all(response.as_bytes())
+        .map_err(|err| err.to_string())
+}
+
+/**
+ * Name:
+ * parse_request_line
+ *
+ * Description:
+ * Split a HTTP request line into it's method & path (without any query)
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * i.e. `GET /metrics?x=1 HTTP/1.1` is `("GET", "/metrics")`
+ */
+pub fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method: &str = parts.next()?;
+    let target: &str = parts.next()?;
+    if !parts.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    Some((method, target.split('?').next().unwrap_or(target)))
+}
+
+/**
+ * Name:
+ * provider_label
+ *
+ * Description:
+ * Value of the `provider` label for a provider type (from settings)
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Readings always come from the sampler, so this is the command it runs (i.e. `nvidia-smi`) rather than the
+ * provider used by the window
+ */
+pub fn provider_label(provider_type: i32) -> String {
+    sampler_command(provider_type).join(" ")
+}
+
+/**
+ * Name:
+ * base_unit
+ *
+ * Description:
+ * OpenMetrics unit of a property & how to convert it's values to that unit
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Properties without a unit (i.e. counts) have no unit in their name. Percentages are exported as a ratio (0-1),
+ * as is the convention for OpenMetrics/Prometheus
+ */
+pub fn base_unit(metric: &Metric) -> (&'static str, fn(f64) -> f64) {
+    match metric.unit {
+        "%" => ("ratio", |value| value / 100.0),
+        "°C" => ("celsius", |value| value),
+        "W" => ("watts", |value| value),
+        "MiB" => ("bytes", |value| value * 1024.0 * 1024.0),
+        "MHz" => ("hertz", |value| value * 1_000_000.0),
+        _ => ("", |value| value),
+    }
+}
+
+/**
+ * Name:
+ * metric_name
+ *
+ * Description:
+ * Name of the metric family of a property (i.e. `nvidia_gpu_temp_celsius`)
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn metric_name(metric: &Metric) -> String {
+    match base_unit(metric) {
+        ("", _) => format!("{}{}", METRIC_PREFIX, metric.name),
+        (unit, _) => format!("{}{}_{}", METRIC_PREFIX, metric.name, unit),
+    }
+}
+
+/**
+ * Name:
+ * escape_label
+ *
+ * Description:
+ * Quote the value of a label
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn escape_label(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/**
+ * Name:
+ * format_openmetrics
+ *
+ * Description:
+ * Format the readings of every GPU as an OpenMetrics exposition, one gauge per property
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Each GPU is labelled with it's `uuid`, `name` & `provider`. Readings that couldn't be read are left out
+ */
+pub fn format_openmetrics(samples: &[GpuSample], metrics: &[&Metric], provider: &str) -> String {
+    let mut output: String = String::new();
+
+    for metric in metrics {
+        let name: String = metric_name(metric);
+        let (unit, convert) = base_unit(metric);
+
+        // Metadata
+        output += &format!("# TYPE {} gauge\n", name);
+        if !unit.is_empty() {
+            output += &format!("# UNIT {} {}\n", name, unit);
+        }
+        output += &format!("# HELP {} {}\n", name, metric.label);
+
+        // Value of each gpu
+        for sample in samples {
+            if let Some(value) = sample.value(metric.name) {
+                output += &format!(
+                    "{}{{uuid={},name={},provider={}}} {}\n",
+                    name,
+                    escape_label(&sample.uuid),
+                    escape_label(&sample.name),
+                    escape_label(provider),
+                    convert(value)
+                );
+            }
+        }
+    }
+
+    output + "# EOF\n"
+}
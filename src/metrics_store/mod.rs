@@ -0,0 +1,1045 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * On-disk store of readings (SQLite), so history survives restarts
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Readings are kept as recorded for an hour, then as 1 minute averages for a week, then as hourly averages for a year.
+ * Nothing here needs GTK, so a headless recorder can fill the same store the app reads
+ */
+// Imports
+use rusqlite::{params, CachedStatement, Connection, OptionalExtension, Transaction};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, SyncSender, TrySendError},
+    thread,
+    time::Duration,
+};
+
+// Modules
+use crate::{history::HistorySample, sampler::GpuSample};
+
+/// Name of the store file
+pub const STORE_NAME: &str = "metrics.db";
+/// Directory (inside the XDG data directory) the store is kept in
+pub const STORE_DIRECTORY: &str = "gtk4-nvidia-monitor-rust";
+/// A minute, in milliseconds
+pub const MINUTE: u64 = 60_000;
+/// An hour, in milliseconds
+pub const HOUR: u64 = 3_600_000;
+/// A day, in milliseconds
+pub const DAY: u64 = 86_400_000;
+/// Every resolution readings are kept at, finest first
+pub const RESOLUTIONS: [Resolution; 3] = [Resolution::Raw, Resolution::Minute, Resolution::Hour];
+/// Most rounds of deleting old readings when over the size limit
+const MAX_TRIM_ROUNDS: usize = 64;
+/// Time a write waits for another process (i.e. a headless recorder) using the store
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Most requests waiting for the worker, anything more is dropped (i.e. while vacuuming a large store)
+pub const WORKER_QUEUE_SIZE: usize = 16;
+
+/// Resolutions readings are kept at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Raw,
+    Minute,
+    Hour,
+}
+
+/// Structure for storing how long readings are kept at each resolution (ms), and the most space (bytes) used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub raw: u64,
+    pub minute: u64,
+    pub hour: u64,
+    pub max_bytes: u64,
+}
+
+/// Structure for storing a single reading to add to the store, no value meaning it couldn't be read
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredSample {
+    pub uuid: String,
+    pub property: String,
+    pub unit: String,
+    pub timestamp: u64,
+    pub value: Option<f64>,
+}
+
+/// Structure for storing what a round of maintenance did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Maintenance {
+    pub rolled_up: usize,
+    pub expired: usize,
+    pub trimmed: usize,
+    pub vacuumed: bool,
+}
+
+/// Structure for storing an open store & how long it keeps readings
+pub struct MetricsStore {
+    connection: Connection,
+    policy: RetentionPolicy,
+}
+
+/// Work done by a `MetricsWorker`
+enum StoreRequest {
+    Record(Vec<StoredSample>),
+    Maintain(RetentionPolicy, u64),
+}
+
+/// Structure for writing to & maintaining a store on a separate thread
+pub struct MetricsWorker {
+    sender: SyncSender<StoreRequest>,
+}
+
+/**
+ * Name:
+ * Resolution
+ *
+ * Description:
+ * Trait shared by all resolutions
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Resolution {
+    /**
+     * Name:
+     * table
+     *
+     * Description:
+     * Table readings of this resolution are kept in
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn table(&self) -> &'static str {
+        match self {
+            Resolution::Raw => "samples_raw",
+            Resolution::Minute => "samples_minute",
+            Resolution::Hour => "samples_hour",
+        }
+    }
+
+    /**
+     * Name:
+     * bucket
+     *
+     * Description:
+     * Time (ms) averaged into a single reading, 0 for readings kept as recorded
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn bucket(&self) -> u64 {
+        match self {
+            Resolution::Raw => 0,
+            Resolution::Minute => MINUTE,
+            Resolution::Hour => HOUR,
+        }
+    }
+}
+
+/**
+ * Name:
+ * RetentionPolicy
+ *
+ * Description:
+ * Default trait for retention policies
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * An hour as recorded, a week of minutes, a year of hours and up to 256 MiB
+ */
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        RetentionPolicy {
+            raw: HOUR,
+            minute: 7 * DAY,
+            hour: 365 * DAY,
+            max_bytes: 256 * 1024 * 1024,
+        }
+    }
+}
+
+/**
+ * Name:
+ * sql_error
+ *
+ * Description:
+ * Convert a database error into the error type used here
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn sql_error(err: rusqlite::Error) -> String {
+    format!("Metrics store error: {}..", err)
+}
+
+/**
+ * Name:
+ * align
+ *
+ * Description:
+ * Round a timestamp down to the start of it's bucket
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn align(timestamp: u64, bucket: u64) -> u64 {
+    match bucket {
+        0 => timestamp,
+        _ => timestamp - timestamp % bucket,
+    }
+}
+
+/**
+ * Name:
+ * MetricsStore
+ *
+ * Description:
+ * Trait shared by all metrics stores
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Timestamps are in milliseconds since the unix epoch
+ */
+impl MetricsStore {
+    /**
+     * Name:
+     * open
+     *
+     * Description:
+     * Open (or create) a store
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Uses a write-ahead log, so the app can read while a headless recorder writes
+     */
+    pub fn open(path: &Path, policy: RetentionPolicy) -> Result<MetricsStore, String> {
+        if let Some(directory) = path.parent() {
+            std::fs::create_dir_all(directory).map_err(|err| {
+                format!(
+                    "Cannot create metrics store directory `{}`: {}..",
+                    directory.display(),
+                    err
+                )
+            })?;
+        }
+
+        let connection: Connection = Connection::open(path)
+            .map_err(|err| format!("Cannot open metrics store `{}`: {}..", path.display(), err))?;
+        connection.busy_timeout(BUSY_TIMEOUT).map_err(sql_error)?;
+        connection
+            .pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+            .map_err(sql_error)?;
+        connection
+            .pragma_update(None, "synchronous", "NORMAL")
+            .map_err(sql_error)?;
+
+        // Tables
+        let mut schema: String = String::from(
+            "CREATE TABLE IF NOT EXISTS series (
+                id INTEGER PRIMARY KEY,
+                uuid TEXT NOT NULL,
+                property TEXT NOT NULL,
+                unit TEXT NOT NULL DEFAULT '',
+                UNIQUE (uuid, property)
+            );",
+        );
+        for resolution in RESOLUTIONS {
+            schema += &format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    series INTEGER NOT NULL,
+                    timestamp INTEGER NOT NULL,
+                    value REAL,
+                    min REAL,
+                    max REAL,
+                    count INTEGER NOT NULL,
+                    PRIMARY KEY (series, timestamp)
+                ) WITHOUT ROWID;
+                CREATE INDEX IF NOT EXISTS {}_timestamp ON {} (timestamp);",
+                resolution.table(),
+                resolution.table(),
+                resolution.table()
+            );
+        }
+        connection.execute_batch(&schema).map_err(sql_error)?;
+
+        Ok(MetricsStore { connection, policy })
+    }
+
+    /**
+     * Name:
+     * policy
+     *
+     * Description:
+     * How long readings are kept & the most space used
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn policy(&self) -> RetentionPolicy {
+        self.policy
+    }
+
+    /**
+     * Name:
+     * set_policy
+     *
+     * Description:
+     * Change how long readings are kept & the most space used, applied at the next maintenance
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn set_policy(&mut self, policy: RetentionPolicy) {
+        self.policy = policy;
+    }
+
+    /**
+     * Name:
+     * record
+     *
+     * Description:
+     * Add readings (i.e. a single refresh of every GPU), returning how many were added
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Added together, so a refresh is never half stored. A second reading at the same time replaces the first
+     */
+    pub fn record(&mut self, samples: &[StoredSample]) -> Result<usize, String> {
+        let transaction: Transaction = self.connection.transaction().map_err(sql_error)?;
+        {
+            let mut find_series: CachedStatement = transaction
+                .prepare_cached("SELECT id, unit FROM series WHERE uuid = ?1 AND property = ?2")
+                .map_err(sql_error)?;
+            let mut add_series: CachedStatement = transaction
+                .prepare_cached("INSERT INTO series (uuid, property, unit) VALUES (?1, ?2, ?3)")
+                .map_err(sql_error)?;
+            let mut update_unit: CachedStatement = transaction
+                .prepare_cached("UPDATE series SET unit = ?2 WHERE id = ?1")
+                .map_err(sql_error)?;
+            let mut add_sample: CachedStatement = transaction
+                .prepare_cached(&format!(
+                    "INSERT OR REPLACE INTO {} (series, timestamp, value, min, max, count)
+                     VALUES (?1, ?2, ?3, ?3, ?3, ?4)",
+                    Resolution::Raw.table()
+                ))
+                .map_err(sql_error)?;
+
+            for sample in samples {
+                let found: Option<(i64, String)> = find_series
+                    .query_row(params![sample.uuid, sample.property], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })
+                    .optional()
+                    .map_err(sql_error)?;
+                let series: i64 = match found {
+                    Some((id, unit)) => {
+                        // Keep the last unit seen (i.e. after switching temperature unit)
+                        if !sample.unit.is_empty() && sample.unit != unit {
+                            update_unit
+                                .execute(params![id, sample.unit])
+                                .map_err(sql_error)?;
+                        }
+                        id
+                    }
+                    None => {
+                        add_series
+                            .execute(params![sample.uuid, sample.property, sample.unit])
+                            .map_err(sql_error)?;
+                        transaction.last_insert_rowid()
+                    }
+                };
+
+                add_sample
+                    .execute(params![
+                        series,
+                        sample.timestamp as i64,
+                        sample.value,
+                        sample.value.is_some() as i64
+                    ])
+                    .map_err(sql_error)?;
+            }
+        }
+        transaction.commit().map_err(sql_error)?;
+
+        Ok(samples.len())
+    }
+
+    /**
+     * Name:
+     * query
+     *
+     * Description:
+     * Readings of a property of a GPU between two timestamps, oldest first
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Each resolution covers a different time, so they are simply joined. Averages are at the start of their bucket
+     */
+    pub fn query(
+        &self,
+        uuid: &str,
+        property: &str,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<HistorySample>, String> {
+        let selects: Vec<String> = RESOLUTIONS
+            .iter()
+            .map(|resolution| {
+                format!(
+                    "SELECT timestamp, value FROM {} WHERE series = ?1 AND timestamp BETWEEN ?2 AND ?3",
+                    resolution.table()
+                )
+            })
+            .collect();
+        let series: i64 = match self.series_id(uuid, property)? {
+            Some(id) => id,
+            None => return Ok(vec![]),
+        };
+
+        let mut statement: CachedStatement = self
+            .connection
+            .prepare_cached(&(selects.join(" UNION ALL ") + " ORDER BY timestamp"))
+            .map_err(sql_error)?;
+        let samples: Vec<HistorySample> = statement
+            .query_map(params![series, start as i64, end as i64], |row| {
+                Ok(HistorySample {
+                    timestamp: row.get::<_, i64>(0)? as u64,
+                    value: row.get(1)?,
+                })
+            })
+            .map_err(sql_error)?
+            .collect::<Result<Vec<HistorySample>, rusqlite::Error>>()
+            .map_err(sql_error)?;
+
+        Ok(samples)
+    }
+
+    /**
+     * Name:
+     * series
+     *
+     * Description:
+     * Every property of every GPU in the store, as (UUID, property, unit)
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn series(&self) -> Result<Vec<(String, String, String)>, String> {
+        let mut statement: CachedStatement = self
+            .connection
+            .prepare_cached("SELECT uuid, property, unit FROM series ORDER BY uuid, property")
+            .map_err(sql_error)?;
+        let series: Vec<(String, String, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .map_err(sql_error)?
+            .collect::<Result<Vec<(String, String, String)>, rusqlite::Error>>()
+            .map_err(sql_error)?;
+
+        Ok(series)
+    }
+
+    /**
+     * Name:
+     * count
+     *
+     * Description:
+     * Number of readings kept at a resolution
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn count(&self, resolution: Resolution) -> Result<usize, String> {
+        self.connection
+            .query_row(
+                &format!("SELECT COUNT(*) FROM {}", resolution.table()),
+                [],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count as usize)
+            .map_err(sql_error)
+    }
+
+    /**
+     * Name:
+     * used_bytes
+     *
+     * Description:
+     * Space (bytes) used by readings, not counting space freed but not yet given back (see `vacuum`)
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn used_bytes(&self) -> Result<u64, String> {
+        let (pages, free_pages, page_size): (i64, i64, i64) = self.page_counts()?;
+
+        Ok(((pages - free_pages).max(0) * page_size) as u64)
+    }
+
+    /**
+     * Name:
+     * maintain
+     *
+     * Description:
+     * Average old readings into coarser resolutions, forget the oldest, keep under the size limit and
+     * give back freed space
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Meant to be run every few minutes, by whichever process writes to the store
+     */
+    pub fn maintain(&mut self, now: u64) -> Result<Maintenance, String> {
+        let policy: RetentionPolicy = self.policy;
+        let mut maintenance: Maintenance = Maintenance::default();
+
+        let transaction: Transaction = self.connection.transaction().map_err(sql_error)?;
+        maintenance.rolled_up += roll_up(
+            &transaction,
+            Resolution::Raw,
+            Resolution::Minute,
+            align(now.saturating_sub(policy.raw), MINUTE),
+        )?;
+        maintenance.rolled_up += roll_up(
+            &transaction,
+            Resolution::Minute,
+            Resolution::Hour,
+            align(now.saturating_sub(policy.minute), HOUR),
+        )?;
+        maintenance.expired += transaction
+            .execute(
+                &format!(
+                    "DELETE FROM {} WHERE timestamp < ?1",
+                    Resolution::Hour.table()
+                ),
+                params![now.saturating_sub(policy.hour) as i64],
+            )
+            .map_err(sql_error)?;
+        transaction
+            .execute("DELETE FROM series WHERE id NOT IN (SELECT series FROM samples_raw UNION SELECT series FROM samples_minute UNION SELECT series FROM samples_hour)", [])
+            .map_err(sql_error)?;
+        transaction.commit().map_err(sql_error)?;
+
+        maintenance.trimmed = self.trim()?;
+
+        // Give back space once a quarter of the store is unused
+        let (pages, free_pages, _): (i64, i64, i64) = self.page_counts()?;
+        if maintenance.trimmed > 0 || free_pages * 4 > pages {
+            self.vacuum()?;
+            maintenance.vacuumed = true;
+        }
+
+        Ok(maintenance)
+    }
+
+    /**
+     * Name:
+     * vacuum
+     *
+     * Description:
+     * Give back space freed by forgetting readings
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Rewrites the whole store, so isn't run on every maintenance
+     */
+    pub fn vacuum(&self) -> Result<(), String> {
+        self.connection.execute_batch("VACUUM").map_err(sql_error)
+    }
+
+    /**
+     * Name:
+     * series_id
+     *
+     * Description:
+     * ID of a property of a GPU, if anything has been stored for it
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn series_id(&self, uuid: &str, property: &str) -> Result<Option<i64>, String> {
+        self.connection
+            .query_row(
+                "SELECT id FROM series WHERE uuid = ?1 AND property = ?2",
+                params![uuid, property],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(sql_error)
+    }
+
+    /**
+     * Name:
+     * page_counts
+     *
+     * Description:
+     * Pages in the store, pages unused and the size (bytes) of a page
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn page_counts(&self) -> Result<(i64, i64, i64), String> {
+        let mut counts: Vec<i64> = vec![];
+        for pragma in ["page_count", "freelist_count", "page_size"] {
+            counts.push(
+                self.connection
+                    .pragma_query_value(None, pragma, |row| row.get(0))
+                    .map_err(sql_error)?,
+            );
+        }
+
+        Ok((counts[0], counts[1], counts[2]))
+    }
+
+    /**
+     * Name:
+     * trim
+     *
+     * Description:
+     * Forget the oldest readings until under the size limit, returning how many were forgotten
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Each round forgets the oldest quarter (by time) of the coarsest resolution with anything in it
+     */
+    fn trim(&mut self) -> Result<usize, String> {
+        let mut trimmed: usize = 0;
+        for _ in 0..MAX_TRIM_ROUNDS {
+            if self.used_bytes()? <= self.policy.max_bytes {
+                break;
+            }
+
+            let mut found: Option<(Resolution, i64, i64)> = None;
+            for resolution in RESOLUTIONS.iter().rev() {
+                let range: (Option<i64>, Option<i64>) = self
+                    .connection
+                    .query_row(
+                        &format!(
+                            "SELECT MIN(timestamp), MAX(timestamp) FROM {}",
+                            resolution.table()
+                        ),
+                        [],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .map_err(sql_error)?;
+                if let (Some(oldest), Some(newest)) = range {
+                    found = Some((*resolution, oldest, newest));
+                    break;
+                }
+            }
+
+            // Nothing left to forget
+            let (resolution, oldest, newest): (Resolution, i64, i64) = match found {
+                Some(range) => range,
+                None => break,
+            };
+            trimmed += self
+                .connection
+                .execute(
+                    &format!("DELETE FROM {} WHERE timestamp <= ?1", resolution.table()),
+                    params![oldest + (newest - oldest) / 4],
+                )
+                .map_err(sql_error)?;
+        }
+
+        Ok(trimmed)
+    }
+}
+
+/**
+ * Name:
+ * roll_up
+ *
+ * Description:
+ * Average readings older than a cutoff into a coarser resolution, returning how many were averaged
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The cutoff should be the start of a bucket, so no bucket is split. Buckets already averaged are merged
+ * with, weighted by the number of readings in each
+ */
+fn roll_up(
+    transaction: &Transaction,
+    from: Resolution,
+    to: Resolution,
+    cutoff: u64,
+) -> Result<usize, String> {
+    transaction
+        .execute(
+            &format!(
+                "INSERT INTO {to} (series, timestamp, value, min, max, count)
+                 SELECT series, (timestamp / ?2) * ?2 AS bucket,
+                        SUM(value * count) / SUM(count), MIN(min), MAX(max), SUM(count)
+                 FROM {from} WHERE timestamp < ?1 GROUP BY series, bucket
+                 ON CONFLICT (series, timestamp) DO UPDATE SET
+                     value = (COALESCE(value * count, 0) + COALESCE(excluded.value * excluded.count, 0))
+                             / NULLIF(count + excluded.count, 0),
+                     min = MIN(COALESCE(min, excluded.min), COALESCE(excluded.min, min)),
+                     max = MAX(COALESCE(max, excluded.max), COALESCE(excluded.max, max)),
+                     count = count + excluded.count",
+                to = to.table(),
+                from = from.table()
+            ),
+            params![cutoff as i64, to.bucket() as i64],
+        )
+        .map_err(sql_error)?;
+
+    transaction
+        .execute(
+            &format!("DELETE FROM {} WHERE timestamp < ?1", from.table()),
+            params![cutoff as i64],
+        )
+        .map_err(sql_error)
+}
+
+/**
+ * Name:
+ * store_path
+ *
+ * Description:
+ * Where the store is kept, in the XDG data directory (`$XDG_DATA_HOME`, or `~/.local/share`)
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn store_path(home: Option<&Path>, data_home: Option<&Path>) -> Option<PathBuf> {
+    let data_directory: PathBuf = match (data_home, home) {
+        (Some(valid_data_home), _) => valid_data_home.to_path_buf(),
+        (None, Some(valid_home)) => valid_home.join(".local/share"),
+        (None, None) => return None,
+    };
+
+    Some(data_directory.join(STORE_DIRECTORY).join(STORE_NAME))
+}
+
+/**
+ * Name:
+ * default_store_path
+ *
+ * Description:
+ * Where the store of the current user is kept
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn default_store_path() -> Option<PathBuf> {
+    let home: Option<PathBuf> = std::env::var_os("HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    let data_home: Option<PathBuf> = std::env::var_os("XDG_DATA_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+
+    store_path(home.as_deref(), data_home.as_deref())
+}
+
+/**
+ * Name:
+ * stored_samples
+ *
+ * Description:
+ * Convert typed readings of every GPU to readings to store
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Kept in the units nvidia-smi reports (i.e. always °C), whatever the app shows
+ */
+pub fn stored_samples(samples: &[GpuSample]) -> Vec<StoredSample> {
+    samples
+        .iter()
+        .flat_map(|sample| {
+            sample.readings.iter().map(move |reading| StoredSample {
+                uuid: sample.uuid.clone(),
+                property: String::from(reading.metric.name),
+                unit: String::from(reading.metric.unit),
+                timestamp: sample.timestamp,
+                value: reading.value,
+            })
+        })
+        .collect()
+}
+
+/**
+ * Name:
+ * MetricsWorker
+ *
+ * Description:
+ * Trait shared by all metrics workers
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Requests are handled in order. Nothing waits for the worker, so the store is closed once the worker is dropped
+ * and everything queued has been handled
+ */
+impl MetricsWorker {
+    /**
+     * Name:
+     * spawn
+     *
+     * Description:
+     * Start handling requests for a store in the background
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn spawn(mut store: MetricsStore) -> Self {
+        let (sender, receiver): (SyncSender<StoreRequest>, Receiver<StoreRequest>) =
+            mpsc::sync_channel(WORKER_QUEUE_SIZE);
+
+        thread::spawn(move || {
+            for request in receiver {
+                match request {
+                    StoreRequest::Record(samples) => {
+                        if let Err(err) = store.record(&samples) {
+                            println!("..Attempt to store readings failed, returning: {}", err);
+                        }
+                    }
+                    StoreRequest::Maintain(policy, now) => {
+                        store.set_policy(policy);
+                        match store.maintain(now) {
+                            Ok(Maintenance {
+                                rolled_up,
+                                expired,
+                                trimmed,
+                                vacuumed,
+                            }) => println!(
+                                "..Metrics store maintained: {} averaged, {} expired, {} trimmed, vacuumed: {}",
+                                rolled_up, expired, trimmed, vacuumed
+                            ),
+                            Err(err) => println!(
+                                "..Attempt to maintain metrics store failed, returning: {}",
+                                err
+                            ),
+                        }
+                    }
+                }
+            }
+        });
+
+        MetricsWorker { sender }
+    }
+
+    /**
+     * Name:
+     * record
+     *
+     * Description:
+     * Queue readings to be stored, returning false if they were dropped
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Readings are dropped (rather than waiting) if the worker has fallen behind
+     */
+    pub fn record(&self, samples: Vec<StoredSample>) -> bool {
+        self.request(StoreRequest::Record(samples))
+    }
+
+    /**
+     * Name:
+     * maintain
+     *
+     * Description:
+     * Queue a round of maintenance with the given policy, returning false if it was dropped
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * See `MetricsStore::maintain`
+     */
+    pub fn maintain(&self, policy: RetentionPolicy, now: u64) -> bool {
+        self.request(StoreRequest::Maintain(policy, now))
+    }
+
+    /**
+     * Name:
+     * request
+     *
+     * Description:
+     * Queue a request without waiting
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn request(&self, request: StoreRequest) -> bool {
+        match self.sender.try_send(request) {
+            Ok(_) => true,
+            Err(TrySendError::Full(_)) => {
+                println!("..Metrics store is busy, dropping request");
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
@@ -0,0 +1,288 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Parsing of the GPU list given by `nvidia-smi -L`, including any MIG (Multi-Instance GPU) devices,
+ * and of the readings of each MIG device listed by `nvidia-smi -q`
+ *
+ * Made:
+ * 20/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * MIG devices are listed (indented) underneath their parent GPU, i.e.
+ * `MIG 1g.5gb     Device  0: (UUID: MIG-c6d4f1ef-42e4-5de3-91c7-45d71c87eb3f)`
+ *
+ * `nvidia-smi --query-gpu` doesn't support MIG devices, their readings only appear in the
+ * `MIG Devices` section of the parent GPU's full query
+ */
+/// Structure for storing a MIG device listed underneath a GPU
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigDevice {
+    pub profile: String,
+    pub index: u32,
+    pub uuid: String,
+}
+
+/// Structure for storing a GPU (and any of it's MIG devices) listed by nvidia-smi
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuListing {
+    pub index: u32,
+    pub name: String,
+    pub uuid: String,
+    pub mig_devices: Vec<MigDevice>,
+}
+
+/// Structure for storing the readings of a MIG device listed by `nvidia-smi -q`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MigDetails {
+    pub index: Option<u32>,
+    pub readings: Vec<(String, String)>,
+}
+
+/**
+ * Name:
+ * find_uuid
+ *
+ * Description:
+ * Grab the uuid from a line of `nvidia-smi -L` output, i.e. `(UUID: GPU-...)`
+ *
+ * Made:
+ * 20/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn find_uuid(line: &str) -> Option<String> {
+    let start: usize = line.find("(UUID:")? + "(UUID:".len();
+    let end: usize = start + line[start..].find(')')?;
+    let uuid: &str = line[start..end].trim();
+
+    if uuid.is_empty() {
+        None
+    } else {
+        Some(String::from(uuid))
+    }
+}
+
+/**
+ * Name:
+ * parse_gpu_list
+ *
+ * Description:
+ * Parse the output of `nvidia-smi -L` into a list of GPUs, each with their MIG devices
+ *
+ * Made:
+ * 20/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Any lines that aren't a GPU or MIG device (or have no uuid) are ignored
+ */
+pub fn parse_gpu_list(output: &str) -> Vec<GpuListing> {
+    let mut gpus: Vec<GpuListing> = vec![];
+
+    for line in output.lines() {
+        let trimmed: &str = line.trim();
+
+        if let Some(rest) = trimmed.strip_prefix("GPU ") {
+            // i.e. `GPU 0: NVIDIA A100-SXM4-40GB (UUID: GPU-...)`
+            if let (Some((index, description)), Some(uuid)) =
+                (rest.split_once(':'), find_uuid(rest))
+            {
+                if let Ok(gpu_index) = index.trim().parse::<u32>() {
+                    let name: &str = match description.find("(UUID:") {
+                        Some(end) => description[..end].trim(),
+                        None => description.trim(),
+                    };
+
+                    gpus.push(GpuListing {
+                        index: gpu_index,
+                        name: String::from(name),
+                        uuid,
+                        mig_devices: vec![],
+                    });
+                }
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("MIG ") {
+            // i.e. `MIG 1g.5gb     Device  0: (UUID: MIG-...)`
+            if let (Some((profile, device)), Some(uuid)) =
+                (rest.split_once("Device"), find_uuid(rest))
+            {
+                let index: Option<u32> = device
+                    .split(':')
+                    .next()
+                    .and_then(|index| index.trim().parse::<u32>().ok());
+
+                // Add to the GPU listed above
+                if let (Some(mig_index), Some(parent)) = (index, gpus.last_mut()) {
+                    parent.mig_devices.push(MigDevice {
+                        profile: String::from(profile.trim()),
+                        index: mig_index,
+                        uuid,
+                    });
+                }
+            }
+        }
+    }
+
+    gpus
+}
+
+/**
+ * Name:
+ * find_mig_devices
+ *
+ * Description:
+ * Find the MIG devices belonging to a GPU using it's uuid
+ *
+ * Made:
+ * 20/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Returns an empty list if the GPU cannot be found or has MIG disabled
+ */
+pub fn find_mig_devices(gpus: &[GpuListing], uuid: &str) -> Vec<MigDevice> {
+    match gpus.iter().find(|gpu| gpu.uuid == uuid) {
+        Some(gpu) => gpu.mig_devices.clone(),
+        None => vec![],
+    }
+}
+
+/**
+ * Name:
+ * parse_mig_details
+ *
+ * Description:
+ * Parse the `MIG Devices` section of `nvidia-smi -q` into the readings of each MIG device
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Readings are named by the sections they're nested in, i.e. `FB Memory Usage/Used`
+ */
+pub fn parse_mig_details(output: &str) -> Vec<MigDetails> {
+    let mut devices: Vec<MigDetails> = vec![];
+    // Indent of the current `MIG Device` header
+    let mut device_indent: Option<usize> = None;
+    // Sections (and their indent) the current line is nested in
+    let mut sections: Vec<(usize, String)> = vec![];
+
+    for line in output.lines() {
+        let trimmed: &str = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let indent: usize = line.len() - line.trim_start().len();
+
+        // Check if we've left the current device
+        if matches!(device_indent, Some(current_indent) if indent <= current_indent) {
+            device_indent = None;
+        }
+
+        if trimmed == "MIG Device" {
+            device_indent = Some(indent);
+            sections.clear();
+            devices.push(MigDetails::default());
+            continue;
+        }
+
+        if let (Some(_), Some(device)) = (device_indent, devices.last_mut()) {
+            // Leave any sections we're no longer nested in
+            sections.retain(|(section_indent, _)| *section_indent < indent);
+
+            match trimmed.split_once(':') {
+                // i.e. `Used : 11 MiB`
+                Some((key, value)) => {
+                    let mut name: Vec<&str> = sections
+                        .iter()
+                        .map(|(_, section)| section.as_str())
+                        .collect();
+                    name.push(key.trim());
+
+                    if name.len() == 1 && name[0] == "Index" {
+                        device.index = value.trim().parse::<u32>().ok();
+                    }
+                    device
+                        .readings
+                        .push((name.join("/"), String::from(value.trim())));
+                }
+                // i.e. `FB Memory Usage`
+                None => sections.push((indent, String::from(trimmed))),
+            }
+        }
+    }
+
+    devices
+}
+
+/**
+ * Name:
+ * mig_reading_name
+ *
+ * Description:
+ * Translate a property name (i.e. `memory_usage`) to the name of a MIG device reading
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Utilization is only listed for a MIG device if the driver reports it
+ */
+pub fn mig_reading_name(property: &str) -> Option<&'static str> {
+    match property {
+        "util" => Some("Utilization/Gpu"),
+        "mem_ctrl_util" => Some("Utilization/Memory"),
+        "memory_usage" => Some("FB Memory Usage/Used"),
+        "memory_total" => Some("FB Memory Usage/Total"),
+        "ecc_dbe_volatile" => Some("ECC Errors/Volatile/SRAM Uncorrectable"),
+        _ => None,
+    }
+}
+
+/**
+ * Name:
+ * find_mig_reading
+ *
+ * Description:
+ * Find a reading (i.e. `FB Memory Usage/Used`) of a MIG device using it's index
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Returns None if the device or reading cannot be found
+ */
+pub fn find_mig_reading(devices: &[MigDetails], index: u32, reading: &str) -> Option<String> {
+    devices
+        .iter()
+        .find(|device| device.index == Some(index))?
+        .readings
+        .iter()
+        .find(|(name, _)| name == reading)
+        .map(|(_, value)| value.clone())
+}
@@ -25,13 +25,17 @@ use glib::{
     subclass::InitializingObject, FromVariant, ParamSpec, Value,
 };
 use gtk::{
-    subclass::prelude::*, Button, CompositeTemplate, DropDown, Entry, ListBox, SpinButton,
-    StringList, TemplateChild,
+    subclass::prelude::*, Align, Button, CompositeTemplate, DropDown, Entry, Grid, Label, ListBox,
+    MenuButton, Orientation, Popover, SpinButton, StringList, TemplateChild,
 };
 use std::{cell::Cell, cell::RefCell, rc::Rc};
 
 // Modules
-use crate::gpu_page::GpuPage;
+use crate::{
+    display_style::{format_component, parse_colour, DisplayKind, DisplayStyle, DISPLAY_KINDS},
+    gpu_page::GpuPage,
+    property::PROPERTY_LIST,
+};
 
 /// Structure for storing a SettingsWindow object and any related information
 #[derive(Default)]
@@ -44,6 +48,247 @@ pub struct ParentContainer {
 pub struct ViewComponent {
     pub name: String,
     pub position: i32,
+    pub style: DisplayStyle,
+}
+
+/// Structure for storing the inputs choosing how a View item is displayed
+#[derive(Clone)]
+pub struct StyleInputs {
+    pub kind: DropDown,
+    pub options: MenuButton,
+    pub min: Entry,
+    pub max: Entry,
+    pub colour: Entry,
+}
+
+/**
+ * Name:
+ * ViewComponent
+ *
+ * Description:
+ * Trait shared by all View items
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ViewComponent {
+    /**
+     * Name:
+     * to_setting
+     *
+     * Description:
+     * Format this item as a viewcomponentconfig of the passed view
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn to_setting(&self, uuid: &str, view_title: &str) -> String {
+        format_component(
+            uuid,
+            view_title,
+            self.position as usize,
+            &self.name,
+            &self.style,
+        )
+    }
+}
+
+/**
+ * Name:
+ * StyleInputs
+ *
+ * Description:
+ * Trait shared by all style inputs
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl StyleInputs {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create inputs for the style of a View item, showing the passed style
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Options (min/max/colour) are kept in a popover, as they don't apply to plain text
+     */
+    pub fn new(index: usize, style: &DisplayStyle) -> StyleInputs {
+        // Create dropdown choice of style
+        let items: Vec<&str> = DISPLAY_KINDS.iter().map(|kind| kind.label()).collect();
+        let model: StringList = StringList::new(&items);
+        let kind: DropDown = DropDown::builder()
+            .name(&(String::from("style_input_") + index.to_string().as_str()))
+            .model(&model)
+            .build();
+        if let Some(selected) = DISPLAY_KINDS.iter().position(|item| *item == style.kind) {
+            kind.set_selected(selected as u32);
+        }
+
+        // Create option inputs
+        let min: Entry = Entry::builder()
+            .placeholder_text("Auto")
+            .text(&style.min.map(|value| value.to_string()).unwrap_or_default())
+            .input_purpose(gtk::InputPurpose::Number)
+            .build();
+        let max: Entry = Entry::builder()
+            .placeholder_text("Auto")
+            .text(&style.max.map(|value| value.to_string()).unwrap_or_default())
+            .input_purpose(gtk::InputPurpose::Number)
+            .build();
+        let colour: Entry = Entry::builder()
+            .placeholder_text("Default (i.e. #3584e4)")
+            .text(style.colour.as_deref().unwrap_or_default())
+            .build();
+
+        // Lay out option inputs
+        let grid: Grid = Grid::builder()
+            .orientation(Orientation::Vertical)
+            .row_spacing(6)
+            .column_spacing(12)
+            .build();
+        for (row, (title, input)) in [("Minimum", &min), ("Maximum", &max), ("Colour", &colour)]
+            .into_iter()
+            .enumerate()
+        {
+            let label: Label = Label::builder().label(title).halign(Align::Start).build();
+            grid.attach(&label, 0, row as i32, 1, 1);
+            grid.attach(input, 1, row as i32, 1, 1);
+        }
+        let popover: Popover = Popover::builder().child(&grid).build();
+        let options: MenuButton = MenuButton::builder()
+            .icon_name("emblem-system-symbolic")
+            .tooltip_text("Style Options")
+            .popover(&popover)
+            .sensitive(style.kind != DisplayKind::Text)
+            .build();
+
+        // Options don't apply to plain text
+        kind.connect_selected_notify(glib::clone!(@weak options => move |dropdown: &DropDown| {
+            options.set_sensitive(
+                DISPLAY_KINDS.get(dropdown.selected() as usize) != Some(&DisplayKind::Text),
+            );
+        }));
+
+        StyleInputs {
+            kind,
+            options,
+            min,
+            max,
+            colour,
+        }
+    }
+
+    /**
+     * Name:
+     * style
+     *
+     * Description:
+     * Style currently chosen by these inputs
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Invalid options are left unset (i.e. worked out from readings)
+     */
+    pub fn style(&self) -> DisplayStyle {
+        let kind: DisplayKind = DISPLAY_KINDS
+            .get(self.kind.selected() as usize)
+            .copied()
+            .unwrap_or_default();
+        if kind == DisplayKind::Text {
+            return DisplayStyle {
+                kind,
+                ..DisplayStyle::default()
+            };
+        }
+
+        // Parse an optional number
+        let number = |input: &Entry| -> Option<f64> {
+            let text: String = input.text().trim().to_owned();
+            match text.parse::<f64>() {
+                Ok(value) if value.is_finite() => Some(value),
+                _ => {
+                    if !text.is_empty() {
+                        println!("..Ignoring invalid style option: `{}`", text);
+                    }
+                    None
+                }
+            }
+        };
+
+        let colour_text: String = self.colour.text().trim().to_lowercase();
+        let colour: Option<String> = match parse_colour(&colour_text) {
+            Some(_) => Some(colour_text),
+            None => {
+                if !colour_text.is_empty() {
+                    println!("..Ignoring invalid style colour: `{}`", colour_text);
+                }
+                None
+            }
+        };
+
+        DisplayStyle {
+            kind,
+            min: number(&self.min),
+            max: number(&self.max),
+            colour,
+        }
+    }
+
+    /**
+     * Name:
+     * attach
+     *
+     * Description:
+     * Add these inputs (after the property dropdown) to the row of a View item
+     *
+     * Made:
+     * 01/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn attach(&self, row: &ActionRow, dropdown: &DropDown) {
+        let content: gtk::Box = gtk::Box::new(Orientation::Horizontal, 6);
+        dropdown.set_hexpand(true);
+        content.append(dropdown);
+        content.append(&self.kind);
+        content.append(&self.options);
+        row.set_child(Some(&content));
+    }
 }
 
 /// Object holding the State and any Template Children
@@ -55,6 +300,7 @@ pub struct ModificationWindow {
     pub view_components_list: Cell<Vec<ViewComponent>>,
     pub parent_window: Rc<RefCell<ParentContainer>>,
     pub dropdowns: Cell<Vec<DropDown>>,
+    pub style_inputs: Cell<Vec<StyleInputs>>,
 
     // Private
     uuid: OnceCell<String>,
@@ -431,6 +677,7 @@ impl ModificationWindow {
             // Get current components
             let mut current_components: Vec<ViewComponent> = self.view_components_list.take();
             let dropdowns: Vec<DropDown> = self.dropdowns.take();
+            let style_inputs: Vec<StyleInputs> = self.style_inputs.take();
             // println!("number of stored dropdowns: `{}`", dropdowns.len()); //TEST
             // Update list using current state of dropdowns
             for index in 0..current_components.len() {
@@ -442,36 +689,26 @@ impl ModificationWindow {
                 let current_dropdown_value: usize = current_dropdown.selected() as usize;
 
                 // From list of possible properties
-                //TODO: Update to use global list
-                let items: [&str; 8] = [
-                    "none",
-                    "util",
-                    "temp",
-                    "power_usage",
-                    "memory_usage",
-                    "memory_total",
-                    "mem_ctrl_util",
-                    "fan_speed",
-                ];
+                let items: Vec<&str> = PROPERTY_LIST
+                    .iter()
+                    .map(|description| description.name)
+                    .collect();
 
                 // Update stored name if required
                 if current_components[index].name != items[current_dropdown_value] {
                     current_components[index].name = items[current_dropdown_value].to_string();
                 }
+
+                // Update stored style
+                current_components[index].style = style_inputs[index].style();
             }
 
             // Create new viewcomponentconfigs
             let mut final_viewcomponentconfigs: Vec<String> = vec![];
             // For each selected property component
             for component in current_components {
-                //UUID:VIEW_TITLE:POSITION:name
-                let formatted_component: String = String::from(&uuid)
-                    + ":"
-                    + &new_view_title
-                    + ":"
-                    + &component.position.to_string()
-                    + ":"
-                    + &component.name;
+                //UUID:VIEW_TITLE:POSITION:name[:STYLE]
+                let formatted_component: String = component.to_setting(&uuid, &new_view_title);
 
                 // Add to final list
                 final_viewcomponentconfigs.push(formatted_component);
@@ -582,6 +819,7 @@ impl ModificationWindow {
                 // Get list current components
                 let mut current_components: Vec<ViewComponent> = self.view_components_list.take();
                 let dropdowns: Vec<DropDown> = self.dropdowns.take();
+                let style_inputs: Vec<StyleInputs> = self.style_inputs.take();
                 // println!("number of stored components: `{}`", current_components.len()); //TEST
                 // println!("number of stored dropdowns: `{}`", dropdowns.len()); //TEST
                 // Update list using current state of dropdowns
@@ -594,22 +832,18 @@ impl ModificationWindow {
                     let current_dropdown_value: usize = current_dropdown.selected() as usize;
 
                     // From list of possible properties
-                    //TODO: Update to use global list
-                    let items: [&str; 8] = [
-                        "none",
-                        "util",
-                        "temp",
-                        "power_usage",
-                        "memory_usage",
-                        "memory_total",
-                        "mem_ctrl_util",
-                        "fan_speed",
-                    ];
+                    let items: Vec<&str> = PROPERTY_LIST
+                        .iter()
+                        .map(|description| description.name)
+                        .collect();
 
                     // Update stored name if required
                     if current_components[index].name != items[current_dropdown_value] {
                         current_components[index].name = items[current_dropdown_value].to_string();
                     }
+
+                    // Update stored style
+                    current_components[index].style = style_inputs[index].style();
                 }
 
                 // Create list of old components
@@ -645,13 +879,8 @@ impl ModificationWindow {
                             // CORRECT NAME, TITLE CHANGE, POSITION CHANGE => STOP
                             (true, false, false) => {
                                 // Create new viewcomponentconfig
-                                let new_viewcomponentconfig: String = uuid.clone()
-                                    + ":"
-                                    + &new_view_title
-                                    + ":"
-                                    + &current_components[new_index].position.to_string()
-                                    + ":"
-                                    + &current_components[new_index].name;
+                                let new_viewcomponentconfig: String = current_components[new_index]
+                                    .to_setting(&uuid, &new_view_title);
 
                                 // Add to updated list of stored viewcomponentconfigs
                                 new_components_list.push(new_viewcomponentconfig);
@@ -662,13 +891,8 @@ impl ModificationWindow {
                             // CORRECT NAME, TITLE CHANGE, NO POSITION CHANGE => STOP
                             (true, false, true) => {
                                 // Create new viewcomponentconfig
-                                let new_viewcomponentconfig: String = uuid.clone()
-                                    + ":"
-                                    + &new_view_title
-                                    + ":"
-                                    + sub_items[2]
-                                    + ":"
-                                    + &current_components[new_index].name;
+                                let new_viewcomponentconfig: String = current_components[new_index]
+                                    .to_setting(&uuid, &new_view_title);
 
                                 // Add to updated list of stored viewcomponentconfigs
                                 new_components_list.push(new_viewcomponentconfig);
@@ -679,13 +903,8 @@ impl ModificationWindow {
                             // CORRECT NAME, NO TITLE CHANGE, POSITION CHANGE => STOP
                             (true, true, false) => {
                                 // Create new viewcomponentconfig
-                                let new_viewcomponentconfig: String = uuid.clone()
-                                    + ":"
-                                    + &old_view_title
-                                    + ":"
-                                    + &current_components[new_index].position.to_string()
-                                    + ":"
-                                    + &current_components[new_index].name;
+                                let new_viewcomponentconfig: String = current_components[new_index]
+                                    .to_setting(&uuid, &old_view_title);
 
                                 // Add to updated list of stored viewcomponentconfigs
                                 new_components_list.push(new_viewcomponentconfig);
@@ -694,10 +913,14 @@ impl ModificationWindow {
                                 break;
                             }
 
-                            // NO CHANGES => STOP
+                            // NO CHANGES (OTHER THAN STYLE) => STOP
                             (true, true, true) => {
+                                // Create new viewcomponentconfig, in case it's style changed
+                                let new_viewcomponentconfig: String = current_components[new_index]
+                                    .to_setting(&uuid, &old_view_title);
+
                                 // Add to updated list of stored viewcomponentconfigs
-                                new_components_list.push(old_components_list[old_index].clone());
+                                new_components_list.push(new_viewcomponentconfig);
                                 component_modified = true;
                                 // println!("NO CHANGES: component not modified.."); //TEST
                                 break;
@@ -711,13 +934,8 @@ impl ModificationWindow {
                     // Check if a new component
                     if !component_modified {
                         // Create new viewcomponentconfig
-                        let new_viewcomponentconfig: String = uuid.clone()
-                            + ":"
-                            + &old_view_title
-                            + ":"
-                            + &current_components[new_index].position.to_string()
-                            + ":"
-                            + &current_components[new_index].name;
+                        let new_viewcomponentconfig: String =
+                            current_components[new_index].to_setting(&uuid, &old_view_title);
 
                         // Add to updated list of stored viewcomponentconfigs
                         new_components_list.push(new_viewcomponentconfig);
@@ -756,6 +974,7 @@ impl ModificationWindow {
                 // Get current components
                 let mut current_components: Vec<ViewComponent> = self.view_components_list.take();
                 let dropdowns: Vec<DropDown> = self.dropdowns.take();
+                let style_inputs: Vec<StyleInputs> = self.style_inputs.take();
                 // println!("number of stored components: `{}`", current_components.len()); //TEST
                 // println!("number of stored dropdowns: `{}`", dropdowns.len()); //TEST
                 // Update list using current state of dropdowns
@@ -768,35 +987,25 @@ impl ModificationWindow {
                     let current_dropdown_value: usize = current_dropdown.selected() as usize;
 
                     // From list of possible properties
-                    //TODO: Update to use global list
-                    let items: [&str; 8] = [
-                        "none",
-                        "util",
-                        "temp",
-                        "power_usage",
-                        "memory_usage",
-                        "memory_total",
-                        "mem_ctrl_util",
-                        "fan_speed",
-                    ];
+                    let items: Vec<&str> = PROPERTY_LIST
+                        .iter()
+                        .map(|description| description.name)
+                        .collect();
 
                     // Update stored name if required
                     if current_components[index].name != items[current_dropdown_value] {
                         current_components[index].name = items[current_dropdown_value].to_string();
                     }
+
+                    // Update stored style
+                    current_components[index].style = style_inputs[index].style();
                 }
 
                 // Create new viewcomponentconfigs
                 // For each selected property component
                 for component in current_components {
-                    //UUID:VIEW_TITLE:POSITION:name
-                    let formatted_component: String = uuid.clone()
-                        + ":"
-                        + &new_view_title
-                        + ":"
-                        + &component.position.to_string()
-                        + ":"
-                        + &component.name;
+                    //UUID:VIEW_TITLE:POSITION:name[:STYLE]
+                    let formatted_component: String = component.to_setting(&uuid, &new_view_title);
 
                     // Add to list
                     stored_views_components.push(formatted_component);
@@ -976,21 +1185,23 @@ impl ModificationWindow {
 
             // Modify view component list
             components.remove(components.len() - 1);
+
+            // Update lists of inputs
+            let mut dropdowns: Vec<DropDown> = self.dropdowns.take();
+            dropdowns.truncate(components.len());
+            self.dropdowns.set(dropdowns);
+            let mut style_inputs: Vec<StyleInputs> = self.style_inputs.take();
+            style_inputs.truncate(components.len());
+            self.style_inputs.set(style_inputs);
         } else if new_amount > components.len() {
             // More than previous
             // println!(">"); //TEST
 
             // Create list of options
-            let items: [&str; 8] = [
-                "None",
-                "GPU Utilization",
-                "GPU Temperature",
-                "Power Usage",
-                "Memory Usage",
-                "Memory Total",
-                "Memory Controller Usage",
-                "Fan Speed",
-            ];
+            let items: Vec<&str> = PROPERTY_LIST
+                .iter()
+                .map(|description| description.label)
+                .collect();
             let model: StringList = StringList::new(&items);
 
             // Create dropdown choice
@@ -1015,8 +1226,12 @@ impl ModificationWindow {
                 .selectable(false)
                 .build();
 
-            // Add dropdown_input to row
-            row.set_child(Some(&dropdown_input));
+            // Create style inputs
+            let style_inputs: StyleInputs =
+                StyleInputs::new(components.len(), &DisplayStyle::default());
+
+            // Add dropdown_input & style inputs to row
+            style_inputs.attach(&row, &dropdown_input);
 
             // Add new item, needs defaults (i.e. None)
             let pos: i32 = components.len() as i32;
@@ -1027,6 +1242,7 @@ impl ModificationWindow {
             let new_item: ViewComponent = ViewComponent {
                 name: String::from("none"),
                 position: pos,
+                style: DisplayStyle::default(),
             };
 
             // Update list of dropdowns
@@ -1035,6 +1251,11 @@ impl ModificationWindow {
             // println!("new number of stored dropdowns: `{}`", dropdowns.len()); //TEST
             self.dropdowns.set(dropdowns);
 
+            // Update list of style inputs
+            let mut stored_style_inputs: Vec<StyleInputs> = self.style_inputs.take();
+            stored_style_inputs.push(style_inputs);
+            self.style_inputs.set(stored_style_inputs);
+
             // Modify view component list
             components.push(new_item);
         } // else if new_amount == components.len() {
@@ -29,8 +29,9 @@ use std::cell::RefMut;
 
 // Modules
 use crate::{
-    gpu_page::GpuPage, modificationwindow::imp::ParentContainer,
-    modificationwindow::imp::ViewComponent, APP_ID,
+    display_style::DisplayStyle, gpu_page::GpuPage, modificationwindow::imp::ParentContainer,
+    modificationwindow::imp::StyleInputs, modificationwindow::imp::ViewComponent,
+    property::find_property_index, property::PROPERTY_LIST, APP_ID,
 };
 
 // GObject wrapper for ModificationWindow
@@ -217,6 +218,7 @@ impl ModificationWindow {
 
         // Create final list of dropdowns
         let mut dropdowns: Vec<DropDown> = vec![];
+        let mut style_inputs: Vec<StyleInputs> = vec![];
 
         // println!("LETS GET LOOPIN"); //TEST
         // If list of components is not empty
@@ -229,27 +231,33 @@ impl ModificationWindow {
                 if sub_items[1] == self.property::<String>("old-view-title") {
                     // println!("View Component: {}", sub_items[3]); //TEST
 
+                    // Load display style, if one was saved
+                    let style: DisplayStyle = match sub_items.get(4) {
+                        Some(saved_style) => match DisplayStyle::from_setting(saved_style) {
+                            Ok(parsed) => parsed,
+                            Err(err) => {
+                                println!("..Ignoring saved display style: {}", err);
+                                DisplayStyle::default()
+                            }
+                        },
+                        None => DisplayStyle::default(),
+                    };
+
                     // Create new item
                     let new_item: ViewComponent = ViewComponent {
                         name: String::from(sub_items[3]),
                         position: sub_items[2]
                             .parse::<i32>()
                             .expect("Malformed gschema data.."),
+                        style: style.clone(),
                     };
                     final_components.push(new_item);
 
                     // Create list of options
-                    //TODO: Update to use global list
-                    let items: [&str; 8] = [
-                        "None",
-                        "GPU Utilization",
-                        "GPU Temperature",
-                        "Power Usage",
-                        "Memory Usage",
-                        "Memory Total",
-                        "Memory Controller Usage",
-                        "Fan Speed",
-                    ];
+                    let items: Vec<&str> = PROPERTY_LIST
+                        .iter()
+                        .map(|description| description.label)
+                        .collect();
                     let model: StringList = StringList::new(&items);
 
                     // Create dropdown choice
@@ -261,41 +269,9 @@ impl ModificationWindow {
                         .build();
 
                     // Set current selected option
-                    //TODO: Update to use global list
-                    match sub_items[3] {
-                        "none" => {
-                            dropdown_input.set_selected(0);
-                            // println!("`none` active"); //TEST
-                        }
-                        "util" => {
-                            dropdown_input.set_selected(1);
-                            // println!("`util` active"); //TEST
-                        }
-                        "temp" => {
-                            dropdown_input.set_selected(2);
-                            // println!("`temp` active"); //TEST
-                        }
-                        "power_usage" => {
-                            dropdown_input.set_selected(3);
-                            // println!("`power_usage` active"); //TEST
-                        }
-                        "memory_usage" => {
-                            dropdown_input.set_selected(4);
-                            // println!("`memory_usage` active"); //TEST
-                        }
-                        "memory_total" => {
-                            dropdown_input.set_selected(5);
-                            // println!("`memory_total` active"); //TEST
-                        }
-                        "mem_ctrl_util" => {
-                            dropdown_input.set_selected(6);
-                            // println!("`mem_ctrl_util` active"); //TEST
-                        }
-                        "fan_speed" => {
-                            dropdown_input.set_selected(7);
-                            // println!("`fan_speed` active"); //TEST
-                        }
-                        _ => panic!("..Unknown property in view config"),
+                    match find_property_index(sub_items[3]) {
+                        Some(index) => dropdown_input.set_selected(index as u32),
+                        None => panic!("..Unknown property in view config"),
                     }
 
                     // Create row to hold dropdown_input
@@ -311,11 +287,16 @@ impl ModificationWindow {
                         .selectable(false)
                         .build();
 
-                    // Add dropdown_input to row
-                    row.set_child(Some(&dropdown_input));
+                    // Create style inputs
+                    let new_style_inputs: StyleInputs =
+                        StyleInputs::new(final_components.len(), &style);
+
+                    // Add dropdown_input & style inputs to row
+                    new_style_inputs.attach(&row, &dropdown_input);
 
-                    // Add dropdown to list of dropdowns
+                    // Add dropdown & style inputs to lists of inputs
                     dropdowns.push(dropdown_input);
+                    style_inputs.push(new_style_inputs);
                     // println!("new number of stored dropdowns: `{}`", dropdowns.len()); //TEST
 
                     // Add row to ListBox
@@ -330,6 +311,7 @@ impl ModificationWindow {
 
         // Set dropdowns
         self.imp().dropdowns.set(dropdowns);
+        self.imp().style_inputs.set(style_inputs);
 
         // Get current number of view components before we get rid of this..
         let current_view_component_amount: f64 = final_components.len() as f64;
@@ -0,0 +1,1233 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Publish the readings of every GPU to an MQTT broker, with Home Assistant discovery
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A small MQTT 3.1.1 client (plain TCP, QoS 0-2), doesn't depend on GTK so also works headless.
+ * Each GPU is published as a single JSON state (`PREFIX/UUID/state`), with a retained discovery
+ * config for each property so it shows up in Home Assistant as a sensor of the GPU's device.
+ * <https://docs.oasis-open.org/mqtt/mqtt/v3.1.1/mqtt-v3.1.1.html>
+ * <https://www.home-assistant.io/integrations/mqtt/#mqtt-discovery>
+ */
+// Imports
+use std::{
+    fs,
+    io::{Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// Modules
+use crate::{
+    sample_log::escape_json,
+    sampler::{GpuSample, Metric},
+};
+
+/// Port used if the broker URL doesn't give one
+pub const DEFAULT_PORT: u16 = 1883;
+/// Prefix of every topic if none is set
+pub const DEFAULT_TOPIC_PREFIX: &str = "nvidia-monitor";
+/// Client ID of every connection, followed by the host & process (so clients never replace each other)
+pub const CLIENT_ID_PREFIX: &str = "gtk4-nvidia-monitor-rust";
+/// Files the name of this machine is read from, in order
+const HOST_ID_FILES: [&str; 3] = [
+    "/proc/sys/kernel/hostname",
+    "/etc/hostname",
+    "/etc/machine-id",
+];
+/// Prefix Home Assistant listens for discovery configs on
+pub const DISCOVERY_PREFIX: &str = "homeassistant";
+/// Longest time to wait for the broker to connect or acknowledge
+const ACK_TIMEOUT: Duration = Duration::from_secs(10);
+/// First wait before reconnecting
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Longest wait before reconnecting
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// Batches of readings waiting to be published, newer batches are dropped once full
+pub const WORKER_QUEUE_SIZE: usize = 4;
+
+// Packet types (upper 4 bits of the first byte)
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+const PUBACK: u8 = 0x40;
+const PUBREC: u8 = 0x50;
+const PUBREL: u8 = 0x62;
+const PUBCOMP: u8 = 0x70;
+const DISCONNECT: u8 = 0xE0;
+
+/// Structure for everything needed to connect & publish
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MqttConfig {
+    pub broker: String,
+    pub username: String,
+    pub password: String,
+    pub topic_prefix: String,
+    pub qos: u8,
+    pub host: String,
+    pub client_id: String,
+    pub keep_alive: u16,
+}
+
+/**
+ * Name:
+ * MqttConfig
+ *
+ * Description:
+ * Default config, a broker on this machine without credentials
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The client ID includes the process, as the app & headless mode may both publish from the same machine
+ */
+impl Default for MqttConfig {
+    fn default() -> Self {
+        let host: String = host_id();
+
+        MqttConfig {
+            broker: format!("mqtt://localhost:{}", DEFAULT_PORT),
+            username: String::new(),
+            password: String::new(),
+            topic_prefix: String::from(DEFAULT_TOPIC_PREFIX),
+            qos: 0,
+            client_id: format!("{}-{}-{}", CLIENT_ID_PREFIX, host, std::process::id()),
+            host,
+            keep_alive: 60,
+        }
+    }
+}
+
+/// A single message to publish
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub topic: String,
+    pub payload: String,
+    pub retain: bool,
+}
+
+/**
+ * Name:
+ * host_id
+ *
+ * Description:
+ * Name of this machine, usable in a topic (i.e. `workstation`)
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The hostname if set, otherwise the machine ID
+ */
+pub fn host_id() -> String {
+    HOST_ID_FILES
+        .iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .map(|contents| node_id(contents.trim()))
+        .find(|id| !id.is_empty())
+        .unwrap_or_else(|| String::from("localhost"))
+}
+
+/**
+ * Name:
+ * parse_broker_url
+ *
+ * Description:
+ * Find the address (i.e. `localhost:1883`) of a broker from it's URL (i.e. `mqtt://localhost`)
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only plain TCP is supported (`mqtt://` or `tcp://`, or no scheme at all)
+ */
+pub fn parse_broker_url(url: &str) -> Result<String, String> {
+    let url: &str = url.trim();
+    let address: &str = match url.split_once("://") {
+        Some(("mqtt" | "tcp", address)) => address,
+        Some((scheme, _)) => return Err(format!("Unsupported broker scheme `{}`..", scheme)),
+        None => url,
+    };
+    let address: &str = address.trim_end_matches('/');
+    if address.is_empty() {
+        return Err(String::from("No broker address given.."));
+    }
+
+    // Add default port, unless given (IPv6 addresses are in brackets)
+    match address.rsplit_once(':') {
+        Some((host, port)) if !host.ends_with(':') && !port.ends_with(']') => {
+            match port.parse::<u16>() {
+                Ok(_) => Ok(String::from(address)),
+                Err(_) => Err(format!("Invalid broker port `{}`..", port)),
+            }
+        }
+        _ => Ok(format!("{}:{}", address, DEFAULT_PORT)),
+    }
+}
+
+/**
+ * Name:
+ * encode_remaining_length
+ *
+ * Description:
+ * Encode the length of the rest of a packet (7 bits per byte, lowest first)
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn encode_remaining_length(mut length: usize) -> Vec<u8> {
+    let mut bytes: Vec<u8> = vec![];
+    loop {
+        let mut byte: u8 = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        bytes.push(byte);
+        if length == 0 {
+            return bytes;
+        }
+    }
+}
+
+/**
+ * Name:
+ * encode_string
+ *
+ * Description:
+ * Add a length-prefixed string to a packet
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn encode_string(packet: &mut Vec<u8>, text: &str) {
+    packet.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    packet.extend_from_slice(text.as_bytes());
+}
+
+/**
+ * Name:
+ * finish_packet
+ *
+ * Description:
+ * Put the fixed header (type & remaining length) in front of the rest of a packet
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn finish_packet(header: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut packet: Vec<u8> = vec![header];
+    packet.extend(encode_remaining_length(body.len()));
+    packet.extend(body);
+
+    packet
+}
+
+/**
+ * Name:
+ * encode_connect
+ *
+ * Description:
+ * Encode a CONNECT packet, with `PREFIX/status` set to `offline` (retained) if we disappear
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Always a clean session, nothing is subscribed to
+ */
+pub fn encode_connect(config: &MqttConfig) -> Vec<u8> {
+    // Flags: clean session, retained will at the chosen QoS, then any credentials
+    let mut flags: u8 = 0x02 | 0x04 | 0x20 | (config.qos.min(2) << 3);
+    if !config.username.is_empty() {
+        flags |= 0x80;
+        if !config.password.is_empty() {
+            flags |= 0x40;
+        }
+    }
+
+    let mut body: Vec<u8> = vec![];
+    encode_string(&mut body, "MQTT");
+    body.push(4);
+    body.push(flags);
+    body.extend_from_slice(&config.keep_alive.to_be_bytes());
+
+    encode_string(&mut body, &config.client_id);
+    encode_string(&mut body, &status_topic(config));
+    encode_string(&mut body, "offline");
+    if !config.username.is_empty() {
+        encode_string(&mut body, &config.username);
+        if !config.password.is_empty() {
+            encode_string(&mut body, &config.password);
+        }
+    }
+
+    finish_packet(CONNECT, body)
+}
+
+/**
+ * Name:
+ * encode_publish
+ *
+ * Description:
+ * Encode a PUBLISH packet, the packet id is only used for QoS 1 & 2
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn encode_publish(message: &Message, qos: u8, packet_id: u16) -> Vec<u8> {
+    let qos: u8 = qos.min(2);
+    let header: u8 = PUBLISH | (qos << 1) | message.retain as u8;
+
+    let mut body: Vec<u8> = vec![];
+    encode_string(&mut body, &message.topic);
+    if qos > 0 {
+        body.extend_from_slice(&packet_id.to_be_bytes());
+    }
+    body.extend_from_slice(message.payload.as_bytes());
+
+    finish_packet(header, body)
+}
+
+/**
+ * Name:
+ * read_packet
+ *
+ * Description:
+ * Read a whole packet, returning it's first byte (type & flags) and the rest of it
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn read_packet(stream: &mut impl Read) -> Result<(u8, Vec<u8>), String> {
+    let mut byte: [u8; 1] = [0];
+    stream
+        .read_exact(&mut byte)
+        .map_err(|err| format!("Cannot read from broker: {}", err))?;
+    let header: u8 = byte[0];
+
+    // Remaining length
+    let mut length: usize = 0;
+    for shift in [0, 7, 14, 21] {
+        stream
+            .read_exact(&mut byte)
+            .map_err(|err| format!("Cannot read from broker: {}", err))?;
+        length |= ((byte[0] & 0x7F) as usize) << shift;
+        if byte[0] & 0x80 == 0 {
+            let mut body: Vec<u8> = vec![0; length];
+            stream
+                .read_exact(&mut body)
+                .map_err(|err| format!("Cannot read from broker: {}", err))?;
+
+            return Ok((header, body));
+        }
+    }
+
+    Err(String::from("Invalid packet length from broker.."))
+}
+
+/**
+ * Name:
+ * status_topic
+ *
+ * Description:
+ * Topic showing if this machine is connected (`online` or `offline`), i.e. `PREFIX/HOST/status`
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * One per machine, so a machine going offline only marks it's own GPUs as unavailable
+ */
+pub fn status_topic(config: &MqttConfig) -> String {
+    format!("{}/{}/status", config.topic_prefix, config.host)
+}
+
+/**
+ * Name:
+ * state_topic
+ *
+ * Description:
+ * Topic the readings of a GPU are published to
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn state_topic(config: &MqttConfig, uuid: &str) -> String {
+    format!("{}/{}/state", config.topic_prefix, node_id(uuid))
+}
+
+/**
+ * Name:
+ * node_id
+ *
+ * Description:
+ * A GPU's UUID with anything not allowed in a topic/discovery id replaced (i.e. `GPU-1a2b3c4d`)
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Home Assistant only allows letters, numbers, `_` and `-`
+ */
+pub fn node_id(uuid: &str) -> String {
+    uuid.chars()
+        .map(
+            |c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                true => c,
+                false => '_',
+            },
+        )
+        .collect()
+}
+
+/**
+ * Name:
+ * device_class
+ *
+ * Description:
+ * Home Assistant device class of a property, from it's unit
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Percentages & counts have no device class
+ */
+pub fn device_class(metric: &Metric) -> Option<&'static str> {
+    match metric.unit {
+        "°C" => Some("temperature"),
+        "W" => Some("power"),
+        "MHz" => Some("frequency"),
+        "MiB" => Some("data_size"),
+        _ => None,
+    }
+}
+
+/**
+ * Name:
+ * discovery_messages
+ *
+ * Description:
+ * Retained discovery config of each property of a GPU, so each shows up as a sensor
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Every sensor belongs to a device for the GPU, and reads it's value from the GPU's JSON state
+ */
+pub fn discovery_messages(config: &MqttConfig, sample: &GpuSample) -> Vec<Message> {
+    let node: String = node_id(&sample.uuid);
+    let device: String = format!(
+        "{{\"identifiers\":[{}],\"name\":{},\"model\":{},\"manufacturer\":\"NVIDIA\"}}",
+        escape_json(&sample.uuid),
+        escape_json(&sample.name),
+        escape_json(&sample.name)
+    );
+
+    sample
+        .readings
+        .iter()
+        .map(|reading| {
+            let metric: &Metric = reading.metric;
+            let mut fields: Vec<String> = vec![
+                format!("\"name\":{}", escape_json(metric.label)),
+                format!(
+                    "\"unique_id\":{}",
+                    escape_json(&format!("{}_{}", node, metric.name))
+                ),
+                format!(
+                    "\"state_topic\":{}",
+                    escape_json(&state_topic(config, &sample.uuid))
+                ),
+                format!(
+                    "\"value_template\":{}",
+                    escape_json(&format!("{{{{ value_json.{} }}}}", metric.name))
+                ),
+                format!(
+                    "\"availability_topic\":{}",
+                    escape_json(&status_topic(config))
+                ),
+                String::from("\"state_class\":\"measurement\""),
+            ];
+            if !metric.unit.is_empty() {
+                fields.push(format!(
+                    "\"unit_of_measurement\":{}",
+                    escape_json(metric.unit)
+                ));
+            }
+            if let Some(class) = device_class(metric) {
+                fields.push(format!("\"device_class\":{}", escape_json(class)));
+            }
+            fields.push(format!("\"device\":{}", device));
+
+            Message {
+                topic: format!(
+                    "{}/sensor/{}/{}/config",
+                    DISCOVERY_PREFIX, node, metric.name
+                ),
+                payload: format!("{{{}}}", fields.join(",")),
+                retain: true,
+            }
+        })
+        .collect()
+}
+
+/**
+ * Name:
+ * state_message
+ *
+ * Description:
+ * Every reading of a GPU as a single JSON object (i.e. `{"temp":52,"util":null}`)
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Values that couldn't be read are `null`, which Home Assistant shows as unknown
+ */
+pub fn state_message(config: &MqttConfig, sample: &GpuSample) -> Message {
+    let values: Vec<String> = sample
+        .readings
+        .iter()
+        .map(|reading| {
+            format!(
+                "{}:{}",
+                escape_json(reading.metric.name),
+                match reading.value {
+                    Some(value) => value.to_string(),
+                    None => String::from("null"),
+                }
+            )
+        })
+        .collect();
+
+    Message {
+        topic: state_topic(config, &sample.uuid),
+        payload: format!("{{{}}}", values.join(",")),
+        retain: false,
+    }
+}
+
+/// Structure for a connection to a broker
+#[derive(Debug)]
+pub struct MqttClient {
+    stream: TcpStream,
+    qos: u8,
+    next_packet_id: u16,
+}
+
+/**
+ * Name:
+ * MqttClient
+ *
+ * Description:
+ * Trait shared by all MqttClient objects
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl MqttClient {
+    /**
+     * Name:
+     * connect
+     *
+     * Description:
+     * Connect (and log in) to the broker, then mark us as `online`
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn connect(config: &MqttConfig) -> Result<Self, String> {
+        let address: String = parse_broker_url(&config.broker)?;
+
+        // Open connection to the first address that works
+        let mut stream: Option<TcpStream> = None;
+        let mut last_error: String = format!("Cannot find broker `{}`..", address);
+        for socket_address in address
+            .to_socket_addrs()
+            .map_err(|err| format!("Cannot find broker `{}`: {}", address, err))?
+        {
+            match TcpStream::connect_timeout(&socket_address, ACK_TIMEOUT) {
+                Ok(valid_stream) => {
+                    stream = Some(valid_stream);
+                    break;
+                }
+                Err(err) => last_error = format!("Cannot connect to `{}`: {}", address, err),
+            }
+        }
+        let mut stream: TcpStream = stream.ok_or(last_error)?;
+        stream
+            .set_read_timeout(Some(ACK_TIMEOUT))
+            .and_then(|_| stream.set_write_timeout(Some(ACK_TIMEOUT)))
+            .map_err(|err| err.to_string())?;
+
+        // Log in
+        stream
+            .write_all(&encode_connect(config))
+            .map_err(|err| format!("Cannot write to broker: {}", err))?;
+        match read_packet(&mut stream)? {
+            (CONNACK, body) if body.len() == 2 => match body[1] {
+                0 => {}
+                1 => {
+                    return Err(String::from(
+                        "Broker refused, unsupported protocol version..",
+                    ))
+                }
+                2 => return Err(String::from("Broker refused, client id rejected..")),
+                3 => return Err(String::from("Broker refused, server unavailable..")),
+                4 => return Err(String::from("Broker refused, bad username or password..")),
+                5 => return Err(String::from("Broker refused, not authorised..")),
+                code => return Err(format!("Broker refused, code `{}`..", code)),
+            },
+            (header, _) => return Err(format!("Unexpected packet `{:#x}` from broker..", header)),
+        }
+
+        let mut client: MqttClient = MqttClient {
+            stream,
+            qos: config.qos.min(2),
+            next_packet_id: 1,
+        };
+        client.publish(&Message {
+            topic: status_topic(config),
+            payload: String::from("online"),
+            retain: true,
+        })?;
+
+        Ok(client)
+    }
+
+    /**
+     * Name:
+     * publish
+     *
+     * Description:
+     * Publish a message, waiting for the broker to acknowledge it (QoS 1 & 2)
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn publish(&mut self, message: &Message) -> Result<(), String> {
+        let packet_id: u16 = self.next_packet_id;
+        self.next_packet_id = self.next_packet_id.checked_add(1).unwrap_or(1);
+
+        self.write(&encode_publish(message, self.qos, packet_id))?;
+        match self.qos {
+            0 => Ok(()),
+            1 => self.expect_ack(PUBACK, packet_id),
+            _ => {
+                self.expect_ack(PUBREC, packet_id)?;
+                self.write(&finish_packet(PUBREL, packet_id.to_be_bytes().to_vec()))?;
+                self.expect_ack(PUBCOMP, packet_id)
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * disconnect
+     *
+     * Description:
+     * Mark us as `offline` then disconnect cleanly
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The broker doesn't send the will after a clean disconnect
+     */
+    pub fn disconnect(mut self, config: &MqttConfig) -> Result<(), String> {
+        self.publish(&Message {
+            topic: status_topic(config),
+            payload: String::from("offline"),
+            retain: true,
+        })?;
+
+        self.write(&[DISCONNECT, 0])
+    }
+
+    /**
+     * Name:
+     * write
+     *
+     * Description:
+     * Write a packet to the broker
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn write(&mut self, packet: &[u8]) -> Result<(), String> {
+        self.stream
+            .write_all(packet)
+            .map_err(|err| format!("Cannot write to broker: {}", err))
+    }
+
+    /**
+     * Name:
+     * expect_ack
+     *
+     * Description:
+     * Wait for the broker to acknowledge a packet
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Nothing is subscribed to, so anything else is unexpected
+     */
+    fn expect_ack(&mut self, ack: u8, packet_id: u16) -> Result<(), String> {
+        match read_packet(&mut self.stream)? {
+            (header, body) if header == ack && body == packet_id.to_be_bytes() => Ok(()),
+            (header, _) => Err(format!("Unexpected packet `{:#x}` from broker..", header)),
+        }
+    }
+}
+
+/// Structure for the time to wait between attempts to reconnect, doubling each time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+/**
+ * Name:
+ * Backoff
+ *
+ * Description:
+ * Trait shared by all Backoff objects
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Backoff {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a backoff, starting at `initial` & never waiting longer than `max`
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Backoff {
+            initial,
+            max,
+            next: initial,
+        }
+    }
+
+    /**
+     * Name:
+     * next_delay
+     *
+     * Description:
+     * Time to wait before the next attempt
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn next_delay(&mut self) -> Duration {
+        let delay: Duration = self.next;
+        self.next = (self.next * 2).min(self.max);
+
+        delay
+    }
+
+    /**
+     * Name:
+     * reset
+     *
+     * Description:
+     * Start again from the initial wait (i.e. after connecting)
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn reset(&mut self) {
+        self.next = self.initial;
+    }
+}
+
+/**
+ * Name:
+ * Backoff
+ *
+ * Description:
+ * Default backoff, from 1 second up to 5 minutes
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff::new(INITIAL_BACKOFF, MAX_BACKOFF)
+    }
+}
+
+/// Structure for publishing samples, reconnecting as needed
+#[derive(Debug)]
+pub struct MqttPublisher {
+    config: MqttConfig,
+    client: Option<MqttClient>,
+    backoff: Backoff,
+    retry_at: Option<Instant>,
+    announced: Vec<String>,
+}
+
+/**
+ * Name:
+ * MqttPublisher
+ *
+ * Description:
+ * Trait shared by all MqttPublisher objects
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl MqttPublisher {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a publisher, nothing is connected until the first samples are published
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(config: MqttConfig, backoff: Backoff) -> Self {
+        MqttPublisher {
+            config,
+            client: None,
+            backoff,
+            retry_at: None,
+            announced: vec![],
+        }
+    }
+
+    /**
+     * Name:
+     * is_connected
+     *
+     * Description:
+     * Check if connected to the broker
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_connected(&self) -> bool {
+        self.client.is_some()
+    }
+
+    /**
+     * Name:
+     * publish_samples
+     *
+     * Description:
+     * Publish the state of each GPU (and it's discovery config the first time), returns the number of messages
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Samples are dropped while waiting to reconnect. Discovery configs are sent again after reconnecting
+     */
+    pub fn publish_samples(
+        &mut self,
+        samples: &[GpuSample],
+        now: Instant,
+    ) -> Result<usize, String> {
+        // Connect if needed (unless still waiting)
+        if self.client.is_none() {
+            if matches!(self.retry_at, Some(retry_at) if now < retry_at) {
+                return Ok(0);
+            }
+            match MqttClient::connect(&self.config) {
+                Ok(client) => {
+                    self.client = Some(client);
+                    self.backoff.reset();
+                    self.retry_at = None;
+                    self.announced.clear();
+                }
+                Err(err) => {
+                    self.retry_at = Some(now + self.backoff.next_delay());
+                    return Err(err);
+                }
+            }
+        }
+
+        // Collect messages
+        let mut messages: Vec<Message> = vec![];
+        for sample in samples {
+            if !self.announced.contains(&sample.uuid) {
+                messages.extend(discovery_messages(&self.config, sample));
+            }
+            messages.push(state_message(&self.config, sample));
+        }
+
+        // Publish, dropping the connection on any failure
+        if let Some(client) = self.client.as_mut() {
+            for message in messages.iter() {
+                if let Err(err) = client.publish(message) {
+                    self.client = None;
+                    self.retry_at = Some(now + self.backoff.next_delay());
+                    return Err(err);
+                }
+            }
+        }
+        for sample in samples {
+            if !self.announced.contains(&sample.uuid) {
+                self.announced.push(sample.uuid.clone());
+            }
+        }
+
+        Ok(messages.len())
+    }
+
+    /**
+     * Name:
+     * disconnect
+     *
+     * Description:
+     * Disconnect cleanly, if connected
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn disconnect(&mut self) {
+        if let Some(client) = self.client.take() {
+            if let Err(err) = client.disconnect(&self.config) {
+                eprintln!(
+                    "..Attempt to disconnect from broker failed, returning: {}",
+                    err
+                );
+            }
+        }
+    }
+}
+
+/// Structure for a publisher running in the background, so a slow broker never blocks the caller
+#[derive(Debug)]
+pub struct MqttWorker {
+    sender: Option<SyncSender<Vec<GpuSample>>>,
+    stopping: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/**
+ * Name:
+ * MqttWorker
+ *
+ * Description:
+ * Trait shared by all MqttWorker objects
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl MqttWorker {
+    /**
+     * Name:
+     * spawn
+     *
+     * Description:
+     * Start publishing in the background, after any previous worker has disconnected
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Waiting for the previous worker happens on the new worker's thread, never the caller's.
+     * Otherwise both would share a client ID, and it's `offline` status could follow our `online`
+     */
+    pub fn spawn(config: MqttConfig, previous: Option<MqttWorker>) -> Self {
+        let (sender, receiver): (SyncSender<Vec<GpuSample>>, Receiver<Vec<GpuSample>>) =
+            mpsc::sync_channel(WORKER_QUEUE_SIZE);
+        let stopping: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let previous_thread: Option<JoinHandle<()>> =
+            previous.and_then(|mut previous| previous.stop());
+
+        let thread_stopping: Arc<AtomicBool> = stopping.clone();
+        let thread: JoinHandle<()> = thread::spawn(move || {
+            if let Some(previous_thread) = previous_thread {
+                let _ = previous_thread.join();
+            }
+
+            let mut publisher: MqttPublisher = MqttPublisher::new(config, Backoff::default());
+            let mut connected: bool = false;
+            for samples in receiver {
+                // Anything still queued once stopped is out of date
+                if thread_stopping.load(Ordering::Relaxed) {
+                    break;
+                }
+                if let Err(err) = publisher.publish_samples(&samples, Instant::now()) {
+                    eprintln!("..Attempt to publish to broker failed, returning: {}", err);
+                }
+                if publisher.is_connected() != connected {
+                    connected = publisher.is_connected();
+                    eprintln!("..MQTT broker connected: {}", connected);
+                }
+            }
+            publisher.disconnect();
+        });
+
+        MqttWorker {
+            sender: Some(sender),
+            stopping,
+            thread: Some(thread),
+        }
+    }
+
+    /**
+     * Name:
+     * send
+     *
+     * Description:
+     * Queue samples to be published, returning false if they were dropped
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Never blocks, samples are dropped if the broker can't keep up
+     */
+    pub fn send(&self, samples: Vec<GpuSample>) -> bool {
+        match self.sender.as_ref().map(|sender| sender.try_send(samples)) {
+            Some(Ok(_)) => true,
+            Some(Err(TrySendError::Full(_))) => {
+                eprintln!("..MQTT broker is busy, dropping readings");
+                false
+            }
+            Some(Err(TrySendError::Disconnected(_))) | None => false,
+        }
+    }
+
+    /**
+     * Name:
+     * stop
+     *
+     * Description:
+     * Tell the thread to disconnect instead of publishing anything still queued
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Returns the thread (if not already stopped), which finishes any publish in progress first
+     */
+    fn stop(&mut self) -> Option<JoinHandle<()>> {
+        self.stopping.store(true, Ordering::Relaxed);
+        self.sender.take();
+        self.thread.take()
+    }
+}
+
+/**
+ * Name:
+ * MqttWorker
+ *
+ * Description:
+ * Stop & disconnect in the background when dropped
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Never joins, so dropping on the GTK thread can't block the UI
+ */
+impl Drop for MqttWorker {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,348 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Clock offsets (overclocking) for all performance levels, set using nvidia-settings
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The valid range of an offset is given when querying it, i.e.
+ * `The valid values for 'GPUGraphicsClockOffsetAllPerformanceLevels' are in the range -200 - 1000 (inclusive).`
+ */
+// Modules
+use crate::command::CommandRunner;
+
+/// The clock offsets that can be changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockOffset {
+    Graphics,
+    Memory,
+}
+
+/// Structure for storing the (inclusive) range of values an offset accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetRange {
+    pub min: i32,
+    pub max: i32,
+}
+
+/// Structure for storing a requested offset and the range it must be within
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffsetRequest {
+    pub offset: ClockOffset,
+    pub value: i32,
+    pub range: OffsetRange,
+}
+
+/**
+ * Name:
+ * ClockOffset
+ *
+ * Description:
+ * Trait shared by all clock offsets
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ClockOffset {
+    /**
+     * Name:
+     * attribute
+     *
+     * Description:
+     * The nvidia-settings attribute for this offset
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn attribute(&self) -> &'static str {
+        match self {
+            ClockOffset::Graphics => "GPUGraphicsClockOffsetAllPerformanceLevels",
+            ClockOffset::Memory => "GPUMemoryTransferRateOffsetAllPerformanceLevels",
+        }
+    }
+
+    /**
+     * Name:
+     * label
+     *
+     * Description:
+     * Human readable name of this offset
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn label(&self) -> &'static str {
+        match self {
+            ClockOffset::Graphics => "Graphics Clock Offset",
+            ClockOffset::Memory => "Memory Transfer Rate Offset",
+        }
+    }
+}
+
+/**
+ * Name:
+ * OffsetRange
+ *
+ * Description:
+ * Trait shared by all offset ranges
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl OffsetRange {
+    /**
+     * Name:
+     * contains
+     *
+     * Description:
+     * Check if a value is within the range
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn contains(&self, value: i32) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/**
+ * Name:
+ * parse_offset_range
+ *
+ * Description:
+ * Find the valid range in the output of `nvidia-settings -q [gpu:0]/<offset> -d`
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Bounds can be negative, so the range is split on " - " (with spaces) rather than "-"
+ */
+pub fn parse_offset_range(output: &str) -> Result<OffsetRange, String> {
+    for line in output.lines() {
+        if let Some(start) = line.find("in the range") {
+            let range: &str = line[start + "in the range".len()..]
+                .trim()
+                .trim_end_matches('.')
+                .trim_end_matches("(inclusive)")
+                .trim();
+
+            return match range.split_once(" - ") {
+                Some((min, max)) => match (min.trim().parse::<i32>(), max.trim().parse::<i32>()) {
+                    (Ok(min), Ok(max)) if min <= max => Ok(OffsetRange { min, max }),
+                    _ => Err(format!("Invalid offset range `{}`..", range)),
+                },
+                None => Err(format!("Invalid offset range `{}`..", range)),
+            };
+        }
+    }
+
+    Err(String::from("No offset range found.."))
+}
+
+/**
+ * Name:
+ * parse_offset_value
+ *
+ * Description:
+ * Find the current value in the output of `nvidia-settings -q [gpu:0]/<offset> -d`
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * i.e. `Attribute 'GPUGraphicsClockOffsetAllPerformanceLevels' (host:0[gpu:0]): 150.`
+ */
+pub fn parse_offset_value(output: &str) -> Result<i32, String> {
+    for line in output.lines() {
+        if line.trim_start().starts_with("Attribute") {
+            if let Some((_, value)) = line.rsplit_once("):") {
+                return value
+                    .trim()
+                    .trim_end_matches('.')
+                    .parse::<i32>()
+                    .map_err(|_| format!("Invalid offset value `{}`..", value.trim()));
+            }
+        }
+    }
+
+    Err(String::from("No offset value found.."))
+}
+
+/**
+ * Name:
+ * create_offset_query
+ *
+ * Description:
+ * Create the nvidia-settings command for querying an offset (and it's range)
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn create_offset_query(gpu_index: u32, offset: ClockOffset) -> Vec<String> {
+    vec![
+        String::from("nvidia-settings"),
+        String::from("-q"),
+        format!("[gpu:{}]/{}", gpu_index, offset.attribute()),
+        String::from("-d"),
+    ]
+}
+
+/**
+ * Name:
+ * create_offset_command
+ *
+ * Description:
+ * Create the nvidia-settings command for setting an offset
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn create_offset_command(gpu_index: u32, offset: ClockOffset, value: i32) -> Vec<String> {
+    vec![
+        String::from("nvidia-settings"),
+        String::from("-a"),
+        format!("[gpu:{}]/{}={}", gpu_index, offset.attribute(), value),
+    ]
+}
+
+/**
+ * Name:
+ * plan_offsets
+ *
+ * Description:
+ * Validate every requested offset, then create the commands needed to set them
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Nothing is returned if any value is outside of it's range, so nothing is half-applied
+ */
+pub fn plan_offsets(
+    gpu_index: u32,
+    requests: &[OffsetRequest],
+) -> Result<Vec<Vec<String>>, String> {
+    // Check everything first
+    for request in requests {
+        if !request.range.contains(request.value) {
+            return Err(format!(
+                "{} of {} is outside of the range {} to {}..",
+                request.offset.label(),
+                request.value,
+                request.range.min,
+                request.range.max
+            ));
+        }
+    }
+
+    Ok(requests
+        .iter()
+        .map(|request| create_offset_command(gpu_index, request.offset, request.value))
+        .collect())
+}
+
+/**
+ * Name:
+ * apply_commands
+ *
+ * Description:
+ * Run a list of planned commands, stopping at the first failure
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn apply_commands(runner: &dyn CommandRunner, commands: &[Vec<String>]) -> Result<(), String> {
+    for command in commands {
+        runner.run(command)?;
+    }
+
+    Ok(())
+}
+
+/**
+ * Name:
+ * format_command
+ *
+ * Description:
+ * Format a command for showing to the user (i.e. `nvidia-settings -a [gpu:0]/...=100`)
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn format_command(command: &[String]) -> String {
+    command.join(" ")
+}
@@ -0,0 +1,351 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * imp.rs
+ *
+ * Description:
+ * Implementation of our custom GObject class (OverclockWindow)
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+// Imports
+use adwaita::{gio, glib, prelude::*, subclass::prelude::*, ActionRow};
+use gio::Settings;
+use glib::{
+    once_cell::sync::Lazy, once_cell::sync::OnceCell, signal::Inhibit,
+    subclass::InitializingObject, FromVariant, ParamSpec, Value,
+};
+use gtk::{subclass::prelude::*, Button, CompositeTemplate, Label, SpinButton, TemplateChild};
+use std::{cell::Cell, cell::RefCell, rc::Rc};
+
+// Modules
+use crate::{gpu_page::GpuPage, overclock::OffsetRange};
+
+/// Structure for storing a GpuPage object and any related information
+#[derive(Default)]
+pub struct ParentContainer {
+    pub window: Option<GpuPage>,
+}
+
+/// Object holding the State and any Template Children
+#[derive(CompositeTemplate, Default)]
+#[template(resource = "/overclock-window.ui")]
+pub struct OverclockWindow {
+    // Public
+    pub settings: OnceCell<Settings>,
+    pub parent_window: Rc<RefCell<ParentContainer>>,
+    pub gpu_index: Cell<u32>,
+    pub graphics_range: Cell<Option<OffsetRange>>,
+    pub memory_range: Cell<Option<OffsetRange>>,
+
+    // Private
+    uuid: OnceCell<String>,
+
+    // Template Children
+    #[template_child]
+    pub graphics_offset_row: TemplateChild<ActionRow>,
+    #[template_child]
+    pub graphics_offset_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub memory_offset_row: TemplateChild<ActionRow>,
+    #[template_child]
+    pub memory_offset_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub commands_label: TemplateChild<Label>,
+    #[template_child]
+    pub overclock_apply_button: TemplateChild<Button>,
+    #[template_child]
+    pub overclock_cancel_button: TemplateChild<Button>,
+    #[template_child]
+    pub overclock_reset_button: TemplateChild<Button>,
+}
+
+/// The central trait for subclassing a GObject
+#[glib::object_subclass]
+impl ObjectSubclass for OverclockWindow {
+    // `NAME` needs to match `class` attribute of template
+    const NAME: &'static str = "NvidiaExtensionOverclockWindow";
+    type Type = super::OverclockWindow;
+    type ParentType = gtk::ApplicationWindow;
+
+    fn class_init(klass: &mut Self::Class) {
+        klass.bind_template();
+    }
+
+    fn instance_init(obj: &InitializingObject<Self>) {
+        obj.init_template();
+    }
+}
+
+/**
+ * Name:
+ * OverclockWindow
+ *
+ * Description:
+ * Trait shared by all OverclockWindow objects
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl OverclockWindow {
+    /**
+     * Name:
+     * get_setting
+     *
+     * Description:
+     * Generic function for getting setting value
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn get_setting<T: FromVariant>(&self, name: &str) -> T {
+        // Return the value of the property
+        match self.settings.get() {
+            Some(settings) => settings.get::<T>(name),
+            None => panic!("`settings` should be set in `setup_settings`."),
+        }
+    }
+
+    /**
+     * Name:
+     * update_setting
+     *
+     * Description:
+     * Generic function for updating setting values
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn update_setting<T: ToVariant>(&self, name: &str, value: T) {
+        // Fetch settings
+        match self.settings.get() {
+            Some(settings) => match settings.set(name, &value) {
+                Ok(_) => println!("..Setting `{}` updated!", name),
+                Err(err) => panic!("..Cannot update `{}` setting: `{}`", name, err),
+            },
+            None => panic!("..Cannot retrieve settings"),
+        }
+    }
+}
+
+/**
+ * Trait Name:
+ * ObjectImpl
+ *
+ * Description:
+ * Trait shared by all GObjects
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ObjectImpl for OverclockWindow {
+    /**
+     * Name:
+     * constructed
+     *
+     * Description:
+     * Called during construction, allows calling setup functions
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn constructed(&self, obj: &Self::Type) {
+        // Call "constructed" on parent
+        self.parent_constructed(obj);
+    }
+
+    /**
+     * Name:
+     * properties
+     *
+     * Description:
+     * Create list of custom properties for our GObject
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * beware that you need to use kebab-case (<https://en.wikipedia.org/wiki/Letter_case#Kebab_case>)
+     */
+    fn properties() -> &'static [ParamSpec] {
+        static PROPERTIES: Lazy<Vec<ParamSpec>> =
+            Lazy::new(|| vec![glib::ParamSpecString::builder("uuid").build()]);
+
+        PROPERTIES.as_ref()
+    }
+
+    /**
+     * Name:
+     * set_property
+     *
+     * Description:
+     * Mutator for custom GObject properties
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn set_property(&self, _obj: &Self::Type, _id: usize, value: &Value, pspec: &ParamSpec) {
+        match pspec.name() {
+            "uuid" => match value.get() {
+                Ok(input_uuid) => self
+                    .uuid
+                    .set(input_uuid)
+                    .expect("`uuid` should not be set after calling constructor.."),
+                Err(_) => panic!("The value needs to be of type `String`."),
+            },
+            _ => panic!("Property `{}` does not exist..", pspec.name()),
+        }
+    }
+
+    /**
+     * Name:
+     * property
+     *
+     * Description:
+     * Accessor for custom GObject properties
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn property(&self, _obj: &Self::Type, _id: usize, pspec: &ParamSpec) -> Value {
+        match pspec.name() {
+            "uuid" => match self.uuid.clone().get() {
+                Some(value) => return value.to_value(),
+                None => panic!("Cannot get value of `uuid` property.."),
+            },
+            _ => panic!("Property `{}` does not exist..", pspec.name()),
+        }
+    }
+}
+
+/**
+ * Trait Name:
+ * WidgetImpl
+ *
+ * Description:
+ * Trait shared by all widgets
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl WidgetImpl for OverclockWindow {}
+
+/**
+ * Trait Name:
+ * WindowImpl
+ *
+ * Description:
+ * Trait shared by all Window's
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl WindowImpl for OverclockWindow {
+    /**
+     * Name:
+     * close_request
+     *
+     * Description:
+     * Run when window closed
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn close_request(&self, window: &Self::Type) -> Inhibit {
+        // Store state in settings
+        self.update_setting("overclock-open", false);
+
+        // Pass close request on to the parent
+        self.parent_close_request(window)
+    }
+}
+
+/**
+ * Trait Name:
+ * ApplicationWindowImpl
+ *
+ * Description:
+ * Trait shared by all ApplicationWindow's
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ApplicationWindowImpl for OverclockWindow {}
@@ -0,0 +1,385 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Public-facing interface/wrapper for our custom GObject (OverclockWindow)
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Offsets the GPU doesn't report a range for are left disabled
+ */
+// Custom GObjects
+mod imp;
+
+// Imports
+use adwaita::{gio, glib, prelude::*, subclass::prelude::*, ActionRow};
+use gio::Settings;
+use glib::{clone, Object};
+use gtk::{Adjustment, SpinButton};
+use std::cell::RefMut;
+
+// Modules
+use crate::{
+    gpu_page::GpuPage,
+    overclock::{
+        apply_commands, format_command, plan_offsets, ClockOffset, OffsetRange, OffsetRequest,
+    },
+    overclockwindow::imp::ParentContainer,
+    subprocess::subprocess::SubprocessRunner,
+    watchdog::lock_tuning,
+    APP_ID,
+};
+
+// GObject wrapper for OverclockWindow
+glib::wrapper! {
+    pub struct OverclockWindow(ObjectSubclass<imp::OverclockWindow>)
+        @extends gtk::ApplicationWindow, gtk::Window, gtk::Widget,
+        @implements gio::ActionGroup, gio::ActionMap, gtk::Accessible, gtk::Buildable,
+                    gtk::ConstraintTarget, gtk::Native, gtk::Root, gtk::ShortcutManager;
+}
+
+/**
+ * Name:
+ * OverclockWindow
+ *
+ * Description:
+ * Trait shared by all OverclockWindow objects
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl OverclockWindow {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new OverclockWindow object
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Offsets are given as their current value and valid range, if supported
+     */
+    pub fn new(
+        app: &adwaita::Application,
+        uuid: &str,
+        gpu_index: u32,
+        graphics_offset: Option<(i32, OffsetRange)>,
+        memory_offset: Option<(i32, OffsetRange)>,
+        parent_window: &GpuPage,
+    ) -> Self {
+        // Create new window
+        let obj: OverclockWindow = Object::new(&[("application", app)])
+            .expect("`OverclockWindow` should be  instantiable.");
+
+        // Set custom properties
+        obj.set_property("uuid", String::from(uuid));
+        obj.imp().gpu_index.set(gpu_index);
+        obj.imp()
+            .graphics_range
+            .set(graphics_offset.map(|(_, range)| range));
+        obj.imp()
+            .memory_range
+            .set(memory_offset.map(|(_, range)| range));
+
+        // Set ref to parent
+        {
+            let mut parent_window_container: RefMut<ParentContainer> =
+                obj.imp().parent_window.borrow_mut();
+            parent_window_container.window = Some(parent_window.to_owned());
+        }
+
+        // Apply any setup actions that need the above properties
+        obj.setup_settings();
+        obj.setup_widgets(
+            graphics_offset.map(|(value, _)| value),
+            memory_offset.map(|(value, _)| value),
+        );
+
+        // Return final object
+        obj
+    }
+
+    /**
+     * Name:
+     * setup_settings
+     *
+     * Description:
+     * Load settings for APP_ID
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_settings(&self) {
+        let settings = Settings::new(APP_ID);
+        self.imp()
+            .settings
+            .set(settings)
+            .expect("`settings` should not be set before calling `setup_settings`..");
+    }
+
+    /**
+     * Name:
+     * create_requests
+     *
+     * Description:
+     * Create a request for each supported offset, using the given values
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn create_requests(&self, graphics_value: i32, memory_value: i32) -> Vec<OffsetRequest> {
+        let mut requests: Vec<OffsetRequest> = vec![];
+
+        if let Some(range) = self.imp().graphics_range.get() {
+            requests.push(OffsetRequest {
+                offset: ClockOffset::Graphics,
+                value: graphics_value,
+                range,
+            });
+        }
+        if let Some(range) = self.imp().memory_range.get() {
+            requests.push(OffsetRequest {
+                offset: ClockOffset::Memory,
+                value: memory_value,
+                range,
+            });
+        }
+
+        requests
+    }
+
+    /**
+     * Name:
+     * update_preview
+     *
+     * Description:
+     * Show the commands that will be run for the current values (or why they can't be)
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn update_preview(&self) {
+        let requests: Vec<OffsetRequest> = self.create_requests(
+            self.imp().graphics_offset_input.value() as i32,
+            self.imp().memory_offset_input.value() as i32,
+        );
+
+        match plan_offsets(self.imp().gpu_index.get(), &requests) {
+            Ok(commands) if commands.is_empty() => {
+                self.imp()
+                    .commands_label
+                    .set_label("No clock offsets supported..");
+                self.imp().overclock_apply_button.set_sensitive(false);
+            }
+            Ok(commands) => {
+                let preview: Vec<String> = commands
+                    .iter()
+                    .map(|command| format_command(command))
+                    .collect();
+                self.imp().commands_label.set_label(&preview.join("\n"));
+                self.imp().overclock_apply_button.set_sensitive(true);
+            }
+            Err(err) => {
+                self.imp().commands_label.set_label(&err);
+                self.imp().overclock_apply_button.set_sensitive(false);
+            }
+        }
+    }
+
+    /**
+     * Name:
+     * apply_offsets
+     *
+     * Description:
+     * Validate and apply the given offsets
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Nothing is run if any value is out of range
+     */
+    fn apply_offsets(&self, graphics_value: i32, memory_value: i32) -> Result<(), String> {
+        let commands: Vec<Vec<String>> = plan_offsets(
+            self.imp().gpu_index.get(),
+            &self.create_requests(graphics_value, memory_value),
+        )?;
+
+        apply_commands(&SubprocessRunner, &commands)?;
+
+        // Arm the watchdog (resetting to zero isn't tuning)
+        if graphics_value != 0 || memory_value != 0 {
+            lock_tuning().mark_tuned(&self.property::<String>("uuid"));
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Name:
+     * setup_offset_input
+     *
+     * Description:
+     * Limit an offset input to it's range (or disable it if unsupported)
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_offset_input(
+        &self,
+        row: &ActionRow,
+        input: &SpinButton,
+        range: Option<OffsetRange>,
+        value: Option<i32>,
+    ) {
+        match (range, value) {
+            (Some(valid_range), Some(current_value)) => {
+                let adjustment: Adjustment = Adjustment::new(
+                    current_value as f64,
+                    valid_range.min as f64,
+                    valid_range.max as f64,
+                    1.0,
+                    10.0,
+                    0.0,
+                );
+                input.configure(Some(&adjustment), 1.0, 0);
+                row.set_property(
+                    "subtitle",
+                    format!("{} to {}", valid_range.min, valid_range.max),
+                );
+            }
+            _ => input.set_sensitive(false),
+        }
+
+        input.connect_value_changed(clone!(@weak self as window => move |_| {
+            window.update_preview();
+        }));
+    }
+
+    /**
+     * Name:
+     * setup_widgets
+     *
+     * Description:
+     * Set up all widgets
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn setup_widgets(&self, graphics_value: Option<i32>, memory_value: Option<i32>) {
+        // Offset inputs
+        self.setup_offset_input(
+            &self.imp().graphics_offset_row.get(),
+            &self.imp().graphics_offset_input.get(),
+            self.imp().graphics_range.get(),
+            graphics_value,
+        );
+        self.setup_offset_input(
+            &self.imp().memory_offset_row.get(),
+            &self.imp().memory_offset_input.get(),
+            self.imp().memory_range.get(),
+            memory_value,
+        );
+
+        // Show commands for current values
+        self.update_preview();
+
+        // Buttons
+        // Apply
+        self.imp().overclock_apply_button.connect_clicked(
+            clone!(@weak self as window => move |_| {
+                match window.apply_offsets(window.imp().graphics_offset_input.value() as i32, window.imp().memory_offset_input.value() as i32) {
+                    Ok(_) => {
+                        // Emit signal to notify changes made (and thus reload required)
+                        let parent_window_container: RefMut<ParentContainer> = window.imp().parent_window.borrow_mut();
+                        let _result = parent_window_container.window.as_ref().unwrap().emit_by_name::<i32>("update-views", &[&(-1).to_value()]);
+
+                        // Close window
+                        window.close();
+                    }
+                    Err(err) => {
+                        println!("..Attempt to apply clock offsets failed, returning: {}", err);
+                        window.imp().commands_label.set_label(&err);
+                    }
+                }
+            }),
+        );
+        // Cancel
+        self.imp().overclock_cancel_button.connect_clicked(
+            clone!(@weak self as window => move |_| {
+                // Close window
+                window.close();
+            }),
+        );
+        // Reset
+        self.imp().overclock_reset_button.connect_clicked(
+            clone!(@weak self as window => move |_| {
+                // Set all offsets back to 0
+                match window.apply_offsets(0, 0) {
+                    Ok(_) => {
+                        window.imp().graphics_offset_input.set_value(0.0);
+                        window.imp().memory_offset_input.set_value(0.0);
+                    }
+                    Err(err) => {
+                        println!("..Attempt to reset clock offsets failed, returning: {}", err);
+                        window.imp().commands_label.set_label(&err);
+                    }
+                }
+            }),
+        );
+    }
+}
@@ -32,6 +32,7 @@ pub struct Processor {
     start_call: Cell<String>,
     middle_call: Cell<Option<String>>,
     end_call: Cell<String>,
+    target_type: Cell<String>,
 }
 
 /// The central trait for subclassing a GObject
@@ -93,6 +94,7 @@ impl ObjectImpl for Processor {
                 glib::ParamSpecString::builder("start-call").build(),
                 glib::ParamSpecString::builder("middle-call").build(),
                 glib::ParamSpecString::builder("end-call").build(),
+                glib::ParamSpecString::builder("target-type").build(),
             ]
         });
 
@@ -146,6 +148,12 @@ impl ObjectImpl for Processor {
                 }
                 Err(_) => panic!("The value needs to be of type `String`."),
             },
+            "target-type" => match value.get() {
+                Ok(input_target_type) => {
+                    self.target_type.replace(input_target_type);
+                }
+                Err(_) => panic!("The value needs to be of type `String`."),
+            },
             _ => panic!("Property `{}` does not exist..", pspec.name()),
         }
     }
@@ -202,6 +210,14 @@ impl ObjectImpl for Processor {
 
                 value.to_value()
             }
+            "target-type" => {
+                //TODO: this seems ridiculous..
+                let value: String = self.target_type.take();
+
+                self.target_type.set(value.clone());
+
+                value.to_value()
+            }
             _ => panic!("Property `{}` does not exist..", pspec.name()),
         }
     }
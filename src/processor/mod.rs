@@ -27,7 +27,7 @@ use gtk::{gio, glib, prelude::ObjectExt};
 use std::ffi::OsStr;
 
 // Crates
-use crate::subprocess::subprocess::exec_communicate_sync;
+use crate::{subprocess::subprocess::exec_communicate_sync, targets::TargetType};
 
 // GObject wrapper for Processor
 glib::wrapper! {
@@ -87,9 +87,32 @@ impl Processor {
         }
         obj.set_property("end-call", String::from(end_call));
 
+        // Default to querying gpus
+        obj.set_property("target-type", String::from(TargetType::Gpu.name()));
+
         obj
     }
 
+    /**
+     * Name:
+     * set_target_type
+     *
+     * Description:
+     * Set the type of target (i.e. gpu or fan) this processor queries through nvidia-settings
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The id passed to `process` is then the index of the target, i.e. `[fan:0]`
+     */
+    pub fn set_target_type(&self, target_type: TargetType) {
+        self.set_property("target-type", String::from(target_type.name()));
+    }
+
     /**
      * Name:
      * process
@@ -120,8 +143,10 @@ impl Processor {
         let mut call_stack: String = self.property("base-call"); //"nvidia-smi" OR "nvidia-settings" OR "optirun"
         if call_stack == "nvidia-settings" {
             call_stack.push(' ');
-            call_stack.push_str(self.property::<String>("start-call").as_str()); //"-q=[gpu:"
+            call_stack.push_str(self.property::<String>("start-call").as_str()); //"-q=["
             if let Some(uuid_val) = uuid {
+                call_stack.push_str(self.property::<String>("target-type").as_str()); //"gpu" OR "fan" OR "thermalsensor"
+                call_stack.push(':');
                 call_stack.push_str(uuid_val);
             }
             if let Some(middle_call) = self.property::<Option<String>>("middle-call") {
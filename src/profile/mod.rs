@@ -0,0 +1,515 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Named tuning profiles (i.e. "Quiet"), bundling the fan curves, clock offsets and power limit of a GPU
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Profiles are stored in the `profiles` setting, one entry per profile per GPU, i.e.
+ * `Quiet:GPU-...:150:0:-500:0;2;5;40/30,80/70|1;2;5;40/30,80/70`
+ * (NAME:UUID:POWER_LIMIT:GRAPHICS_OFFSET:MEMORY_OFFSET:FAN_CURVES), with empty values left unchanged
+ */
+// Modules
+use crate::{
+    command::CommandRunner,
+    fan_curve::{parse_points, FanCurve, FanCurveConfig},
+    helper::{create_helper_command, HelperOperation},
+    overclock::{plan_offsets, ClockOffset, OffsetRange, OffsetRequest},
+};
+
+/// Structure for storing the settings of a GPU saved in a profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub uuid: String,
+    pub power_limit: Option<u32>,
+    pub graphics_offset: Option<i32>,
+    pub memory_offset: Option<i32>,
+    pub fan_curves: Vec<(u32, FanCurve)>,
+}
+
+/// Structure for storing everything needed to apply a profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfilePlan {
+    pub commands: Vec<Vec<String>>,
+    pub privileged_commands: Vec<Vec<String>>,
+    pub fan_curves: Vec<String>,
+}
+
+/**
+ * Name:
+ * validate_profile_name
+ *
+ * Description:
+ * Check a profile name can be stored (no separators used in the setting)
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        Err(String::from("Profile name cannot be empty.."))
+    } else if name.len() > 64 {
+        Err(String::from("Profile name is too long.."))
+    } else if name.contains([':', ';', '|']) {
+        Err(String::from(
+            "Profile name cannot contain `:`, `;` or `|`..",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/**
+ * Name:
+ * parse_optional
+ *
+ * Description:
+ * Parse an optional value of a profile entry (empty means unset)
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn parse_optional<T: std::str::FromStr>(input: &str) -> Result<Option<T>, String> {
+    if input.is_empty() {
+        Ok(None)
+    } else {
+        match input.parse::<T>() {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => Err(format!("Invalid profile value `{}`..", input)),
+        }
+    }
+}
+
+/**
+ * Name:
+ * format_optional
+ *
+ * Description:
+ * Format an optional value of a profile entry (unset is empty)
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn format_optional<T: ToString>(value: &Option<T>) -> String {
+    match value {
+        Some(valid_value) => valid_value.to_string(),
+        None => String::new(),
+    }
+}
+
+/**
+ * Name:
+ * Profile
+ *
+ * Description:
+ * Trait shared by all profiles
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Profile {
+    /**
+     * Name:
+     * from_setting
+     *
+     * Description:
+     * Parse a profile from it's entry in the `profiles` setting
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * NAME:UUID:POWER_LIMIT:GRAPHICS_OFFSET:MEMORY_OFFSET:FAN_CURVES
+     */
+    pub fn from_setting(input: &str) -> Result<Self, String> {
+        // Split into the 6 parts
+        let parts: Vec<&str> = input.split(':').collect();
+        if parts.len() != 6 || parts[1].is_empty() {
+            return Err(String::from("Malformed profile data.."));
+        }
+        validate_profile_name(parts[0])?;
+
+        // Parse each fan curve, i.e. `FAN;HYSTERESIS;MIN_CHANGE;POINTS`
+        let mut fan_curves: Vec<(u32, FanCurve)> = vec![];
+        for item in parts[5].split('|').filter(|item| !item.is_empty()) {
+            let curve_parts: Vec<&str> = item.split(';').collect();
+            if curve_parts.len() != 4 {
+                return Err(String::from("Malformed profile fan curve.."));
+            }
+            match (
+                curve_parts[0].parse::<u32>(),
+                curve_parts[1].parse::<u32>(),
+                curve_parts[2].parse::<u32>(),
+            ) {
+                (Ok(fan), Ok(hysteresis), Ok(min_change)) => fan_curves.push((
+                    fan,
+                    FanCurve::new(parse_points(curve_parts[3])?, hysteresis, min_change),
+                )),
+                _ => return Err(String::from("Malformed profile fan curve..")),
+            }
+        }
+
+        Ok(Profile {
+            name: String::from(parts[0]),
+            uuid: String::from(parts[1]),
+            power_limit: parse_optional::<u32>(parts[2])?,
+            graphics_offset: parse_optional::<i32>(parts[3])?,
+            memory_offset: parse_optional::<i32>(parts[4])?,
+            fan_curves,
+        })
+    }
+
+    /**
+     * Name:
+     * to_setting
+     *
+     * Description:
+     * Convert a profile to it's entry in the `profiles` setting
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn to_setting(&self) -> String {
+        let fan_curves: Vec<String> = self
+            .fan_curves
+            .iter()
+            .map(|(fan, curve)| {
+                format!(
+                    "{};{};{};{}",
+                    fan,
+                    curve.hysteresis,
+                    curve.min_change,
+                    curve.points_to_string()
+                )
+            })
+            .collect();
+
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            self.name,
+            self.uuid,
+            format_optional(&self.power_limit),
+            format_optional(&self.graphics_offset),
+            format_optional(&self.memory_offset),
+            fan_curves.join("|")
+        )
+    }
+
+    /**
+     * Name:
+     * capture
+     *
+     * Description:
+     * Create a profile from the current settings of a GPU
+     *
+     * Made:
+     * 25/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only enabled fan curves (from the `fancurves` setting) are kept
+     */
+    pub fn capture(
+        name: &str,
+        uuid: &str,
+        power_limit: Option<u32>,
+        graphics_offset: Option<i32>,
+        memory_offset: Option<i32>,
+        fan_curves: &[String],
+    ) -> Self {
+        Profile {
+            name: String::from(name),
+            uuid: String::from(uuid),
+            power_limit,
+            graphics_offset,
+            memory_offset,
+            fan_curves: fan_curves
+                .iter()
+                .filter_map(|item| FanCurveConfig::from_setting(item).ok())
+                .filter(|config| config.uuid == uuid && config.enabled)
+                .map(|config| (config.fan, config.curve))
+                .collect(),
+        }
+    }
+}
+
+/**
+ * Name:
+ * load_profiles
+ *
+ * Description:
+ * Parse every entry of the `profiles` setting, ignoring any malformed ones
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn load_profiles(entries: &[String]) -> Vec<Profile> {
+    entries
+        .iter()
+        .filter_map(|entry| match Profile::from_setting(entry) {
+            Ok(profile) => Some(profile),
+            Err(err) => {
+                println!("..Ignoring profile: {}", err);
+                None
+            }
+        })
+        .collect()
+}
+
+/**
+ * Name:
+ * profile_names
+ *
+ * Description:
+ * List the name of every saved profile (once each, in the order saved)
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn profile_names(profiles: &[Profile]) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for profile in profiles {
+        if !names.contains(&profile.name) {
+            names.push(profile.name.clone());
+        }
+    }
+
+    names
+}
+
+/**
+ * Name:
+ * replace_profile
+ *
+ * Description:
+ * Add a profile to the `profiles` setting, replacing any with the same name & GPU
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn replace_profile(entries: Vec<String>, profile: &Profile) -> Vec<String> {
+    let mut new_entries: Vec<String> = entries
+        .into_iter()
+        .filter(|entry| match Profile::from_setting(entry) {
+            Ok(old_profile) => {
+                !(old_profile.name == profile.name && old_profile.uuid == profile.uuid)
+            }
+            Err(_) => true,
+        })
+        .collect();
+    new_entries.push(profile.to_setting());
+
+    new_entries
+}
+
+/**
+ * Name:
+ * apply_fan_curves
+ *
+ * Description:
+ * Update the `fancurves` setting so the GPU uses only the fan curves of a profile
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Other curves of the GPU are kept but disabled
+ */
+pub fn apply_fan_curves(fan_curves: Vec<String>, profile: &Profile) -> Vec<String> {
+    let mut new_fan_curves: Vec<String> = vec![];
+
+    for entry in fan_curves {
+        match FanCurveConfig::from_setting(&entry) {
+            Ok(mut config) if config.uuid == profile.uuid => {
+                // Replaced below
+                if profile.fan_curves.iter().any(|(fan, _)| *fan == config.fan) {
+                    continue;
+                }
+                config.enabled = false;
+                new_fan_curves.push(config.to_setting());
+            }
+            _ => new_fan_curves.push(entry),
+        }
+    }
+
+    for (fan, curve) in &profile.fan_curves {
+        new_fan_curves.push(
+            FanCurveConfig {
+                uuid: profile.uuid.clone(),
+                fan: *fan,
+                enabled: true,
+                curve: curve.clone(),
+            }
+            .to_setting(),
+        );
+    }
+
+    new_fan_curves
+}
+
+/**
+ * Name:
+ * plan_profile
+ *
+ * Description:
+ * Validate a profile for a GPU and create everything needed to apply it
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The gpu index & offset ranges are only needed if the profile has clock offsets
+ */
+pub fn plan_profile(
+    profile: &Profile,
+    gpu_index: Option<u32>,
+    graphics_range: Option<OffsetRange>,
+    memory_range: Option<OffsetRange>,
+    fan_curves: Vec<String>,
+) -> Result<ProfilePlan, String> {
+    // Clock offsets
+    let mut requests: Vec<OffsetRequest> = vec![];
+    for (offset, value, range) in [
+        (
+            ClockOffset::Graphics,
+            profile.graphics_offset,
+            graphics_range,
+        ),
+        (ClockOffset::Memory, profile.memory_offset, memory_range),
+    ] {
+        if let Some(valid_value) = value {
+            match range {
+                Some(valid_range) => requests.push(OffsetRequest {
+                    offset,
+                    value: valid_value,
+                    range: valid_range,
+                }),
+                None => return Err(format!("{} not supported by this GPU..", offset.label())),
+            }
+        }
+    }
+    let commands: Vec<Vec<String>> = if requests.is_empty() {
+        vec![]
+    } else {
+        match gpu_index {
+            Some(valid_index) => plan_offsets(valid_index, &requests)?,
+            None => return Err(String::from("Clock offsets not supported by this GPU..")),
+        }
+    };
+
+    // Power limit
+    let mut privileged_commands: Vec<Vec<String>> = vec![];
+    if let Some(watts) = profile.power_limit {
+        privileged_commands.push(create_helper_command(&HelperOperation::SetPowerLimit {
+            uuid: profile.uuid.clone(),
+            watts,
+        })?);
+    }
+
+    Ok(ProfilePlan {
+        commands,
+        privileged_commands,
+        fan_curves: apply_fan_curves(fan_curves, profile),
+    })
+}
+
+/**
+ * Name:
+ * apply_plan
+ *
+ * Description:
+ * Run the commands of a planned profile, stopping at the first failure
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Fan curves are applied by saving `ProfilePlan.fan_curves` to settings
+ */
+pub fn apply_plan(
+    plan: &ProfilePlan,
+    runner: &dyn CommandRunner,
+    privileged_runner: &dyn CommandRunner,
+) -> Result<(), String> {
+    for command in &plan.commands {
+        runner.run(command)?;
+    }
+    for command in &plan.privileged_commands {
+        privileged_runner.run(command)?;
+    }
+
+    Ok(())
+}
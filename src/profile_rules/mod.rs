@@ -0,0 +1,1038 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Rules for switching tuning profiles automatically when matching processes are running
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Rules are stored in the `profile-rules` setting as PROFILE:GPU:PATTERN, i.e. `Render:0:python.*train.py`,
+ * where GPU is empty (any GPU), an index or a uuid. Rules are checked in order, the first match wins
+ */
+// Imports
+use std::{env, fs, path::Path, process::Command};
+
+// Modules
+use crate::mig::{parse_gpu_list, GpuListing};
+
+/// Structure for storing a running process
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub command_line: String,
+    pub gpu_uuids: Vec<String>,
+}
+
+/// Enum for choosing which GPU a rule watches (`Any` being a process using at least one GPU)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GpuSelector {
+    Any,
+    Index(u32),
+    Uuid(String),
+}
+
+/// Structure for storing one part of a pattern (a character, or any character, maybe repeated).
+/// Only exactly once, zero or more (`*`) and optional (`?`) are stored, `+` becomes the character then `*`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Token {
+    character: Option<char>,
+    min: usize,
+    max: Option<usize>,
+}
+
+/// Structure for storing a (small subset of a) regular expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    source: String,
+    tokens: Vec<Token>,
+    anchor_start: bool,
+    anchor_end: bool,
+}
+
+/// Structure for storing a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileRule {
+    pub profile: String,
+    pub gpu: GpuSelector,
+    pub pattern: Pattern,
+}
+
+/// Enum for the decisions made by the rule engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProfileSwitch {
+    Apply {
+        profile: String,
+        rule: usize,
+        pid: u32,
+        process: String,
+    },
+    Revert {
+        profile: String,
+    },
+}
+
+/// Structure for storing the rules and which (if any) is active
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuleEngine {
+    rules: Vec<ProfileRule>,
+    active: Option<usize>,
+}
+
+/**
+ * Name:
+ * Pattern
+ *
+ * Description:
+ * Trait shared by all patterns
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Pattern {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Compile a pattern, supporting `.`, `*`, `+`, `?`, `^`, `$` and `\` escapes
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Like a regular expression, the pattern can match anywhere unless anchored.
+     * Other regular expression syntax (alternation, classes, groups, counts) is an error, rather than matched literally
+     */
+    pub fn new(source: &str) -> Result<Self, String> {
+        if source.is_empty() {
+            return Err(String::from("Pattern cannot be empty.."));
+        }
+
+        let mut chars: Vec<char> = source.chars().collect();
+        let anchor_start: bool = chars.first() == Some(&'^');
+        if anchor_start {
+            chars.remove(0);
+        }
+        let anchor_end: bool = chars.last() == Some(&'$') && !ends_with_escape(&chars);
+        if anchor_end {
+            chars.pop();
+        }
+
+        let mut tokens: Vec<Token> = vec![];
+        let mut index: usize = 0;
+        while index < chars.len() {
+            match chars[index] {
+                '*' | '+' | '?' => {
+                    let previous: &mut Token = match tokens.last_mut() {
+                        Some(token) if token.min == 1 && token.max == Some(1) => token,
+                        _ => return Err(format!("Nothing to repeat in `{}`..", source)),
+                    };
+                    // Zero or more, one or more (once then zero or more), or optional
+                    match chars[index] {
+                        '*' => {
+                            previous.min = 0;
+                            previous.max = None;
+                        }
+                        '?' => previous.min = 0,
+                        _ => {
+                            let repeat: Token = Token {
+                                min: 0,
+                                max: None,
+                                ..*previous
+                            };
+                            tokens.push(repeat);
+                        }
+                    }
+                }
+                '\\' => {
+                    index += 1;
+                    match chars.get(index) {
+                        Some(escaped) => tokens.push(Token {
+                            character: Some(*escaped),
+                            min: 1,
+                            max: Some(1),
+                        }),
+                        None => return Err(format!("Unfinished escape in `{}`..", source)),
+                    }
+                }
+                '|' | '[' | ']' | '(' | ')' | '{' | '}' => {
+                    return Err(format!(
+                        "Unsupported `{}` in `{}` (use `\\{}` to match it)..",
+                        chars[index], source, chars[index]
+                    ))
+                }
+                '.' => tokens.push(Token {
+                    character: None,
+                    min: 1,
+                    max: Some(1),
+                }),
+                character => tokens.push(Token {
+                    character: Some(character),
+                    min: 1,
+                    max: Some(1),
+                }),
+            }
+            index += 1;
+        }
+
+        Ok(Pattern {
+            source: String::from(source),
+            tokens,
+            anchor_start,
+            anchor_end,
+        })
+    }
+
+    /**
+     * Name:
+     * as_str
+     *
+     * Description:
+     * Get the pattern as it was written
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /**
+     * Name:
+     * is_match
+     *
+     * Description:
+     * Check if the pattern matches (part of) some text
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Takes time proportional to the length of the pattern times the length of the text, whatever the pattern
+     */
+    pub fn is_match(&self, text: &str) -> bool {
+        let count: usize = self.tokens.len();
+
+        // Every token we could be up to, tried for all characters at once (so never backtracking)
+        let mut current: Vec<bool> = vec![false; count + 1];
+        current[0] = true;
+        self.skip_optional(&mut current);
+
+        for character in text.chars() {
+            if current[count] && !self.anchor_end {
+                return true;
+            }
+
+            let mut next: Vec<bool> = vec![false; count + 1];
+            for (index, token) in self.tokens.iter().enumerate() {
+                if current[index] && !matches!(token.character, Some(c) if c != character) {
+                    // Repeats can take more, everything else moves on
+                    match token.max {
+                        None => next[index] = true,
+                        Some(_) => next[index + 1] = true,
+                    }
+                }
+            }
+            // Unanchored patterns can start anywhere
+            if !self.anchor_start {
+                next[0] = true;
+            }
+            self.skip_optional(&mut next);
+            current = next;
+        }
+
+        current[count]
+    }
+
+    /**
+     * Name:
+     * skip_optional
+     *
+     * Description:
+     * Add the tokens reachable by skipping optional/repeated tokens (that can take nothing)
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Tokens are only ever skipped forwards, so a single pass is enough
+     */
+    fn skip_optional(&self, states: &mut [bool]) {
+        for (index, token) in self.tokens.iter().enumerate() {
+            if states[index] && token.min == 0 {
+                states[index + 1] = true;
+            }
+        }
+    }
+}
+
+/**
+ * Name:
+ * ends_with_escape
+ *
+ * Description:
+ * Check if the last character of a pattern is escaped (i.e. `\$`)
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn ends_with_escape(chars: &[char]) -> bool {
+    let backslashes: usize = chars[..chars.len() - 1]
+        .iter()
+        .rev()
+        .take_while(|character| **character == '\\')
+        .count();
+
+    backslashes % 2 == 1
+}
+
+/**
+ * Name:
+ * ProfileRule
+ *
+ * Description:
+ * Trait shared by all rules
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ProfileRule {
+    /**
+     * Name:
+     * from_setting
+     *
+     * Description:
+     * Parse a rule from it's entry in the `profile-rules` setting
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * PROFILE:GPU:PATTERN (the pattern may itself contain `:`)
+     */
+    pub fn from_setting(input: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = input.splitn(3, ':').collect();
+        if parts.len() != 3 || parts[0].is_empty() {
+            return Err(String::from("Malformed profile rule.."));
+        }
+
+        let gpu: GpuSelector = if parts[1].is_empty() {
+            GpuSelector::Any
+        } else if let Ok(index) = parts[1].parse::<u32>() {
+            GpuSelector::Index(index)
+        } else {
+            GpuSelector::Uuid(String::from(parts[1]))
+        };
+
+        Ok(ProfileRule {
+            profile: String::from(parts[0]),
+            gpu,
+            pattern: Pattern::new(parts[2])?,
+        })
+    }
+
+    /**
+     * Name:
+     * to_setting
+     *
+     * Description:
+     * Convert a rule to it's entry in the `profile-rules` setting
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn to_setting(&self) -> String {
+        let gpu: String = match &self.gpu {
+            GpuSelector::Any => String::new(),
+            GpuSelector::Index(index) => index.to_string(),
+            GpuSelector::Uuid(uuid) => uuid.clone(),
+        };
+
+        format!("{}:{}:{}", self.profile, gpu, self.pattern.as_str())
+    }
+
+    /**
+     * Name:
+     * matches
+     *
+     * Description:
+     * Check if a process matches this rule (by name or command line, on the chosen GPU)
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * gpu_uuids is the list of GPUs in index order, used to match GPUs by index.
+     * Processes not using a GPU (i.e. only found by scanning `/proc`) never match
+     */
+    pub fn matches(&self, process: &ProcessInfo, gpu_uuids: &[String]) -> bool {
+        let on_gpu: bool = match &self.gpu {
+            GpuSelector::Any => !process.gpu_uuids.is_empty(),
+            GpuSelector::Index(index) => match gpu_uuids.get(*index as usize) {
+                Some(uuid) => process.gpu_uuids.contains(uuid),
+                None => false,
+            },
+            GpuSelector::Uuid(uuid) => process.gpu_uuids.contains(uuid),
+        };
+
+        on_gpu
+            && (self.pattern.is_match(&process.name)
+                || self.pattern.is_match(&process.command_line))
+    }
+}
+
+/**
+ * Name:
+ * RuleEngine
+ *
+ * Description:
+ * Trait shared by all rule engines
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl RuleEngine {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new rule engine, with no rule active
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(rules: Vec<ProfileRule>) -> Self {
+        RuleEngine {
+            rules,
+            active: None,
+        }
+    }
+
+    /**
+     * Name:
+     * from_settings
+     *
+     * Description:
+     * Create a new rule engine from the `profile-rules` setting, ignoring any malformed rules
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn from_settings(entries: &[String]) -> Self {
+        RuleEngine::new(
+            entries
+                .iter()
+                .filter_map(|entry| match ProfileRule::from_setting(entry) {
+                    Ok(rule) => Some(rule),
+                    Err(err) => {
+                        println!("..Ignoring profile rule: {}", err);
+                        None
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /**
+     * Name:
+     * invalid_settings
+     *
+     * Description:
+     * Check the `profile-rules` setting, returning why each malformed rule would be ignored
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn invalid_settings(entries: &[String]) -> Vec<String> {
+        entries
+            .iter()
+            .filter_map(|entry| match ProfileRule::from_setting(entry) {
+                Ok(_) => None,
+                Err(err) => Some(format!("`{}`: {}", entry, err)),
+            })
+            .collect()
+    }
+
+    /**
+     * Name:
+     * rules
+     *
+     * Description:
+     * Get the list of rules
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn rules(&self) -> &[ProfileRule] {
+        &self.rules
+    }
+
+    /**
+     * Name:
+     * active_rule
+     *
+     * Description:
+     * Get the rule whose profile is currently applied, if any
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn active_rule(&self) -> Option<&ProfileRule> {
+        self.active.and_then(|index| self.rules.get(index))
+    }
+
+    /**
+     * Name:
+     * update
+     *
+     * Description:
+     * Check the running processes against the rules, returning any switch to make
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Switching straight between two rules is an `Apply`, `Revert` is only used once no rules match.
+     * An applied rule only becomes active once `confirm`ed, so a failed apply is tried again next update
+     */
+    pub fn update(
+        &mut self,
+        processes: &[ProcessInfo],
+        gpu_uuids: &[String],
+    ) -> Option<ProfileSwitch> {
+        // Find the first rule with a matching process
+        let found: Option<(usize, &ProcessInfo)> =
+            self.rules.iter().enumerate().find_map(|(index, rule)| {
+                processes
+                    .iter()
+                    .find(|process| rule.matches(process, gpu_uuids))
+                    .map(|process| (index, process))
+            });
+
+        match found {
+            Some((index, _)) if self.active == Some(index) => None,
+            Some((index, process)) => Some(ProfileSwitch::Apply {
+                profile: self.rules[index].profile.clone(),
+                rule: index,
+                pid: process.pid,
+                process: process.name.clone(),
+            }),
+            None => match self.active.take() {
+                Some(index) => Some(ProfileSwitch::Revert {
+                    profile: self.rules[index].profile.clone(),
+                }),
+                None => None,
+            },
+        }
+    }
+
+    /**
+     * Name:
+     * confirm
+     *
+     * Description:
+     * Mark a rule as active, once the profile it switched to has been applied
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Ignored if the rules have changed since (i.e. the rule now uses a different profile)
+     */
+    pub fn confirm(&mut self, rule: usize, profile: &str) {
+        if self
+            .rules
+            .get(rule)
+            .map(|valid_rule| valid_rule.profile.as_str())
+            == Some(profile)
+        {
+            self.active = Some(rule);
+        }
+    }
+}
+
+/**
+ * Name:
+ * ProfileSwitch
+ *
+ * Description:
+ * Trait shared by all profile switches
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ProfileSwitch {
+    /**
+     * Name:
+     * describe
+     *
+     * Description:
+     * Describe a switch, for logging
+     *
+     * Made:
+     * 26/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn describe(&self) -> String {
+        match self {
+            ProfileSwitch::Apply {
+                profile,
+                rule,
+                pid,
+                process,
+            } => format!(
+                "Rule {} matched process `{}` ({}), applying profile `{}`",
+                rule, process, pid, profile
+            ),
+            ProfileSwitch::Revert { profile } => format!(
+                "No processes match any rule, reverting profile `{}`",
+                profile
+            ),
+        }
+    }
+}
+
+/**
+ * Name:
+ * parse_process_table
+ *
+ * Description:
+ * Parse the processes using each GPU, from the process table shown by nvidia-smi
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * i.e. `|    0   N/A  N/A      1234    C+G   /opt/blender/blender      512MiB |`, covering graphics and
+ * compute processes. GPUs are given by index, so are looked up in @gpus (from `nvidia-smi -L`)
+ */
+pub fn parse_process_table(output: &str, gpus: &[GpuListing]) -> Vec<ProcessInfo> {
+    let mut processes: Vec<ProcessInfo> = vec![];
+    let mut in_table: bool = false;
+
+    for line in output.lines() {
+        if line.contains("Processes:") {
+            in_table = true;
+            continue;
+        }
+        if !in_table {
+            continue;
+        }
+
+        // Rows start with a GPU index, then the pid right before the type (C, G, C+G, M, M+C..)
+        let parts: Vec<&str> = line.trim().trim_matches('|').split_whitespace().collect();
+        let uuid: &str = match parts
+            .first()
+            .and_then(|index| index.parse::<u32>().ok())
+            .and_then(|index| gpus.iter().find(|gpu| gpu.index == index))
+        {
+            Some(gpu) => &gpu.uuid,
+            None => continue,
+        };
+        let type_position: usize = match parts
+            .iter()
+            .skip(1)
+            .position(|part| part.chars().all(|c| matches!(c, 'C' | 'G' | 'M' | '+')))
+        {
+            Some(position) => position + 1,
+            None => continue,
+        };
+        let pid: u32 = match parts[type_position - 1].parse::<u32>() {
+            Ok(pid) => pid,
+            Err(_) => continue,
+        };
+
+        // Memory usage comes after the name
+        let mut name_parts: &[&str] = &parts[type_position + 1..];
+        if let Some((usage, rest)) = name_parts.split_last() {
+            if usage.ends_with("MiB") || *usage == "N/A" {
+                name_parts = rest;
+            }
+        }
+        let command_line: String = name_parts.join(" ");
+
+        // One row per GPU used by a process
+        match processes.iter_mut().find(|process| process.pid == pid) {
+            Some(process) => {
+                if !process.gpu_uuids.iter().any(|used| used == uuid) {
+                    process.gpu_uuids.push(String::from(uuid));
+                }
+            }
+            None => processes.push(ProcessInfo {
+                pid,
+                name: process_name(&command_line),
+                command_line,
+                gpu_uuids: vec![String::from(uuid)],
+            }),
+        }
+    }
+
+    processes
+}
+
+/**
+ * Name:
+ * process_name
+ *
+ * Description:
+ * Get the name of a program from it's path
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn process_name(path: &str) -> String {
+    match path.split_whitespace().next() {
+        Some(program) => String::from(program.rsplit('/').next().unwrap_or(program)),
+        None => String::new(),
+    }
+}
+
+/**
+ * Name:
+ * parse_cmdline
+ *
+ * Description:
+ * Convert the contents of `/proc/PID/cmdline` (NUL separated) into a command line
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn parse_cmdline(contents: &[u8]) -> String {
+    String::from_utf8_lossy(contents)
+        .split('\0')
+        .filter(|arg| !arg.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/**
+ * Name:
+ * scan_processes
+ *
+ * Description:
+ * List the running processes, from a `/proc` style directory
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Processes that exit while scanning are skipped
+ */
+pub fn scan_processes(proc_root: &Path) -> Vec<ProcessInfo> {
+    let entries: fs::ReadDir = match fs::read_dir(proc_root) {
+        Ok(entries) => entries,
+        Err(err) => {
+            println!("..Attempt to scan processes failed, returning: {}", err);
+            return vec![];
+        }
+    };
+
+    let mut processes: Vec<ProcessInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let pid: u32 = entry.file_name().to_str()?.parse::<u32>().ok()?;
+            let name: String = fs::read_to_string(entry.path().join("comm"))
+                .ok()?
+                .trim()
+                .to_string();
+            let command_line: String = fs::read(entry.path().join("cmdline"))
+                .map(|contents| parse_cmdline(&contents))
+                .unwrap_or_default();
+
+            Some(ProcessInfo {
+                pid,
+                name,
+                command_line,
+                gpu_uuids: vec![],
+            })
+        })
+        .collect();
+    processes.sort_by_key(|process| process.pid);
+
+    processes
+}
+
+/**
+ * Name:
+ * merge_processes
+ *
+ * Description:
+ * Add the GPUs used by each process (from nvidia-smi) to the list of all processes
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * GPU processes missing from the scan (i.e. in another pid namespace) are kept as nvidia-smi sees them
+ */
+pub fn merge_processes(
+    mut processes: Vec<ProcessInfo>,
+    gpu_processes: Vec<ProcessInfo>,
+) -> Vec<ProcessInfo> {
+    for app in gpu_processes {
+        match processes.iter_mut().find(|process| process.pid == app.pid) {
+            Some(process) => process.gpu_uuids.extend(app.gpu_uuids),
+            None => processes.push(app),
+        }
+    }
+
+    processes
+}
+
+/**
+ * Name:
+ * is_installed
+ *
+ * Description:
+ * Check if a program can be found in `PATH`
+ *
+ * Made:
+ * 09/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn is_installed(program: &str) -> bool {
+    match env::var_os("PATH") {
+        Some(paths) => env::split_paths(&paths).any(|path| path.join(program).is_file()),
+        None => false,
+    }
+}
+
+/**
+ * Name:
+ * gpu_processes_command
+ *
+ * Description:
+ * Create the nvidia-smi command used to find the processes using each GPU, for a provider type
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * nvidia-settings doesn't know which processes use which GPU, so nvidia-smi is used if installed.
+ * None if there is no way of finding out
+ */
+pub fn gpu_processes_command(
+    provider_type: i32,
+    nvidia_smi_installed: bool,
+) -> Option<Vec<String>> {
+    match provider_type {
+        // Nvidia Optimus
+        3 => Some(vec![String::from("optirun"), String::from("nvidia-smi")]),
+        // Nvidia Settings/SMI OR Nvidia SMI
+        0 | 2 => Some(vec![String::from("nvidia-smi")]),
+        // Nvidia Settings
+        _ if nvidia_smi_installed => Some(vec![String::from("nvidia-smi")]),
+        _ => None,
+    }
+}
+
+/**
+ * Name:
+ * run_nvidia_smi
+ *
+ * Description:
+ * Run nvidia-smi (@command) with some extra arguments, returning the output
+ *
+ * Made:
+ * 09/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+fn run_nvidia_smi(command: &[String], args: &[&str]) -> Result<String, String> {
+    let (program, command_args) = match command.split_first() {
+        Some(split) => split,
+        None => return Err(String::from("No command given..")),
+    };
+
+    match Command::new(program).args(command_args).args(args).output() {
+        Ok(output) if output.status.success() => {
+            Ok(String::from(String::from_utf8_lossy(&output.stdout)))
+        }
+        Ok(output) => Err(format!(
+            "`{}` failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(err) => Err(format!("Cannot run `{}`: {}", program, err)),
+    }
+}
+
+/**
+ * Name:
+ * list_processes
+ *
+ * Description:
+ * Scan the running processes and add the GPUs used by each (using nvidia-smi, @command)
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only uses std, so can be run off the main thread. Returns an error if the GPUs used by each process
+ * can't be found (no @command, or it fails), as no rule could match
+ */
+pub fn list_processes(
+    proc_root: &Path,
+    command: Option<&[String]>,
+) -> Result<Vec<ProcessInfo>, String> {
+    let command: &[String] = match command {
+        Some(valid_command) => valid_command,
+        None => {
+            return Err(String::from(
+                "nvidia-smi isn't installed, so the processes using each GPU can't be found",
+            ))
+        }
+    };
+
+    // GPUs are listed by index, so look up their uuids
+    let gpus: Vec<GpuListing> = parse_gpu_list(&run_nvidia_smi(command, &["-L"])?);
+    let gpu_processes: Vec<ProcessInfo> =
+        parse_process_table(&run_nvidia_smi(command, &[])?, &gpus);
+
+    Ok(merge_processes(scan_processes(proc_root), gpu_processes))
+}
@@ -36,6 +36,433 @@ glib::wrapper! {
     @implements gtk::Accessible, gtk::Actionable, gtk::Buildable, gtk::ConstraintTarget;
 }
 
+/// Structure for describing a property that can be displayed on a GpuPage
+///
+/// `query` is the nvidia-smi `--query-gpu` field giving it as a number (empty if it can't be read that way),
+/// `unit` the unit nvidia-smi gives it in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PropertyDescription {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub space: i32,
+    pub query: &'static str,
+    pub unit: &'static str,
+}
+
+/// Global list of all properties that can be displayed on a GpuPage
+pub static PROPERTY_LIST: &[PropertyDescription] = &[
+    PropertyDescription {
+        name: "none",
+        label: "None",
+        space: 5,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "util",
+        label: "GPU Utilization",
+        space: 5,
+        query: "utilization.gpu",
+        unit: "%",
+    },
+    PropertyDescription {
+        name: "temp",
+        label: "GPU Temperature",
+        space: 5,
+        query: "temperature.gpu",
+        unit: "°C",
+    },
+    PropertyDescription {
+        name: "power_usage",
+        label: "Power Usage",
+        space: 8,
+        query: "power.draw",
+        unit: "W",
+    },
+    PropertyDescription {
+        name: "memory_usage",
+        label: "Memory Usage",
+        space: 8,
+        query: "memory.used",
+        unit: "MiB",
+    },
+    PropertyDescription {
+        name: "memory_total",
+        label: "Memory Total",
+        space: 8,
+        query: "memory.total",
+        unit: "MiB",
+    },
+    PropertyDescription {
+        name: "mem_ctrl_util",
+        label: "Memory Controller Usage",
+        space: 5,
+        query: "utilization.memory",
+        unit: "%",
+    },
+    PropertyDescription {
+        name: "encoder_util",
+        label: "Encoder Utilization",
+        space: 5,
+        query: "utilization.encoder",
+        unit: "%",
+    },
+    PropertyDescription {
+        name: "decoder_util",
+        label: "Decoder Utilization",
+        space: 5,
+        query: "utilization.decoder",
+        unit: "%",
+    },
+    PropertyDescription {
+        name: "fan_speed",
+        label: "Fan Speed",
+        space: 5,
+        query: "fan.speed",
+        unit: "%",
+    },
+    PropertyDescription {
+        name: "gr_clock",
+        label: "Graphics Clock",
+        space: 9,
+        query: "clocks.gr",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "sm_clock",
+        label: "SM Clock",
+        space: 9,
+        query: "clocks.sm",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "mem_clock",
+        label: "Memory Clock",
+        space: 9,
+        query: "clocks.mem",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "video_clock",
+        label: "Video Clock",
+        space: 9,
+        query: "clocks.video",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "gr_clock_app",
+        label: "Graphics Clock (Application)",
+        space: 9,
+        query: "clocks.applications.graphics",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "mem_clock_app",
+        label: "Memory Clock (Application)",
+        space: 9,
+        query: "clocks.applications.memory",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "gr_clock_max",
+        label: "Graphics Clock (Max)",
+        space: 9,
+        query: "clocks.max.graphics",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "sm_clock_max",
+        label: "SM Clock (Max)",
+        space: 9,
+        query: "clocks.max.sm",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "mem_clock_max",
+        label: "Memory Clock (Max)",
+        space: 9,
+        query: "clocks.max.memory",
+        unit: "MHz",
+    },
+    PropertyDescription {
+        name: "throttle_reasons",
+        label: "Throttle Reasons",
+        space: 12,
+        query: "clocks_throttle_reasons.active",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "pcie_gen_current",
+        label: "PCIe Generation",
+        space: 6,
+        query: "pcie.link.gen.current",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "pcie_gen_max",
+        label: "PCIe Generation (Max)",
+        space: 6,
+        query: "pcie.link.gen.max",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "pcie_width_current",
+        label: "PCIe Link Width",
+        space: 5,
+        query: "pcie.link.width.current",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "pcie_width_max",
+        label: "PCIe Link Width (Max)",
+        space: 5,
+        query: "pcie.link.width.max",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "pcie_util",
+        label: "PCIe Bandwidth Utilization",
+        space: 5,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "pcie_rx",
+        label: "PCIe RX Throughput",
+        space: 9,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "pcie_tx",
+        label: "PCIe TX Throughput",
+        space: 9,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "ecc_mode",
+        label: "ECC Mode",
+        space: 8,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "ecc_sbe_volatile",
+        label: "ECC Single-Bit Errors (Volatile)",
+        space: 5,
+        query: "ecc.errors.corrected.volatile.total",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "ecc_dbe_volatile",
+        label: "ECC Double-Bit Errors (Volatile)",
+        space: 5,
+        query: "ecc.errors.uncorrected.volatile.total",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "ecc_sbe_aggregate",
+        label: "ECC Single-Bit Errors (Aggregate)",
+        space: 5,
+        query: "ecc.errors.corrected.aggregate.total",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "ecc_dbe_aggregate",
+        label: "ECC Double-Bit Errors (Aggregate)",
+        space: 5,
+        query: "ecc.errors.uncorrected.aggregate.total",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "retired_pages_sbe",
+        label: "Retired Pages (Single-Bit)",
+        space: 5,
+        query: "retired_pages.single_bit_ecc.count",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "retired_pages_dbe",
+        label: "Retired Pages (Double-Bit)",
+        space: 5,
+        query: "retired_pages.double_bit.count",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "retired_pages_pending",
+        label: "Page Retirement Pending",
+        space: 5,
+        query: "retired_pages.pending",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "remapped_rows_correctable",
+        label: "Remapped Rows (Correctable)",
+        space: 5,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "remapped_rows_uncorrectable",
+        label: "Remapped Rows (Uncorrectable)",
+        space: 5,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "remapped_rows_pending",
+        label: "Row Remapping Pending",
+        space: 5,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "remapped_rows_failure",
+        label: "Row Remapping Failure",
+        space: 5,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "power_fraction",
+        label: "Power Usage (of Limit)",
+        space: 16,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "power_limit",
+        label: "Power Limit",
+        space: 8,
+        query: "power.limit",
+        unit: "W",
+    },
+    PropertyDescription {
+        name: "enforced_power_limit",
+        label: "Enforced Power Limit",
+        space: 8,
+        query: "enforced.power.limit",
+        unit: "W",
+    },
+    PropertyDescription {
+        name: "power_min_limit",
+        label: "Power Limit (Min)",
+        space: 8,
+        query: "power.min_limit",
+        unit: "W",
+    },
+    PropertyDescription {
+        name: "power_max_limit",
+        label: "Power Limit (Max)",
+        space: 8,
+        query: "power.max_limit",
+        unit: "W",
+    },
+    PropertyDescription {
+        name: "power_default_limit",
+        label: "Power Limit (Default)",
+        space: 8,
+        query: "power.default_limit",
+        unit: "W",
+    },
+    PropertyDescription {
+        name: "pstate",
+        label: "Performance State",
+        space: 5,
+        query: "pstate",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "perf_level",
+        label: "Performance Level",
+        space: 8,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "perf_modes",
+        label: "Performance Levels",
+        space: 16,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "fans",
+        label: "Fans",
+        space: 24,
+        query: "",
+        unit: "",
+    },
+    PropertyDescription {
+        name: "thermal_sensors",
+        label: "Thermal Sensors",
+        space: 8,
+        query: "",
+        unit: "",
+    },
+];
+
+/// List of properties shown in the "Health" view template
+pub static HEALTH_VIEW_TEMPLATE: &[&str] = &[
+    "ecc_mode",
+    "ecc_sbe_volatile",
+    "ecc_dbe_volatile",
+    "ecc_sbe_aggregate",
+    "ecc_dbe_aggregate",
+    "retired_pages_sbe",
+    "retired_pages_dbe",
+    "retired_pages_pending",
+    "remapped_rows_correctable",
+    "remapped_rows_uncorrectable",
+    "remapped_rows_pending",
+    "remapped_rows_failure",
+];
+
+/**
+ * Name:
+ * find_property_description
+ *
+ * Description:
+ * Find the description of a property (from the global list) using it's name
+ *
+ * Made:
+ * 14/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn find_property_description(name: &str) -> Option<&'static PropertyDescription> {
+    PROPERTY_LIST
+        .iter()
+        .find(|description| description.name == name)
+}
+
+/**
+ * Name:
+ * find_property_index
+ *
+ * Description:
+ * Find the position of a property in the global list using it's name
+ *
+ * Made:
+ * 14/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Used for setting the selected item of dropdowns
+ */
+pub fn find_property_index(name: &str) -> Option<usize> {
+    PROPERTY_LIST
+        .iter()
+        .position(|description| description.name == name)
+}
+
 /**
  * Trait Name:
  * Property
@@ -108,8 +535,14 @@ impl Property {
         // Grab property name
         let mut property: String = self.property("id");
         // println!("ID: `{}`", property); //TEST
-        if let "GPUUtilization.gpu" | "GPUUtilization.mem" = property.as_str() {
-            property = String::from("GPUUtilization");
+        match property.as_str() {
+            "GPUUtilization.gpu" | "GPUUtilization.mem" | "GPUUtilization.pcie" => {
+                property = String::from("GPUUtilization");
+            }
+            "GPUCurrentClockFreqs.gr" | "GPUCurrentClockFreqs.mem" => {
+                property = String::from("GPUCurrentClockFreqs");
+            }
+            _ => {}
         }
         // println!("NEW ID: `{}`", property); //TEST
 
@@ -127,11 +560,27 @@ impl Property {
 
                         // Catch these as formatting screws them up
                         let mut clean_required: bool = true;
-                        if let "gpu_name" | "GPUUtilization" = property.as_str() {
+                        if let "gpu_name"
+                        | "GPUUtilization"
+                        | "GPUCurrentClockFreqs"
+                        | "clocks_throttle_reasons.active"
+                        | "ecc.mode.current"
+                        | "retired_pages.pending"
+                        | "remapped_rows.pending"
+                        | "remapped_rows.failure"
+                        | "power.draw,enforced.power.limit"
+                        | "GPUPerfModes" = property.as_str()
+                        {
                             clean_required = false;
                         }
+                        // Catch empty output (i.e. query not supported by this GPU)
+                        let first_line: String = match valid_result.get(0) {
+                            Some(line) => line.to_owned(),
+                            None => return None,
+                        };
+
                         // Format returned property using formatter and then return
-                        match formatter.format(valid_result[0].to_owned(), clean_required) {
+                        match formatter.format(first_line, clean_required) {
                             Some(formatted_result) => return Some(formatted_result),
                             None => return None,
                         }
@@ -21,7 +21,7 @@ use adwaita::glib;
 // Imports
 use glib::{once_cell::sync::Lazy, ParamSpec, Value};
 use gtk::{prelude::*, subclass::prelude::*};
-use std::{cell::Cell, cell::RefCell};
+use std::{cell::Cell, cell::RefCell, collections::HashMap, time::Instant};
 
 // Modules
 use crate::property::Property;
@@ -31,6 +31,10 @@ use crate::property::Property;
 pub struct Provider {
     pub properties: RefCell<Vec<Property>>,
     provider_type: Cell<i32>,
+    // Parent GPU uuid & index of each MIG device (by uuid)
+    pub mig_parents: RefCell<HashMap<String, (String, u32)>>,
+    // Output of the last PCIe query & when it was read (by uuid)
+    pub pcie_details: RefCell<HashMap<String, (Instant, String)>>,
 }
 
 /// The central trait for subclassing a GObject
@@ -25,14 +25,33 @@ use adwaita::{gio, glib};
 use gio::{Cancellable, Settings};
 use glib::Object;
 use gtk::{prelude::*, subclass::prelude::*};
-use std::ffi::OsStr;
+use std::{
+    cell::RefMut,
+    collections::HashMap,
+    ffi::OsStr,
+    time::{Duration, Instant},
+};
 
 // Crates
 use crate::{
-    processor::Processor, property::Property, subprocess::subprocess::exec_communicate_async,
+    formatter::{format_throughput, parse_pcie_throughput},
+    mig::{
+        find_mig_devices, find_mig_reading, mig_reading_name, parse_gpu_list, parse_mig_details,
+        MigDetails, MigDevice,
+    },
+    overclock::{
+        create_offset_query, parse_offset_range, parse_offset_value, ClockOffset, OffsetRange,
+    },
+    processor::Processor,
+    property::Property,
+    subprocess::subprocess::exec_communicate_async,
+    targets::{find_connected_targets, find_gpu_index, parse_targets, Target, TargetType},
     APP_ID,
 };
 
+/// How long a PCIe query is reused for, so RX & TX of the same refresh share one query
+const PCIE_DETAILS_LIFETIME: Duration = Duration::from_millis(500);
+
 // GObject wrapper for Provider
 glib::wrapper! {
     pub struct Provider(ObjectSubclass<imp::Provider>)
@@ -145,17 +164,12 @@ impl Provider {
                         0 | 1 => Ok(valid_output),
                         // Nvidia SMI or Nvidia Optimus
                         2 | 3 => {
-                            let mut cleaned_output: Vec<String> = vec![];
-                            for line in valid_output {
-                                // Grab mostly-correct contents
-                                let wanted: Vec<&str> = line.split("(UUID: ").collect();
-
-                                // Remove any unwanted chars
-                                let cleaned_line = wanted[1].replace(")", "");
-
-                                // Add to output
-                                cleaned_output.push(cleaned_line);
-                            }
+                            // Grab uuid of each GPU (MIG devices are handled by `get_mig_devices`)
+                            let cleaned_output: Vec<String> =
+                                parse_gpu_list(&valid_output.join("\n"))
+                                    .into_iter()
+                                    .map(|gpu| gpu.uuid)
+                                    .collect();
 
                             Ok(cleaned_output)
                         }
@@ -177,6 +191,223 @@ impl Provider {
         }
     }
 
+    /**
+     * Name:
+     * get_mig_devices
+     *
+     * Description:
+     * Grab the list of MIG devices belonging to a GPU given it's uuid
+     *
+     * Made:
+     * 20/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only nvidia-smi knows about MIG devices, the list comes from `nvidia-smi -L`
+     */
+    pub fn get_mig_devices(&self, uuid: &str) -> Result<Vec<MigDevice>, String> {
+        // Check provider type
+        let processor_args: [&str; 2] = match self.property::<i32>("provider-type") {
+            // Nvidia Settings/SMI OR Nvidia SMI
+            0 | 2 => ["nvidia-smi", "-L"],
+            // Nvidia Optimus
+            3 => ["optirun", "nvidia-smi -L"],
+            // Nvidia Settings
+            1 => {
+                // Return error..
+                return Err(String::from("MIG devices not supported by this provider.."));
+            }
+            _ => {
+                // Return error..
+                return Err(String::from("Invalid provider, check preferences.."));
+            }
+        };
+
+        // Create a processor object with appropriate args
+        let processor: Processor = Processor::new(processor_args[0], processor_args[1], None, "");
+
+        // Validate output
+        let mig_devices: Vec<MigDevice> = match processor.process(None, None) {
+            Ok(Some(valid_output)) => {
+                find_mig_devices(&parse_gpu_list(&valid_output.join("\n")), uuid)
+            }
+            Ok(None) => return Err(String::from("Process encountered an unknown error..")),
+            Err(err) => return Err(String::from(err.message())),
+        };
+
+        // Remember parent of each MIG device, their readings come from it's full query
+        let mut mig_parents: RefMut<HashMap<String, (String, u32)>> =
+            self.imp().mig_parents.borrow_mut();
+        for mig_device in mig_devices.iter() {
+            mig_parents.insert(
+                mig_device.uuid.clone(),
+                (String::from(uuid), mig_device.index),
+            );
+        }
+
+        Ok(mig_devices)
+    }
+
+    /**
+     * Name:
+     * get_gpu_details
+     *
+     * Description:
+     * Grab the full nvidia-smi query of a GPU (or MIG device) given it's uuid
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Some readings (i.e. MIG devices) are only listed here, not by `--query-gpu`
+     */
+    pub fn get_gpu_details(&self, uuid: &str) -> Result<String, String> {
+        self.run_query(&(String::from("-q -i ") + uuid))
+    }
+
+    /**
+     * Name:
+     * get_pcie_details
+     *
+     * Description:
+     * Grab the PCIe section of the nvidia-smi query of a GPU given it's uuid
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Reused for a moment, so reading both RX & TX only runs nvidia-smi once
+     */
+    fn get_pcie_details(&self, uuid: &str) -> Result<String, String> {
+        if let Some((read_at, details)) = self.imp().pcie_details.borrow().get(uuid) {
+            if read_at.elapsed() < PCIE_DETAILS_LIFETIME {
+                return Ok(details.clone());
+            }
+        }
+
+        let details: String = self.run_query(&(String::from("-q -d PCIE -i ") + uuid))?;
+        self.imp()
+            .pcie_details
+            .borrow_mut()
+            .insert(String::from(uuid), (Instant::now(), details.clone()));
+
+        Ok(details)
+    }
+
+    /**
+     * Name:
+     * run_query
+     *
+     * Description:
+     * Run an nvidia-smi query (i.e. `-q -i UUID`) using the current provider
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Not supported by Nvidia Settings
+     */
+    fn run_query(&self, query: &str) -> Result<String, String> {
+        // Check provider type
+        let processor: Processor = match self.property::<i32>("provider-type") {
+            // Nvidia Settings/SMI OR Nvidia SMI
+            0 | 2 => Processor::new("nvidia-smi", query, None, ""),
+            // Nvidia Optimus
+            3 => Processor::new("optirun", &(String::from("nvidia-smi ") + query), None, ""),
+            // Nvidia Settings
+            1 => {
+                // Return error..
+                return Err(String::from("Not supported by this provider.."));
+            }
+            _ => {
+                // Return error..
+                return Err(String::from("Invalid provider, check preferences.."));
+            }
+        };
+
+        // Validate output
+        match processor.process(None, None) {
+            Ok(Some(valid_output)) => Ok(valid_output.join("\n")),
+            Ok(None) => Err(String::from("Process encountered an unknown error..")),
+            Err(err) => Err(String::from(err.message())),
+        }
+    }
+
+    /**
+     * Name:
+     * get_pcie_throughput
+     *
+     * Description:
+     * Grab the PCIe RX (`pcie_rx`) or TX (`pcie_tx`) throughput of a GPU given it's uuid
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Read from `nvidia-smi -q -d PCIE`, shared between RX & TX
+     */
+    fn get_pcie_throughput(&self, uuid: &str, property: &str) -> Result<String, String> {
+        let direction: &str = match property {
+            "pcie_rx" => "Rx",
+            _ => "Tx",
+        };
+
+        match parse_pcie_throughput(&self.get_pcie_details(uuid)?, direction) {
+            Some(throughput) => Ok(format_throughput(throughput)),
+            None => Ok(String::from("N/A")),
+        }
+    }
+
+    /**
+     * Name:
+     * get_mig_data
+     *
+     * Description:
+     * Grab MIG device data given the parent GPU uuid, the index of the MIG device and a property name
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Anything not listed for MIG devices (i.e. clocks) is an error
+     */
+    fn get_mig_data(
+        &self,
+        parent_uuid: &str,
+        index: u32,
+        property: &str,
+    ) -> Result<String, String> {
+        // Translate to appropriate name
+        let reading: &str = match mig_reading_name(property) {
+            Some(name) => name,
+            None => return Err(String::from("Not supported by MIG devices..")),
+        };
+
+        // Find reading in the MIG section of the parent's full query
+        let devices: Vec<MigDetails> = parse_mig_details(&self.get_gpu_details(parent_uuid)?);
+        match find_mig_reading(&devices, index, reading) {
+            Some(value) => Ok(value),
+            None => Ok(String::from("N/A")),
+        }
+    }
+
     /**
     * Name:
     * get_gpu_data
@@ -207,6 +438,17 @@ impl Provider {
         // println!("ASKED TO FETCH: `{}`", property); //TEST
         // println!("TYPE: `{}`", self.property::<i32>("provider_type")); //TEST
 
+        // MIG devices aren't supported by `--query-gpu`, read them from their parent's full query
+        let mig_parent: Option<(String, u32)> = self.imp().mig_parents.borrow().get(uuid).cloned();
+        if let Some((parent_uuid, index)) = mig_parent {
+            return self.get_mig_data(&parent_uuid, index, property);
+        }
+
+        // PCIe throughput is only listed in the full nvidia-smi query
+        if let "pcie_rx" | "pcie_tx" = property {
+            return self.get_pcie_throughput(uuid, property);
+        }
+
         // Translate to appropriate name
         let final_property: String;
         match self.property::<i32>("provider_type") {
@@ -222,6 +464,62 @@ impl Provider {
                 "memory_total" => final_property = String::from("memory.total"),
                 "fan_speed" => final_property = String::from("fan.speed"),
                 "power_usage" => final_property = String::from("power.draw"),
+                "gr_clock" => final_property = String::from("clocks.gr"),
+                "sm_clock" => final_property = String::from("clocks.sm"),
+                "mem_clock" => final_property = String::from("clocks.mem"),
+                "video_clock" => final_property = String::from("clocks.video"),
+                "gr_clock_app" => final_property = String::from("clocks.applications.graphics"),
+                "mem_clock_app" => final_property = String::from("clocks.applications.memory"),
+                "gr_clock_max" => final_property = String::from("clocks.max.graphics"),
+                "sm_clock_max" => final_property = String::from("clocks.max.sm"),
+                "mem_clock_max" => final_property = String::from("clocks.max.memory"),
+                "throttle_reasons" => {
+                    final_property = String::from("clocks_throttle_reasons.active")
+                }
+                "pcie_gen_current" => final_property = String::from("pcie.link.gen.current"),
+                "pcie_gen_max" => final_property = String::from("pcie.link.gen.max"),
+                "pcie_width_current" => final_property = String::from("pcie.link.width.current"),
+                "pcie_width_max" => final_property = String::from("pcie.link.width.max"),
+                "ecc_mode" => final_property = String::from("ecc.mode.current"),
+                "ecc_sbe_volatile" => {
+                    final_property = String::from("ecc.errors.corrected.volatile.total")
+                }
+                "ecc_dbe_volatile" => {
+                    final_property = String::from("ecc.errors.uncorrected.volatile.total")
+                }
+                "ecc_sbe_aggregate" => {
+                    final_property = String::from("ecc.errors.corrected.aggregate.total")
+                }
+                "ecc_dbe_aggregate" => {
+                    final_property = String::from("ecc.errors.uncorrected.aggregate.total")
+                }
+                "retired_pages_sbe" => {
+                    final_property = String::from("retired_pages.single_bit_ecc.count")
+                }
+                "retired_pages_dbe" => {
+                    final_property = String::from("retired_pages.double_bit.count")
+                }
+                "retired_pages_pending" => final_property = String::from("retired_pages.pending"),
+                "remapped_rows_correctable" => {
+                    final_property = String::from("remapped_rows.correctable")
+                }
+                "remapped_rows_uncorrectable" => {
+                    final_property = String::from("remapped_rows.uncorrectable")
+                }
+                "remapped_rows_pending" => final_property = String::from("remapped_rows.pending"),
+                "remapped_rows_failure" => final_property = String::from("remapped_rows.failure"),
+                "power_fraction" => {
+                    final_property = String::from("power.draw,enforced.power.limit")
+                }
+                "power_limit" => final_property = String::from("power.limit"),
+                "enforced_power_limit" => final_property = String::from("enforced.power.limit"),
+                "power_min_limit" => final_property = String::from("power.min_limit"),
+                "power_max_limit" => final_property = String::from("power.max_limit"),
+                "power_default_limit" => final_property = String::from("power.default_limit"),
+                "pstate" => final_property = String::from("pstate"),
+                "perf_level" => final_property = String::from("GPUCurrentPerfLevel"),
+                "perf_modes" => final_property = String::from("GPUPerfModes"),
+                "pcie_util" => final_property = String::from("GPUUtilization.pcie"),
                 _ => return Err(String::from("Unknown property..")),
             },
             // Nvidia Settings
@@ -234,6 +532,15 @@ impl Provider {
                 "memory_total" => final_property = String::from("TotalDedicatedGPUMemory"),
                 // This isn't queried by GPU UUID, just returns *all*
                 //"fan_speed" => final_property = String::from("GPUCurrentFanSpeedRPM"),
+                "gr_clock" => final_property = String::from("GPUCurrentClockFreqs.gr"),
+                "mem_clock" => final_property = String::from("GPUCurrentClockFreqs.mem"),
+                "pcie_gen_current" => final_property = String::from("PCIECurrentLinkSpeed"),
+                "pcie_gen_max" => final_property = String::from("PCIEMaxLinkSpeed"),
+                "pcie_width_current" => final_property = String::from("PCIECurrentLinkWidth"),
+                "pcie_width_max" => final_property = String::from("PCIEMaxLinkWidth"),
+                "pcie_util" => final_property = String::from("GPUUtilization.pcie"),
+                "perf_level" => final_property = String::from("GPUCurrentPerfLevel"),
+                "perf_modes" => final_property = String::from("GPUPerfModes"),
                 _ => return Err(String::from("Unknown property..")),
             },
             // Nvidia SMI
@@ -248,6 +555,59 @@ impl Provider {
                 "memory_total" => final_property = String::from("memory.total"),
                 "fan_speed" => final_property = String::from("fan.speed"),
                 "power_usage" => final_property = String::from("power.draw"),
+                "gr_clock" => final_property = String::from("clocks.gr"),
+                "sm_clock" => final_property = String::from("clocks.sm"),
+                "mem_clock" => final_property = String::from("clocks.mem"),
+                "video_clock" => final_property = String::from("clocks.video"),
+                "gr_clock_app" => final_property = String::from("clocks.applications.graphics"),
+                "mem_clock_app" => final_property = String::from("clocks.applications.memory"),
+                "gr_clock_max" => final_property = String::from("clocks.max.graphics"),
+                "sm_clock_max" => final_property = String::from("clocks.max.sm"),
+                "mem_clock_max" => final_property = String::from("clocks.max.memory"),
+                "throttle_reasons" => {
+                    final_property = String::from("clocks_throttle_reasons.active")
+                }
+                "pcie_gen_current" => final_property = String::from("pcie.link.gen.current"),
+                "pcie_gen_max" => final_property = String::from("pcie.link.gen.max"),
+                "pcie_width_current" => final_property = String::from("pcie.link.width.current"),
+                "pcie_width_max" => final_property = String::from("pcie.link.width.max"),
+                "ecc_mode" => final_property = String::from("ecc.mode.current"),
+                "ecc_sbe_volatile" => {
+                    final_property = String::from("ecc.errors.corrected.volatile.total")
+                }
+                "ecc_dbe_volatile" => {
+                    final_property = String::from("ecc.errors.uncorrected.volatile.total")
+                }
+                "ecc_sbe_aggregate" => {
+                    final_property = String::from("ecc.errors.corrected.aggregate.total")
+                }
+                "ecc_dbe_aggregate" => {
+                    final_property = String::from("ecc.errors.uncorrected.aggregate.total")
+                }
+                "retired_pages_sbe" => {
+                    final_property = String::from("retired_pages.single_bit_ecc.count")
+                }
+                "retired_pages_dbe" => {
+                    final_property = String::from("retired_pages.double_bit.count")
+                }
+                "retired_pages_pending" => final_property = String::from("retired_pages.pending"),
+                "remapped_rows_correctable" => {
+                    final_property = String::from("remapped_rows.correctable")
+                }
+                "remapped_rows_uncorrectable" => {
+                    final_property = String::from("remapped_rows.uncorrectable")
+                }
+                "remapped_rows_pending" => final_property = String::from("remapped_rows.pending"),
+                "remapped_rows_failure" => final_property = String::from("remapped_rows.failure"),
+                "power_fraction" => {
+                    final_property = String::from("power.draw,enforced.power.limit")
+                }
+                "power_limit" => final_property = String::from("power.limit"),
+                "enforced_power_limit" => final_property = String::from("enforced.power.limit"),
+                "power_min_limit" => final_property = String::from("power.min_limit"),
+                "power_max_limit" => final_property = String::from("power.max_limit"),
+                "power_default_limit" => final_property = String::from("power.default_limit"),
+                "pstate" => final_property = String::from("pstate"),
                 _ => return Err(String::from("Unknown property..")),
             },
             // Nvidia Optimus
@@ -262,12 +622,274 @@ impl Provider {
                 "memory_total" => final_property = String::from("memory.total"),
                 "fan_speed" => final_property = String::from("fan.speed"),
                 "power_usage" => final_property = String::from("power.draw"),
+                "gr_clock" => final_property = String::from("clocks.gr"),
+                "sm_clock" => final_property = String::from("clocks.sm"),
+                "mem_clock" => final_property = String::from("clocks.mem"),
+                "video_clock" => final_property = String::from("clocks.video"),
+                "gr_clock_app" => final_property = String::from("clocks.applications.graphics"),
+                "mem_clock_app" => final_property = String::from("clocks.applications.memory"),
+                "gr_clock_max" => final_property = String::from("clocks.max.graphics"),
+                "sm_clock_max" => final_property = String::from("clocks.max.sm"),
+                "mem_clock_max" => final_property = String::from("clocks.max.memory"),
+                "throttle_reasons" => {
+                    final_property = String::from("clocks_throttle_reasons.active")
+                }
+                "pcie_gen_current" => final_property = String::from("pcie.link.gen.current"),
+                "pcie_gen_max" => final_property = String::from("pcie.link.gen.max"),
+                "pcie_width_current" => final_property = String::from("pcie.link.width.current"),
+                "pcie_width_max" => final_property = String::from("pcie.link.width.max"),
+                "ecc_mode" => final_property = String::from("ecc.mode.current"),
+                "ecc_sbe_volatile" => {
+                    final_property = String::from("ecc.errors.corrected.volatile.total")
+                }
+                "ecc_dbe_volatile" => {
+                    final_property = String::from("ecc.errors.uncorrected.volatile.total")
+                }
+                "ecc_sbe_aggregate" => {
+                    final_property = String::from("ecc.errors.corrected.aggregate.total")
+                }
+                "ecc_dbe_aggregate" => {
+                    final_property = String::from("ecc.errors.uncorrected.aggregate.total")
+                }
+                "retired_pages_sbe" => {
+                    final_property = String::from("retired_pages.single_bit_ecc.count")
+                }
+                "retired_pages_dbe" => {
+                    final_property = String::from("retired_pages.double_bit.count")
+                }
+                "retired_pages_pending" => final_property = String::from("retired_pages.pending"),
+                "remapped_rows_correctable" => {
+                    final_property = String::from("remapped_rows.correctable")
+                }
+                "remapped_rows_uncorrectable" => {
+                    final_property = String::from("remapped_rows.uncorrectable")
+                }
+                "remapped_rows_pending" => final_property = String::from("remapped_rows.pending"),
+                "remapped_rows_failure" => final_property = String::from("remapped_rows.failure"),
+                "power_fraction" => {
+                    final_property = String::from("power.draw,enforced.power.limit")
+                }
+                "power_limit" => final_property = String::from("power.limit"),
+                "enforced_power_limit" => final_property = String::from("enforced.power.limit"),
+                "power_min_limit" => final_property = String::from("power.min_limit"),
+                "power_max_limit" => final_property = String::from("power.max_limit"),
+                "power_default_limit" => final_property = String::from("power.default_limit"),
+                "pstate" => final_property = String::from("pstate"),
                 _ => return Err(String::from("Unknown property..")),
             },
             // ???
             _ => return Err(String::from("Unknown provider type..")),
         }
 
+        // Grab relevant property and run it
+        self.run_property(uuid, &final_property)
+    }
+
+    /**
+     * Name:
+     * get_targets
+     *
+     * Description:
+     * Grab the indexes of all targets of a certain type (i.e. fans) belonging to a GPU
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Only nvidia-settings knows about targets, the fan to GPU mapping comes from
+     * `nvidia-settings -q gpus -V` and `nvidia-settings -q fans -V`
+     */
+    pub fn get_targets(&self, uuid: &str, target_type: TargetType) -> Result<Vec<u32>, String> {
+        // Check provider type
+        match self.property::<i32>("provider-type") {
+            // Nvidia Settings/SMI OR Nvidia Settings
+            0 | 1 => {}
+            // Nvidia SMI or Nvidia Optimus
+            2 | 3 => {
+                // Return error..
+                return Err(String::from("Targets not supported by this provider.."));
+            }
+            _ => {
+                // Return error..
+                return Err(String::from("Invalid provider, check preferences.."));
+            }
+        }
+
+        // List all gpus and all targets of this type
+        let gpus: Vec<Target> = parse_targets(&self.list_targets(TargetType::Gpu)?);
+        let targets: Vec<Target> = parse_targets(&self.list_targets(target_type)?);
+
+        // Find targets belonging to this gpu
+        match find_gpu_index(&gpus, uuid) {
+            Some(gpu_index) => Ok(find_connected_targets(&gpus, &targets, gpu_index)),
+            None => Err(String::from("Cannot find GPU in target list..")),
+        }
+    }
+
+    /**
+     * Name:
+     * get_gpu_index
+     *
+     * Description:
+     * Grab the index of a GPU as used by nvidia-settings targets (i.e. `[gpu:0]`)
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn get_gpu_index(&self, uuid: &str) -> Result<u32, String> {
+        // Check provider type
+        match self.property::<i32>("provider-type") {
+            // Nvidia Settings/SMI OR Nvidia Settings
+            0 | 1 => {}
+            // Nvidia SMI or Nvidia Optimus
+            2 | 3 => {
+                // Return error..
+                return Err(String::from("Targets not supported by this provider.."));
+            }
+            _ => {
+                // Return error..
+                return Err(String::from("Invalid provider, check preferences.."));
+            }
+        }
+
+        // Find gpu in list of all gpus
+        let gpus: Vec<Target> = parse_targets(&self.list_targets(TargetType::Gpu)?);
+        match find_gpu_index(&gpus, uuid) {
+            Some(gpu_index) => Ok(gpu_index),
+            None => Err(String::from("Cannot find GPU in target list..")),
+        }
+    }
+
+    /**
+     * Name:
+     * get_clock_offset
+     *
+     * Description:
+     * Grab the current value and valid range of a clock offset
+     *
+     * Made:
+     * 23/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn get_clock_offset(
+        &self,
+        uuid: &str,
+        offset: ClockOffset,
+    ) -> Result<(i32, OffsetRange), String> {
+        // Find index of gpu (also checks provider type)
+        let gpu_index: u32 = self.get_gpu_index(uuid)?;
+
+        // Create a processor object with appropriate args
+        let query: String = create_offset_query(gpu_index, offset)[1..].join(" ");
+        let processor: Processor = Processor::new("nvidia-settings", &query, None, "");
+
+        // Validate output
+        let output: String = match processor.process(None, None) {
+            Ok(Some(valid_output)) => valid_output.join("\n"),
+            Ok(None) => return Err(String::from("Process encountered an unknown error..")),
+            Err(err) => return Err(String::from(err.message())),
+        };
+
+        Ok((parse_offset_value(&output)?, parse_offset_range(&output)?))
+    }
+
+    /**
+     * Name:
+     * list_targets
+     *
+     * Description:
+     * Run the nvidia-settings query listing all targets of a certain type
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn list_targets(&self, target_type: TargetType) -> Result<String, String> {
+        // Create a processor object with appropriate args
+        let query: String = String::from("-q ") + target_type.query() + " -V";
+        let processor: Processor = Processor::new("nvidia-settings", &query, None, "");
+
+        // Validate output
+        match processor.process(None, None) {
+            Ok(Some(valid_output)) => Ok(valid_output.join("\n")),
+            Ok(None) => Err(String::from("Process encountered an unknown error..")),
+            Err(err) => Err(String::from(err.message())),
+        }
+    }
+
+    /**
+     * Name:
+     * get_target_data
+     *
+     * Description:
+     * Grab fan/thermal sensor data from provider program given a target index and property name
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn get_target_data(&self, index: u32, property: &str) -> Result<String, String> {
+        // Translate to appropriate name
+        let final_property: &str = match self.property::<i32>("provider-type") {
+            // Nvidia Settings/SMI OR Nvidia Settings
+            0 | 1 => match property {
+                "fan_speed" => "GPUCurrentFanSpeed",
+                "fan_speed_rpm" => "GPUCurrentFanSpeedRPM",
+                "fan_target" => "GPUTargetFanSpeed",
+                "thermal_sensor" => "ThermalSensorReading",
+                _ => return Err(String::from("Unknown property..")),
+            },
+            // Nvidia SMI or Nvidia Optimus
+            2 | 3 => return Err(String::from("Targets not supported by this provider..")),
+            // ???
+            _ => return Err(String::from("Unknown provider type..")),
+        };
+
+        // Grab relevant property and run it
+        self.run_property(&index.to_string(), final_property)
+    }
+
+    /**
+     * Name:
+     * run_property
+     *
+     * Description:
+     * Find a property by it's id and run it for the given gpu uuid/target index
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    fn run_property(&self, id: &str, final_property: &str) -> Result<String, String> {
         // Grab relevant property
         for prop in self.imp().properties.borrow().iter() {
             // println!("current property: `{}`", prop.property::<String>("id")); //TEST
@@ -275,7 +897,7 @@ impl Provider {
 
             if prop.property::<String>("id") == final_property {
                 // Run and return output
-                match prop.to_owned().parse(uuid) {
+                match prop.to_owned().parse(id) {
                     Some(stat) => return Ok(stat),
                     None => {
                         return Err(String::from(
@@ -0,0 +1,569 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Log every sample of a GPU to file (CSV or JSON Lines), starting a new file each day or when too big
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Files are named after the GPU & day (i.e. `GPU-1a2b3c4d_2023-02-04.csv`), with a number added for
+ * any further files that day (i.e. `GPU-1a2b3c4d_2023-02-04.1.csv`)
+ */
+// Imports
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+// Modules
+use crate::{
+    history::{escape_csv, format_timestamp},
+    metrics_store::default_store_path,
+    sampler::GpuSample,
+};
+
+/// Directory (next to the metrics store) logs are kept in by default
+pub const LOG_DIRECTORY: &str = "logs";
+
+/// Format of a log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Csv,
+    JsonLines,
+}
+
+/**
+ * Name:
+ * LogFormat
+ *
+ * Description:
+ * Trait shared by all LogFormat objects
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl LogFormat {
+    /**
+     * Name:
+     * from_name
+     *
+     * Description:
+     * Find a format from it's name in settings (`csv` or `jsonl`)
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(LogFormat::Csv),
+            "jsonl" => Some(LogFormat::JsonLines),
+            _ => None,
+        }
+    }
+
+    /**
+     * Name:
+     * name
+     *
+     * Description:
+     * Name of the format in settings, also used as the file extension
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogFormat::Csv => "csv",
+            LogFormat::JsonLines => "jsonl",
+        }
+    }
+}
+
+/// When to start a new log file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RotationPolicy {
+    pub max_bytes: u64,
+    pub daily: bool,
+}
+
+/**
+ * Name:
+ * RotationPolicy
+ *
+ * Description:
+ * Default rotation, a new file each day or after 64 MiB
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Default for RotationPolicy {
+    fn default() -> Self {
+        RotationPolicy {
+            max_bytes: 64 * 1024 * 1024,
+            daily: true,
+        }
+    }
+}
+
+/// Structure for the open log file of a single GPU
+#[derive(Debug)]
+pub struct SampleLog {
+    directory: PathBuf,
+    uuid: String,
+    format: LogFormat,
+    policy: RotationPolicy,
+    file: Option<File>,
+    path: Option<PathBuf>,
+    day: String,
+    part: u32,
+    size: u64,
+}
+
+/**
+ * Name:
+ * SampleLog
+ *
+ * Description:
+ * Trait shared by all SampleLog objects
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl SampleLog {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a log for a GPU, no file is created until the first sample is written
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(directory: &Path, uuid: &str, format: LogFormat, policy: RotationPolicy) -> Self {
+        SampleLog {
+            directory: directory.to_path_buf(),
+            uuid: String::from(uuid),
+            format,
+            policy,
+            file: None,
+            path: None,
+            day: String::new(),
+            part: 0,
+            size: 0,
+        }
+    }
+
+    /**
+     * Name:
+     * directory
+     *
+     * Description:
+     * Directory log files are written to
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /**
+     * Name:
+     * format
+     *
+     * Description:
+     * Format log files are written in
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn format(&self) -> LogFormat {
+        self.format
+    }
+
+    /**
+     * Name:
+     * set_policy
+     *
+     * Description:
+     * Change when to start a new log file
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Applied at the next sample written
+     */
+    pub fn set_policy(&mut self, policy: RotationPolicy) {
+        self.policy = policy;
+    }
+
+    /**
+     * Name:
+     * path
+     *
+     * Description:
+     * Path of the file currently being written, if any
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /**
+     * Name:
+     * write
+     *
+     * Description:
+     * Write a sample as a single row, starting a new file first if needed
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * A file is never split part way through a row, so can go over the size limit by one row
+     */
+    pub fn write(&mut self, sample: &GpuSample) -> Result<(), String> {
+        let row: String = match self.format {
+            LogFormat::Csv => format_csv_row(sample),
+            LogFormat::JsonLines => format_json_line(sample),
+        };
+
+        // Start a new file if needed
+        let day: String = format_timestamp(sample.timestamp)[..10].to_string();
+        if self.file.is_none() || (self.policy.daily && day != self.day) {
+            self.day = day;
+            self.part = 0;
+            self.open(sample)?;
+        } else if self.size > 0 && self.size + row.len() as u64 > self.policy.max_bytes {
+            self.part += 1;
+            self.open(sample)?;
+        }
+
+        // Write row
+        match self.file.as_mut() {
+            Some(file) => {
+                file.write_all(row.as_bytes())
+                    .map_err(|err| format!("Cannot write to log: {}", err))?;
+                self.size += row.len() as u64;
+
+                Ok(())
+            }
+            None => Err(String::from("Log file isn't open..")),
+        }
+    }
+
+    /**
+     * Name:
+     * open
+     *
+     * Description:
+     * Create the next unused log file, writing the header if there is one
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Existing files are never appended to (i.e. after restarting), the next number is used instead
+     */
+    fn open(&mut self, sample: &GpuSample) -> Result<(), String> {
+        fs::create_dir_all(&self.directory)
+            .map_err(|err| format!("Cannot create log directory: {}", err))?;
+
+        loop {
+            let path: PathBuf =
+                self.directory
+                    .join(log_file_name(&self.uuid, &self.day, self.part, self.format));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    self.file = Some(file);
+                    self.path = Some(path);
+                    self.size = 0;
+                    break;
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => self.part += 1,
+                Err(err) => {
+                    self.file = None;
+                    return Err(format!("Cannot create log file: {}", err));
+                }
+            }
+        }
+
+        // Header
+        if self.format == LogFormat::Csv {
+            let header: String = format_csv_header(sample);
+            if let Some(file) = self.file.as_mut() {
+                file.write_all(header.as_bytes())
+                    .map_err(|err| format!("Cannot write to log: {}", err))?;
+            }
+            self.size += header.len() as u64;
+        }
+
+        Ok(())
+    }
+}
+
+/**
+ * Name:
+ * log_file_name
+ *
+ * Description:
+ * Name of a log file of a GPU (i.e. `GPU-1a2b3c4d_2023-02-04.1.csv`)
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The first file of a day isn't numbered
+ */
+pub fn log_file_name(uuid: &str, day: &str, part: u32, format: LogFormat) -> String {
+    match part {
+        0 => format!("{}_{}.{}", uuid, day, format.name()),
+        _ => format!("{}_{}.{}.{}", uuid, day, part, format.name()),
+    }
+}
+
+/**
+ * Name:
+ * default_log_directory
+ *
+ * Description:
+ * Directory logs are kept in if none is set, next to the metrics store
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * i.e. `~/.local/share/gtk4-nvidia-monitor-rust/logs`
+ */
+pub fn default_log_directory() -> Option<PathBuf> {
+    default_store_path()
+        .as_deref()
+        .and_then(Path::parent)
+        .map(|directory| directory.join(LOG_DIRECTORY))
+}
+
+/**
+ * Name:
+ * format_csv_header
+ *
+ * Description:
+ * Header of a CSV log, with the unit of each property (i.e. `temp (°C)`)
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn format_csv_header(sample: &GpuSample) -> String {
+    let mut fields: Vec<String> = vec![
+        String::from("timestamp"),
+        String::from("time"),
+        String::from("uuid"),
+    ];
+    fields.extend(
+        sample
+            .readings
+            .iter()
+            .map(|reading| match reading.metric.unit {
+                "" => String::from(reading.metric.name),
+                unit => escape_csv(&format!("{} ({})", reading.metric.name, unit)),
+            }),
+    );
+
+    fields.join(",") + "\n"
+}
+
+/**
+ * Name:
+ * format_csv_row
+ *
+ * Description:
+ * A sample as a row of a CSV log
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Values that couldn't be read are left empty
+ */
+pub fn format_csv_row(sample: &GpuSample) -> String {
+    let mut fields: Vec<String> = vec![
+        sample.timestamp.to_string(),
+        format_timestamp(sample.timestamp),
+        escape_csv(&sample.uuid),
+    ];
+    fields.extend(sample.readings.iter().map(|reading| match reading.value {
+        Some(value) => value.to_string(),
+        None => String::new(),
+    }));
+
+    fields.join(",") + "\n"
+}
+
+/**
+ * Name:
+ * format_json_line
+ *
+ * Description:
+ * A sample as a line of a JSON Lines log, each value given with it's unit
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * i.e. `{"timestamp":1675468800000,...,"values":{"temp":{"value":52,"unit":"°C"}}}`.
+ * Values that couldn't be read are `null`
+ */
+pub fn format_json_line(sample: &GpuSample) -> String {
+    let values: Vec<String> = sample
+        .readings
+        .iter()
+        .map(|reading| {
+            format!(
+                "{}:{{\"value\":{},\"unit\":{}}}",
+                escape_json(reading.metric.name),
+                match reading.value {
+                    Some(value) => value.to_string(),
+                    None => String::from("null"),
+                },
+                escape_json(reading.metric.unit)
+            )
+        })
+        .collect();
+
+    format!(
+        "{{\"timestamp\":{},\"time\":{},\"uuid\":{},\"name\":{},\"values\":{{{}}}}}\n",
+        sample.timestamp,
+        escape_json(&format_timestamp(sample.timestamp)),
+        escape_json(&sample.uuid),
+        escape_json(&sample.name),
+        values.join(",")
+    )
+}
+
+/**
+ * Name:
+ * escape_json
+ *
+ * Description:
+ * Quote a string for JSON
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn escape_json(text: &str) -> String {
+    let mut output: String = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+
+    output
+}
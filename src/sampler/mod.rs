@@ -0,0 +1,430 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Typed readings of every GPU from a single nvidia-smi query (without any formatting)
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Doesn't call into GTK, so can be used without a window (i.e. for logging & exporters).
+ * Properties are those in the global property list with a `query`. Values are kept in the units nvidia-smi reports them in (`--format=csv,noheader,nounits`).
+ * Row remapping (`remapped_rows.*`) isn't sampled, as it's only given by `--query-remapped-rows`,
+ * which can't be combined with `--query-gpu` into one query
+ */
+// Imports
+use std::process::{Command, Output};
+
+// Modules
+use crate::{
+    history::now_millis,
+    property::{PropertyDescription, PROPERTY_LIST},
+};
+
+/// A property that can be read as a number from nvidia-smi (one with a `query`)
+pub type Metric = PropertyDescription;
+
+/// A single value of a GPU, `None` if it couldn't be read (i.e. not supported)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reading {
+    pub metric: &'static Metric,
+    pub value: Option<f64>,
+}
+
+/// Every value of a GPU read at the same time (ms since the unix epoch)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuSample {
+    pub uuid: String,
+    pub name: String,
+    pub timestamp: u64,
+    pub readings: Vec<Reading>,
+}
+
+/**
+ * Name:
+ * GpuSample
+ *
+ * Description:
+ * Trait shared by all GpuSample objects
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl GpuSample {
+    /**
+     * Name:
+     * value
+     *
+     * Description:
+     * Grab the value of a property, if it was read
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn value(&self, property: &str) -> Option<f64> {
+        self.readings
+            .iter()
+            .find(|reading| reading.metric.name == property)
+            .and_then(|reading| reading.value)
+    }
+}
+
+/// Structure for reading every GPU using nvidia-smi
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sampler {
+    command: Vec<String>,
+    metrics: Vec<&'static Metric>,
+}
+
+/**
+ * Name:
+ * Sampler
+ *
+ * Description:
+ * Trait shared by all Sampler objects
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Sampler {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a sampler reading every known property, using the given command (i.e. `["nvidia-smi"]`)
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * The query arguments are added to the end of the command
+     */
+    pub fn new(command: Vec<String>) -> Self {
+        Sampler {
+            command,
+            metrics: PROPERTY_LIST
+                .iter()
+                .filter(|description| !description.query.is_empty())
+                .collect(),
+        }
+    }
+
+    /**
+     * Name:
+     * with_properties
+     *
+     * Description:
+     * Create a sampler reading only the given properties
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Useful for older drivers that don't support every query
+     */
+    pub fn with_properties(command: Vec<String>, properties: &[&str]) -> Result<Self, String> {
+        let mut metrics: Vec<&'static Metric> = vec![];
+        for property in properties {
+            match find_metric(property) {
+                Some(metric) => metrics.push(metric),
+                None => return Err(format!("Property `{}` can't be sampled..", property)),
+            }
+        }
+
+        Ok(Sampler { command, metrics })
+    }
+
+    /**
+     * Name:
+     * metrics
+     *
+     * Description:
+     * The properties read by this sampler, in the order they are read
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn metrics(&self) -> &[&'static Metric] {
+        &self.metrics
+    }
+
+    /**
+     * Name:
+     * sample
+     *
+     * Description:
+     * Read every GPU (blocking)
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn sample(&self) -> Result<Vec<GpuSample>, String> {
+        let (program, arguments) = match self.command.split_first() {
+            Some(command) => command,
+            None => return Err(String::from("No command to sample with..")),
+        };
+
+        // Run query
+        let output: Output = Command::new(program)
+            .args(arguments)
+            .args(query_arguments(&self.metrics))
+            .output()
+            .map_err(|err| format!("Cannot run `{}`: {}", program, err))?;
+        if !output.status.success() {
+            return Err(format!(
+                "`{}` failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stdout).trim()
+            ));
+        }
+
+        parse_samples(
+            &String::from_utf8_lossy(&output.stdout),
+            &self.metrics,
+            now_millis(),
+        )
+    }
+
+    /**
+     * Name:
+     * probe
+     *
+     * Description:
+     * Create a sampler reading only the properties this driver supports (blocking)
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Older drivers reject the whole query if a single property is unknown, so each property is tried on it's own
+     * if the full query fails. Meant to be run once, before sampling regularly
+     */
+    pub fn probe(&self) -> Sampler {
+        if self.sample().is_ok() {
+            return self.clone();
+        }
+
+        let supported: Vec<&str> = self
+            .metrics
+            .iter()
+            .filter(|metric| {
+                Sampler {
+                    command: self.command.clone(),
+                    metrics: vec![metric],
+                }
+                .sample()
+                .is_ok()
+            })
+            .map(|metric| metric.name)
+            .collect();
+
+        // Nothing works at all (i.e. no nvidia-smi), so leave the errors to sampling
+        if supported.is_empty() {
+            return self.clone();
+        }
+
+        Sampler::with_properties(self.command.clone(), &supported).unwrap_or_else(|_| self.clone())
+    }
+}
+
+/**
+ * Name:
+ * find_metric
+ *
+ * Description:
+ * Find the description of a property that can be sampled
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn find_metric(property: &str) -> Option<&'static Metric> {
+    PROPERTY_LIST
+        .iter()
+        .find(|description| description.name == property && !description.query.is_empty())
+}
+
+/**
+ * Name:
+ * sampler_command
+ *
+ * Description:
+ * The command used to run nvidia-smi with a provider type (from settings)
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Provider type 3 is Nvidia Optimus, which needs optirun
+ */
+pub fn sampler_command(provider_type: i32) -> Vec<String> {
+    match provider_type {
+        3 => vec![String::from("optirun"), String::from("nvidia-smi")],
+        _ => vec![String::from("nvidia-smi")],
+    }
+}
+
+/**
+ * Name:
+ * query_arguments
+ *
+ * Description:
+ * Arguments for nvidia-smi to read the given properties of every GPU
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The name comes last, as it's the only field that could contain a comma
+ */
+pub fn query_arguments(metrics: &[&Metric]) -> Vec<String> {
+    let mut fields: Vec<&str> = vec!["uuid"];
+    fields.extend(metrics.iter().map(|metric| metric.query));
+    fields.push("name");
+
+    vec![
+        format!("--query-gpu={}", fields.join(",")),
+        String::from("--format=csv,noheader,nounits"),
+    ]
+}
+
+/**
+ * Name:
+ * parse_value
+ *
+ * Description:
+ * Parse a single field of nvidia-smi output
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Fields that aren't supported are shown as `[N/A]` or `[Not Supported]`.
+ * Performance states are read as their number (i.e. `P2` is 2), bitmasks from hex (i.e. `0x0000000000000004`)
+ * and yes/no as 1/0
+ */
+pub fn parse_value(field: &str) -> Option<f64> {
+    let field: &str = field.trim();
+    match field {
+        "Yes" => return Some(1.0),
+        "No" => return Some(0.0),
+        _ => {}
+    }
+    if let Some(digits) = field.strip_prefix("0x") {
+        return u64::from_str_radix(digits, 16)
+            .ok()
+            .map(|bitmask| bitmask as f64);
+    }
+    let number: &str = field.strip_prefix('P').unwrap_or(field);
+
+    number.parse::<f64>().ok().filter(|value| value.is_finite())
+}
+
+/**
+ * Name:
+ * parse_samples
+ *
+ * Description:
+ * Parse the output of nvidia-smi (run with `query_arguments`) into a sample of each GPU
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn parse_samples(
+    output: &str,
+    metrics: &[&'static Metric],
+    timestamp: u64,
+) -> Result<Vec<GpuSample>, String> {
+    let mut samples: Vec<GpuSample> = vec![];
+
+    for line in output.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(',').collect();
+        if fields.len() < metrics.len() + 2 {
+            return Err(format!("Unexpected output from nvidia-smi: `{}`..", line));
+        }
+
+        samples.push(GpuSample {
+            uuid: String::from(fields[0].trim()),
+            name: String::from(fields[metrics.len() + 1..].join(",").trim()),
+            timestamp,
+            readings: metrics
+                .iter()
+                .zip(&fields[1..])
+                .map(|(metric, field)| Reading {
+                    metric,
+                    value: parse_value(field),
+                })
+                .collect(),
+        });
+    }
+
+    Ok(samples)
+}
@@ -0,0 +1,84 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Read secrets (MQTT password, InfluxDB token) from an environment variable or a file
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Secrets are never kept in settings or passed on the command line, where other users could read them.
+ * Only the path of a secret file is stored, and the file must only be readable by it's owner (i.e. `chmod 600`)
+ */
+// Imports
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+/// Environment variable holding the MQTT password, used if no password file is set
+pub const MQTT_PASSWORD_VARIABLE: &str = "NVIDIA_MONITOR_MQTT_PASSWORD";
+/// Environment variable holding the InfluxDB token, used if no token file is set
+pub const INFLUX_TOKEN_VARIABLE: &str = "NVIDIA_MONITOR_INFLUX_TOKEN";
+
+/**
+ * Name:
+ * read_secret_file
+ *
+ * Description:
+ * Read a secret from a file, without the trailing newline
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Refused if the file can be read or written by anyone other than it's owner
+ */
+pub fn read_secret_file(path: &Path) -> Result<String, String> {
+    let mode: u32 = fs::metadata(path)
+        .map_err(|err| format!("Cannot read `{}`: {}", path.display(), err))?
+        .permissions()
+        .mode();
+    if mode & 0o077 != 0 {
+        return Err(format!(
+            "`{}` can be read by other users, only it's owner should have access (chmod 600)..",
+            path.display()
+        ));
+    }
+
+    let contents: String = fs::read_to_string(path)
+        .map_err(|err| format!("Cannot read `{}`: {}", path.display(), err))?;
+
+    Ok(String::from(contents.trim_end_matches(&['\r', '\n'][..])))
+}
+
+/**
+ * Name:
+ * read_secret
+ *
+ * Description:
+ * Read a secret from a file if one is given, otherwise from an environment variable
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Empty if neither is set (i.e. no password)
+ */
+pub fn read_secret(variable: &str, path: Option<&Path>) -> Result<String, String> {
+    match path {
+        Some(valid_path) => read_secret_file(valid_path),
+        None => Ok(std::env::var(variable).unwrap_or_default()),
+    }
+}
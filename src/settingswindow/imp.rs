@@ -22,12 +22,12 @@ use gio::Settings;
 // Imports
 use glib::{once_cell::sync::Lazy, ParamSpec, Value};
 use glib::{once_cell::sync::OnceCell, signal::Inhibit, subclass::InitializingObject};
-use gtk::{subclass::prelude::*, CheckButton, CompositeTemplate, SpinButton, TemplateChild};
+use gtk::{subclass::prelude::*, CheckButton, CompositeTemplate, Entry, SpinButton, TemplateChild};
 use std::{cell::RefCell, cell::RefMut, rc::Rc};
 
 // Modules
 //use crate::utils::data_path;
-use crate::mainwindow::MainWindow;
+use crate::{history::with_history, mainwindow::MainWindow};
 
 /// Structure for storing a SettingsWindow object and any related information
 #[derive(Default)]
@@ -52,6 +52,52 @@ pub struct SettingsWindow {
     pub temp_unit_f: TemplateChild<CheckButton>,
     #[template_child]
     pub provider_input: TemplateChild<ComboRow>,
+    #[template_child]
+    pub watchdog_temp_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub watchdog_failures_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub history_retention_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub history_window_input: TemplateChild<ComboRow>,
+    #[template_child]
+    pub metrics_store_input: TemplateChild<CheckButton>,
+    #[template_child]
+    pub metrics_store_size_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub log_format_input: TemplateChild<ComboRow>,
+    #[template_child]
+    pub log_size_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub metrics_server_input: TemplateChild<CheckButton>,
+    #[template_child]
+    pub metrics_server_address_input: TemplateChild<Entry>,
+    #[template_child]
+    pub metrics_server_port_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub mqtt_input: TemplateChild<CheckButton>,
+    #[template_child]
+    pub mqtt_broker_input: TemplateChild<Entry>,
+    #[template_child]
+    pub mqtt_username_input: TemplateChild<Entry>,
+    #[template_child]
+    pub mqtt_password_file_input: TemplateChild<Entry>,
+    #[template_child]
+    pub mqtt_topic_prefix_input: TemplateChild<Entry>,
+    #[template_child]
+    pub mqtt_qos_input: TemplateChild<SpinButton>,
+    #[template_child]
+    pub influx_input: TemplateChild<CheckButton>,
+    #[template_child]
+    pub influx_target_input: TemplateChild<Entry>,
+    #[template_child]
+    pub influx_token_file_input: TemplateChild<Entry>,
+    #[template_child]
+    pub influx_measurement_input: TemplateChild<Entry>,
+    #[template_child]
+    pub influx_uuid_tag_input: TemplateChild<Entry>,
+    #[template_child]
+    pub influx_name_tag_input: TemplateChild<Entry>,
 }
 
 /// The central trait for subclassing a GObject
@@ -143,6 +189,452 @@ impl SettingsWindow {
         self.update_setting("refreshrate", new_value);
     }
 
+    /**
+     * Name:
+     * watchdog_temp_set
+     *
+     * Description:
+     * Template callback for setting critical temperature of the watchdog
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Always in Celcius, regardless of temperature unit
+     */
+    #[template_callback]
+    fn watchdog_temp_set(&self, button: &SpinButton) {
+        self.update_setting("watchdog-critical-temp", button.value_as_int());
+    }
+
+    /**
+     * Name:
+     * watchdog_failures_set
+     *
+     * Description:
+     * Template callback for setting how many failed refreshes trip the watchdog
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn watchdog_failures_set(&self, button: &SpinButton) {
+        self.update_setting("watchdog-max-failures", button.value_as_int());
+    }
+
+    /**
+     * Name:
+     * history_retention_set
+     *
+     * Description:
+     * Template callback for setting how long readings are kept for
+     *
+     * Made:
+     * 30/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Applied straight away, so shrinking it frees memory without reloading the pages
+     */
+    #[template_callback]
+    fn history_retention_set(&self, button: &SpinButton) {
+        let minutes: i32 = button.value_as_int();
+        self.update_setting("history-retention", minutes);
+        with_history(|history| history.set_retention(minutes as u64 * 60));
+    }
+
+    /**
+     * Name:
+     * metrics_store_set
+     *
+     * Description:
+     * Template callback for setting if history is kept on disk
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Applied after restarting
+     */
+    #[template_callback]
+    fn metrics_store_set(&self, button: &CheckButton) {
+        self.update_setting("metrics-store", button.is_active());
+    }
+
+    /**
+     * Name:
+     * metrics_store_size_set
+     *
+     * Description:
+     * Template callback for setting the most space (MiB) used by history on disk
+     *
+     * Made:
+     * 03/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Applied at the next maintenance of the store
+     */
+    #[template_callback]
+    fn metrics_store_size_set(&self, button: &SpinButton) {
+        self.update_setting("metrics-store-max-size", button.value_as_int());
+    }
+
+    /**
+     * Name:
+     * log_size_set
+     *
+     * Description:
+     * Template callback for setting the size (MiB) a log file can reach before a new one is started
+     *
+     * Made:
+     * 04/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn log_size_set(&self, button: &SpinButton) {
+        self.update_setting("log-max-size", button.value_as_int());
+    }
+
+    /**
+     * Name:
+     * metrics_server_set
+     *
+     * Description:
+     * Template callback for turning the metrics server on/off
+     *
+     * Made:
+     * 05/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn metrics_server_set(&self, button: &CheckButton) {
+        self.update_setting("metrics-server", button.is_active());
+    }
+
+    /**
+     * Name:
+     * metrics_server_address_set
+     *
+     * Description:
+     * Template callback for setting the address the metrics server listens on
+     *
+     * Made:
+     * 05/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn metrics_server_address_set(&self, entry: &Entry) {
+        self.update_setting("metrics-server-address", entry.text().trim().to_string());
+    }
+
+    /**
+     * Name:
+     * metrics_server_port_set
+     *
+     * Description:
+     * Template callback for setting the port the metrics server listens on
+     *
+     * Made:
+     * 05/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn metrics_server_port_set(&self, button: &SpinButton) {
+        self.update_setting("metrics-server-port", button.value_as_int());
+    }
+
+    /**
+     * Name:
+     * mqtt_set
+     *
+     * Description:
+     * Template callback for turning publishing to MQTT on/off
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn mqtt_set(&self, button: &CheckButton) {
+        self.update_setting("mqtt", button.is_active());
+    }
+
+    /**
+     * Name:
+     * mqtt_broker_set
+     *
+     * Description:
+     * Template callback for setting the URL of the MQTT broker
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn mqtt_broker_set(&self, entry: &Entry) {
+        self.update_setting("mqtt-broker", entry.text().trim().to_string());
+    }
+
+    /**
+     * Name:
+     * mqtt_username_set
+     *
+     * Description:
+     * Template callback for setting the username to log in to the MQTT broker with
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn mqtt_username_set(&self, entry: &Entry) {
+        self.update_setting("mqtt-username", entry.text().to_string());
+    }
+
+    /**
+     * Name:
+     * mqtt_password_file_set
+     *
+     * Description:
+     * Template callback for setting the file holding the password to log in to the MQTT broker with
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn mqtt_password_file_set(&self, entry: &Entry) {
+        self.update_setting("mqtt-password-file", entry.text().to_string());
+    }
+
+    /**
+     * Name:
+     * mqtt_topic_prefix_set
+     *
+     * Description:
+     * Template callback for setting the prefix of every MQTT topic
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn mqtt_topic_prefix_set(&self, entry: &Entry) {
+        self.update_setting(
+            "mqtt-topic-prefix",
+            entry.text().trim().trim_matches('/').to_string(),
+        );
+    }
+
+    /**
+     * Name:
+     * mqtt_qos_set
+     *
+     * Description:
+     * Template callback for setting the quality of service of every MQTT message
+     *
+     * Made:
+     * 06/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn mqtt_qos_set(&self, button: &SpinButton) {
+        self.update_setting("mqtt-qos", button.value_as_int());
+    }
+
+    /**
+     * Name:
+     * influx_set
+     *
+     * Description:
+     * Template callback for turning writing to InfluxDB on/off
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn influx_set(&self, button: &CheckButton) {
+        self.update_setting("influx", button.is_active());
+    }
+
+    /**
+     * Name:
+     * influx_target_set
+     *
+     * Description:
+     * Template callback for setting where InfluxDB lines are written to
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn influx_target_set(&self, entry: &Entry) {
+        self.update_setting("influx-target", entry.text().trim().to_string());
+    }
+
+    /**
+     * Name:
+     * influx_token_file_set
+     *
+     * Description:
+     * Template callback for setting the file holding the token to write to a InfluxDB HTTP target with
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn influx_token_file_set(&self, entry: &Entry) {
+        self.update_setting("influx-token-file", entry.text().to_string());
+    }
+
+    /**
+     * Name:
+     * influx_measurement_set
+     *
+     * Description:
+     * Template callback for setting the measurement of every InfluxDB line
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn influx_measurement_set(&self, entry: &Entry) {
+        self.update_setting("influx-measurement", entry.text().trim().to_string());
+    }
+
+    /**
+     * Name:
+     * influx_uuid_tag_set
+     *
+     * Description:
+     * Template callback for setting the name of the InfluxDB tag holding each GPU's UUID
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn influx_uuid_tag_set(&self, entry: &Entry) {
+        self.update_setting("influx-uuid-tag", entry.text().trim().to_string());
+    }
+
+    /**
+     * Name:
+     * influx_name_tag_set
+     *
+     * Description:
+     * Template callback for setting the name of the InfluxDB tag holding each GPU's name
+     *
+     * Made:
+     * 07/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    #[template_callback]
+    fn influx_name_tag_set(&self, entry: &Entry) {
+        self.update_setting("influx-name-tag", entry.text().trim().to_string());
+    }
+
     /**
      * Name:
      * temp_unit_set
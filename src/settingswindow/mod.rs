@@ -28,7 +28,10 @@ use gtk::{Adjustment, CheckButton, StringList};
 use std::cell::RefMut;
 
 // Modules
-use crate::{mainwindow::MainWindow, settingswindow::imp::ParentContainer, APP_ID};
+use crate::{
+    history::STATISTICS_WINDOWS, mainwindow::MainWindow, sample_log::LogFormat,
+    settingswindow::imp::ParentContainer, APP_ID,
+};
 
 // GObject wrapper for Property
 glib::wrapper! {
@@ -166,6 +169,102 @@ impl SettingsWindow {
             .refreshrate_input
             .configure(Some(&adjustment), 1.0, 0);
 
+        // Create adjustment settings for watchdog SpinButtons
+        let current_critical_temp: f64 = self.settings().int("watchdog-critical-temp").into();
+        let adjustment: Adjustment =
+            Adjustment::new(current_critical_temp, 50.0, 110.0, 1.0, 5.0, 0.0);
+        self.imp()
+            .watchdog_temp_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        let current_max_failures: f64 = self.settings().int("watchdog-max-failures").into();
+        let adjustment: Adjustment =
+            Adjustment::new(current_max_failures, 1.0, 20.0, 1.0, 5.0, 0.0);
+        self.imp()
+            .watchdog_failures_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        // Create adjustment settings for history retention SpinButton
+        let current_retention: f64 = self.settings().int("history-retention").into();
+        let adjustment: Adjustment =
+            Adjustment::new(current_retention, 1.0, 1440.0, 1.0, 10.0, 0.0);
+        self.imp()
+            .history_retention_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        // Load history on disk settings
+        self.imp()
+            .metrics_store_input
+            .set_active(self.settings().boolean("metrics-store"));
+        let current_max_size: f64 = self.settings().int("metrics-store-max-size").into();
+        let adjustment: Adjustment =
+            Adjustment::new(current_max_size, 16.0, 16384.0, 16.0, 256.0, 0.0);
+        self.imp()
+            .metrics_store_size_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        // Create adjustment settings for log file size SpinButton
+        let current_log_size: f64 = self.settings().int("log-max-size").into();
+        let adjustment: Adjustment = Adjustment::new(current_log_size, 1.0, 4096.0, 1.0, 64.0, 0.0);
+        self.imp()
+            .log_size_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        // Load metrics server settings
+        self.imp()
+            .metrics_server_input
+            .set_active(self.settings().boolean("metrics-server"));
+        self.imp()
+            .metrics_server_address_input
+            .set_text(&self.settings().string("metrics-server-address"));
+        let current_port: f64 = self.settings().int("metrics-server-port").into();
+        let adjustment: Adjustment = Adjustment::new(current_port, 1.0, 65535.0, 1.0, 100.0, 0.0);
+        self.imp()
+            .metrics_server_port_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        // Load MQTT settings
+        self.imp()
+            .mqtt_input
+            .set_active(self.settings().boolean("mqtt"));
+        self.imp()
+            .mqtt_broker_input
+            .set_text(&self.settings().string("mqtt-broker"));
+        self.imp()
+            .mqtt_username_input
+            .set_text(&self.settings().string("mqtt-username"));
+        self.imp()
+            .mqtt_password_file_input
+            .set_text(&self.settings().string("mqtt-password-file"));
+        self.imp()
+            .mqtt_topic_prefix_input
+            .set_text(&self.settings().string("mqtt-topic-prefix"));
+        let current_qos: f64 = self.settings().int("mqtt-qos").into();
+        let adjustment: Adjustment = Adjustment::new(current_qos, 0.0, 2.0, 1.0, 1.0, 0.0);
+        self.imp()
+            .mqtt_qos_input
+            .configure(Some(&adjustment), 1.0, 0);
+
+        // Load InfluxDB settings
+        self.imp()
+            .influx_input
+            .set_active(self.settings().boolean("influx"));
+        self.imp()
+            .influx_target_input
+            .set_text(&self.settings().string("influx-target"));
+        self.imp()
+            .influx_token_file_input
+            .set_text(&self.settings().string("influx-token-file"));
+        self.imp()
+            .influx_measurement_input
+            .set_text(&self.settings().string("influx-measurement"));
+        self.imp()
+            .influx_uuid_tag_input
+            .set_text(&self.settings().string("influx-uuid-tag"));
+        self.imp()
+            .influx_name_tag_input
+            .set_text(&self.settings().string("influx-name-tag"));
+
         // Group together Temp-Unit CheckButtons
         let group: &CheckButton = &self.imp().temp_unit_f;
         self.imp().temp_unit_c.set_group(Some(group));
@@ -209,6 +308,29 @@ impl SettingsWindow {
             }
             _ => panic!("..Unknown provider value in settings"),
         }
+
+        // Set options for statistics window
+        let items: Vec<&str> = STATISTICS_WINDOWS.iter().map(|(label, _)| *label).collect();
+        let model: StringList = StringList::new(&items);
+        self.imp().history_window_input.set_model(Some(&model));
+
+        // Set current selected option from settings
+        match self.settings().int("history-window") {
+            index @ 0..=3 => self.imp().history_window_input.set_selected(index as u32),
+            _ => panic!("..Unknown statistics window in settings"),
+        }
+
+        // Set options for log format
+        let items: [&str; 2] = ["CSV", "JSON Lines"];
+        let model: StringList = StringList::new(&items);
+        self.imp().log_format_input.set_model(Some(&model));
+
+        // Set current selected option from settings
+        match LogFormat::from_name(&self.settings().string("log-format")) {
+            Some(LogFormat::Csv) => self.imp().log_format_input.set_selected(0),
+            Some(LogFormat::JsonLines) => self.imp().log_format_input.set_selected(1),
+            None => panic!("..Unknown log format in settings"),
+        }
     }
 
     /**
@@ -307,6 +429,27 @@ impl SettingsWindow {
                 window.imp().update_setting("provider", provider_type);
             }),
         );
+
+        // Setup callback for changing statistics window
+        self.imp().history_window_input.connect_selected_notify(
+            clone!(@weak self as window => move |_| {
+                // Store chosen window
+                let index: i32 = window.imp().history_window_input.selected() as i32;
+                window.imp().update_setting("history-window", index);
+            }),
+        );
+
+        // Setup callback for changing log format
+        self.imp().log_format_input.connect_selected_notify(
+            clone!(@weak self as window => move |_| {
+                // Store chosen format
+                let format: LogFormat = match window.imp().log_format_input.selected() {
+                    0 => LogFormat::Csv,
+                    _ => LogFormat::JsonLines,
+                };
+                window.imp().update_setting("log-format", String::from(format.name()));
+            }),
+        );
     }
 
     /**
@@ -19,6 +19,7 @@
  */
 pub mod subprocess {
     // Imports
+    use crate::command::CommandRunner;
     use glib::Bytes;
     use gtk::{gio, glib, prelude::*};
     use std::ffi::OsStr;
@@ -138,4 +139,198 @@ pub mod subprocess {
             }
         }
     }
+
+    /**
+     * Name:
+     * exec_check_sync
+     *
+     * Description:
+     * Execute a command, returning an error (with any output) if the exit status isn't successful
+     *
+     * If given, @cancellable can be used to stop the process before it finishes.
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn exec_check_sync(
+        argv: &[&OsStr],
+        cancellable: Option<&impl IsA<gio::Cancellable>>,
+    ) -> Result<(), String> {
+        // Create subprocess
+        let proc: gio::Subprocess = gio::Subprocess::newv(
+            argv,
+            gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_PIPE,
+        )
+        .map_err(|err| String::from(err.message()))?;
+
+        // Run subprocess, check exit status
+        match proc.communicate(None, cancellable) {
+            Ok((_, stderr_buffer)) => {
+                if proc.is_successful() {
+                    Ok(())
+                } else {
+                    match stderr_buffer {
+                        Some(buffer) => Err(String::from(String::from_utf8_lossy(&buffer).trim())),
+                        None => Err(String::from("Process exited unsuccessfully..")),
+                    }
+                }
+            }
+            Err(err) => Err(String::from(err.message())),
+        }
+    }
+
+    /**
+     * Name:
+     * exec_check_callback
+     *
+     * Description:
+     * Execute a command asynchronously, passing an error (with any output) to @callback if the
+     * exit status isn't successful
+     *
+     * If given, @cancellable can be used to stop the process before it finishes.
+     *
+     * Made:
+     * 08/02/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * @callback is run on the main loop once the process exits (i.e. after a pkexec password prompt)
+     */
+    pub fn exec_check_callback<Q: FnOnce(Result<(), String>) + 'static>(
+        argv: &[&OsStr],
+        cancellable: Option<&impl IsA<gio::Cancellable>>,
+        callback: Q,
+    ) {
+        // Create subprocess
+        let proc: gio::Subprocess = match gio::Subprocess::newv(
+            argv,
+            gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_PIPE,
+        ) {
+            Ok(valid_proc) => valid_proc,
+            Err(err) => return callback(Err(String::from(err.message()))),
+        };
+
+        // Run subprocess, check exit status once finished
+        proc.clone()
+            .communicate_async(None, cancellable, move |result| match result {
+                Ok((_, stderr_buffer)) => {
+                    if proc.is_successful() {
+                        callback(Ok(()))
+                    } else {
+                        match stderr_buffer {
+                            Some(buffer) => {
+                                callback(Err(String::from(String::from_utf8_lossy(&buffer).trim())))
+                            }
+                            None => callback(Err(String::from("Process exited unsuccessfully.."))),
+                        }
+                    }
+                }
+                Err(err) => callback(Err(String::from(err.message()))),
+            });
+    }
+
+    /// Command runner that runs each command as a (blocking) subprocess
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct SubprocessRunner;
+
+    /**
+     * Name:
+     * SubprocessRunner
+     *
+     * Description:
+     * Trait for running commands as a subprocess
+     *
+     * Made:
+     * 21/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * nvidia-settings reports some failed assignments only in it's output, so check both the
+     * exit status and any `ERROR` lines
+     */
+    impl CommandRunner for SubprocessRunner {
+        fn run(&self, argv: &[String]) -> Result<(), String> {
+            // Build OsStr array of all args
+            let argv_osstr: Vec<&OsStr> = argv.iter().map(OsStr::new).collect();
+
+            // Create subprocess
+            let proc: gio::Subprocess = gio::Subprocess::newv(
+                &argv_osstr,
+                gio::SubprocessFlags::STDOUT_PIPE | gio::SubprocessFlags::STDERR_PIPE,
+            )
+            .map_err(|err| String::from(err.message()))?;
+
+            // Run process, check exit status and output
+            match proc.communicate(None, None::<&gio::Cancellable>) {
+                Ok((stdout_buffer, stderr_buffer)) => {
+                    let read = |buffer: Option<Bytes>| -> String {
+                        match buffer {
+                            Some(bytes) => String::from(String::from_utf8_lossy(&bytes).trim()),
+                            None => String::new(),
+                        }
+                    };
+                    let output: String = read(stdout_buffer);
+                    let errors: String = read(stderr_buffer);
+
+                    if !proc.is_successful() {
+                        // Prefer stderr, nvidia-settings reports most failures there
+                        if !errors.is_empty() {
+                            Err(errors)
+                        } else if !output.is_empty() {
+                            Err(output)
+                        } else {
+                            Err(String::from("Process exited unsuccessfully.."))
+                        }
+                    } else if output.contains("ERROR") {
+                        Err(output)
+                    } else if errors.contains("ERROR") {
+                        Err(errors)
+                    } else {
+                        Ok(())
+                    }
+                }
+                Err(err) => Err(String::from(err.message())),
+            }
+        }
+    }
+
+    /// Command runner that checks the exit status of each command (i.e. for pkexec)
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct PrivilegedRunner;
+
+    /**
+     * Name:
+     * PrivilegedRunner
+     *
+     * Description:
+     * Trait for running privileged commands as a subprocess
+     *
+     * Made:
+     * 24/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * pkexec only reports failures (i.e. cancelled authentication) through it's exit status
+     */
+    impl CommandRunner for PrivilegedRunner {
+        fn run(&self, argv: &[String]) -> Result<(), String> {
+            // Build OsStr array of all args
+            let argv_osstr: Vec<&OsStr> = argv.iter().map(OsStr::new).collect();
+
+            // Run process, check exit status
+            exec_check_sync(&argv_osstr, None::<&gio::Cancellable>)
+        }
+    }
 }
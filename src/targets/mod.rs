@@ -0,0 +1,348 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Target types (gpu, fan, thermal sensor) used by nvidia-settings, and parsing of target lists
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Target lists come from `nvidia-settings -q gpus -V`, `nvidia-settings -q fans -V` etc,
+ * each target starts with a line like `[0] hostname:0[fan:0] (Fan 0)`
+ */
+/// Enum for the types of target that can be queried through nvidia-settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetType {
+    Gpu,
+    Fan,
+    ThermalSensor,
+}
+
+/// Structure for storing a target (and it's connections) listed by nvidia-settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    pub target_type: TargetType,
+    pub index: u32,
+    pub description: String,
+    pub names: Vec<String>,
+    pub connections: Vec<(TargetType, u32)>,
+}
+
+/**
+ * Name:
+ * TargetType
+ *
+ * Description:
+ * Trait shared by all target types
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl TargetType {
+    /**
+     * Name:
+     * name
+     *
+     * Description:
+     * Name of the target type as used by nvidia-settings (i.e. `[fan:0]`)
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn name(&self) -> &'static str {
+        match self {
+            TargetType::Gpu => "gpu",
+            TargetType::Fan => "fan",
+            TargetType::ThermalSensor => "thermalsensor",
+        }
+    }
+
+    /**
+     * Name:
+     * query
+     *
+     * Description:
+     * Name of the query used to list all targets of this type (i.e. `nvidia-settings -q fans`)
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn query(&self) -> &'static str {
+        match self {
+            TargetType::Gpu => "gpus",
+            TargetType::Fan => "fans",
+            TargetType::ThermalSensor => "thermalsensors",
+        }
+    }
+
+    /**
+     * Name:
+     * from_name
+     *
+     * Description:
+     * Get target type from it's nvidia-settings name
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn from_name(name: &str) -> Option<TargetType> {
+        match name {
+            "gpu" => Some(TargetType::Gpu),
+            "fan" => Some(TargetType::Fan),
+            "thermalsensor" => Some(TargetType::ThermalSensor),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * Name:
+ * Target
+ *
+ * Description:
+ * Trait shared by all targets
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Target {
+    /**
+     * Name:
+     * connections_of
+     *
+     * Description:
+     * List the indexes of all connected targets of a certain type
+     *
+     * Made:
+     * 19/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn connections_of(&self, target_type: TargetType) -> Vec<u32> {
+        self.connections
+            .iter()
+            .filter(|(connection_type, _)| *connection_type == target_type)
+            .map(|(_, index)| *index)
+            .collect()
+    }
+}
+
+/**
+ * Name:
+ * find_target_references
+ *
+ * Description:
+ * Find all target references (i.e. `[gpu:0]`) in a line of text
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Unknown target types (i.e. `[dpy:2]`) are ignored
+ */
+fn find_target_references(line: &str) -> Vec<(TargetType, u32)> {
+    let mut references: Vec<(TargetType, u32)> = vec![];
+
+    // For each `[...]` in the line
+    for part in line.split('[').skip(1) {
+        if let Some(end) = part.find(']') {
+            // Split into type and index
+            if let Some((type_name, index)) = part[..end].split_once(':') {
+                if let (Some(target_type), Ok(target_index)) =
+                    (TargetType::from_name(type_name), index.parse::<u32>())
+                {
+                    references.push((target_type, target_index));
+                }
+            }
+        }
+    }
+
+    references
+}
+
+/**
+ * Name:
+ * parse_targets
+ *
+ * Description:
+ * Parse the output of an nvidia-settings target query into a list of targets
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Any target references found inside a target's block are stored as connections
+ */
+pub fn parse_targets(output: &str) -> Vec<Target> {
+    let mut targets: Vec<Target> = vec![];
+    let mut reading_names: bool = false;
+
+    for line in output.lines() {
+        let trimmed: &str = line.trim();
+
+        // Check for the start of a new target, i.e. `[0] hostname:0[fan:0] (Fan 0)`
+        let is_header: bool = match trimmed
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+        {
+            Some((number, _)) => !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()),
+            None => false,
+        };
+        if is_header {
+            let references: Vec<(TargetType, u32)> = find_target_references(trimmed);
+            if let Some((target_type, index)) = references.last() {
+                // Grab description from brackets (if any)
+                let description: String = match (trimmed.find('('), trimmed.rfind(')')) {
+                    (Some(start), Some(end)) if start < end => {
+                        String::from(&trimmed[start + 1..end])
+                    }
+                    _ => String::new(),
+                };
+
+                targets.push(Target {
+                    target_type: *target_type,
+                    index: *index,
+                    description,
+                    names: vec![],
+                    connections: vec![],
+                });
+                reading_names = false;
+                continue;
+            }
+        }
+
+        // Everything else belongs to the current target
+        if let Some(current_target) = targets.last_mut() {
+            if trimmed.is_empty() {
+                reading_names = false;
+            } else if trimmed.starts_with("Has the following name") {
+                reading_names = true;
+            } else if reading_names {
+                current_target.names.push(String::from(trimmed));
+            } else {
+                current_target
+                    .connections
+                    .append(&mut find_target_references(trimmed));
+            }
+        }
+    }
+
+    targets
+}
+
+/**
+ * Name:
+ * find_gpu_index
+ *
+ * Description:
+ * Find the index of a gpu (as used by nvidia-settings targets) using it's uuid
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn find_gpu_index(gpus: &[Target], uuid: &str) -> Option<u32> {
+    gpus.iter()
+        .find(|gpu| gpu.names.iter().any(|name| name == uuid))
+        .map(|gpu| gpu.index)
+}
+
+/**
+ * Name:
+ * find_connected_targets
+ *
+ * Description:
+ * Find the indexes of all targets (i.e. fans) belonging to a gpu
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Connections may be listed by the gpu or by the targets themselves,
+ * if there are none and only one gpu exists then every target must belong to it
+ */
+pub fn find_connected_targets(gpus: &[Target], targets: &[Target], gpu_index: u32) -> Vec<u32> {
+    // Check if the gpu lists it's connections
+    if let (Some(gpu), Some(first_target)) = (
+        gpus.iter().find(|gpu| gpu.index == gpu_index),
+        targets.first(),
+    ) {
+        let listed: Vec<u32> = gpu.connections_of(first_target.target_type);
+        if !listed.is_empty() {
+            return listed;
+        }
+    }
+
+    // Check if the targets list their gpu
+    let connected: Vec<u32> = targets
+        .iter()
+        .filter(|target| target.connections_of(TargetType::Gpu).contains(&gpu_index))
+        .map(|target| target.index)
+        .collect();
+    if !connected.is_empty() {
+        return connected;
+    }
+
+    // Only one gpu, so everything belongs to it
+    if gpus.len() == 1 {
+        targets.iter().map(|target| target.index).collect()
+    } else {
+        vec![]
+    }
+}
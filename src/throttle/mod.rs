@@ -0,0 +1,239 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Decoding of the clock throttle reasons bitmask reported by nvidia-smi
+ *
+ * Made:
+ * 15/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * <https://docs.nvidia.com/deploy/nvml-api/group__nvmlClocksThrottleReasons.html>
+ */
+/// Enum of all known reasons for the GPU clocks being throttled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrottleReason {
+    Idle,
+    ApplicationsClocks,
+    PowerCap,
+    HwSlowdown,
+    SyncBoost,
+    SwThermalSlowdown,
+    HwThermalSlowdown,
+    HwPowerBrakeSlowdown,
+    DisplayClocks,
+}
+
+/// List of all throttle reasons and their bit in the bitmask
+const THROTTLE_REASONS: [(u64, ThrottleReason); 9] = [
+    (0x0000_0001, ThrottleReason::Idle),
+    (0x0000_0002, ThrottleReason::ApplicationsClocks),
+    (0x0000_0004, ThrottleReason::PowerCap),
+    (0x0000_0008, ThrottleReason::HwSlowdown),
+    (0x0000_0010, ThrottleReason::SyncBoost),
+    (0x0000_0020, ThrottleReason::SwThermalSlowdown),
+    (0x0000_0040, ThrottleReason::HwThermalSlowdown),
+    (0x0000_0080, ThrottleReason::HwPowerBrakeSlowdown),
+    (0x0000_0100, ThrottleReason::DisplayClocks),
+];
+
+/**
+ * Name:
+ * ThrottleReason
+ *
+ * Description:
+ * Trait shared by all throttle reasons
+ *
+ * Made:
+ * 15/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl ThrottleReason {
+    /**
+     * Name:
+     * name
+     *
+     * Description:
+     * Human-readable name of the throttle reason
+     *
+     * Made:
+     * 15/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThrottleReason::Idle => "Idle",
+            ThrottleReason::ApplicationsClocks => "Application Clocks",
+            ThrottleReason::PowerCap => "Power Cap",
+            ThrottleReason::HwSlowdown => "HW Slowdown",
+            ThrottleReason::SyncBoost => "Sync Boost",
+            ThrottleReason::SwThermalSlowdown => "Thermal Slowdown",
+            ThrottleReason::HwThermalSlowdown => "HW Thermal Slowdown",
+            ThrottleReason::HwPowerBrakeSlowdown => "HW Power Brake",
+            ThrottleReason::DisplayClocks => "Display Clocks",
+        }
+    }
+
+    /**
+     * Name:
+     * is_problem
+     *
+     * Description:
+     * Whether the throttle reason indicates a problem (i.e. something worth alerting on)
+     *
+     * Made:
+     * 15/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Idle, sync boost and clock settings are expected behaviour
+     */
+    pub fn is_problem(&self) -> bool {
+        matches!(
+            self,
+            ThrottleReason::PowerCap
+                | ThrottleReason::HwSlowdown
+                | ThrottleReason::SwThermalSlowdown
+                | ThrottleReason::HwThermalSlowdown
+                | ThrottleReason::HwPowerBrakeSlowdown
+        )
+    }
+
+    /**
+     * Name:
+     * colour
+     *
+     * Description:
+     * Background colour of the badge shown for this throttle reason
+     *
+     * Made:
+     * 15/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * Uses the GNOME HIG palette
+     */
+    pub fn colour(&self) -> &'static str {
+        match self {
+            ThrottleReason::PowerCap => "#e66100",
+            ThrottleReason::HwSlowdown
+            | ThrottleReason::SwThermalSlowdown
+            | ThrottleReason::HwThermalSlowdown
+            | ThrottleReason::HwPowerBrakeSlowdown => "#c01c28",
+            ThrottleReason::SyncBoost => "#1c71d8",
+            ThrottleReason::Idle
+            | ThrottleReason::ApplicationsClocks
+            | ThrottleReason::DisplayClocks => "#5e5c64",
+        }
+    }
+}
+
+/**
+ * Name:
+ * parse_throttle_bitmask
+ *
+ * Description:
+ * Parse the bitmask given by nvidia-smi (i.e. `0x0000000000000004`)
+ *
+ * Made:
+ * 15/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Returns None for anything that isn't a valid hex number (i.e. `[N/A]`)
+ */
+pub fn parse_throttle_bitmask(input: &str) -> Option<u64> {
+    // Remove any whitespace & hex prefix
+    let trimmed: &str = input.trim();
+    let digits: &str = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+        .unwrap_or(trimmed);
+
+    // Convert from hex
+    u64::from_str_radix(digits, 16).ok()
+}
+
+/**
+ * Name:
+ * decode_throttle_reasons
+ *
+ * Description:
+ * Decode a throttle reasons bitmask into the list of active reasons
+ *
+ * Made:
+ * 15/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Unknown bits are ignored
+ */
+pub fn decode_throttle_reasons(bitmask: u64) -> Vec<ThrottleReason> {
+    THROTTLE_REASONS
+        .iter()
+        .filter(|(bit, _)| bitmask & bit != 0)
+        .map(|(_, reason)| *reason)
+        .collect()
+}
+
+/**
+ * Name:
+ * create_badge_markup
+ *
+ * Description:
+ * Create pango markup showing each throttle reason as a coloured badge
+ *
+ * Made:
+ * 15/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn create_badge_markup(reasons: &[ThrottleReason]) -> String {
+    // Show something when not throttled at all
+    if reasons.is_empty() {
+        return String::from("None");
+    }
+
+    // Create a badge for each reason
+    let badges: Vec<String> = reasons
+        .iter()
+        .map(|reason| {
+            format!(
+                "<span background=\"{}\" foreground=\"#ffffff\"> {} </span>",
+                reason.colour(),
+                reason.name()
+            )
+        })
+        .collect();
+
+    badges.join(" ")
+}
@@ -0,0 +1,759 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/**
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Thermal safety watchdog, reverting all tuning to driver defaults if a GPU overheats
+ * or can't be monitored
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The watchdog only arms while some tuning (fan control, clock offsets, power limit or locked clocks)
+ * has been applied by this app. Once tripped it stays tripped until the alert is acknowledged
+ */
+// Imports
+use std::{
+    process::Command,
+    sync::{Mutex, MutexGuard},
+};
+
+// Modules
+use crate::{
+    command::CommandRunner,
+    fan_override::create_restore_command,
+    helper::{create_helper_command, HelperOperation},
+    overclock::{create_offset_command, ClockOffset},
+    throttle::{decode_throttle_reasons, parse_throttle_bitmask, ThrottleReason},
+};
+
+/// Default temperature (in celcius) to revert all tuning at
+pub const DEFAULT_CRITICAL_TEMPERATURE: u32 = 90;
+/// Default number of ticks in a row the provider can fail for before reverting
+pub const DEFAULT_MAX_FAILURES: u32 = 3;
+
+/// GPUs tuned by this app (other than fan control, see `fan_override`)
+pub static TUNING: Mutex<TuningState> = Mutex::new(TuningState::new());
+
+/// Structure for storing which GPUs have been tuned, and if the watchdog has tripped
+#[derive(Debug, Default)]
+pub struct TuningState {
+    tuned: Vec<String>,
+    tripped: bool,
+}
+
+/// Structure for storing the limits of the watchdog
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchdogConfig {
+    pub critical_temperature: f64,
+    pub max_failures: u32,
+}
+
+/// Enum for a sample of a GPU's state taken each tick
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sample {
+    Reading {
+        uuid: String,
+        temperature: f64,
+        throttle_reasons: Option<u64>,
+    },
+    Failure {
+        uuid: String,
+        error: String,
+    },
+}
+
+/// Enum for the reasons the watchdog can trip
+#[derive(Debug, Clone, PartialEq)]
+pub enum TripReason {
+    CriticalTemperature { uuid: String, temperature: f64 },
+    ThermalSlowdown { uuid: String },
+    ProviderFailure { ticks: u32, error: String },
+}
+
+/// Enum for the state of the watchdog
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchdogState {
+    Idle,
+    Armed { failures: u32 },
+    Tripped(TripReason),
+}
+
+/// Enum for changes in the state of the watchdog
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchdogEvent {
+    Armed,
+    Disarmed,
+    Tripped(TripReason),
+}
+
+/// Structure for storing the watchdog
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watchdog {
+    config: WatchdogConfig,
+    state: WatchdogState,
+}
+
+/// Structure for storing the commands needed to revert a GPU to driver defaults
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevertPlan {
+    pub commands: Vec<Vec<String>>,
+    pub privileged_commands: Vec<Vec<String>>,
+}
+
+/**
+ * Name:
+ * TuningState
+ *
+ * Description:
+ * Trait shared by all tuning states
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl TuningState {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new tuning state, with no GPUs tuned
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * const so it can be used in a static
+     */
+    pub const fn new() -> Self {
+        TuningState {
+            tuned: vec![],
+            tripped: false,
+        }
+    }
+
+    /**
+     * Name:
+     * mark_tuned
+     *
+     * Description:
+     * Record that a GPU (by uuid) has had tuning applied
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn mark_tuned(&mut self, uuid: &str) {
+        if !self.tuned.iter().any(|tuned| tuned == uuid) {
+            self.tuned.push(String::from(uuid));
+        }
+    }
+
+    /**
+     * Name:
+     * tuned
+     *
+     * Description:
+     * Get the list of tuned GPUs
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn tuned(&self) -> &[String] {
+        &self.tuned
+    }
+
+    /**
+     * Name:
+     * is_empty
+     *
+     * Description:
+     * Check if no GPUs have been tuned
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_empty(&self) -> bool {
+        self.tuned.is_empty()
+    }
+
+    /**
+     * Name:
+     * clear
+     *
+     * Description:
+     * Forget all tuned GPUs (i.e. once reverted)
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn clear(&mut self) {
+        self.tuned.clear();
+    }
+
+    /**
+     * Name:
+     * is_tripped
+     *
+     * Description:
+     * Check if the watchdog has tripped (nothing should re-apply tuning until acknowledged)
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn is_tripped(&self) -> bool {
+        self.tripped
+    }
+
+    /**
+     * Name:
+     * set_tripped
+     *
+     * Description:
+     * Set if the watchdog has tripped
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn set_tripped(&mut self, tripped: bool) {
+        self.tripped = tripped;
+    }
+}
+
+/**
+ * Name:
+ * lock_tuning
+ *
+ * Description:
+ * Lock the global tuning state
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A poisoned lock is still used, the state is always valid
+ */
+pub fn lock_tuning() -> MutexGuard<'static, TuningState> {
+    match TUNING.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/**
+ * Name:
+ * TripReason
+ *
+ * Description:
+ * Trait shared by all trip reasons
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl TripReason {
+    /**
+     * Name:
+     * describe
+     *
+     * Description:
+     * Describe why the watchdog tripped, for the alert
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn describe(&self) -> String {
+        match self {
+            TripReason::CriticalTemperature { uuid, temperature } => format!(
+                "GPU {} reached {:.0}\u{00B0}C, above the critical temperature",
+                uuid, temperature
+            ),
+            TripReason::ThermalSlowdown { uuid } => {
+                format!(
+                    "GPU {} is being slowed down by hardware to stop it overheating",
+                    uuid
+                )
+            }
+            TripReason::ProviderFailure { ticks, error } => format!(
+                "GPUs could not be monitored {} times in a row: {}",
+                ticks, error
+            ),
+        }
+    }
+}
+
+/**
+ * Name:
+ * Watchdog
+ *
+ * Description:
+ * Trait shared by all watchdogs
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Watchdog {
+    /**
+     * Name:
+     * new
+     *
+     * Description:
+     * Create a new watchdog, idle until tuning is applied
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn new(config: WatchdogConfig) -> Self {
+        Watchdog {
+            config,
+            state: WatchdogState::Idle,
+        }
+    }
+
+    /**
+     * Name:
+     * state
+     *
+     * Description:
+     * Get the current state
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn state(&self) -> &WatchdogState {
+        &self.state
+    }
+
+    /**
+     * Name:
+     * set_config
+     *
+     * Description:
+     * Change the limits of the watchdog (i.e. from settings)
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn set_config(&mut self, config: WatchdogConfig) {
+        self.config = config;
+    }
+
+    /**
+     * Name:
+     * tick
+     *
+     * Description:
+     * Check the latest samples, returning any change in state
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     * A tick fails if any sample failed. Samples are only needed while tuning is active
+     */
+    pub fn tick(&mut self, tuning_active: bool, samples: &[Sample]) -> Option<WatchdogEvent> {
+        // Arm/disarm
+        let (failures, event): (u32, Option<WatchdogEvent>) = match (&self.state, tuning_active) {
+            // Wait to be acknowledged
+            (WatchdogState::Tripped(_), _) => return None,
+            (WatchdogState::Idle, false) => return None,
+            (WatchdogState::Armed { .. }, false) => {
+                self.state = WatchdogState::Idle;
+                return Some(WatchdogEvent::Disarmed);
+            }
+            (WatchdogState::Idle, true) => (0, Some(WatchdogEvent::Armed)),
+            (WatchdogState::Armed { failures }, true) => (*failures, None),
+        };
+
+        // Check temperatures & throttling
+        for sample in samples {
+            if let Sample::Reading {
+                uuid,
+                temperature,
+                throttle_reasons,
+            } = sample
+            {
+                let thermal_slowdown: bool = match throttle_reasons {
+                    Some(bitmask) => decode_throttle_reasons(*bitmask)
+                        .contains(&ThrottleReason::HwThermalSlowdown),
+                    None => false,
+                };
+                let reason: Option<TripReason> = if *temperature >= self.config.critical_temperature
+                {
+                    Some(TripReason::CriticalTemperature {
+                        uuid: uuid.clone(),
+                        temperature: *temperature,
+                    })
+                } else if thermal_slowdown {
+                    Some(TripReason::ThermalSlowdown { uuid: uuid.clone() })
+                } else {
+                    None
+                };
+
+                if let Some(valid_reason) = reason {
+                    self.state = WatchdogState::Tripped(valid_reason.clone());
+                    return Some(WatchdogEvent::Tripped(valid_reason));
+                }
+            }
+        }
+
+        // Check for failures
+        let error: Option<&String> = samples.iter().find_map(|sample| match sample {
+            Sample::Failure { error, .. } => Some(error),
+            _ => None,
+        });
+        let failures: u32 = match error {
+            Some(_) => failures + 1,
+            None => 0,
+        };
+        if let Some(valid_error) = error {
+            if failures >= self.config.max_failures {
+                let reason: TripReason = TripReason::ProviderFailure {
+                    ticks: failures,
+                    error: valid_error.clone(),
+                };
+                self.state = WatchdogState::Tripped(reason.clone());
+                return Some(WatchdogEvent::Tripped(reason));
+            }
+        }
+
+        self.state = WatchdogState::Armed { failures };
+        event
+    }
+
+    /**
+     * Name:
+     * acknowledge
+     *
+     * Description:
+     * Acknowledge the alert once tripped, allowing the watchdog to arm again
+     *
+     * Made:
+     * 27/01/2023
+     *
+     * Made by:
+     * Deren Vural
+     *
+     * Notes:
+     *
+     */
+    pub fn acknowledge(&mut self) {
+        if let WatchdogState::Tripped(_) = self.state {
+            self.state = WatchdogState::Idle;
+        }
+    }
+}
+
+/**
+ * Name:
+ * Watchdog
+ *
+ * Description:
+ * Default watchdog, using the default limits
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new(WatchdogConfig {
+            critical_temperature: DEFAULT_CRITICAL_TEMPERATURE as f64,
+            max_failures: DEFAULT_MAX_FAILURES,
+        })
+    }
+}
+
+/**
+ * Name:
+ * create_revert_plan
+ *
+ * Description:
+ * Create the commands to put a GPU back to driver defaults
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Fan control & clock offsets need the nvidia-settings gpu index
+ * Privileged commands are all resets, which polkit allows without a password
+ */
+pub fn create_revert_plan(uuid: &str, gpu_index: Option<u32>) -> Result<RevertPlan, String> {
+    let mut commands: Vec<Vec<String>> = vec![];
+    if let Some(valid_index) = gpu_index {
+        commands.push(create_restore_command(valid_index));
+        commands.push(create_offset_command(valid_index, ClockOffset::Graphics, 0));
+        commands.push(create_offset_command(valid_index, ClockOffset::Memory, 0));
+    }
+
+    let operations: Vec<HelperOperation> = vec![
+        HelperOperation::ResetGpuClocks {
+            uuid: String::from(uuid),
+        },
+        HelperOperation::ResetMemoryClocks {
+            uuid: String::from(uuid),
+        },
+        HelperOperation::ResetPowerLimit {
+            uuid: String::from(uuid),
+        },
+    ];
+    let privileged_commands: Vec<Vec<String>> = operations
+        .iter()
+        .map(create_helper_command)
+        .collect::<Result<Vec<Vec<String>>, String>>()?;
+
+    Ok(RevertPlan {
+        commands,
+        privileged_commands,
+    })
+}
+
+/**
+ * Name:
+ * run_revert_plan
+ *
+ * Description:
+ * Run every command of a revert plan, returning any errors
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Unlike applying tuning, nothing stops on failure (as much as possible is reverted)
+ */
+pub fn run_revert_plan(
+    plan: &RevertPlan,
+    runner: &dyn CommandRunner,
+    privileged_runner: &dyn CommandRunner,
+) -> Vec<String> {
+    let mut errors: Vec<String> = vec![];
+
+    for command in &plan.commands {
+        if let Err(err) = runner.run(command) {
+            errors.push(err);
+        }
+    }
+    for command in &plan.privileged_commands {
+        if let Err(err) = privileged_runner.run(command) {
+            errors.push(err);
+        }
+    }
+
+    errors
+}
+
+/**
+ * Name:
+ * sample_arguments
+ *
+ * Description:
+ * Arguments for nvidia-smi to read the temperature & throttle reasons of every GPU
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+pub fn sample_arguments() -> Vec<String> {
+    vec![
+        String::from("--query-gpu=uuid,temperature.gpu,clocks_throttle_reasons.active"),
+        String::from("--format=csv,noheader,nounits"),
+    ]
+}
+
+/**
+ * Name:
+ * parse_watchdog_samples
+ *
+ * Description:
+ * Parse the output of nvidia-smi (run with `sample_arguments`) into a sample of each GPU
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Throttle reasons are optional, as not every GPU has them
+ */
+pub fn parse_watchdog_samples(output: &str) -> Vec<Sample> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+            let uuid: String = String::from(fields[0]);
+            match fields.get(1).and_then(|field| field.parse::<f64>().ok()) {
+                Some(temperature) => Sample::Reading {
+                    uuid,
+                    temperature,
+                    throttle_reasons: fields
+                        .get(2)
+                        .and_then(|field| parse_throttle_bitmask(field)),
+                },
+                None => Sample::Failure {
+                    uuid,
+                    error: String::from("Temperature unavailable.."),
+                },
+            }
+        })
+        .collect()
+}
+
+/**
+ * Name:
+ * read_watchdog_samples
+ *
+ * Description:
+ * Read the temperature & throttle reasons of every GPU, using @command (i.e. `nvidia-smi`)
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Only uses std, so can be run off the main thread. If the command fails (or reads no GPUs), a single failure is returned
+ */
+pub fn read_watchdog_samples(command: &[String]) -> Vec<Sample> {
+    let failure = |error: String| -> Vec<Sample> {
+        vec![Sample::Failure {
+            uuid: String::new(),
+            error,
+        }]
+    };
+
+    let (program, args): (&String, &[String]) = match command.split_first() {
+        Some(split) => split,
+        None => return failure(String::from("No command to read GPUs with..")),
+    };
+    match Command::new(program)
+        .args(args)
+        .args(sample_arguments())
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            let samples: Vec<Sample> =
+                parse_watchdog_samples(&String::from_utf8_lossy(&output.stdout));
+            if samples.is_empty() {
+                failure(String::from("Cannot read GPUs: no GPUs returned.."))
+            } else {
+                samples
+            }
+        }
+        Ok(output) => failure(format!(
+            "Cannot read GPUs: {}..",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(err) => failure(format!("Cannot read GPUs: {}..", err)),
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * mod.rs
+ *
+ * Description:
+ * Helpers shared by the integration tests
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Each integration test is it's own crate, so not every helper is used by every test
+ */
+#![allow(dead_code)]
+
+// Imports
+use gtk4_nvidia_monitor_rust::{
+    command::CommandRunner,
+    sampler::{find_metric, parse_samples, GpuSample, Metric},
+};
+use std::cell::RefCell;
+
+/// Properties read by the sample readings below
+pub const PROPERTIES: [&str; 3] = ["util", "temp", "memory_usage"];
+
+/// nvidia-smi readings of one GPU, (uuid, util, temp, memory usage, name)
+pub const OUTPUT: &str = "GPU-1a2b3c4d, 45, 52, [N/A], NVIDIA GeForce RTX 3080\n";
+
+/// nvidia-smi readings of a GPU that couldn't read anything
+pub const UNREAD_OUTPUT: &str = "GPU-5e6f7a8b, [N/A], [N/A], [N/A], Tesla T4\n";
+
+/// nvidia-smi power draw & enforced limit of one GPU
+pub const POWER_OUTPUT: &str = "123.45 W, 250.00 W";
+
+/// nvidia-settings performance levels of one GPU
+pub const PERF_MODES_OUTPUT: &str = "perf=0, nvclock=210, nvclockmin=210, nvclockmax=420, nvclockeditable=1, memclock=405, memclockmin=405, memclockmax=405; perf=1, nvclock=210, nvclockmin=210, nvclockmax=1995, nvclockeditable=1, memclock=9501, memclockmin=9501, memclockmax=9501";
+
+/// Command runner that records commands instead of running them
+pub struct FakeRunner {
+    pub commands: RefCell<Vec<Vec<String>>>,
+    pub fail: bool,
+}
+
+impl CommandRunner for FakeRunner {
+    fn run(&self, argv: &[String]) -> Result<(), String> {
+        self.commands.borrow_mut().push(argv.to_vec());
+        if self.fail {
+            Err(String::from("failed"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub fn create_runner(fail: bool) -> FakeRunner {
+    FakeRunner {
+        commands: RefCell::new(vec![]),
+        fail,
+    }
+}
+
+pub fn metrics(properties: &[&str]) -> Vec<&'static Metric> {
+    properties
+        .iter()
+        .map(|property| find_metric(property).unwrap())
+        .collect()
+}
+
+/// Parse readings of `PROPERTIES`, as read at `timestamp`
+pub fn samples(output: &str, timestamp: u64) -> Vec<GpuSample> {
+    parse_samples(output, &metrics(&PROPERTIES), timestamp).unwrap()
+}
+
+pub fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
@@ -0,0 +1,410 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_10.rs
+ *
+ * Description:
+ * Integration tests for switching profiles when processes start/exit
+ *
+ * Made:
+ * 26/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Processes are given as fake lists, rather than scanned
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::{
+    mig::{parse_gpu_list, GpuListing},
+    profile_rules::{
+        gpu_processes_command, list_processes, merge_processes, parse_cmdline, parse_process_table,
+        scan_processes, GpuSelector, Pattern, ProcessInfo, ProfileRule, ProfileSwitch, RuleEngine,
+    },
+};
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+const GPU_0: &str = "GPU-1a2b3c4d";
+const GPU_1: &str = "GPU-5e6f7a8b";
+
+/// Output of `nvidia-smi -L`
+const GPU_LIST: &str = "GPU 0: NVIDIA GeForce RTX 3080 (UUID: GPU-1a2b3c4d)\n\
+                        GPU 1: Tesla T4 (UUID: GPU-5e6f7a8b)\n";
+
+fn create_process(pid: u32, name: &str, command_line: &str, gpu_uuids: &[&str]) -> ProcessInfo {
+    ProcessInfo {
+        pid,
+        name: String::from(name),
+        command_line: String::from(command_line),
+        gpu_uuids: gpu_uuids.iter().map(|uuid| String::from(*uuid)).collect(),
+    }
+}
+
+fn gpu_uuids() -> Vec<String> {
+    vec![String::from(GPU_0), String::from(GPU_1)]
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_pattern() {
+    let pattern: Pattern = Pattern::new("python.*train.py").unwrap();
+    assert!(pattern.is_match("/usr/bin/python3 /home/user/train.py --epochs 10"));
+    assert!(pattern.is_match("python train.py"));
+    assert!(!pattern.is_match("python test.py"));
+
+    // Anchors
+    let pattern: Pattern = Pattern::new("^blender$").unwrap();
+    assert!(pattern.is_match("blender"));
+    assert!(!pattern.is_match("blender-thumbnailer"));
+    assert!(!pattern.is_match("/usr/bin/blender"));
+
+    // Repeats & escapes
+    assert!(Pattern::new("ab+c").unwrap().is_match("xabbbc"));
+    assert!(!Pattern::new("ab+c").unwrap().is_match("xac"));
+    assert!(Pattern::new("colou?r").unwrap().is_match("color"));
+    assert!(Pattern::new("train\\.py").unwrap().is_match("train.py"));
+    assert!(!Pattern::new("train\\.py").unwrap().is_match("train_py"));
+    assert!(Pattern::new("cost\\$").unwrap().is_match("cost$ 5"));
+
+    // Invalid
+    // Repeats
+    assert!(Pattern::new("^a+b*c?$").unwrap().is_match("aab"));
+    assert!(!Pattern::new("^a+b*c?$").unwrap().is_match("bc"));
+    assert!(Pattern::new("x.*$").unwrap().is_match("ax"));
+
+    assert!(Pattern::new("").is_err());
+    assert!(Pattern::new("*blender").is_err());
+    assert!(Pattern::new("a**").is_err());
+    assert!(Pattern::new("blender\\").is_err());
+
+    // Unsupported syntax is an error, unless escaped
+    for source in [
+        "blender|maya",
+        "[bB]lender",
+        "(py)+",
+        "a{2}",
+        "blender]",
+        "a)",
+    ] {
+        assert!(Pattern::new(source).is_err());
+    }
+    assert!(Pattern::new("\\(1\\)").unwrap().is_match("blender (1)"));
+}
+
+#[test]
+fn test_pathological_pattern() {
+    // Would take exponential time if backtracking
+    let pattern: Pattern = Pattern::new(".*a.*a.*a.*a.*a.*a.*b").unwrap();
+    let command_line: String = "a".repeat(5000);
+    let start: Instant = Instant::now();
+    assert!(!pattern.is_match(&command_line));
+    assert!(pattern.is_match(&(command_line + "b")));
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_rule_setting() {
+    let rule: ProfileRule = ProfileRule::from_setting("Render:0:python.*train.py").unwrap();
+    assert_eq!(rule.profile, "Render");
+    assert_eq!(rule.gpu, GpuSelector::Index(0));
+    assert_eq!(rule.to_setting(), "Render:0:python.*train.py");
+
+    // Patterns may contain `:`
+    let rule: ProfileRule = ProfileRule::from_setting("Quiet::^a:b").unwrap();
+    assert_eq!(rule.gpu, GpuSelector::Any);
+    assert_eq!(rule.pattern.as_str(), "^a:b");
+
+    let rule: ProfileRule = ProfileRule::from_setting("Render:GPU-1a2b3c4d:blender").unwrap();
+    assert_eq!(rule.gpu, GpuSelector::Uuid(String::from(GPU_0)));
+
+    assert!(ProfileRule::from_setting("Render:0").is_err());
+    assert!(ProfileRule::from_setting(":0:blender").is_err());
+    assert!(ProfileRule::from_setting("Render:0:").is_err());
+}
+
+#[test]
+fn test_rule_matches() {
+    let rule: ProfileRule = ProfileRule::from_setting("Render:0:blender").unwrap();
+
+    // Must be using GPU 0
+    assert!(rule.matches(
+        &create_process(10, "blender", "blender scene.blend", &[GPU_0]),
+        &gpu_uuids()
+    ));
+    assert!(!rule.matches(
+        &create_process(10, "blender", "blender scene.blend", &[GPU_1]),
+        &gpu_uuids()
+    ));
+    assert!(!rule.matches(
+        &create_process(10, "blender", "blender scene.blend", &[]),
+        &gpu_uuids()
+    ));
+
+    // Any GPU, but not processes without one
+    let rule: ProfileRule = ProfileRule::from_setting("Render::blender").unwrap();
+    assert!(rule.matches(
+        &create_process(10, "blender", "blender", &[GPU_1]),
+        &gpu_uuids()
+    ));
+    assert!(!rule.matches(&create_process(10, "blender", "blender", &[]), &gpu_uuids()));
+
+    // Index out of range
+    let rule: ProfileRule = ProfileRule::from_setting("Render:5:blender").unwrap();
+    assert!(!rule.matches(
+        &create_process(10, "blender", "blender", &[GPU_0]),
+        &gpu_uuids()
+    ));
+}
+
+#[test]
+fn test_engine_switching() {
+    let mut engine: RuleEngine = RuleEngine::from_settings(&[
+        String::from("Training:0:python.*train.py"),
+        String::from("Render::blender"),
+        String::from("broken"),
+    ]);
+    assert_eq!(engine.rules().len(), 2);
+    assert_eq!(
+        RuleEngine::invalid_settings(&[
+            String::from("Render::blender"),
+            String::from("broken"),
+            String::from("Render::blender|maya"),
+        ]),
+        vec![
+            String::from("`broken`: Malformed profile rule.."),
+            String::from(
+                "`Render::blender|maya`: Unsupported `|` in `blender|maya` (use `\\|` to match it).."
+            ),
+        ]
+    );
+
+    let idle: Vec<ProcessInfo> = vec![create_process(1, "systemd", "/sbin/init", &[])];
+    let rendering: Vec<ProcessInfo> = vec![
+        create_process(1, "systemd", "/sbin/init", &[]),
+        create_process(20, "blender", "blender scene.blend", &[GPU_1]),
+    ];
+    let training: Vec<ProcessInfo> = vec![
+        create_process(20, "blender", "blender scene.blend", &[GPU_1]),
+        create_process(30, "python3", "python3 train.py", &[GPU_0]),
+    ];
+
+    // Nothing to do
+    assert_eq!(engine.update(&idle, &gpu_uuids()), None);
+
+    // Blender starts
+    assert_eq!(
+        engine.update(&rendering, &gpu_uuids()),
+        Some(ProfileSwitch::Apply {
+            profile: String::from("Render"),
+            rule: 1,
+            pid: 20,
+            process: String::from("blender"),
+        })
+    );
+
+    // Not active until applied, so a failed apply is tried again
+    assert!(engine.active_rule().is_none());
+    assert!(engine.update(&rendering, &gpu_uuids()).is_some());
+    engine.confirm(1, "Render");
+    assert_eq!(engine.update(&rendering, &gpu_uuids()), None);
+    assert_eq!(engine.active_rule().unwrap().profile, "Render");
+
+    // Confirming a rule that has since changed does nothing
+    engine.confirm(0, "Render");
+    assert_eq!(engine.active_rule().unwrap().profile, "Render");
+
+    // Training starts, first rule wins
+    match engine.update(&training, &gpu_uuids()) {
+        Some(ProfileSwitch::Apply { profile, pid, .. }) => {
+            assert_eq!((profile.as_str(), pid), ("Training", 30));
+            engine.confirm(0, &profile);
+        }
+        other => panic!("unexpected switch: {:?}", other),
+    }
+
+    // Training exits, back to blender
+    match engine.update(&rendering, &gpu_uuids()) {
+        Some(ProfileSwitch::Apply { profile, rule, .. }) => {
+            assert_eq!(profile, "Render");
+            engine.confirm(rule, &profile);
+        }
+        other => panic!("unexpected switch: {:?}", other),
+    }
+
+    // Everything exits
+    assert_eq!(
+        engine.update(&idle, &gpu_uuids()),
+        Some(ProfileSwitch::Revert {
+            profile: String::from("Render")
+        })
+    );
+    assert_eq!(engine.update(&idle, &gpu_uuids()), None);
+    assert!(engine.active_rule().is_none());
+}
+
+#[test]
+fn test_describe() {
+    let switch: ProfileSwitch = ProfileSwitch::Apply {
+        profile: String::from("Render"),
+        rule: 1,
+        pid: 20,
+        process: String::from("blender"),
+    };
+    assert_eq!(
+        switch.describe(),
+        "Rule 1 matched process `blender` (20), applying profile `Render`"
+    );
+}
+
+#[test]
+fn test_parse_process_table() {
+    let gpus: Vec<GpuListing> = parse_gpu_list(GPU_LIST);
+    let output: &str = "\
++-----------------------------------------------------------------------------+
+| Processes:                                                                  |
+|  GPU   GI   CI        PID   Type   Process name                  GPU Memory |
+|        ID   ID                                                   Usage      |
+|=============================================================================|
+|    0   N/A  N/A      1100      G   /usr/lib/xorg/Xorg                 45MiB |
+|    0   N/A  N/A        30      C   /usr/bin/python3                  900MiB |
+|    1   N/A  N/A        30      C   /usr/bin/python3                  900MiB |
+|    1   N/A  N/A        40    C+G   ...ender/blender --render          N/A |
+|    7   N/A  N/A        50      G   /usr/bin/unknown-gpu               1MiB |
++-----------------------------------------------------------------------------+
+";
+    let processes: Vec<ProcessInfo> = parse_process_table(output, &gpus);
+
+    // Graphics processes too
+    assert_eq!(processes.len(), 3);
+    assert_eq!(
+        processes[0],
+        create_process(1100, "Xorg", "/usr/lib/xorg/Xorg", &[GPU_0])
+    );
+    assert_eq!(processes[1].name, "python3");
+    assert_eq!(processes[1].gpu_uuids, vec![GPU_0, GPU_1]);
+    assert_eq!(processes[2].name, "blender");
+    assert_eq!(processes[2].command_line, "...ender/blender --render");
+
+    // Older drivers, without MIG columns
+    let output: &str = "\
+| Processes:                                                       GPU Memory |
+|  GPU       PID   Type   Process name                             Usage      |
+|    0      1100      G   /usr/lib/xorg/Xorg                            45MiB |
+|  No running processes found                                                 |
+";
+    assert_eq!(
+        parse_process_table(output, &gpus),
+        vec![create_process(1100, "Xorg", "/usr/lib/xorg/Xorg", &[GPU_0])]
+    );
+}
+
+#[test]
+fn test_merge_processes() {
+    let scanned: Vec<ProcessInfo> = vec![
+        create_process(30, "python3", "python3 train.py", &[]),
+        create_process(50, "bash", "bash", &[]),
+    ];
+    let gpu_processes: Vec<ProcessInfo> = vec![
+        create_process(30, "python3", "/usr/bin/python3", &[GPU_0]),
+        create_process(60, "blender", "/opt/blender/blender", &[GPU_1]),
+    ];
+    let processes: Vec<ProcessInfo> = merge_processes(scanned, gpu_processes);
+
+    assert_eq!(processes.len(), 3);
+    assert_eq!(processes[0].command_line, "python3 train.py");
+    assert_eq!(processes[0].gpu_uuids, vec![GPU_0]);
+    assert!(processes[1].gpu_uuids.is_empty());
+    assert_eq!(processes[2].pid, 60);
+}
+
+#[test]
+fn test_scan_processes() {
+    assert_eq!(
+        parse_cmdline(b"python3\0train.py\0--epochs\x0010\0"),
+        "python3 train.py --epochs 10"
+    );
+
+    // Fake `/proc`
+    let root: PathBuf = std::env::temp_dir().join(format!("proc-test-{}", std::process::id()));
+    fs::create_dir_all(root.join("42")).unwrap();
+    fs::write(root.join("42/comm"), "python3\n").unwrap();
+    fs::write(root.join("42/cmdline"), b"python3\0train.py\0").unwrap();
+    fs::create_dir_all(root.join("self")).unwrap();
+    fs::create_dir_all(root.join("43")).unwrap();
+
+    let processes: Vec<ProcessInfo> = scan_processes(&root);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        processes,
+        vec![create_process(42, "python3", "python3 train.py", &[])]
+    );
+}
+
+#[test]
+fn test_list_processes() {
+    assert_eq!(
+        gpu_processes_command(3, false).unwrap().join(" "),
+        "optirun nvidia-smi"
+    );
+    assert_eq!(
+        gpu_processes_command(2, false).unwrap().join(" "),
+        "nvidia-smi"
+    );
+
+    // nvidia-settings falls back to nvidia-smi
+    assert_eq!(
+        gpu_processes_command(1, true).unwrap().join(" "),
+        "nvidia-smi"
+    );
+    assert_eq!(gpu_processes_command(1, false), None);
+
+    // Fake `/proc` & nvidia-smi
+    let root: PathBuf = std::env::temp_dir().join(format!("proc-list-{}", std::process::id()));
+    fs::create_dir_all(root.join("42")).unwrap();
+    fs::write(root.join("42/comm"), "python3\n").unwrap();
+    fs::write(root.join("42/cmdline"), b"python3\0train.py\0").unwrap();
+    let script: String = format!(
+        "if [ \"$1\" = -L ]; then printf '%s' '{}'; else printf '%s' '{}'; fi",
+        GPU_LIST, "| Processes: |\n|    1   N/A  N/A      42      G   python3      10MiB |\n"
+    );
+    let command: Vec<String> = vec![
+        String::from("sh"),
+        String::from("-c"),
+        script,
+        String::from("nvidia-smi"),
+    ];
+    let failing_command: Vec<String> = vec![String::from("false")];
+
+    let processes: Result<Vec<ProcessInfo>, String> = list_processes(&root, Some(&command));
+    let failed: Result<Vec<ProcessInfo>, String> = list_processes(&root, Some(&failing_command));
+    let unknown: Result<Vec<ProcessInfo>, String> = list_processes(&root, None);
+    fs::remove_dir_all(&root).unwrap();
+
+    assert_eq!(
+        processes,
+        Ok(vec![create_process(
+            42,
+            "python3",
+            "python3 train.py",
+            &[GPU_1]
+        )])
+    );
+
+    // Can't tell which processes use a GPU
+    assert!(failed.is_err());
+    assert!(unknown.is_err());
+}
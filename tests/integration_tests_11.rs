@@ -0,0 +1,294 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_11.rs
+ *
+ * Description:
+ * Integration tests for the thermal safety watchdog
+ *
+ * Made:
+ * 27/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * The watchdog is driven by a scripted stream of samples, commands are captured by a fake runner
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{create_runner, FakeRunner};
+use gtk4_nvidia_monitor_rust::{
+    helper::HELPER_PATH,
+    watchdog::{
+        create_revert_plan, parse_watchdog_samples, read_watchdog_samples, run_revert_plan,
+        RevertPlan, Sample, TripReason, TuningState, Watchdog, WatchdogConfig, WatchdogEvent,
+        WatchdogState,
+    },
+};
+
+const UUID: &str = "GPU-1a2b3c4d";
+
+const CONFIG: WatchdogConfig = WatchdogConfig {
+    critical_temperature: 90.0,
+    max_failures: 3,
+};
+
+fn reading(temperature: f64, throttle_reasons: Option<u64>) -> Sample {
+    Sample::Reading {
+        uuid: String::from(UUID),
+        temperature,
+        throttle_reasons,
+    }
+}
+
+fn failure() -> Sample {
+    Sample::Failure {
+        uuid: String::from(UUID),
+        error: String::from("nvidia-smi not found"),
+    }
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_arming() {
+    let mut watchdog: Watchdog = Watchdog::new(CONFIG);
+
+    // Nothing happens without tuning, even when hot
+    assert_eq!(watchdog.tick(false, &[reading(95.0, None)]), None);
+    assert_eq!(watchdog.state(), &WatchdogState::Idle);
+
+    // Arms once tuned, disarms once reverted
+    assert_eq!(
+        watchdog.tick(true, &[reading(60.0, None)]),
+        Some(WatchdogEvent::Armed)
+    );
+    assert_eq!(watchdog.tick(true, &[reading(65.0, None)]), None);
+    assert_eq!(watchdog.tick(false, &[]), Some(WatchdogEvent::Disarmed));
+    assert_eq!(watchdog.state(), &WatchdogState::Idle);
+}
+
+#[test]
+fn test_critical_temperature() {
+    let mut watchdog: Watchdog = Watchdog::new(CONFIG);
+
+    // Scripted temperatures, rising past critical
+    let stream: [f64; 5] = [70.0, 80.0, 85.0, 89.9, 91.0];
+    let events: Vec<Option<WatchdogEvent>> = stream
+        .iter()
+        .map(|temperature| watchdog.tick(true, &[reading(*temperature, Some(0x1))]))
+        .collect();
+
+    assert_eq!(events[0], Some(WatchdogEvent::Armed));
+    assert!(events[1..4].iter().all(|event| event.is_none()));
+    assert_eq!(
+        events[4],
+        Some(WatchdogEvent::Tripped(TripReason::CriticalTemperature {
+            uuid: String::from(UUID),
+            temperature: 91.0
+        }))
+    );
+
+    // Stays tripped (without more events) until acknowledged
+    assert_eq!(watchdog.tick(true, &[reading(95.0, None)]), None);
+    assert_eq!(watchdog.tick(false, &[]), None);
+    assert!(matches!(watchdog.state(), WatchdogState::Tripped(_)));
+
+    watchdog.acknowledge();
+    assert_eq!(watchdog.state(), &WatchdogState::Idle);
+    assert_eq!(
+        watchdog.tick(true, &[reading(60.0, None)]),
+        Some(WatchdogEvent::Armed)
+    );
+}
+
+#[test]
+fn test_trips_on_arming() {
+    // Already critical when tuning is applied
+    let mut watchdog: Watchdog = Watchdog::new(CONFIG);
+    assert!(matches!(
+        watchdog.tick(true, &[reading(92.0, None)]),
+        Some(WatchdogEvent::Tripped(_))
+    ));
+}
+
+#[test]
+fn test_thermal_slowdown() {
+    let mut watchdog: Watchdog = Watchdog::new(CONFIG);
+    watchdog.tick(true, &[reading(70.0, None)]);
+
+    // Power cap & software thermal slowdown are expected
+    assert_eq!(
+        watchdog.tick(true, &[reading(75.0, Some(0x0000_0024))]),
+        None
+    );
+
+    // Hardware thermal slowdown isn't
+    assert_eq!(
+        watchdog.tick(true, &[reading(75.0, Some(0x0000_0040))]),
+        Some(WatchdogEvent::Tripped(TripReason::ThermalSlowdown {
+            uuid: String::from(UUID)
+        }))
+    );
+}
+
+#[test]
+fn test_provider_failure() {
+    let mut watchdog: Watchdog = Watchdog::new(CONFIG);
+
+    // Failures must be in a row
+    let stream: Vec<Vec<Sample>> = vec![
+        vec![reading(70.0, None)],
+        vec![failure()],
+        vec![failure()],
+        vec![reading(70.0, None)],
+        vec![failure()],
+        vec![reading(70.0, None), failure()],
+    ];
+    for (index, samples) in stream.iter().enumerate() {
+        let event: Option<WatchdogEvent> = watchdog.tick(true, samples);
+        if index == 0 {
+            assert_eq!(event, Some(WatchdogEvent::Armed));
+        } else {
+            assert_eq!(event, None);
+        }
+    }
+    assert_eq!(watchdog.state(), &WatchdogState::Armed { failures: 2 });
+
+    // Third failure in a row
+    match watchdog.tick(true, &[failure()]) {
+        Some(WatchdogEvent::Tripped(TripReason::ProviderFailure { ticks, error })) => {
+            assert_eq!(ticks, 3);
+            assert_eq!(error, "nvidia-smi not found");
+        }
+        other => panic!("unexpected event: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_watchdog_samples() {
+    assert_eq!(
+        parse_watchdog_samples(
+            "GPU-1a2b3c4d, 95, 0x0000000000000040\nGPU-5e6f7a8b, [N/A], [N/A]\n\n"
+        ),
+        vec![
+            reading(95.0, Some(0x40)),
+            Sample::Failure {
+                uuid: String::from("GPU-5e6f7a8b"),
+                error: String::from("Temperature unavailable.."),
+            },
+        ]
+    );
+    assert_eq!(
+        parse_watchdog_samples("GPU-1a2b3c4d, 70, [Not Supported]"),
+        vec![reading(70.0, None)]
+    );
+    assert_eq!(parse_watchdog_samples(""), vec![]);
+}
+
+#[test]
+fn test_read_watchdog_samples() {
+    // Query arguments are added after the command
+    let command: Vec<String> = ["sh", "-c", "echo 'GPU-1a2b3c4d, 95, 0x0'", "sh"]
+        .iter()
+        .map(|arg| String::from(*arg))
+        .collect();
+    assert_eq!(
+        read_watchdog_samples(&command),
+        vec![reading(95.0, Some(0))]
+    );
+
+    // A failed command is a single failure, reporting stderr
+    let command: Vec<String> = [
+        "sh",
+        "-c",
+        "echo 'ignored'; echo 'driver error' >&2; exit 1",
+        "sh",
+    ]
+    .iter()
+    .map(|arg| String::from(*arg))
+    .collect();
+    assert_eq!(
+        read_watchdog_samples(&command),
+        vec![Sample::Failure {
+            uuid: String::new(),
+            error: String::from("Cannot read GPUs: driver error.."),
+        }]
+    );
+
+    // As is reading no GPUs at all
+    let command: Vec<String> = ["sh", "-c", "true", "sh"]
+        .iter()
+        .map(|arg| String::from(*arg))
+        .collect();
+    for command in [command, vec![String::from("false")], vec![]] {
+        assert!(matches!(
+            read_watchdog_samples(&command).as_slice(),
+            [Sample::Failure { .. }]
+        ));
+    }
+}
+
+#[test]
+fn test_tuning_state() {
+    let mut state: TuningState = TuningState::new();
+    assert!(state.is_empty());
+
+    state.mark_tuned(UUID);
+    state.mark_tuned(UUID);
+    assert_eq!(state.tuned(), &[String::from(UUID)]);
+
+    state.set_tripped(true);
+    assert!(state.is_tripped());
+    state.clear();
+    assert!(state.is_empty());
+}
+
+#[test]
+fn test_revert_plan() {
+    let plan: RevertPlan = create_revert_plan(UUID, Some(0)).unwrap();
+
+    assert_eq!(plan.commands.len(), 3);
+    assert_eq!(
+        plan.commands[0],
+        vec!["nvidia-settings", "-a", "[gpu:0]/GPUFanControlState=0"]
+    );
+    assert_eq!(
+        plan.privileged_commands,
+        vec![
+            vec!["pkexec", HELPER_PATH, "reset-gpu-clocks", UUID],
+            vec!["pkexec", HELPER_PATH, "reset-memory-clocks", UUID],
+            vec!["pkexec", HELPER_PATH, "reset-power-limit", UUID],
+        ]
+    );
+
+    // Without nvidia-settings
+    let plan: RevertPlan = create_revert_plan(UUID, None).unwrap();
+    assert!(plan.commands.is_empty());
+    assert_eq!(plan.privileged_commands.len(), 3);
+
+    // Invalid uuid
+    assert!(create_revert_plan("GPU-; reboot", None).is_err());
+}
+
+#[test]
+fn test_run_revert_plan() {
+    let plan: RevertPlan = create_revert_plan(UUID, Some(0)).unwrap();
+
+    // Everything is attempted, even after failures
+    let runner: FakeRunner = create_runner(true);
+    let privileged_runner: FakeRunner = create_runner(false);
+    let errors: Vec<String> = run_revert_plan(&plan, &runner, &privileged_runner);
+
+    assert_eq!(errors.len(), 3);
+    assert_eq!(runner.commands.borrow().len(), 3);
+    assert_eq!(privileged_runner.commands.borrow().len(), 3);
+}
@@ -0,0 +1,308 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_12.rs
+ *
+ * Description:
+ * Integration tests for importing GreenWithEnvy profiles
+ *
+ * Made:
+ * 28/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * A database with GWE's schema is created in a temporary directory
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::{
+    fan_curve::FanCurve,
+    gwe::{
+        convert_fan_profile, convert_gwe, convert_overclock_profile, database_paths, read_database,
+        GweData, GweFanProfile, GweImport, GweOverclockProfile, GweSpeedStep,
+    },
+    profile::Profile,
+};
+use rusqlite::Connection;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const GPU_0: &str = "GPU-1a2b3c4d";
+const GPU_1: &str = "GPU-5e6f7a8b";
+
+/// Schema & default data of a GWE database
+const GWE_DATABASE: &str = "
+    CREATE TABLE fan_profile (
+        id INTEGER NOT NULL PRIMARY KEY, type VARCHAR(255) NOT NULL, name VARCHAR(255) NOT NULL,
+        read_only INTEGER NOT NULL, vbios_silent_mode INTEGER NOT NULL, sort_index INTEGER NOT NULL,
+        timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE speed_step (
+        id INTEGER NOT NULL PRIMARY KEY, profile_id INTEGER NOT NULL, temperature INTEGER NOT NULL,
+        duty INTEGER NOT NULL, timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE overclock_profile (
+        id INTEGER NOT NULL PRIMARY KEY, name VARCHAR(255) NOT NULL, gpu INTEGER NOT NULL,
+        memory INTEGER NOT NULL, read_only INTEGER NOT NULL,
+        timestamp DATETIME DEFAULT CURRENT_TIMESTAMP
+    );
+    INSERT INTO fan_profile (id, type, name, read_only, vbios_silent_mode, sort_index) VALUES
+        (1, 'fan', 'Auto (VBIOS controlled)', 1, 0, 0),
+        (2, 'fan', 'Custom', 0, 0, 2),
+        (3, 'fan', 'Quiet', 0, 0, 1),
+        (4, 'fan', 'Broken', 0, 0, 3);
+    INSERT INTO speed_step (profile_id, temperature, duty) VALUES
+        (2, 80, 100), (2, 40, 30), (2, 60, 60),
+        (3, 50, 25), (3, 85, 70),
+        (4, 60, 150);
+    INSERT INTO overclock_profile (id, name, gpu, memory, read_only) VALUES
+        (1, 'Default', 0, 0, 1),
+        (2, 'Custom', 100, 500, 0);
+";
+
+fn create_database(name: &str, sql: &str) -> PathBuf {
+    let path: PathBuf =
+        std::env::temp_dir().join(format!("gwe-test-{}-{}.db", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    let connection: Connection = Connection::open(&path).unwrap();
+    connection.execute_batch(sql).unwrap();
+
+    path
+}
+
+fn gpus() -> Vec<(String, Vec<u32>)> {
+    vec![
+        (String::from(GPU_0), vec![0, 1]),
+        (String::from(GPU_1), vec![]),
+    ]
+}
+
+fn create_fan_profile(name: &str, steps: &[(i64, i64)]) -> GweFanProfile {
+    GweFanProfile {
+        id: 1,
+        name: String::from(name),
+        vbios_controlled: false,
+        steps: steps
+            .iter()
+            .map(|(temperature, duty)| GweSpeedStep {
+                temperature: *temperature,
+                duty: *duty,
+            })
+            .collect(),
+    }
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_database_paths() {
+    let paths: Vec<PathBuf> = database_paths(Some(Path::new("/home/user")), None);
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("/home/user/.local/share/gwe/gwe.db"),
+            PathBuf::from("/home/user/.var/app/com.leinardi.gwe/data/gwe/gwe.db"),
+        ]
+    );
+
+    // XDG data directory first
+    let paths: Vec<PathBuf> =
+        database_paths(Some(Path::new("/home/user")), Some(Path::new("/data/user")));
+    assert_eq!(paths.len(), 3);
+    assert_eq!(paths[0], PathBuf::from("/data/user/gwe/gwe.db"));
+
+    assert!(database_paths(None, None).is_empty());
+}
+
+#[test]
+fn test_read_database() {
+    let path: PathBuf = create_database("read", GWE_DATABASE);
+    let data: GweData = read_database(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    // Ordered as shown in GWE
+    let names: Vec<&str> = data
+        .fan_profiles
+        .iter()
+        .map(|profile| profile.name.as_str())
+        .collect();
+    assert_eq!(
+        names,
+        vec!["Auto (VBIOS controlled)", "Quiet", "Custom", "Broken"]
+    );
+
+    // Steps sorted by temperature
+    assert!(data.fan_profiles[0].steps.is_empty());
+    assert_eq!(
+        data.fan_profiles[2].steps,
+        vec![
+            GweSpeedStep {
+                temperature: 40,
+                duty: 30
+            },
+            GweSpeedStep {
+                temperature: 60,
+                duty: 60
+            },
+            GweSpeedStep {
+                temperature: 80,
+                duty: 100
+            },
+        ]
+    );
+
+    assert_eq!(
+        data.overclock_profiles[1],
+        GweOverclockProfile {
+            id: 2,
+            name: String::from("Custom"),
+            gpu: 100,
+            memory: 500,
+        }
+    );
+}
+
+#[test]
+fn test_read_database_old_schema() {
+    // Without optional columns or overclock profiles
+    let path: PathBuf = create_database(
+        "old",
+        "CREATE TABLE fan_profile (id INTEGER PRIMARY KEY, name VARCHAR(255));
+         INSERT INTO fan_profile (id, name) VALUES (1, 'Custom');",
+    );
+    let data: GweData = read_database(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(data.fan_profiles.len(), 1);
+    assert!(data.overclock_profiles.is_empty());
+}
+
+#[test]
+fn test_read_database_invalid() {
+    // Missing
+    assert!(read_database(Path::new("/nonexistent/gwe.db")).is_err());
+
+    // Some other database
+    let path: PathBuf = create_database("other", "CREATE TABLE other (id INTEGER);");
+    assert!(read_database(&path).is_err());
+    fs::remove_file(&path).unwrap();
+
+    // Not a database
+    let path: PathBuf = std::env::temp_dir().join(format!("gwe-test-text-{}", std::process::id()));
+    fs::write(&path, "not a database").unwrap();
+    assert!(read_database(&path).is_err());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_convert_fan_profile() {
+    let curve: FanCurve =
+        convert_fan_profile(&create_fan_profile("Quiet", &[(85, 70), (50, 25)])).unwrap();
+    assert_eq!(curve.points_to_string(), "50/25,85/70");
+    assert_eq!((curve.hysteresis, curve.min_change), (0, 0));
+
+    // VBIOS controlled
+    assert!(convert_fan_profile(&create_fan_profile("Auto", &[])).is_err());
+    let mut profile: GweFanProfile = create_fan_profile("Silent", &[(50, 25)]);
+    profile.vbios_controlled = true;
+    assert!(convert_fan_profile(&profile).is_err());
+
+    // Out of range
+    assert!(convert_fan_profile(&create_fan_profile("Broken", &[(60, 150)])).is_err());
+    assert!(convert_fan_profile(&create_fan_profile("Broken", &[(-5, 20)])).is_err());
+}
+
+#[test]
+fn test_convert_overclock_profile() {
+    let profile: GweOverclockProfile = GweOverclockProfile {
+        id: 1,
+        name: String::from("Custom"),
+        gpu: -100,
+        memory: 1000,
+    };
+    assert_eq!(convert_overclock_profile(&profile), Ok((-100, 1000)));
+
+    let profile: GweOverclockProfile = GweOverclockProfile {
+        gpu: i64::MAX,
+        ..profile
+    };
+    assert!(convert_overclock_profile(&profile).is_err());
+}
+
+#[test]
+fn test_convert_gwe() {
+    let path: PathBuf = create_database("convert", GWE_DATABASE);
+    let data: GweData = read_database(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let import: GweImport = convert_gwe(&data, &gpus(), &[String::from("Default")]);
+
+    // Fan & overclock profiles with the same name are merged
+    let custom: Vec<&Profile> = import
+        .profiles
+        .iter()
+        .filter(|profile| profile.name == "Custom")
+        .collect();
+    assert_eq!(custom.len(), 2);
+    assert_eq!(custom[0].uuid, GPU_0);
+    assert_eq!(custom[0].fan_curves.len(), 2);
+    assert_eq!(custom[0].graphics_offset, Some(100));
+    assert_eq!(custom[0].memory_offset, Some(500));
+    assert_eq!(
+        custom[0].to_setting(),
+        "Custom:GPU-1a2b3c4d::100:500:0;0;0;40/30,60/60,80/100|1;0;0;40/30,60/60,80/100"
+    );
+
+    // No fans, offsets only
+    assert_eq!(custom[1].uuid, GPU_1);
+    assert!(custom[1].fan_curves.is_empty());
+    assert_eq!(custom[1].graphics_offset, Some(100));
+
+    // Fan curve only, nothing for the GPU without fans
+    let quiet: Vec<&Profile> = import
+        .profiles
+        .iter()
+        .filter(|profile| profile.name == "Quiet")
+        .collect();
+    assert_eq!(quiet.len(), 1);
+    assert_eq!(quiet[0].graphics_offset, None);
+
+    // Everything that couldn't be converted is reported
+    assert_eq!(import.profiles.len(), 3);
+    assert_eq!(import.problems.len(), 5);
+    assert!(import.problems[0].starts_with("Fan profile `Auto (VBIOS controlled)`"));
+    assert!(import.problems[1].starts_with("Fan profile `Broken`"));
+    assert!(import.problems[2].contains(GPU_1));
+    assert!(import.problems[3].contains(GPU_1));
+    assert_eq!(
+        import.problems[4],
+        "Profile `Default` already exists, skipped.."
+    );
+}
+
+#[test]
+fn test_convert_gwe_invalid_name() {
+    let data: GweData = GweData {
+        fan_profiles: vec![],
+        overclock_profiles: vec![GweOverclockProfile {
+            id: 1,
+            name: String::from("Game: Max"),
+            gpu: 100,
+            memory: 0,
+        }],
+    };
+    let import: GweImport = convert_gwe(&data, &gpus(), &[]);
+
+    assert!(import.profiles.is_empty());
+    assert_eq!(import.problems.len(), 1);
+}
@@ -0,0 +1,173 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_13.rs
+ *
+ * Description:
+ * Integration tests for migrating settings from the original gnome-nvidia-extension
+ *
+ * Made:
+ * 29/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::legacy::{
+    config_key, enabled_properties, translate_legacy, LegacyImport, LegacySettings,
+};
+
+const GPU_0: &str = "GPU-1a2b3c4d";
+const GPU_1: &str = "GPU-5e6f7a8b";
+
+fn gpu_uuids() -> Vec<String> {
+    vec![String::from(GPU_0), String::from(GPU_1)]
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_config_key() {
+    assert_eq!(config_key(0), Some("settingsconfig"));
+    assert_eq!(config_key(1), Some("settingsconfig"));
+    assert_eq!(config_key(2), Some("smiconfig"));
+    assert_eq!(config_key(3), Some("optimusconfig"));
+    assert_eq!(config_key(4), None);
+}
+
+#[test]
+fn test_enabled_properties() {
+    assert_eq!(
+        enabled_properties(&[true, true, false, true, false]),
+        vec!["util", "temp", "fan_speed"]
+    );
+
+    // Unknown properties ignored
+    assert_eq!(
+        enabled_properties(&[false, false, false, false, true, true]),
+        vec!["power_usage"]
+    );
+    assert!(enabled_properties(&[]).is_empty());
+}
+
+#[test]
+fn test_is_empty() {
+    assert!(LegacySettings::default().is_empty());
+    assert!(!LegacySettings {
+        refresh_rate: Some(5),
+        ..LegacySettings::default()
+    }
+    .is_empty());
+}
+
+#[test]
+fn test_translate_values() {
+    let legacy: LegacySettings = LegacySettings {
+        refresh_rate: Some(2),
+        temp_format: Some(1),
+        provider: Some(2),
+        properties: vec![],
+    };
+    let import: LegacyImport = translate_legacy(&legacy, &gpu_uuids(), vec![], vec![]);
+
+    assert_eq!(import.refresh_rate, Some(2));
+    assert_eq!(import.temp_format, Some(1));
+    assert_eq!(import.provider, Some(2));
+    assert!(import.viewconfigs.is_empty());
+    assert!(import.problems.is_empty());
+
+    // Out of range
+    let legacy: LegacySettings = LegacySettings {
+        refresh_rate: Some(60),
+        temp_format: Some(3),
+        provider: Some(7),
+        properties: vec![],
+    };
+    let import: LegacyImport = translate_legacy(&legacy, &gpu_uuids(), vec![], vec![]);
+
+    assert_eq!(import.refresh_rate, Some(20));
+    assert_eq!(import.temp_format, None);
+    assert_eq!(import.provider, None);
+    assert_eq!(import.problems.len(), 3);
+}
+
+#[test]
+fn test_translate_views() {
+    let legacy: LegacySettings = LegacySettings {
+        properties: vec![vec![true, true, false, false, true]],
+        ..LegacySettings::default()
+    };
+    let import: LegacyImport = translate_legacy(
+        &legacy,
+        &gpu_uuids(),
+        vec![format!("{}:0:GPU", GPU_0), String::from("GPU-other:0:GPU")],
+        vec![
+            format!("{}:GPU:0:util", GPU_0),
+            String::from("GPU-other:GPU:0:util"),
+        ],
+    );
+
+    // Same properties for every GPU, other GPUs untouched
+    assert_eq!(
+        import.viewconfigs,
+        vec![
+            String::from("GPU-other:0:GPU"),
+            format!("{}:0:Overview", GPU_0),
+            format!("{}:0:Overview", GPU_1),
+        ]
+    );
+    assert_eq!(import.viewcomponentconfigs.len(), 7);
+    assert_eq!(import.viewcomponentconfigs[0], "GPU-other:GPU:0:util");
+    assert_eq!(
+        import.viewcomponentconfigs[1..4],
+        [
+            format!("{}:Overview:0:util", GPU_0),
+            format!("{}:Overview:1:temp", GPU_0),
+            format!("{}:Overview:2:power_usage", GPU_0),
+        ]
+    );
+}
+
+#[test]
+fn test_translate_views_per_gpu() {
+    let legacy: LegacySettings = LegacySettings {
+        properties: vec![vec![false, true], vec![false, false]],
+        ..LegacySettings::default()
+    };
+    let import: LegacyImport = translate_legacy(
+        &legacy,
+        &gpu_uuids(),
+        vec![format!("{}:0:GPU", GPU_1)],
+        vec![],
+    );
+
+    assert_eq!(
+        import.viewconfigs,
+        vec![format!("{}:0:GPU", GPU_1), format!("{}:0:Overview", GPU_0),]
+    );
+    assert_eq!(
+        import.viewcomponentconfigs,
+        vec![format!("{}:Overview:0:temp", GPU_0)]
+    );
+
+    // Second GPU had nothing enabled, so it's views are kept
+    assert_eq!(
+        import.problems,
+        vec![format!("No properties enabled for GPU {}..", GPU_1)]
+    );
+
+    // Fewer GPUs than before
+    let import: LegacyImport = translate_legacy(&legacy, &[String::from(GPU_0)], vec![], vec![]);
+    assert_eq!(import.viewconfigs.len(), 1);
+    assert_eq!(import.problems.len(), 1);
+}
@@ -0,0 +1,293 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_14.rs
+ *
+ * Description:
+ * Integration tests for the in-memory metric history
+ *
+ * Made:
+ * 30/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::history::{
+    format_value, parse_sample, with_history, HistorySample, MetricHistory, PropertyHistory,
+    Statistics, MAX_SAMPLES,
+};
+use std::{cell::RefCell, rc::Rc};
+
+const GPU_0: &str = "GPU-1a2b3c4d";
+const GPU_1: &str = "GPU-5e6f7a8b";
+
+fn create_history(values: &[Option<f64>]) -> PropertyHistory {
+    let mut history: PropertyHistory = PropertyHistory::new(600);
+    for (index, value) in values.iter().enumerate() {
+        history.push(HistorySample {
+            timestamp: index as u64 * 1000,
+            value: *value,
+        });
+    }
+
+    history
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_parse_sample() {
+    assert_eq!(parse_sample("55°C"), Some((55.0, String::from("°C"))));
+    assert_eq!(parse_sample("45 %"), Some((45.0, String::from("%"))));
+    assert_eq!(parse_sample("150.50 W"), Some((150.5, String::from("W"))));
+    assert_eq!(parse_sample("-5"), Some((-5.0, String::new())));
+    assert_eq!(
+        parse_sample("45 % / 1500 RPM (target 50 %)"),
+        Some((45.0, String::from("%")))
+    );
+
+    // Clock speeds kept in MHz
+    assert_eq!(
+        parse_sample("1.50 GHz"),
+        Some((1500.0, String::from("MHz")))
+    );
+    assert_eq!(parse_sample("900 MHz"), Some((900.0, String::from("MHz"))));
+
+    // Throughput kept in KB/s
+    assert_eq!(
+        parse_sample("1.2 MB/s"),
+        Some((1200.0, String::from("KB/s")))
+    );
+    assert_eq!(
+        parse_sample("950 KB/s"),
+        Some((950.0, String::from("KB/s")))
+    );
+
+    // Temperature kept in °C
+    assert_eq!(parse_sample("131°F"), Some((55.0, String::from("°C"))));
+
+    // PCIe links & performance states kept as their number
+    assert_eq!(parse_sample("Gen 3"), Some((3.0, String::new())));
+    assert_eq!(parse_sample("x16"), Some((16.0, String::new())));
+    assert_eq!(parse_sample("P2"), Some((2.0, String::new())));
+
+    // Not numbers
+    assert_eq!(parse_sample("N/A"), None);
+    assert_eq!(parse_sample(""), None);
+    assert_eq!(parse_sample("Enabled"), None);
+    assert_eq!(parse_sample("Performance"), None);
+}
+
+#[test]
+fn test_format_value() {
+    assert_eq!(format_value(55.0, "°C"), "55°C");
+    assert_eq!(format_value(45.0, "%"), "45 %");
+    assert_eq!(format_value(150.25, "W"), "150.25 W");
+    assert_eq!(format_value(3.0, ""), "3");
+}
+
+#[test]
+fn test_statistics() {
+    let history: PropertyHistory =
+        create_history(&[Some(10.0), Some(30.0), None, Some(20.0), Some(40.0)]);
+    let statistics: Statistics = history.statistics(4000, 0).unwrap();
+    assert_eq!(
+        statistics,
+        Statistics {
+            min: 10.0,
+            max: 40.0,
+            average: 25.0,
+            p95: 40.0,
+            count: 4,
+        }
+    );
+
+    // Window only covers the last 2 seconds
+    let statistics: Statistics = history.statistics(4000, 2).unwrap();
+    assert_eq!(statistics.count, 2);
+    assert_eq!(statistics.min, 20.0);
+
+    // Nothing in window
+    assert!(history.statistics(60_000, 10).is_none());
+    assert!(create_history(&[None, None]).statistics(1000, 0).is_none());
+}
+
+#[test]
+fn test_statistics_p95() {
+    let values: Vec<Option<f64>> = (1..=100).map(|value| Some(value as f64)).collect();
+    let history: PropertyHistory = create_history(&values);
+
+    let statistics: Statistics = history.statistics(99_000, 0).unwrap();
+    assert_eq!(statistics.p95, 95.0);
+    assert_eq!(statistics.average, 50.5);
+}
+
+#[test]
+fn test_retention() {
+    let mut history: PropertyHistory = PropertyHistory::new(10);
+    for second in 0..30 {
+        history.push(HistorySample {
+            timestamp: second * 1000,
+            value: Some(second as f64),
+        });
+    }
+
+    // Only the last 10 seconds kept
+    assert_eq!(history.samples().len(), 11);
+    assert_eq!(history.samples()[0].timestamp, 19_000);
+
+    // Shrinking retention prunes straight away
+    history.set_retention(2);
+    assert_eq!(history.samples().len(), 3);
+
+    // Limited number of samples
+    let mut history: PropertyHistory = PropertyHistory::new(u64::MAX / 1000);
+    for index in 0..(MAX_SAMPLES as u64 + 10) {
+        history.push(HistorySample {
+            timestamp: index,
+            value: None,
+        });
+    }
+    assert_eq!(history.samples().len(), MAX_SAMPLES);
+}
+
+#[test]
+fn test_peak() {
+    let mut history: PropertyHistory = create_history(&[Some(10.0), Some(70.0), Some(30.0)]);
+    assert_eq!(history.peak(), Some(70.0));
+    assert_eq!(history.latest(), Some(30.0));
+
+    // Peak survives pruning
+    history.set_retention(0);
+    assert_eq!(history.samples().len(), 1);
+    assert_eq!(history.peak(), Some(70.0));
+
+    // Reset to current value
+    history.reset_peak();
+    assert_eq!(history.peak(), Some(30.0));
+    history.push(HistorySample {
+        timestamp: 5000,
+        value: Some(50.0),
+    });
+    assert_eq!(history.peak(), Some(50.0));
+}
+
+#[test]
+fn test_metric_history() {
+    let mut history: MetricHistory = MetricHistory::new(600);
+    history.record_text(GPU_0, "temp", 1000, "55°C");
+    history.record_text(GPU_0, "temp", 2000, "N/A");
+    history.record_text(GPU_0, "fan:0", 1000, "40 %");
+    history.record(GPU_1, "temp", 1000, Some(60.0));
+
+    let temp: &PropertyHistory = history.get(GPU_0, "temp").unwrap();
+    assert_eq!(temp.samples().len(), 2);
+    assert_eq!(temp.latest(), None);
+    assert_eq!(temp.peak(), Some(55.0));
+    assert_eq!(temp.unit(), "°C");
+    assert!(history.get(GPU_1, "fan:0").is_none());
+    assert_eq!(
+        history.properties(GPU_0),
+        vec![String::from("fan:0"), String::from("temp")]
+    );
+
+    // Retention applies to every property
+    history.set_retention(0);
+    assert_eq!(history.retention(), 0);
+    assert_eq!(history.get(GPU_0, "temp").unwrap().samples().len(), 1);
+}
+
+#[test]
+fn test_throughput_history() {
+    let mut history: MetricHistory = MetricHistory::new(600);
+    history.record_text(GPU_0, "pcie_rx", 1000, "950 KB/s");
+    history.record_text(GPU_0, "pcie_rx", 2000, "1.2 MB/s");
+
+    let rx: &PropertyHistory = history.get(GPU_0, "pcie_rx").unwrap();
+    assert_eq!(rx.unit(), "KB/s");
+    assert_eq!(rx.peak(), Some(1200.0));
+    assert_eq!(
+        rx.statistics(2000, 10_000).map(|stats| stats.max),
+        Some(1200.0)
+    );
+}
+
+#[test]
+fn test_units_never_mixed() {
+    let mut history: MetricHistory = MetricHistory::new(600);
+
+    // Switching to fahrenheit keeps recording in celcius
+    assert!(history.record_text(GPU_0, "temp", 1000, "55°C"));
+    assert!(history.record_text(GPU_0, "temp", 2000, "131°F"));
+    let temp: &PropertyHistory = history.get(GPU_0, "temp").unwrap();
+    assert_eq!(temp.unit(), "°C");
+    assert_eq!(temp.latest(), Some(55.0));
+
+    // Any other unit is rejected
+    assert!(!history.record_text(GPU_0, "temp", 3000, "40 %"));
+    let temp: &PropertyHistory = history.get(GPU_0, "temp").unwrap();
+    assert_eq!(temp.samples().len(), 2);
+    assert_eq!(temp.unit(), "°C");
+
+    // Missing values don't fix the unit
+    assert!(history.record_text(GPU_0, "power_usage", 1000, "N/A"));
+    assert!(history.record_text(GPU_0, "power_usage", 2000, "150 W"));
+    assert_eq!(history.get(GPU_0, "power_usage").unwrap().unit(), "W");
+}
+
+#[test]
+fn test_subscribe() {
+    let mut history: MetricHistory = MetricHistory::new(600);
+    let seen: Rc<RefCell<Vec<Option<f64>>>> = Rc::new(RefCell::new(vec![]));
+
+    let store: Rc<RefCell<Vec<Option<f64>>>> = Rc::clone(&seen);
+    let id: u64 = history.subscribe(
+        GPU_0,
+        "temp",
+        Box::new(move |property| {
+            store.borrow_mut().push(property.peak());
+            true
+        }),
+    );
+
+    // Only told about this property
+    history.record(GPU_0, "temp", 1000, Some(50.0));
+    history.record(GPU_0, "util", 1000, Some(20.0));
+    history.record(GPU_1, "temp", 1000, Some(90.0));
+    history.record(GPU_0, "temp", 2000, Some(40.0));
+    history.reset_peaks(GPU_0);
+    assert_eq!(*seen.borrow(), vec![Some(50.0), Some(50.0), Some(40.0)]);
+
+    assert!(history.unsubscribe(id));
+    assert!(!history.unsubscribe(id));
+    history.record(GPU_0, "temp", 3000, Some(45.0));
+    assert_eq!(seen.borrow().len(), 3);
+
+    // Dropped once finished
+    history.subscribe(GPU_0, "temp", Box::new(|_| false));
+    assert_eq!(history.subscriptions(), 1);
+    history.record(GPU_0, "temp", 4000, Some(45.0));
+    assert_eq!(history.subscriptions(), 0);
+}
+
+#[test]
+fn test_with_history() {
+    with_history(|history| history.record(GPU_0, "power_usage", 1000, Some(100.0)));
+    let peak: Option<f64> = with_history(|history| {
+        history
+            .get(GPU_0, "power_usage")
+            .and_then(|property| property.peak())
+    });
+    assert_eq!(peak, Some(100.0));
+}
@@ -0,0 +1,205 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_15.rs
+ *
+ * Description:
+ * Integration tests for laying out recorded history as a line chart
+ *
+ * Made:
+ * 31/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::{
+    chart::{
+        axis_ticks, build_segments, format_age, nearest_sample, value_range, ChartArea, ChartRange,
+    },
+    history::HistorySample,
+};
+use std::collections::VecDeque;
+
+const AREA: ChartArea = ChartArea {
+    x: 10.0,
+    y: 0.0,
+    width: 100.0,
+    height: 50.0,
+};
+
+fn create_samples(values: &[Option<f64>]) -> VecDeque<HistorySample> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| HistorySample {
+            timestamp: index as u64 * 1000,
+            value: *value,
+        })
+        .collect()
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_position() {
+    let range: ChartRange = ChartRange {
+        start: 0,
+        end: 10_000,
+        min: 0.0,
+        max: 100.0,
+    };
+    assert_eq!(range.position(&AREA, 0, 0.0), (10.0, 50.0));
+    assert_eq!(range.position(&AREA, 10_000, 100.0), (110.0, 0.0));
+    assert_eq!(range.position(&AREA, 5_000, 50.0), (60.0, 25.0));
+
+    assert_eq!(range.timestamp_at(&AREA, 60.0), 5_000);
+    assert_eq!(range.timestamp_at(&AREA, 0.0), 0);
+    assert_eq!(range.timestamp_at(&AREA, 500.0), 10_000);
+}
+
+#[test]
+fn test_value_range() {
+    let samples: VecDeque<HistorySample> =
+        create_samples(&[Some(40.0), None, Some(70.0), Some(55.0)]);
+    assert_eq!(value_range(&samples, 0, 3000), Some((40.0, 70.0)));
+    assert_eq!(value_range(&samples, 2000, 3000), Some((55.0, 70.0)));
+
+    // Flat line
+    assert_eq!(value_range(&samples, 3000, 3000), Some((54.0, 56.0)));
+
+    // Nothing to show
+    assert_eq!(value_range(&samples, 1000, 1000), None);
+    assert_eq!(value_range(&VecDeque::new(), 0, 1000), None);
+}
+
+#[test]
+fn test_build_segments() {
+    let samples: VecDeque<HistorySample> = create_samples(&[
+        Some(0.0),
+        Some(100.0),
+        None,
+        Some(50.0),
+        Some(50.0),
+        None,
+        None,
+        Some(0.0),
+    ]);
+    let range: ChartRange = ChartRange {
+        start: 0,
+        end: 10_000,
+        min: 0.0,
+        max: 100.0,
+    };
+    let segments: Vec<Vec<(f64, f64)>> = build_segments(&samples, &range, &AREA, 5000);
+
+    // Broken at each missing value
+    assert_eq!(
+        segments,
+        vec![
+            vec![(10.0, 50.0), (20.0, 0.0)],
+            vec![(40.0, 25.0), (50.0, 25.0)],
+            vec![(80.0, 50.0)],
+        ]
+    );
+
+    // Broken where nothing was recorded for too long
+    let samples: VecDeque<HistorySample> = VecDeque::from(vec![
+        HistorySample {
+            timestamp: 0,
+            value: Some(10.0),
+        },
+        HistorySample {
+            timestamp: 1000,
+            value: Some(10.0),
+        },
+        HistorySample {
+            timestamp: 9000,
+            value: Some(10.0),
+        },
+    ]);
+    assert_eq!(build_segments(&samples, &range, &AREA, 5000).len(), 2);
+
+    // Only samples within the range
+    assert_eq!(
+        build_segments(
+            &samples,
+            &ChartRange {
+                start: 5000,
+                ..range
+            },
+            &AREA,
+            5000
+        ),
+        vec![vec![(110.0 - 100.0 * 1000.0 / 5000.0, 45.0)]]
+    );
+}
+
+#[test]
+fn test_build_segments_many_samples() {
+    // One sample per ms, far more than the chart is wide
+    let samples: VecDeque<HistorySample> = (0..10_000)
+        .map(|timestamp| HistorySample {
+            timestamp,
+            value: Some((timestamp % 100) as f64),
+        })
+        .collect();
+    let range: ChartRange = ChartRange {
+        start: 0,
+        end: 10_000,
+        min: 0.0,
+        max: 100.0,
+    };
+    let segments: Vec<Vec<(f64, f64)>> = build_segments(&samples, &range, &AREA, 5000);
+
+    // Lowest & highest of each column kept
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].len(), 200);
+    assert_eq!(segments[0][0].1, 50.0);
+    assert_eq!(segments[0][1].1, 50.0 - 99.0 / 2.0);
+}
+
+#[test]
+fn test_axis_ticks() {
+    assert_eq!(
+        axis_ticks(0.0, 100.0, 5),
+        vec![0.0, 20.0, 40.0, 60.0, 80.0, 100.0]
+    );
+    assert_eq!(axis_ticks(33.0, 71.0, 4), vec![40.0, 50.0, 60.0, 70.0]);
+    assert_eq!(axis_ticks(-1.0, 1.0, 2), vec![-1.0, 0.0, 1.0]);
+    assert_eq!(axis_ticks(0.0, 0.5, 5), vec![0.0, 0.1, 0.2, 0.3, 0.4, 0.5]);
+
+    // Nothing to show
+    assert!(axis_ticks(5.0, 5.0, 4).is_empty());
+    assert!(axis_ticks(0.0, 10.0, 0).is_empty());
+}
+
+#[test]
+fn test_nearest_sample() {
+    let samples: VecDeque<HistorySample> = create_samples(&[Some(1.0), None, Some(3.0)]);
+    assert_eq!(nearest_sample(&samples, 0).unwrap().value, Some(1.0));
+    assert_eq!(nearest_sample(&samples, 400).unwrap().value, Some(1.0));
+    assert_eq!(nearest_sample(&samples, 600).unwrap().value, None);
+    assert_eq!(nearest_sample(&samples, 9000).unwrap().value, Some(3.0));
+    assert!(nearest_sample(&VecDeque::new(), 0).is_none());
+}
+
+#[test]
+fn test_format_age() {
+    assert_eq!(format_age(500), "now");
+    assert_eq!(format_age(30_000), "30s");
+    assert_eq!(format_age(300_000), "5m");
+    assert_eq!(format_age(90_000), "1m 30s");
+    assert_eq!(format_age(7_200_000), "2h");
+    assert_eq!(format_age(5_400_000), "1h 30m");
+}
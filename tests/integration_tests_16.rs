@@ -0,0 +1,184 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_16.rs
+ *
+ * Description:
+ * Integration tests for how properties are displayed in a view
+ *
+ * Made:
+ * 01/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::display_style::{
+    format_component, fraction, parse_colour, ComponentConfig, DisplayKind, DisplayStyle,
+    DISPLAY_KINDS,
+};
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_display_kind() {
+    for kind in DISPLAY_KINDS {
+        assert_eq!(DisplayKind::from_name(kind.name()), Some(kind));
+    }
+    assert_eq!(DisplayKind::from_name("dial"), None);
+    assert_eq!(DisplayKind::default(), DisplayKind::Sparkline);
+}
+
+#[test]
+fn test_style_from_setting() {
+    assert_eq!(
+        DisplayStyle::from_setting("bar;min=10;max=90.5;colour=#3584E4"),
+        Ok(DisplayStyle {
+            kind: DisplayKind::Bar,
+            min: Some(10.0),
+            max: Some(90.5),
+            colour: Some(String::from("#3584e4")),
+        })
+    );
+    assert_eq!(
+        DisplayStyle::from_setting("text"),
+        Ok(DisplayStyle {
+            kind: DisplayKind::Text,
+            ..DisplayStyle::default()
+        })
+    );
+
+    // Unknown options ignored
+    assert_eq!(
+        DisplayStyle::from_setting("gauge;needle=red;max=80"),
+        Ok(DisplayStyle {
+            kind: DisplayKind::Gauge,
+            max: Some(80.0),
+            ..DisplayStyle::default()
+        })
+    );
+
+    // Malformed
+    assert!(DisplayStyle::from_setting("dial").is_err());
+    assert!(DisplayStyle::from_setting("bar;max").is_err());
+    assert!(DisplayStyle::from_setting("bar;max=lots").is_err());
+    assert!(DisplayStyle::from_setting("bar;colour=red").is_err());
+}
+
+#[test]
+fn test_style_to_setting() {
+    let style: DisplayStyle = DisplayStyle {
+        kind: DisplayKind::Gauge,
+        min: Some(0.0),
+        max: Some(250.0),
+        colour: Some(String::from("#e01b24")),
+    };
+    assert_eq!(style.to_setting(), "gauge;min=0;max=250;colour=#e01b24");
+    assert_eq!(DisplayStyle::from_setting(&style.to_setting()), Ok(style));
+
+    assert!(DisplayStyle::default().is_default());
+    assert_eq!(DisplayStyle::default().to_setting(), "sparkline");
+}
+
+#[test]
+fn test_component_config() {
+    // Saved before styles existed
+    let component: ComponentConfig =
+        ComponentConfig::from_setting("GPU-1a2b3c4d:Default:2:temp").unwrap();
+    assert_eq!(component.uuid, "GPU-1a2b3c4d");
+    assert_eq!(component.view_title, "Default");
+    assert_eq!(component.position, 2);
+    assert_eq!(component.property, "temp");
+    assert!(component.style.is_default());
+    assert_eq!(component.to_setting(), "GPU-1a2b3c4d:Default:2:temp");
+
+    // With a style
+    let component: ComponentConfig =
+        ComponentConfig::from_setting("GPU-1a2b3c4d:Default:0:power_usage:bar;max=300").unwrap();
+    assert_eq!(component.style.kind, DisplayKind::Bar);
+    assert_eq!(component.style.max, Some(300.0));
+    assert_eq!(
+        component.to_setting(),
+        "GPU-1a2b3c4d:Default:0:power_usage:bar;max=300"
+    );
+
+    // Malformed
+    assert!(ComponentConfig::from_setting("GPU-1a2b3c4d:Default:temp").is_err());
+    assert!(ComponentConfig::from_setting("GPU-1a2b3c4d:Default:first:temp").is_err());
+    assert!(ComponentConfig::from_setting("GPU-1a2b3c4d:Default:0:temp:dial").is_err());
+}
+
+#[test]
+fn test_format_component() {
+    let style: DisplayStyle = DisplayStyle {
+        kind: DisplayKind::Text,
+        ..DisplayStyle::default()
+    };
+    assert_eq!(
+        format_component("GPU-1a2b3c4d", "Fans", 1, "fan_speed", &style),
+        "GPU-1a2b3c4d:Fans:1:fan_speed:text"
+    );
+    assert_eq!(
+        format_component(
+            "GPU-1a2b3c4d",
+            "Fans",
+            1,
+            "fan_speed",
+            &DisplayStyle::default()
+        ),
+        "GPU-1a2b3c4d:Fans:1:fan_speed"
+    );
+}
+
+#[test]
+fn test_bounds() {
+    let style: DisplayStyle = DisplayStyle::default();
+    assert_eq!(style.bounds("%", Some(40.0)), (0.0, 100.0));
+    assert_eq!(style.bounds("°C", None), (0.0, 100.0));
+    assert_eq!(style.bounds("W", Some(180.0)), (0.0, 180.0));
+    assert_eq!(style.bounds("W", None), (0.0, 1.0));
+
+    let style: DisplayStyle = DisplayStyle {
+        min: Some(30.0),
+        max: Some(90.0),
+        ..DisplayStyle::default()
+    };
+    assert_eq!(style.bounds("°C", Some(60.0)), (30.0, 90.0));
+
+    // Highest below lowest
+    let style: DisplayStyle = DisplayStyle {
+        min: Some(50.0),
+        max: Some(10.0),
+        ..DisplayStyle::default()
+    };
+    assert_eq!(style.bounds("%", None), (50.0, 51.0));
+}
+
+#[test]
+fn test_fraction() {
+    assert_eq!(fraction(50.0, 0.0, 100.0), 0.5);
+    assert_eq!(fraction(150.0, 0.0, 100.0), 1.0);
+    assert_eq!(fraction(-5.0, 0.0, 100.0), 0.0);
+    assert_eq!(fraction(5.0, 10.0, 10.0), 0.0);
+}
+
+#[test]
+fn test_parse_colour() {
+    assert_eq!(parse_colour("#ff0000"), Some((1.0, 0.0, 0.0)));
+    assert_eq!(parse_colour("#0f0"), Some((0.0, 1.0, 0.0)));
+    assert_eq!(parse_colour(" #FFFFFF "), Some((1.0, 1.0, 1.0)));
+    assert_eq!(parse_colour("ff0000"), None);
+    assert_eq!(parse_colour("#ff00"), None);
+    assert_eq!(parse_colour("#gg0000"), None);
+    assert_eq!(parse_colour("#ff000é"), None);
+}
@@ -0,0 +1,182 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_17.rs
+ *
+ * Description:
+ * Integration tests for zooming, panning & exporting the history chart
+ *
+ * Made:
+ * 02/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::{
+    chart::{time_ticks, TimeWindow},
+    history::{export_csv, format_timestamp, MetricHistory, PropertyHistory},
+};
+
+const GPU_0: &str = "GPU-1a2b3c4d";
+const GPU_1: &str = "GPU-5e6f7a8b";
+
+const LIMITS: TimeWindow = TimeWindow {
+    start: 0,
+    end: 3_600_000,
+};
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_zoom() {
+    // Zoom in around the middle
+    let window: TimeWindow = LIMITS.zoom(1_800_000, 0.5, &LIMITS, 10_000);
+    assert_eq!(
+        window,
+        TimeWindow {
+            start: 900_000,
+            end: 2_700_000,
+        }
+    );
+
+    // Anchor stays in place
+    let window: TimeWindow = LIMITS.zoom(3_600_000, 0.5, &LIMITS, 10_000);
+    assert_eq!(window.end, 3_600_000);
+    assert_eq!(window.span(), 1_800_000);
+
+    // Never smaller than the minimum
+    let window: TimeWindow = LIMITS.zoom(0, 0.0, &LIMITS, 10_000);
+    assert_eq!(
+        window,
+        TimeWindow {
+            start: 0,
+            end: 10_000,
+        }
+    );
+
+    // Never larger than the limits
+    let window: TimeWindow = TimeWindow {
+        start: 600_000,
+        end: 1_200_000,
+    };
+    assert_eq!(window.zoom(900_000, 100.0, &LIMITS, 10_000), LIMITS);
+}
+
+#[test]
+fn test_pan() {
+    let window: TimeWindow = TimeWindow {
+        start: 600_000,
+        end: 1_200_000,
+    };
+    assert_eq!(
+        window.pan(-60_000, &LIMITS),
+        TimeWindow {
+            start: 540_000,
+            end: 1_140_000,
+        }
+    );
+
+    // Stops at the limits
+    assert_eq!(
+        window.pan(-1_000_000, &LIMITS),
+        TimeWindow {
+            start: 0,
+            end: 600_000,
+        }
+    );
+    assert_eq!(
+        window.pan(10_000_000, &LIMITS),
+        TimeWindow {
+            start: 3_000_000,
+            end: 3_600_000,
+        }
+    );
+}
+
+#[test]
+fn test_time_ticks() {
+    // Every 10 minutes over an hour
+    assert_eq!(
+        time_ticks(0, 3_600_000, 6),
+        vec![0, 600_000, 1_200_000, 1_800_000, 2_400_000, 3_000_000, 3_600_000]
+    );
+
+    // Aligned to the step
+    assert_eq!(time_ticks(61_000, 179_000, 2), vec![120_000]);
+
+    // Whole days for long ranges
+    assert_eq!(
+        time_ticks(0, 4 * 86_400_000, 2),
+        vec![0, 2 * 86_400_000, 4 * 86_400_000]
+    );
+
+    // Nothing to show
+    assert!(time_ticks(5000, 5000, 4).is_empty());
+    assert!(time_ticks(0, 5000, 0).is_empty());
+}
+
+#[test]
+fn test_format_timestamp() {
+    assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+    assert_eq!(
+        format_timestamp(1_675_332_930_250),
+        "2023-02-02T10:15:30.250Z"
+    );
+    assert_eq!(
+        format_timestamp(951_782_400_000),
+        "2000-02-29T00:00:00.000Z"
+    );
+}
+
+#[test]
+fn test_uuids() {
+    let mut history: MetricHistory = MetricHistory::new(600);
+    history.record(GPU_1, "temp", 1000, Some(60.0));
+    history.record(GPU_0, "temp", 1000, Some(50.0));
+    history.record(GPU_0, "util", 1000, Some(20.0));
+    assert_eq!(
+        history.uuids(),
+        vec![String::from(GPU_0), String::from(GPU_1)]
+    );
+}
+
+#[test]
+fn test_export_csv() {
+    let mut history: MetricHistory = MetricHistory::new(600);
+    history.record_text(GPU_0, "temp", 1000, "50°C");
+    history.record_text(GPU_0, "temp", 2000, "N/A");
+    history.record_text(GPU_0, "temp", 3000, "52°C");
+    history.record(GPU_0, "util", 2000, Some(12.5));
+    history.record(GPU_0, "util", 9000, Some(20.0));
+
+    let temp: &PropertyHistory = history.get(GPU_0, "temp").unwrap();
+    let util: &PropertyHistory = history.get(GPU_0, "util").unwrap();
+    assert_eq!(
+        export_csv(
+            &[("GPU 0, Temperature", temp), ("GPU 0 Utilization", util)],
+            1000,
+            3000
+        ),
+        "timestamp,time,\"GPU 0, Temperature (°C)\",GPU 0 Utilization\n\
+         1000,1970-01-01T00:00:01.000Z,50,\n\
+         2000,1970-01-01T00:00:02.000Z,,12.5\n\
+         3000,1970-01-01T00:00:03.000Z,52,\n"
+    );
+
+    // Nothing in range
+    assert_eq!(
+        export_csv(&[("Temperature", temp)], 5000, 6000),
+        "timestamp,time,Temperature (°C)\n"
+    );
+}
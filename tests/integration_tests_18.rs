@@ -0,0 +1,368 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_18.rs
+ *
+ * Description:
+ * Integration tests for the on-disk metrics store
+ *
+ * Made:
+ * 03/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::{
+    history::{HistorySample, MetricHistory},
+    metrics_store::{
+        store_path, stored_samples, Maintenance, MetricsStore, MetricsWorker, Resolution,
+        RetentionPolicy, StoredSample, DAY, HOUR, MINUTE,
+    },
+    sampler::{find_metric, parse_samples, GpuSample, Metric},
+};
+use std::{
+    cell::RefCell,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    thread,
+    time::Duration,
+};
+
+const GPU_0: &str = "GPU-1a2b3c4d";
+const GPU_1: &str = "GPU-5e6f7a8b";
+
+/// A whole number of days since the unix epoch, so buckets line up
+const NOW: u64 = 19_391 * DAY;
+
+fn open_store(name: &str, policy: RetentionPolicy) -> (MetricsStore, PathBuf) {
+    let path: PathBuf = std::env::temp_dir().join(format!(
+        "metrics-test-{}-{}/metrics.db",
+        name,
+        std::process::id()
+    ));
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+
+    (MetricsStore::open(&path, policy).unwrap(), path)
+}
+
+fn sample(uuid: &str, property: &str, timestamp: u64, value: Option<f64>) -> StoredSample {
+    StoredSample {
+        uuid: String::from(uuid),
+        property: String::from(property),
+        unit: String::from("°C"),
+        timestamp,
+        value,
+    }
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_store_path() {
+    assert_eq!(
+        store_path(Some(Path::new("/home/user")), None),
+        Some(PathBuf::from(
+            "/home/user/.local/share/gtk4-nvidia-monitor-rust/metrics.db"
+        ))
+    );
+    assert_eq!(
+        store_path(Some(Path::new("/home/user")), Some(Path::new("/data/user"))),
+        Some(PathBuf::from(
+            "/data/user/gtk4-nvidia-monitor-rust/metrics.db"
+        ))
+    );
+    assert_eq!(store_path(None, None), None);
+}
+
+#[test]
+fn test_record_and_query() {
+    let (mut store, path) = open_store("record", RetentionPolicy::default());
+    assert_eq!(
+        store.record(&[
+            sample(GPU_0, "temp", NOW - 2000, Some(50.0)),
+            sample(GPU_0, "temp", NOW - 1000, None),
+            sample(GPU_0, "temp", NOW, Some(52.5)),
+            sample(GPU_1, "temp", NOW, Some(70.0)),
+        ]),
+        Ok(4)
+    );
+
+    assert_eq!(
+        store.query(GPU_0, "temp", NOW - 1500, NOW),
+        Ok(vec![
+            HistorySample {
+                timestamp: NOW - 1000,
+                value: None,
+            },
+            HistorySample {
+                timestamp: NOW,
+                value: Some(52.5),
+            },
+        ])
+    );
+    assert_eq!(store.query(GPU_0, "util", 0, NOW), Ok(vec![]));
+    assert_eq!(
+        store.series(),
+        Ok(vec![
+            (
+                String::from(GPU_0),
+                String::from("temp"),
+                String::from("°C")
+            ),
+            (
+                String::from(GPU_1),
+                String::from("temp"),
+                String::from("°C")
+            ),
+        ])
+    );
+
+    // Still there after re-opening
+    drop(store);
+    let store: MetricsStore = MetricsStore::open(&path, RetentionPolicy::default()).unwrap();
+    assert_eq!(store.count(Resolution::Raw), Ok(4));
+}
+
+#[test]
+fn test_roll_up() {
+    let (mut store, _) = open_store("roll-up", RetentionPolicy::default());
+
+    // Every 10 seconds for 2 hours, the value being the minute it was read in
+    let samples: Vec<StoredSample> = (0..720)
+        .map(|step| {
+            let timestamp: u64 = NOW - 2 * HOUR + step * 10_000;
+            sample(GPU_0, "temp", timestamp, Some((step / 6) as f64))
+        })
+        .collect();
+    store.record(&samples).unwrap();
+
+    let maintenance: Maintenance = store.maintain(NOW).unwrap();
+    assert_eq!(maintenance.rolled_up, 360);
+    assert_eq!(maintenance.expired, 0);
+    assert_eq!(store.count(Resolution::Raw), Ok(360));
+    assert_eq!(store.count(Resolution::Minute), Ok(60));
+
+    // Averaged into the minute they were read in
+    let oldest: Vec<HistorySample> = store
+        .query(GPU_0, "temp", NOW - 2 * HOUR, NOW - 2 * HOUR + 2 * MINUTE)
+        .unwrap();
+    assert_eq!(
+        oldest,
+        vec![
+            HistorySample {
+                timestamp: NOW - 2 * HOUR,
+                value: Some(0.0),
+            },
+            HistorySample {
+                timestamp: NOW - 2 * HOUR + MINUTE,
+                value: Some(1.0),
+            },
+            HistorySample {
+                timestamp: NOW - 2 * HOUR + 2 * MINUTE,
+                value: Some(2.0),
+            },
+        ]
+    );
+
+    // Then into hours after a week
+    let maintenance: Maintenance = store.maintain(NOW + 8 * DAY).unwrap();
+    assert_eq!(store.count(Resolution::Raw), Ok(0));
+    assert_eq!(store.count(Resolution::Minute), Ok(0));
+    assert_eq!(store.count(Resolution::Hour), Ok(2));
+    assert_eq!(maintenance.rolled_up, 360 + 120);
+    assert_eq!(
+        store.query(GPU_0, "temp", 0, NOW),
+        Ok(vec![
+            HistorySample {
+                timestamp: NOW - 2 * HOUR,
+                value: Some(29.5),
+            },
+            HistorySample {
+                timestamp: NOW - HOUR,
+                value: Some(89.5),
+            },
+        ])
+    );
+
+    // Forgotten after a year
+    let maintenance: Maintenance = store.maintain(NOW + 366 * DAY).unwrap();
+    assert_eq!(maintenance.expired, 2);
+    assert_eq!(store.series(), Ok(vec![]));
+}
+
+#[test]
+fn test_roll_up_merges() {
+    let (mut store, _) = open_store("merge", RetentionPolicy::default());
+
+    // A minute split across two rounds of maintenance, with a missing reading
+    store
+        .record(&[
+            sample(GPU_0, "temp", NOW - 2 * HOUR, Some(40.0)),
+            sample(GPU_0, "temp", NOW - 2 * HOUR + 10_000, None),
+        ])
+        .unwrap();
+    store.maintain(NOW).unwrap();
+    store
+        .record(&[
+            sample(GPU_0, "temp", NOW - 2 * HOUR + 20_000, Some(50.0)),
+            sample(GPU_0, "temp", NOW - 2 * HOUR + 30_000, Some(60.0)),
+        ])
+        .unwrap();
+    store.maintain(NOW).unwrap();
+
+    assert_eq!(store.count(Resolution::Minute), Ok(1));
+    assert_eq!(
+        store.query(GPU_0, "temp", 0, NOW),
+        Ok(vec![HistorySample {
+            timestamp: NOW - 2 * HOUR,
+            value: Some(50.0),
+        }])
+    );
+
+    // Nothing but missing readings stays missing
+    store
+        .record(&[sample(GPU_1, "temp", NOW - 2 * HOUR, None)])
+        .unwrap();
+    store.maintain(NOW).unwrap();
+    assert_eq!(
+        store.query(GPU_1, "temp", 0, NOW),
+        Ok(vec![HistorySample {
+            timestamp: NOW - 2 * HOUR,
+            value: None,
+        }])
+    );
+}
+
+#[test]
+fn test_size_limit() {
+    let (mut store, _) = open_store(
+        "size",
+        RetentionPolicy {
+            max_bytes: 64 * 1024,
+            ..RetentionPolicy::default()
+        },
+    );
+
+    let samples: Vec<StoredSample> = (0..20_000)
+        .map(|step| sample(GPU_0, "temp", NOW - 20_000 + step, Some(step as f64)))
+        .collect();
+    store.record(&samples).unwrap();
+    assert!(store.used_bytes().unwrap() > 64 * 1024);
+
+    let maintenance: Maintenance = store.maintain(NOW).unwrap();
+    assert!(maintenance.trimmed > 0);
+    assert!(maintenance.vacuumed);
+    assert!(store.used_bytes().unwrap() <= 64 * 1024);
+
+    // Oldest readings forgotten first
+    let kept: Vec<HistorySample> = store.query(GPU_0, "temp", 0, NOW).unwrap();
+    assert!(!kept.is_empty());
+    assert_eq!(kept.last().unwrap().timestamp, NOW - 1);
+    assert!(kept[0].timestamp > NOW - 20_000);
+}
+
+#[test]
+fn test_history_recorder() {
+    let recorded: Rc<RefCell<Vec<StoredSample>>> = Rc::new(RefCell::new(vec![]));
+    let mut history: MetricHistory = MetricHistory::new(600);
+
+    // Restored samples aren't recorded again
+    history.restore(
+        GPU_0,
+        "temp",
+        "°C",
+        &[HistorySample {
+            timestamp: 1000,
+            value: Some(40.0),
+        }],
+    );
+    let recorder_store: Rc<RefCell<Vec<StoredSample>>> = Rc::clone(&recorded);
+    history.set_recorder(Some(Box::new(move |uuid, property, unit, sample| {
+        recorder_store.borrow_mut().push(StoredSample {
+            uuid: String::from(uuid),
+            property: String::from(property),
+            unit: String::from(unit),
+            timestamp: sample.timestamp,
+            value: sample.value,
+        })
+    })));
+    history.record_text(GPU_0, "temp", 2000, "45°C");
+    history.record(GPU_0, "temp", 3000, None);
+
+    assert_eq!(history.get(GPU_0, "temp").unwrap().samples().len(), 3);
+    assert_eq!(
+        *recorded.borrow(),
+        vec![
+            sample(GPU_0, "temp", 2000, Some(45.0)),
+            sample(GPU_0, "temp", 3000, None),
+        ]
+    );
+}
+
+#[test]
+fn test_stored_samples() {
+    let metrics: Vec<&Metric> = ["temp", "gr_clock"]
+        .iter()
+        .map(|property| find_metric(property).unwrap())
+        .collect();
+    let samples: Vec<GpuSample> = parse_samples(
+        "GPU-1a2b3c4d, 45, 1500, Tesla T4\nGPU-5e6f7a8b, [N/A], 300, Tesla T4\n",
+        &metrics,
+        NOW,
+    )
+    .unwrap();
+
+    // Typed values, in the units nvidia-smi reports
+    let stored: Vec<StoredSample> = stored_samples(&samples);
+    assert_eq!(stored.len(), 4);
+    assert_eq!(stored[0], sample(GPU_0, "temp", NOW, Some(45.0)));
+    assert_eq!(
+        stored[1],
+        StoredSample {
+            uuid: String::from(GPU_0),
+            property: String::from("gr_clock"),
+            unit: String::from("MHz"),
+            timestamp: NOW,
+            value: Some(1500.0),
+        }
+    );
+    assert_eq!(stored[2], sample(GPU_1, "temp", NOW, None));
+}
+
+#[test]
+fn test_metrics_worker() {
+    let (store, path) = open_store("worker", RetentionPolicy::default());
+    let worker: MetricsWorker = MetricsWorker::spawn(store);
+    assert!(worker.record(vec![
+        sample(GPU_0, "temp", NOW - 1000, Some(50.0)),
+        sample(GPU_0, "temp", NOW, Some(52.5)),
+    ]));
+    assert!(worker.maintain(RetentionPolicy::default(), NOW));
+    drop(worker);
+
+    // Written in the background
+    let store: MetricsStore = MetricsStore::open(&path, RetentionPolicy::default()).unwrap();
+    let mut count: usize = 0;
+    for _ in 0..50 {
+        count = store.count(Resolution::Raw).unwrap();
+        if count == 2 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    assert_eq!(count, 2);
+}
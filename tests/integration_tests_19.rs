@@ -0,0 +1,313 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_19.rs
+ *
+ * Description:
+ * Integration tests for the sampler and logging samples to file
+ *
+ * Made:
+ * 04/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::metrics;
+use gtk4_nvidia_monitor_rust::{
+    sample_log::{
+        format_csv_header, format_csv_row, format_json_line, log_file_name, LogFormat,
+        RotationPolicy, SampleLog,
+    },
+    sampler::{
+        find_metric, parse_samples, parse_value, query_arguments, sampler_command, GpuSample,
+        Sampler,
+    },
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const GPU_0: &str = "GPU-1a2b3c4d";
+const GPU_1: &str = "GPU-5e6f7a8b";
+
+/// 2023-02-04T00:00:00Z
+const MIDNIGHT: u64 = 1_675_468_800_000;
+
+fn sample(timestamp: u64, temp: Option<f64>) -> GpuSample {
+    parse_samples(
+        &format!(
+            "{}, 45, {}, NVIDIA GeForce RTX 3080",
+            GPU_0,
+            match temp {
+                Some(value) => value.to_string(),
+                None => String::from("[N/A]"),
+            }
+        ),
+        &metrics(&["util", "temp"]),
+        timestamp,
+    )
+    .unwrap()
+    .remove(0)
+}
+
+fn log_directory(name: &str) -> PathBuf {
+    let directory: PathBuf =
+        std::env::temp_dir().join(format!("sample-log-test-{}-{}", name, std::process::id()));
+    if let Ok(entries) = fs::read_dir(&directory) {
+        for entry in entries.flatten() {
+            let _ = fs::remove_file(entry.path());
+        }
+    }
+
+    directory
+}
+
+fn file_names(directory: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(directory)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+
+    names
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_query_arguments() {
+    assert_eq!(
+        query_arguments(&metrics(&["temp", "power_usage"])),
+        vec![
+            String::from("--query-gpu=uuid,temperature.gpu,power.draw,name"),
+            String::from("--format=csv,noheader,nounits"),
+        ]
+    );
+    assert_eq!(sampler_command(2), vec![String::from("nvidia-smi")]);
+    assert_eq!(
+        sampler_command(3),
+        vec![String::from("optirun"), String::from("nvidia-smi")]
+    );
+}
+
+#[test]
+fn test_parse_value() {
+    assert_eq!(parse_value(" 52"), Some(52.0));
+    assert_eq!(parse_value("115.47"), Some(115.47));
+    assert_eq!(parse_value("P2"), Some(2.0));
+    assert_eq!(parse_value("0x0000000000000044"), Some(68.0));
+    assert_eq!(parse_value("0xnothing"), None);
+    assert_eq!(parse_value("Yes"), Some(1.0));
+    assert_eq!(parse_value("No"), Some(0.0));
+    assert_eq!(parse_value("[N/A]"), None);
+    assert_eq!(parse_value("[Not Supported]"), None);
+    assert_eq!(parse_value("NaN"), None);
+}
+
+#[test]
+fn test_parse_samples() {
+    let output: &str = "GPU-1a2b3c4d, 45, 52, P2, NVIDIA GeForce RTX 3080\n\
+                        GPU-5e6f7a8b, 0, [N/A], P8, Tesla, Model X\n";
+    let samples: Vec<GpuSample> =
+        parse_samples(output, &metrics(&["util", "temp", "pstate"]), MIDNIGHT).unwrap();
+
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].uuid, GPU_0);
+    assert_eq!(samples[0].name, "NVIDIA GeForce RTX 3080");
+    assert_eq!(samples[0].timestamp, MIDNIGHT);
+    assert_eq!(samples[0].value("util"), Some(45.0));
+    assert_eq!(samples[0].value("temp"), Some(52.0));
+    assert_eq!(samples[0].value("pstate"), Some(2.0));
+    assert_eq!(samples[0].readings[1].metric.unit, "°C");
+
+    // Missing values & commas in the name
+    assert_eq!(samples[1].uuid, GPU_1);
+    assert_eq!(samples[1].name, "Tesla, Model X");
+    assert_eq!(samples[1].value("temp"), None);
+    assert_eq!(samples[1].value("pstate"), Some(8.0));
+
+    // Not enough fields
+    assert!(parse_samples("GPU-1a2b3c4d, 45\n", &metrics(&["util", "temp"]), 0).is_err());
+    assert_eq!(parse_samples("\n", &metrics(&["util"]), 0), Ok(vec![]));
+}
+
+#[test]
+fn test_sampler() {
+    // Stand-in for nvidia-smi (query arguments are ignored)
+    let sampler: Sampler = Sampler::with_properties(
+        vec![
+            String::from("sh"),
+            String::from("-c"),
+            format!("echo '{}, 45, 52.5, NVIDIA GeForce RTX 3080'", GPU_0),
+        ],
+        &["util", "temp"],
+    )
+    .unwrap();
+    assert_eq!(sampler.metrics().len(), 2);
+
+    let samples: Vec<GpuSample> = sampler.sample().unwrap();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].value("temp"), Some(52.5));
+    assert!(samples[0].timestamp > 0);
+
+    // Failures
+    assert!(Sampler::with_properties(vec![String::from("sh")], &["not_a_property"]).is_err());
+    assert!(Sampler::new(vec![String::from("/nonexistent/nvidia-smi")])
+        .sample()
+        .is_err());
+    assert!(Sampler::new(vec![String::from("false")]).sample().is_err());
+    assert!(Sampler::new(vec![]).sample().is_err());
+}
+
+#[test]
+fn test_probe_sampler() {
+    // Stand-in for an older nvidia-smi, rejecting the whole query if it has a fan speed
+    let script: String = format!(
+        "case \"$1\" in *fan.speed*) echo 'Field \"fan.speed\" is not a valid field to query.'; exit 2;; esac; \
+         out=''; IFS=,; for field in ${{1#--query-gpu=}}; do case $field in \
+         uuid) out='{}';; name) out=\"$out, Tesla T4\";; *) out=\"$out, 1\";; esac; done; echo \"$out\"",
+        GPU_0
+    );
+    let command: Vec<String> = vec![
+        String::from("sh"),
+        String::from("-c"),
+        script,
+        String::from("nvidia-smi"),
+    ];
+    assert!(Sampler::new(command.clone()).sample().is_err());
+
+    // Everything else is still read
+    let sampler: Sampler = Sampler::new(command.clone()).probe();
+    assert_eq!(
+        sampler.metrics().len(),
+        Sampler::new(vec![]).metrics().len() - 1
+    );
+    assert!(find_metric("fan_speed")
+        .map(|metric| !sampler.metrics().contains(&metric))
+        .unwrap());
+    let samples: Vec<GpuSample> = sampler.sample().unwrap();
+    assert_eq!(samples[0].value("temp"), Some(1.0));
+    assert_eq!(samples[0].name, "Tesla T4");
+
+    // Nothing to leave out
+    let sampler: Sampler = Sampler::with_properties(command, &["util", "temp"]).unwrap();
+    assert_eq!(sampler.probe(), sampler);
+
+    // Nothing works
+    let sampler: Sampler = Sampler::new(vec![String::from("false")]);
+    assert_eq!(sampler.probe(), sampler);
+}
+
+#[test]
+fn test_format_rows() {
+    assert_eq!(
+        format_csv_header(&sample(MIDNIGHT, Some(52.0))),
+        "timestamp,time,uuid,util (%),temp (°C)\n"
+    );
+    assert_eq!(
+        format_csv_row(&sample(MIDNIGHT, Some(52.5))),
+        "1675468800000,2023-02-04T00:00:00.000Z,GPU-1a2b3c4d,45,52.5\n"
+    );
+    assert_eq!(
+        format_csv_row(&sample(MIDNIGHT, None)),
+        "1675468800000,2023-02-04T00:00:00.000Z,GPU-1a2b3c4d,45,\n"
+    );
+    assert_eq!(
+        format_json_line(&sample(MIDNIGHT, None)),
+        "{\"timestamp\":1675468800000,\"time\":\"2023-02-04T00:00:00.000Z\",\
+         \"uuid\":\"GPU-1a2b3c4d\",\"name\":\"NVIDIA GeForce RTX 3080\",\"values\":{\
+         \"util\":{\"value\":45,\"unit\":\"%\"},\"temp\":{\"value\":null,\"unit\":\"°C\"}}}\n"
+    );
+    assert_eq!(LogFormat::from_name("jsonl"), Some(LogFormat::JsonLines));
+    assert_eq!(LogFormat::from_name("xml"), None);
+}
+
+#[test]
+fn test_log_rotation() {
+    let directory: PathBuf = log_directory("rotation");
+    let row_size: u64 = format_csv_row(&sample(MIDNIGHT, Some(52.0))).len() as u64;
+    let header_size: u64 = format_csv_header(&sample(MIDNIGHT, Some(52.0))).len() as u64;
+    let mut log: SampleLog = SampleLog::new(
+        &directory,
+        GPU_0,
+        LogFormat::Csv,
+        RotationPolicy {
+            max_bytes: header_size + 2 * row_size,
+            daily: true,
+        },
+    );
+    assert_eq!(log.path(), None);
+
+    // Two rows fit in each file
+    for second in 0..3 {
+        log.write(&sample(MIDNIGHT + second * 1000, Some(52.0)))
+            .unwrap();
+    }
+    // New day
+    log.write(&sample(MIDNIGHT + 86_400_000, Some(52.0)))
+        .unwrap();
+    assert_eq!(
+        log.path(),
+        Some(directory.join("GPU-1a2b3c4d_2023-02-05.csv").as_path())
+    );
+
+    assert_eq!(
+        file_names(&directory),
+        vec![
+            String::from("GPU-1a2b3c4d_2023-02-04.1.csv"),
+            String::from("GPU-1a2b3c4d_2023-02-04.csv"),
+            String::from("GPU-1a2b3c4d_2023-02-05.csv"),
+        ]
+    );
+    let first: String = fs::read_to_string(directory.join("GPU-1a2b3c4d_2023-02-04.csv")).unwrap();
+    assert_eq!(first.lines().count(), 3);
+    assert!(first.starts_with("timestamp,"));
+
+    // Restarting never overwrites an existing file
+    let mut log: SampleLog =
+        SampleLog::new(&directory, GPU_0, LogFormat::Csv, RotationPolicy::default());
+    log.write(&sample(MIDNIGHT + 5000, Some(52.0))).unwrap();
+    assert_eq!(
+        log.path(),
+        Some(directory.join("GPU-1a2b3c4d_2023-02-04.2.csv").as_path())
+    );
+}
+
+#[test]
+fn test_log_json_lines() {
+    let directory: PathBuf = log_directory("jsonl");
+    let mut log: SampleLog = SampleLog::new(
+        &directory,
+        GPU_0,
+        LogFormat::JsonLines,
+        RotationPolicy::default(),
+    );
+    log.write(&sample(MIDNIGHT, Some(52.0))).unwrap();
+    log.write(&sample(MIDNIGHT + 1000, None)).unwrap();
+
+    let contents: String = fs::read_to_string(directory.join(log_file_name(
+        GPU_0,
+        "2023-02-04",
+        0,
+        LogFormat::JsonLines,
+    )))
+    .unwrap();
+    assert_eq!(contents.lines().count(), 2);
+    assert!(contents
+        .lines()
+        .all(|line| line.starts_with('{') && line.ends_with('}')));
+}
@@ -0,0 +1,114 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_2.rs
+ *
+ * Description:
+ * Integration tests for decoding of clock throttle reasons
+ *
+ * Made:
+ * 15/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::throttle::{
+    create_badge_markup, decode_throttle_reasons, parse_throttle_bitmask, ThrottleReason,
+};
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_parse_throttle_bitmask() {
+    // Output format of nvidia-smi
+    assert_eq!(parse_throttle_bitmask("0x0000000000000004"), Some(0x4));
+    assert_eq!(parse_throttle_bitmask(" 0x0000000000000060 "), Some(0x60));
+    assert_eq!(parse_throttle_bitmask("0x0000000000000000"), Some(0x0));
+
+    // Unsupported
+    assert_eq!(parse_throttle_bitmask("[N/A]"), None);
+    assert_eq!(parse_throttle_bitmask(""), None);
+}
+
+#[test]
+fn test_decode_no_reasons() {
+    assert_eq!(decode_throttle_reasons(0x0), vec![]);
+}
+
+#[test]
+fn test_decode_single_reasons() {
+    assert_eq!(decode_throttle_reasons(0x1), vec![ThrottleReason::Idle]);
+    assert_eq!(decode_throttle_reasons(0x4), vec![ThrottleReason::PowerCap]);
+    assert_eq!(
+        decode_throttle_reasons(0x8),
+        vec![ThrottleReason::HwSlowdown]
+    );
+    assert_eq!(
+        decode_throttle_reasons(0x10),
+        vec![ThrottleReason::SyncBoost]
+    );
+    assert_eq!(
+        decode_throttle_reasons(0x20),
+        vec![ThrottleReason::SwThermalSlowdown]
+    );
+    assert_eq!(
+        decode_throttle_reasons(0x40),
+        vec![ThrottleReason::HwThermalSlowdown]
+    );
+}
+
+#[test]
+fn test_decode_multiple_reasons() {
+    // Power cap + sync boost
+    assert_eq!(
+        decode_throttle_reasons(0x14),
+        vec![ThrottleReason::PowerCap, ThrottleReason::SyncBoost]
+    );
+
+    // HW slowdown triggered by both thermal and power brake
+    assert_eq!(
+        decode_throttle_reasons(0xC8),
+        vec![
+            ThrottleReason::HwSlowdown,
+            ThrottleReason::HwThermalSlowdown,
+            ThrottleReason::HwPowerBrakeSlowdown,
+        ]
+    );
+}
+
+#[test]
+fn test_decode_ignores_unknown_bits() {
+    assert_eq!(
+        decode_throttle_reasons(0x8000_0000_0000_0004),
+        vec![ThrottleReason::PowerCap]
+    );
+}
+
+#[test]
+fn test_problem_reasons() {
+    assert!(!ThrottleReason::Idle.is_problem());
+    assert!(!ThrottleReason::SyncBoost.is_problem());
+    assert!(ThrottleReason::PowerCap.is_problem());
+    assert!(ThrottleReason::SwThermalSlowdown.is_problem());
+    assert!(ThrottleReason::HwSlowdown.is_problem());
+}
+
+#[test]
+fn test_badge_markup() {
+    assert_eq!(create_badge_markup(&[]), "None");
+    assert_eq!(
+        create_badge_markup(&decode_throttle_reasons(0x4)),
+        "<span background=\"#e66100\" foreground=\"#ffffff\"> Power Cap </span>"
+    );
+}
@@ -0,0 +1,259 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_20.rs
+ *
+ * Description:
+ * Integration tests for the OpenMetrics server and headless mode
+ *
+ * Made:
+ * 05/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{args, metrics, samples, PROPERTIES};
+use gtk4_nvidia_monitor_rust::{
+    headless::{is_headless, parse_arguments, run, HeadlessConfig},
+    metrics_server::{
+        format_openmetrics, metric_name, parse_request_line, provider_label, MetricsServer,
+        CONTENT_TYPE,
+    },
+    sampler::{find_metric, GpuSample, Sampler},
+};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Memory readings & a name that needs escaping
+const SCRAPE_OUTPUT: &str = "GPU-1a2b3c4d, 45, 52, 1024, NVIDIA GeForce RTX 3080\n\
+                             GPU-5e6f7a8b, 3, [N/A], 512, Tesla \"T4\"\n";
+
+fn fake_sampler(output: &str) -> Sampler {
+    Sampler::with_properties(
+        // Stand-in for nvidia-smi, query arguments are ignored
+        vec![
+            String::from("sh"),
+            String::from("-c"),
+            String::from("printf '%s' \"$0\""),
+            String::from(output),
+        ],
+        &PROPERTIES,
+    )
+    .unwrap()
+}
+
+fn scrape(address: SocketAddr, request: &str) -> String {
+    let mut stream: TcpStream = TcpStream::connect(address).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response: String = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_format_openmetrics() {
+    let samples: Vec<GpuSample> = samples(SCRAPE_OUTPUT, 0);
+
+    assert_eq!(
+        format_openmetrics(&samples, &metrics(&PROPERTIES), "nvidia-smi"),
+        "# TYPE nvidia_gpu_util_ratio gauge\n\
+         # UNIT nvidia_gpu_util_ratio ratio\n\
+         # HELP nvidia_gpu_util_ratio GPU Utilization\n\
+         nvidia_gpu_util_ratio{uuid=\"GPU-1a2b3c4d\",name=\"NVIDIA GeForce RTX 3080\",provider=\"nvidia-smi\"} 0.45\n\
+         nvidia_gpu_util_ratio{uuid=\"GPU-5e6f7a8b\",name=\"Tesla \\\"T4\\\"\",provider=\"nvidia-smi\"} 0.03\n\
+         # TYPE nvidia_gpu_temp_celsius gauge\n\
+         # UNIT nvidia_gpu_temp_celsius celsius\n\
+         # HELP nvidia_gpu_temp_celsius GPU Temperature\n\
+         nvidia_gpu_temp_celsius{uuid=\"GPU-1a2b3c4d\",name=\"NVIDIA GeForce RTX 3080\",provider=\"nvidia-smi\"} 52\n\
+         # TYPE nvidia_gpu_memory_usage_bytes gauge\n\
+         # UNIT nvidia_gpu_memory_usage_bytes bytes\n\
+         # HELP nvidia_gpu_memory_usage_bytes Memory Usage\n\
+         nvidia_gpu_memory_usage_bytes{uuid=\"GPU-1a2b3c4d\",name=\"NVIDIA GeForce RTX 3080\",provider=\"nvidia-smi\"} 1073741824\n\
+         nvidia_gpu_memory_usage_bytes{uuid=\"GPU-5e6f7a8b\",name=\"Tesla \\\"T4\\\"\",provider=\"nvidia-smi\"} 536870912\n\
+         # EOF\n"
+    );
+
+    // Names
+    assert_eq!(
+        metric_name(find_metric("gr_clock").unwrap()),
+        "nvidia_gpu_gr_clock_hertz"
+    );
+    assert_eq!(
+        metric_name(find_metric("ecc_dbe_volatile").unwrap()),
+        "nvidia_gpu_ecc_dbe_volatile"
+    );
+    assert_eq!(
+        metric_name(find_metric("power_max_limit").unwrap()),
+        "nvidia_gpu_power_max_limit_watts"
+    );
+    assert_eq!(
+        metric_name(find_metric("throttle_reasons").unwrap()),
+        "nvidia_gpu_throttle_reasons"
+    );
+    assert!(find_metric("remapped_rows_pending").is_none());
+    assert_eq!(provider_label(1), "nvidia-smi");
+    assert_eq!(provider_label(2), "nvidia-smi");
+    assert_eq!(provider_label(3), "optirun nvidia-smi");
+    assert_eq!(format_openmetrics(&[], &[], "nvidia-smi"), "# EOF\n");
+}
+
+#[test]
+fn test_parse_request_line() {
+    assert_eq!(
+        parse_request_line("GET /metrics HTTP/1.1\r\n"),
+        Some(("GET", "/metrics"))
+    );
+    assert_eq!(
+        parse_request_line("GET /metrics?debug=1 HTTP/1.0\r\n"),
+        Some(("GET", "/metrics"))
+    );
+    assert_eq!(parse_request_line("GET /metrics\r\n"), None);
+    assert_eq!(parse_request_line(""), None);
+}
+
+#[test]
+fn test_scrape() {
+    let mut server: MetricsServer =
+        MetricsServer::start("127.0.0.1:0", fake_sampler(SCRAPE_OUTPUT), "nvidia-smi").unwrap();
+    let address: SocketAddr = server.address();
+    assert_ne!(address.port(), 0);
+
+    // Scraped twice, each read fresh
+    for _ in 0..2 {
+        let response: String = scrape(
+            address,
+            "GET /metrics HTTP/1.1\r\nHost: localhost\r\nAccept: application/openmetrics-text\r\n\r\n",
+        );
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(head.contains(&format!("Content-Type: {}\r\n", CONTENT_TYPE)));
+        assert!(head.contains(&format!("Content-Length: {}\r\n", body.len())));
+        assert!(body.contains(
+            "nvidia_gpu_temp_celsius{uuid=\"GPU-1a2b3c4d\",name=\"NVIDIA GeForce RTX 3080\",provider=\"nvidia-smi\"} 52\n"
+        ));
+        assert!(!body.contains("query-gpu"));
+        assert!(body.ends_with("# EOF\n"));
+    }
+
+    // Anything else
+    assert!(scrape(address, "GET /nothing HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 404 "));
+    assert!(scrape(address, "POST /metrics HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 405 "));
+    assert!(scrape(address, "nonsense\r\n\r\n").starts_with("HTTP/1.1 400 "));
+
+    // Stopped, and the address can be used again straight away
+    server.stop().unwrap();
+    assert!(TcpStream::connect(address).is_err());
+    let mut restarted: MetricsServer = MetricsServer::start(
+        &address.to_string(),
+        fake_sampler(SCRAPE_OUTPUT),
+        "nvidia-smi",
+    )
+    .unwrap();
+    restarted.stop().unwrap();
+}
+
+#[test]
+fn test_stop_with_idle_client() {
+    let mut server: MetricsServer =
+        MetricsServer::start("127.0.0.1:0", fake_sampler(SCRAPE_OUTPUT), "nvidia-smi").unwrap();
+    let address: SocketAddr = server.address();
+
+    // Connected but never sends a request, so the server waits on it
+    let client: TcpStream = TcpStream::connect(address).unwrap();
+    thread::sleep(Duration::from_millis(200));
+
+    // Doesn't wait for the client to time out, and frees the port straight away
+    let start: Instant = Instant::now();
+    server.stop().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert!(TcpStream::connect(address).is_err());
+    drop(client);
+}
+
+#[test]
+fn test_scrape_failure() {
+    let server: MetricsServer = MetricsServer::start(
+        "127.0.0.1:0",
+        Sampler::new(vec![String::from("false")]),
+        "nvidia-smi",
+    )
+    .unwrap();
+
+    assert!(scrape(server.address(), "GET /metrics HTTP/1.1\r\n\r\n").starts_with("HTTP/1.1 500 "));
+
+    // Can't share an address
+    assert!(MetricsServer::start(
+        &server.address().to_string(),
+        fake_sampler(SCRAPE_OUTPUT),
+        "nvidia-smi"
+    )
+    .is_err());
+}
+
+#[test]
+fn test_headless_arguments() {
+    assert!(is_headless(&args("monitor --headless")));
+    assert!(!is_headless(&args("monitor --profile quiet")));
+
+    assert_eq!(
+        parse_arguments(&args("monitor --headless")),
+        Ok(HeadlessConfig::default())
+    );
+    assert_eq!(
+        parse_arguments(&args(
+            "monitor --headless --provider 3 --metrics-address 0.0.0.0:9500"
+        )),
+        Ok(HeadlessConfig {
+            provider_type: 3,
+            metrics_address: Some(String::from("0.0.0.0:9500")),
+            ..HeadlessConfig::default()
+        })
+    );
+    assert_eq!(
+        parse_arguments(&args("monitor --headless --metrics-address=127.0.0.1")),
+        Ok(HeadlessConfig {
+            provider_type: 2,
+            metrics_address: Some(String::from("127.0.0.1:9400")),
+            ..HeadlessConfig::default()
+        })
+    );
+    assert_eq!(
+        parse_arguments(&args(
+            "monitor --headless --refresh-rate 60 --metrics-store /var/lib/gpu/metrics.db"
+        )),
+        Ok(HeadlessConfig {
+            refresh_rate: 60,
+            metrics_store: Some(PathBuf::from("/var/lib/gpu/metrics.db")),
+            ..HeadlessConfig::default()
+        })
+    );
+    assert!(parse_arguments(&args("monitor --headless --metrics-store=")).is_err());
+    assert!(run(HeadlessConfig {
+        metrics_store: Some(PathBuf::from("/proc/gtk4-nvidia-monitor-rust/metrics.db")),
+        ..HeadlessConfig::default()
+    })
+    .is_err());
+    assert!(parse_arguments(&args("monitor --headless --provider 7")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --metrics-address")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --verbose")).is_err());
+}
@@ -0,0 +1,470 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_21.rs
+ *
+ * Description:
+ * Integration tests for publishing to MQTT (with Home Assistant discovery)
+ *
+ * Made:
+ * 06/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Published to a tiny broker on localhost, which records every packet
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{args, samples, OUTPUT};
+use gtk4_nvidia_monitor_rust::{
+    headless::{parse_arguments, HeadlessConfig},
+    mqtt::{
+        device_class, discovery_messages, encode_connect, encode_publish, encode_remaining_length,
+        parse_broker_url, read_packet, state_message, status_topic, Backoff, Message, MqttConfig,
+        MqttPublisher, MqttWorker, WORKER_QUEUE_SIZE,
+    },
+    sampler::{find_metric, GpuSample},
+    secret::{read_secret, read_secret_file},
+};
+use std::{
+    env, fs,
+    io::Write,
+    net::{TcpListener, TcpStream},
+    os::unix::fs::PermissionsExt,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A packet received by the test broker, (type & flags, topic, payload)
+type Received = (u8, String, String);
+
+/// Accept `connections` clients in turn, acknowledging & recording everything they send
+fn start_broker(listener: TcpListener, connections: usize) -> Receiver<Received> {
+    let (sender, receiver): (Sender<Received>, Receiver<Received>) = mpsc::channel();
+    thread::spawn(move || {
+        for _ in 0..connections {
+            let mut stream: TcpStream = listener.accept().unwrap().0;
+            while let Ok((header, body)) = read_packet(&mut stream) {
+                match header & 0xF0 {
+                    0x10 => {
+                        stream.write_all(&[0x20, 2, 0, 0]).unwrap();
+                        sender.send((header, String::new(), String::new())).unwrap();
+                    }
+                    0x30 => {
+                        let length: usize = u16::from_be_bytes([body[0], body[1]]) as usize;
+                        let topic: String =
+                            String::from_utf8(body[2..2 + length].to_vec()).unwrap();
+                        let qos: u8 = (header >> 1) & 3;
+                        let start: usize = 2 + length + if qos > 0 { 2 } else { 0 };
+                        let payload: String = String::from_utf8(body[start..].to_vec()).unwrap();
+                        match qos {
+                            1 => stream
+                                .write_all(&[0x40, 2, body[2 + length], body[3 + length]])
+                                .unwrap(),
+                            2 => stream
+                                .write_all(&[0x50, 2, body[2 + length], body[3 + length]])
+                                .unwrap(),
+                            _ => {}
+                        }
+                        sender.send((header, topic, payload)).unwrap();
+                    }
+                    0x60 => stream.write_all(&[0x70, 2, body[0], body[1]]).unwrap(),
+                    0xE0 => {
+                        sender.send((header, String::new(), String::new())).unwrap();
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    });
+
+    receiver
+}
+
+fn config(address: &str, qos: u8) -> MqttConfig {
+    MqttConfig {
+        broker: format!("mqtt://{}", address),
+        qos,
+        host: String::from("host"),
+        ..MqttConfig::default()
+    }
+}
+
+/// Write a secret file readable as `mode`, in a fresh directory
+fn secret_file(name: &str, contents: &str, mode: u32) -> PathBuf {
+    let path: PathBuf = env::temp_dir().join(format!(
+        "gtk4-nvidia-monitor-rust-{}-{}",
+        std::process::id(),
+        name
+    ));
+    fs::write(&path, contents).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+    path
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_parse_broker_url() {
+    assert_eq!(
+        parse_broker_url("mqtt://localhost"),
+        Ok(String::from("localhost:1883"))
+    );
+    assert_eq!(
+        parse_broker_url("tcp://10.0.0.2:1884/"),
+        Ok(String::from("10.0.0.2:1884"))
+    );
+    assert_eq!(
+        parse_broker_url("broker.lan"),
+        Ok(String::from("broker.lan:1883"))
+    );
+    assert_eq!(
+        parse_broker_url("mqtt://[::1]"),
+        Ok(String::from("[::1]:1883"))
+    );
+    assert!(parse_broker_url("mqtts://localhost").is_err());
+    assert!(parse_broker_url("mqtt://localhost:port").is_err());
+    assert!(parse_broker_url("mqtt://").is_err());
+}
+
+#[test]
+fn test_encode_packets() {
+    // Remaining length
+    assert_eq!(encode_remaining_length(0), vec![0x00]);
+    assert_eq!(encode_remaining_length(127), vec![0x7F]);
+    assert_eq!(encode_remaining_length(128), vec![0x80, 0x01]);
+    assert_eq!(encode_remaining_length(16_383), vec![0xFF, 0x7F]);
+    assert_eq!(encode_remaining_length(16_384), vec![0x80, 0x80, 0x01]);
+
+    // Connect, with credentials & a retained will
+    let connect: Vec<u8> = encode_connect(&MqttConfig {
+        username: String::from("user"),
+        password: String::from("pass"),
+        client_id: String::from("id"),
+        topic_prefix: String::from("gpu"),
+        host: String::from("pc"),
+        qos: 1,
+        ..MqttConfig::default()
+    });
+    let (header, body) = read_packet(&mut connect.as_slice()).unwrap();
+    assert_eq!(header, 0x10);
+    assert_eq!(&body[..7], &[0, 4, b'M', b'Q', b'T', b'T', 4]);
+    assert_eq!(body[7], 0x80 | 0x40 | 0x20 | 0x08 | 0x04 | 0x02);
+    assert_eq!(&body[8..10], &[0, 60]);
+    assert_eq!(
+        &body[10..],
+        b"\0\x02id\0\x0dgpu/pc/status\0\x07offline\0\x04user\0\x04pass".as_slice()
+    );
+
+    // Publish
+    let message: Message = Message {
+        topic: String::from("a/b"),
+        payload: String::from("42"),
+        retain: true,
+    };
+    assert_eq!(
+        encode_publish(&message, 0, 7),
+        vec![0x31, 7, 0, 3, b'a', b'/', b'b', b'4', b'2']
+    );
+    assert_eq!(
+        encode_publish(&message, 1, 7),
+        vec![0x33, 9, 0, 3, b'a', b'/', b'b', 0, 7, b'4', b'2']
+    );
+    assert!(read_packet(&mut [0x30, 5, 0].as_slice()).is_err());
+}
+
+#[test]
+fn test_home_assistant_discovery() {
+    let config: MqttConfig = MqttConfig {
+        host: String::from("host"),
+        ..MqttConfig::default()
+    };
+    let sample: &GpuSample = &samples(OUTPUT, 0)[0];
+
+    // State
+    assert_eq!(
+        state_message(&config, sample),
+        Message {
+            topic: String::from("nvidia-monitor/GPU-1a2b3c4d/state"),
+            payload: String::from("{\"util\":45,\"temp\":52,\"memory_usage\":null}"),
+            retain: false,
+        }
+    );
+
+    // A sensor for each property
+    let messages: Vec<Message> = discovery_messages(&config, sample);
+    assert_eq!(messages.len(), 3);
+    assert!(messages.iter().all(|message| message.retain));
+    assert_eq!(
+        messages[1].topic,
+        "homeassistant/sensor/GPU-1a2b3c4d/temp/config"
+    );
+    assert_eq!(
+        messages[1].payload,
+        "{\"name\":\"GPU Temperature\",\"unique_id\":\"GPU-1a2b3c4d_temp\",\
+         \"state_topic\":\"nvidia-monitor/GPU-1a2b3c4d/state\",\
+         \"value_template\":\"{{ value_json.temp }}\",\
+         \"availability_topic\":\"nvidia-monitor/host/status\",\"state_class\":\"measurement\",\
+         \"unit_of_measurement\":\"°C\",\"device_class\":\"temperature\",\
+         \"device\":{\"identifiers\":[\"GPU-1a2b3c4d\"],\"name\":\"NVIDIA GeForce RTX 3080\",\
+         \"model\":\"NVIDIA GeForce RTX 3080\",\"manufacturer\":\"NVIDIA\"}}"
+    );
+    assert!(!messages[0].payload.contains("device_class"));
+
+    // Device classes
+    assert_eq!(
+        device_class(find_metric("power_usage").unwrap()),
+        Some("power")
+    );
+    assert_eq!(
+        device_class(find_metric("gr_clock").unwrap()),
+        Some("frequency")
+    );
+    assert_eq!(
+        device_class(find_metric("memory_total").unwrap()),
+        Some("data_size")
+    );
+    assert_eq!(device_class(find_metric("util").unwrap()), None);
+}
+
+#[test]
+fn test_publish_to_broker() {
+    for qos in 0..=2 {
+        let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address: String = listener.local_addr().unwrap().to_string();
+        let received: Receiver<Received> = start_broker(listener, 1);
+        let mut publisher: MqttPublisher =
+            MqttPublisher::new(config(&address, qos), Backoff::default());
+
+        // Discovery is only sent the first time
+        assert_eq!(
+            publisher.publish_samples(&samples(OUTPUT, 0), Instant::now()),
+            Ok(4)
+        );
+        assert_eq!(
+            publisher.publish_samples(&samples(OUTPUT, 0), Instant::now()),
+            Ok(1)
+        );
+        assert!(publisher.is_connected());
+        publisher.disconnect();
+        assert!(!publisher.is_connected());
+
+        let packets: Vec<Received> = received.iter().collect();
+        let topics: Vec<&str> = packets.iter().map(|(_, topic, _)| topic.as_str()).collect();
+        assert_eq!(
+            topics,
+            vec![
+                "",
+                "nvidia-monitor/host/status",
+                "homeassistant/sensor/GPU-1a2b3c4d/util/config",
+                "homeassistant/sensor/GPU-1a2b3c4d/temp/config",
+                "homeassistant/sensor/GPU-1a2b3c4d/memory_usage/config",
+                "nvidia-monitor/GPU-1a2b3c4d/state",
+                "nvidia-monitor/GPU-1a2b3c4d/state",
+                "nvidia-monitor/host/status",
+                "",
+            ]
+        );
+        assert_eq!(packets[1].2, "online");
+        assert_eq!(packets[7].2, "offline");
+        assert_eq!(
+            packets[5].2,
+            "{\"util\":45,\"temp\":52,\"memory_usage\":null}"
+        );
+
+        // Flags, retained discovery & status only
+        assert_eq!(packets[0].0, 0x10);
+        assert_eq!(packets[1].0, 0x31 | (qos << 1));
+        assert_eq!(packets[2].0, 0x31 | (qos << 1));
+        assert_eq!(packets[5].0, 0x30 | (qos << 1));
+        assert_eq!(packets[8].0, 0xE0);
+    }
+}
+
+#[test]
+fn test_reconnect_with_backoff() {
+    // Doubles up to the limit, then starts again
+    let mut backoff: Backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+    let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+    assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+    backoff.reset();
+    assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+
+    // Find a free port, with nothing listening
+    let address: String = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .to_string();
+    let mut publisher: MqttPublisher = MqttPublisher::new(
+        config(&address, 1),
+        Backoff::new(Duration::from_secs(10), Duration::from_secs(60)),
+    );
+    let start: Instant = Instant::now();
+
+    // Refused, then waits before trying again
+    assert!(publisher
+        .publish_samples(&samples(OUTPUT, 0), start)
+        .is_err());
+    let listener: TcpListener = TcpListener::bind(&address).unwrap();
+    let received: Receiver<Received> = start_broker(listener, 2);
+    assert_eq!(
+        publisher.publish_samples(&samples(OUTPUT, 0), start + Duration::from_secs(5)),
+        Ok(0)
+    );
+    assert!(!publisher.is_connected());
+
+    // Connects once the wait is over
+    assert_eq!(
+        publisher.publish_samples(&samples(OUTPUT, 0), start + Duration::from_secs(10)),
+        Ok(4)
+    );
+    assert!(publisher.is_connected());
+    publisher.disconnect();
+    assert_eq!(received.iter().take(8).count(), 8);
+
+    // Discovery is sent again after reconnecting
+    assert_eq!(
+        publisher.publish_samples(&samples(OUTPUT, 0), start + Duration::from_secs(20)),
+        Ok(4)
+    );
+    publisher.disconnect();
+}
+
+#[test]
+fn test_worker_never_blocks() {
+    // Connections are never accepted, so the worker waits for a CONNACK
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address: String = listener.local_addr().unwrap().to_string();
+    let worker: MqttWorker = MqttWorker::spawn(config(&address, 0), None);
+
+    // Readings past the queue are dropped, & dropping doesn't wait for the broker
+    let start: Instant = Instant::now();
+    let queued: Vec<bool> = (0..WORKER_QUEUE_SIZE + 2)
+        .map(|_| worker.send(samples(OUTPUT, 0)))
+        .collect();
+    assert!(queued.contains(&false));
+    drop(worker);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_worker_restart() {
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address: String = listener.local_addr().unwrap().to_string();
+    let received: Receiver<Received> = start_broker(listener, 2);
+    let mut packets: Vec<Received> = vec![];
+    let wait_for_state = |packets: &mut Vec<Received>| loop {
+        let packet: Received = received.recv().unwrap();
+        let state: bool = packet.1.ends_with("/state");
+        packets.push(packet);
+        if state {
+            break;
+        }
+    };
+
+    // The new worker only connects once the previous one has disconnected
+    let first: MqttWorker = MqttWorker::spawn(config(&address, 0), None);
+    assert!(first.send(samples(OUTPUT, 0)));
+    wait_for_state(&mut packets);
+    let second: MqttWorker = MqttWorker::spawn(config(&address, 0), Some(first));
+    assert!(second.send(samples(OUTPUT, 0)));
+    wait_for_state(&mut packets);
+    drop(second);
+    packets.extend(received.iter());
+
+    let statuses: Vec<&str> = packets
+        .iter()
+        .filter(|(_, topic, _)| topic == "nvidia-monitor/host/status")
+        .map(|(_, _, payload)| payload.as_str())
+        .collect();
+    assert_eq!(statuses, vec!["online", "offline", "online", "offline"]);
+}
+
+#[test]
+fn test_client_per_host() {
+    let config: MqttConfig = MqttConfig::default();
+    assert!(!config.host.is_empty());
+    assert!(!config.host.contains('/'));
+    assert!(config.client_id.starts_with("gtk4-nvidia-monitor-rust-"));
+    assert!(config
+        .client_id
+        .ends_with(&format!("-{}", std::process::id())));
+    assert_eq!(
+        status_topic(&config),
+        format!("nvidia-monitor/{}/status", config.host)
+    );
+}
+
+#[test]
+fn test_read_secret() {
+    // Only readable by it's owner, without the trailing newline
+    let private: PathBuf = secret_file("private", "secret\n", 0o600);
+    assert_eq!(read_secret_file(&private), Ok(String::from("secret")));
+
+    // Readable by others
+    let shared: PathBuf = secret_file("shared", "secret\n", 0o644);
+    assert!(read_secret_file(&shared).is_err());
+    assert!(read_secret_file(&env::temp_dir().join("gtk4-nvidia-monitor-rust-missing")).is_err());
+
+    // A file is used before the variable, which is used if there's no file
+    env::set_var("GTK4_NVIDIA_MONITOR_TEST_SECRET", "from-env");
+    assert_eq!(
+        read_secret("GTK4_NVIDIA_MONITOR_TEST_SECRET", Some(&private)),
+        Ok(String::from("secret"))
+    );
+    assert_eq!(
+        read_secret("GTK4_NVIDIA_MONITOR_TEST_SECRET", None),
+        Ok(String::from("from-env"))
+    );
+    assert_eq!(
+        read_secret("GTK4_NVIDIA_MONITOR_TEST_UNSET", None),
+        Ok(String::new())
+    );
+
+    fs::remove_file(private).unwrap();
+    fs::remove_file(shared).unwrap();
+}
+
+#[test]
+fn test_headless_mqtt_arguments() {
+    let password: PathBuf = secret_file("mqtt-password", "secret\n", 0o600);
+    assert_eq!(
+        parse_arguments(&args(&format!(
+            "monitor --headless --refresh-rate 30 --mqtt-broker mqtt://broker.lan --mqtt-username gpu --mqtt-password-file {} --mqtt-topic-prefix /lab/ --mqtt-qos 1",
+            password.display()
+        ))),
+        Ok(HeadlessConfig {
+            refresh_rate: 30,
+            mqtt: Some(MqttConfig {
+                broker: String::from("mqtt://broker.lan"),
+                username: String::from("gpu"),
+                password: String::from("secret"),
+                topic_prefix: String::from("lab"),
+                qos: 1,
+                keep_alive: 90,
+                ..MqttConfig::default()
+            }),
+            ..HeadlessConfig::default()
+        })
+    );
+    assert!(parse_arguments(&args("monitor --headless --mqtt-username gpu")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --mqtt-password secret")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --mqtt-broker https://x")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --mqtt-broker x --mqtt-qos 3")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --refresh-rate 0")).is_err());
+
+    fs::remove_file(password).unwrap();
+}
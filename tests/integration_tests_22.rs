@@ -0,0 +1,277 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_22.rs
+ *
+ * Description:
+ * Integration tests for writing InfluxDB line protocol
+ *
+ * Made:
+ * 07/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Written to sockets & a tiny HTTP server on localhost
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{args, OUTPUT, UNREAD_OUTPUT};
+use gtk4_nvidia_monitor_rust::{
+    headless::{parse_arguments, HeadlessConfig},
+    influx::{
+        format_batch, format_line, parse_target, split_datagrams, InfluxConfig, InfluxTarget,
+        InfluxWorker, InfluxWriter, WORKER_QUEUE_SIZE,
+    },
+    sampler::GpuSample,
+};
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream, UdpSocket},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+/// 2023-02-07T00:00:00Z
+const MIDNIGHT: u64 = 1_675_728_000_000;
+
+/// A GPU with readings, & one without any
+fn samples() -> Vec<GpuSample> {
+    common::samples(&[OUTPUT, UNREAD_OUTPUT].concat(), MIDNIGHT)
+}
+
+fn config(target: InfluxTarget) -> InfluxConfig {
+    InfluxConfig {
+        target,
+        ..InfluxConfig::default()
+    }
+}
+
+/// Answer a single request with `status`, returning the request
+fn start_http_server(listener: TcpListener, status: &'static str) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let stream: TcpStream = listener.accept().unwrap().0;
+        let mut reader: BufReader<&TcpStream> = BufReader::new(&stream);
+        let mut request: String = String::new();
+        let mut length: usize = 0;
+        loop {
+            let mut line: String = String::new();
+            reader.read_line(&mut line).unwrap();
+            if let Some(value) = line.strip_prefix("Content-Length: ") {
+                length = value.trim().parse().unwrap();
+            }
+            request += &line;
+            if line == "\r\n" {
+                break;
+            }
+        }
+        let mut body: Vec<u8> = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+        (&stream)
+            .write_all(format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).as_bytes())
+            .unwrap();
+
+        request + &String::from_utf8(body).unwrap()
+    })
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_format_line_protocol() {
+    let samples: Vec<GpuSample> = samples();
+
+    // Unread readings are left out, as are GPUs without any
+    assert_eq!(
+        format_batch(&InfluxConfig::default(), &samples),
+        "nvidia_gpu,uuid=GPU-1a2b3c4d,name=NVIDIA\\ GeForce\\ RTX\\ 3080 util=45,temp=52 1675728000000000000\n"
+    );
+    assert_eq!(format_line(&InfluxConfig::default(), &samples[1]), None);
+    assert_eq!(format_batch(&InfluxConfig::default(), &[]), "");
+
+    // Custom measurement & tags, escaped
+    assert_eq!(
+        format_line(
+            &InfluxConfig {
+                measurement: String::from("gpu stats,\nlab\r\n"),
+                uuid_tag: String::from("gpu=id"),
+                name_tag: String::new(),
+                ..InfluxConfig::default()
+            },
+            &samples[0]
+        ),
+        Some(String::from(
+            "gpu\\ stats\\,lab,gpu\\=id=GPU-1a2b3c4d util=45,temp=52 1675728000000000000"
+        ))
+    );
+
+    // Whole lines per datagram
+    let line: String = format!("{}\n", "x".repeat(600));
+    assert_eq!(
+        split_datagrams(&line.repeat(3)),
+        vec![line.repeat(2), line.clone()]
+    );
+    assert_eq!(split_datagrams(&"y".repeat(2000)), vec!["y".repeat(2000)]);
+    assert!(split_datagrams("").is_empty());
+}
+
+#[test]
+fn test_parse_target() {
+    assert_eq!(parse_target("stdout"), Ok(InfluxTarget::Stdout));
+    assert_eq!(parse_target("-"), Ok(InfluxTarget::Stdout));
+    assert_eq!(
+        parse_target("udp://127.0.0.1:8089"),
+        Ok(InfluxTarget::Udp(String::from("127.0.0.1:8089")))
+    );
+    assert_eq!(
+        parse_target("tcp://telegraf:8094/"),
+        Ok(InfluxTarget::Tcp(String::from("telegraf:8094")))
+    );
+    assert_eq!(
+        parse_target("http://influx:8086/write?db=gpus"),
+        Ok(InfluxTarget::Http {
+            address: String::from("influx:8086"),
+            path: String::from("/write?db=gpus"),
+        })
+    );
+    assert_eq!(
+        parse_target("http://influx?org=lab&bucket=gpus"),
+        Ok(InfluxTarget::Http {
+            address: String::from("influx:80"),
+            path: String::from("/api/v2/write?org=lab&bucket=gpus"),
+        })
+    );
+    assert!(parse_target("udp://127.0.0.1").is_err());
+    assert!(parse_target("https://influx:8086").is_err());
+    assert!(parse_target("http://influx:port/write").is_err());
+    assert!(parse_target("influx:8086").is_err());
+}
+
+#[test]
+fn test_write_to_sockets() {
+    // UDP, a datagram per batch
+    let socket: UdpSocket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut writer: InfluxWriter = InfluxWriter::new(config(InfluxTarget::Udp(
+        socket.local_addr().unwrap().to_string(),
+    )));
+    assert_eq!(writer.write(&samples()), Ok(1));
+    let mut buffer: [u8; 2048] = [0; 2048];
+    let length: usize = socket.recv(&mut buffer).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer[..length].to_vec()).unwrap(),
+        format_batch(&InfluxConfig::default(), &samples())
+    );
+
+    // TCP, a single connection kept open between batches
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut writer: InfluxWriter = InfluxWriter::new(config(InfluxTarget::Tcp(
+        listener.local_addr().unwrap().to_string(),
+    )));
+    assert_eq!(writer.write(&samples()), Ok(1));
+    assert_eq!(writer.write(&samples()), Ok(1));
+    drop(writer);
+    let mut received: String = String::new();
+    listener
+        .accept()
+        .unwrap()
+        .0
+        .read_to_string(&mut received)
+        .unwrap();
+    assert_eq!(
+        received,
+        format_batch(&InfluxConfig::default(), &samples()).repeat(2)
+    );
+
+    // Nothing listening
+    let address: String = listener.local_addr().unwrap().to_string();
+    drop(listener);
+    let mut writer: InfluxWriter = InfluxWriter::new(config(InfluxTarget::Tcp(address)));
+    assert!(writer.write(&samples()).is_err());
+    assert_eq!(writer.write(&[]), Ok(0));
+}
+
+#[test]
+fn test_write_to_http() {
+    // Accepted
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address: String = listener.local_addr().unwrap().to_string();
+    let server: JoinHandle<String> = start_http_server(listener, "204 No Content");
+    let mut writer: InfluxWriter = InfluxWriter::new(InfluxConfig {
+        target: parse_target(&format!("http://{}?org=lab&bucket=gpus", address)).unwrap(),
+        token: String::from("secret"),
+        ..InfluxConfig::default()
+    });
+    assert_eq!(writer.write(&samples()), Ok(1));
+    let request: String = server.join().unwrap();
+    assert!(request.starts_with("POST /api/v2/write?org=lab&bucket=gpus HTTP/1.1\r\n"));
+    assert!(request.contains(&format!("Host: {}\r\n", address)));
+    assert!(request.contains("Authorization: Token secret\r\n"));
+    assert!(request.ends_with(&format!(
+        "\r\n\r\n{}",
+        format_batch(&InfluxConfig::default(), &samples())
+    )));
+
+    // Refused
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address: String = listener.local_addr().unwrap().to_string();
+    let server: JoinHandle<String> = start_http_server(listener, "401 Unauthorized");
+    let mut writer: InfluxWriter = InfluxWriter::new(config(
+        parse_target(&format!("http://{}/write", address)).unwrap(),
+    ));
+    assert!(writer.write(&samples()).is_err());
+    assert!(!server.join().unwrap().contains("Authorization"));
+}
+
+#[test]
+fn test_worker_never_blocks() {
+    // Connections are never accepted, so the worker waits for a response
+    let listener: TcpListener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address: String = listener.local_addr().unwrap().to_string();
+    let worker: InfluxWorker = InfluxWorker::spawn(config(
+        parse_target(&format!("http://{}/write", address)).unwrap(),
+    ));
+
+    // Batches past the queue are dropped, & dropping doesn't wait for the target
+    let start: Instant = Instant::now();
+    let queued: Vec<bool> = (0..WORKER_QUEUE_SIZE + 2)
+        .map(|_| worker.send(samples()))
+        .collect();
+    assert!(queued.contains(&false));
+    drop(worker);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+fn test_headless_influx_arguments() {
+    assert_eq!(
+        parse_arguments(&args(
+            "monitor --headless --influx-target stdout --influx-measurement gpu --influx-name-tag="
+        )),
+        Ok(HeadlessConfig {
+            influx: Some(InfluxConfig {
+                measurement: String::from("gpu"),
+                name_tag: String::new(),
+                ..InfluxConfig::default()
+            }),
+            ..HeadlessConfig::default()
+        })
+    );
+    assert!(parse_arguments(&args("monitor --headless --influx-token secret")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --influx-token-file /tmp/token")).is_err());
+    assert!(parse_arguments(&args("monitor --headless --influx-target udp://host")).is_err());
+    assert!(parse_arguments(&args(
+        "monitor --headless --influx-target stdout --influx-measurement="
+    ))
+    .is_err());
+}
@@ -0,0 +1,74 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_23.rs
+ *
+ * Description:
+ * Integration tests for the global list of properties
+ *
+ * Made:
+ * 08/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::{
+    property::{find_property_description, find_property_index, PROPERTY_LIST},
+    sampler::{find_metric, Sampler},
+};
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_property_lookup() {
+    // Labels of saved views are kept
+    for (name, label) in [
+        ("util", "GPU Utilization"),
+        ("encoder_util", "Encoder Utilization"),
+        ("decoder_util", "Decoder Utilization"),
+        ("gr_clock", "Graphics Clock"),
+        ("pcie_rx", "PCIe RX Throughput"),
+    ] {
+        assert_eq!(
+            find_property_description(name).map(|description| description.label),
+            Some(label)
+        );
+        assert_eq!(
+            find_property_index(name).map(|index| PROPERTY_LIST[index].name),
+            Some(name)
+        );
+    }
+    assert!(find_property_description("unknown").is_none());
+    assert_eq!(find_property_index("none"), Some(0));
+}
+
+#[test]
+fn test_sampled_properties() {
+    // Sampled properties are the ones nvidia-smi can read as a number
+    let sampler: Sampler = Sampler::new(vec![]);
+    assert_eq!(
+        sampler.metrics().len(),
+        PROPERTY_LIST
+            .iter()
+            .filter(|description| !description.query.is_empty())
+            .count()
+    );
+    assert_eq!(
+        find_metric("encoder_util").map(|metric| metric.label),
+        find_property_description("encoder_util").map(|description| description.label)
+    );
+    assert_eq!(find_metric("temp").map(|metric| metric.unit), Some("°C"));
+    assert!(find_metric("fans").is_none());
+    assert!(find_metric("remapped_rows_pending").is_none());
+}
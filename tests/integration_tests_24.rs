@@ -0,0 +1,69 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_24.rs
+ *
+ * Description:
+ * Integration tests for formatting power draw & performance levels
+ *
+ * Made:
+ * 09/02/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{PERF_MODES_OUTPUT, POWER_OUTPUT};
+use gtk4_nvidia_monitor_rust::formatter::{format_perf_modes, format_power_fraction};
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_format_power_fraction() {
+    assert_eq!(
+        format_power_fraction(POWER_OUTPUT),
+        Some(String::from("123 / 250 W (49%)"))
+    );
+    assert_eq!(
+        format_power_fraction("250.00 W, 250.00 W"),
+        Some(String::from("250 / 250 W (100%)"))
+    );
+
+    // Either value unavailable
+    assert_eq!(format_power_fraction("123.45 W, [N/A]"), None);
+    assert_eq!(format_power_fraction("[N/A], 250.00 W"), None);
+    assert_eq!(format_power_fraction("[N/A], [N/A]"), None);
+    assert_eq!(format_power_fraction(""), None);
+
+    // Zero enforced limit
+    assert_eq!(format_power_fraction("123.45 W, 0.00 W"), None);
+}
+
+#[test]
+fn test_format_perf_modes() {
+    assert_eq!(format_perf_modes(PERF_MODES_OUTPUT), "2 (max 2.00 GHz)");
+
+    // Trailing separator, clocks below 1 GHz
+    assert_eq!(
+        format_perf_modes("perf=0, nvclock=210, nvclockmin=210, nvclockmax=420;"),
+        "1 (max 420 MHz)"
+    );
+
+    // Levels without a readable max clock
+    assert_eq!(
+        format_perf_modes("perf=0, nvclockmax=[N/A]; perf=1"),
+        "2 (max 0 MHz)"
+    );
+    assert_eq!(format_perf_modes(""), "0 (max 0 MHz)");
+}
@@ -0,0 +1,160 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_3.rs
+ *
+ * Description:
+ * Integration tests for parsing of nvidia-settings target lists (gpus, fans, thermal sensors)
+ *
+ * Made:
+ * 19/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::targets::{
+    find_connected_targets, find_gpu_index, parse_targets, Target, TargetType,
+};
+
+// Sample output of `nvidia-settings -q gpus -V`
+const GPUS_OUTPUT: &str = "
+2 GPUs on hostname:0
+
+    [0] hostname:0[gpu:0] (NVIDIA GeForce RTX 3080)
+
+      Has the following names:
+        GPU-0
+        GPU-11111111-2222-3333-4444-555555555555
+
+      Is connected to the following targets:
+        FAN-0 (Fan 0) [fan:0]
+        FAN-1 (Fan 1) [fan:1]
+        THERMAL-SENSOR-0 [thermalsensor:0]
+
+    [1] hostname:0[gpu:1] (NVIDIA GeForce GTX 1080)
+
+      Has the following names:
+        GPU-1
+        GPU-66666666-7777-8888-9999-000000000000
+
+      Is connected to the following targets:
+        FAN-2 (Fan 2) [fan:2]
+        THERMAL-SENSOR-1 [thermalsensor:1]
+";
+
+// Sample output of `nvidia-settings -q fans -V`
+const FANS_OUTPUT: &str = "
+3 Fans on hostname:0
+
+    [0] hostname:0[fan:0] (Fan 0)
+
+      Has the following name:
+        FAN-0
+
+    [1] hostname:0[fan:1] (Fan 1)
+
+      Has the following name:
+        FAN-1
+
+    [2] hostname:0[fan:2] (Fan 2)
+
+      Has the following name:
+        FAN-2
+";
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_parse_gpu_targets() {
+    let gpus: Vec<Target> = parse_targets(GPUS_OUTPUT);
+
+    assert_eq!(gpus.len(), 2);
+    assert_eq!(gpus[0].target_type, TargetType::Gpu);
+    assert_eq!(gpus[0].index, 0);
+    assert_eq!(gpus[0].description, "NVIDIA GeForce RTX 3080");
+    assert_eq!(
+        gpus[0].names,
+        vec!["GPU-0", "GPU-11111111-2222-3333-4444-555555555555"]
+    );
+    assert_eq!(gpus[0].connections_of(TargetType::Fan), vec![0, 1]);
+    assert_eq!(gpus[0].connections_of(TargetType::ThermalSensor), vec![0]);
+    assert_eq!(gpus[1].connections_of(TargetType::Fan), vec![2]);
+}
+
+#[test]
+fn test_parse_fan_targets() {
+    let fans: Vec<Target> = parse_targets(FANS_OUTPUT);
+
+    assert_eq!(fans.len(), 3);
+    assert_eq!(fans[2].target_type, TargetType::Fan);
+    assert_eq!(fans[2].index, 2);
+    assert_eq!(fans[2].description, "Fan 2");
+    assert_eq!(fans[2].names, vec!["FAN-2"]);
+    assert!(fans[2].connections.is_empty());
+}
+
+#[test]
+fn test_parse_empty_targets() {
+    assert!(parse_targets("").is_empty());
+    assert!(parse_targets("0 Fans on hostname:0").is_empty());
+}
+
+#[test]
+fn test_find_gpu_index() {
+    let gpus: Vec<Target> = parse_targets(GPUS_OUTPUT);
+
+    assert_eq!(
+        find_gpu_index(&gpus, "GPU-66666666-7777-8888-9999-000000000000"),
+        Some(1)
+    );
+    assert_eq!(find_gpu_index(&gpus, "GPU-unknown"), None);
+}
+
+#[test]
+fn test_find_connected_targets_listed_by_gpu() {
+    let gpus: Vec<Target> = parse_targets(GPUS_OUTPUT);
+    let fans: Vec<Target> = parse_targets(FANS_OUTPUT);
+
+    assert_eq!(find_connected_targets(&gpus, &fans, 0), vec![0, 1]);
+    assert_eq!(find_connected_targets(&gpus, &fans, 1), vec![2]);
+}
+
+#[test]
+fn test_find_connected_targets_listed_by_target() {
+    let gpus: Vec<Target> = parse_targets(
+        "
+    [0] hostname:0[gpu:0] (GPU 0)
+    [1] hostname:0[gpu:1] (GPU 1)
+",
+    );
+    let fans: Vec<Target> = parse_targets(
+        "
+    [0] hostname:0[fan:0] (Fan 0)
+
+      Is connected to the following target:
+        GPU-1 [gpu:1]
+",
+    );
+
+    assert_eq!(find_connected_targets(&gpus, &fans, 1), vec![0]);
+    assert!(find_connected_targets(&gpus, &fans, 0).is_empty());
+}
+
+#[test]
+fn test_find_connected_targets_single_gpu() {
+    let gpus: Vec<Target> = parse_targets("    [0] hostname:0[gpu:0] (GPU 0)");
+    let fans: Vec<Target> = parse_targets(FANS_OUTPUT);
+
+    assert_eq!(find_connected_targets(&gpus, &fans, 0), vec![0, 1, 2]);
+}
@@ -0,0 +1,187 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_4.rs
+ *
+ * Description:
+ * Integration tests for parsing of the GPU list given by `nvidia-smi -L` (including MIG devices)
+ *
+ * Made:
+ * 20/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::mig::{
+    find_mig_devices, find_mig_reading, mig_reading_name, parse_gpu_list, parse_mig_details,
+    GpuListing, MigDetails, MigDevice,
+};
+
+// Sample output of `nvidia-smi -L` with MIG enabled on the first GPU
+const MIG_OUTPUT: &str =
+    "GPU 0: NVIDIA A100-SXM4-40GB (UUID: GPU-5d5ba0d6-d33d-2b2c-524d-9e3d8d2b8a77)
+  MIG 3g.20gb     Device  0: (UUID: MIG-c6d4f1ef-42e4-5de3-91c7-45d71c87eb3f)
+  MIG 1g.5gb      Device  1: (UUID: MIG-cba663e8-9bed-5b26-b243-5deb0e78dbbd)
+GPU 1: NVIDIA A100-SXM4-40GB (UUID: GPU-0e5c6a3b-9a1f-4f5b-8c55-a1b2c3d4e5f6)
+";
+
+// Sample `MIG Devices` section of `nvidia-smi -q`
+const MIG_DETAILS_OUTPUT: &str = "GPU 00000000:07:00.0
+    MIG Mode
+        Current                           : Enabled
+    MIG Devices
+        MIG Device
+            Index                         : 0
+            GPU Instance ID               : 2
+            Device Attributes
+                Shared
+                    Multiprocessor count  : 42
+            ECC Errors
+                Volatile
+                    SRAM Uncorrectable    : 0
+            FB Memory Usage
+                Total                     : 19968 MiB
+                Used                      : 11 MiB
+        MIG Device
+            Index                         : 1
+            FB Memory Usage
+                Total                     : 4864 MiB
+                Used                      : 3 MiB
+    FB Memory Usage
+        Total                             : 40960 MiB
+        Used                              : 14 MiB
+";
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_parse_gpus_without_mig() {
+    let gpus: Vec<GpuListing> = parse_gpu_list(
+        "GPU 0: NVIDIA GeForce RTX 3080 (UUID: GPU-11111111-2222-3333-4444-555555555555)",
+    );
+
+    assert_eq!(
+        gpus,
+        vec![GpuListing {
+            index: 0,
+            name: String::from("NVIDIA GeForce RTX 3080"),
+            uuid: String::from("GPU-11111111-2222-3333-4444-555555555555"),
+            mig_devices: vec![],
+        }]
+    );
+}
+
+#[test]
+fn test_parse_gpus_with_mig() {
+    let gpus: Vec<GpuListing> = parse_gpu_list(MIG_OUTPUT);
+
+    // MIG devices are not listed as GPUs
+    assert_eq!(gpus.len(), 2);
+    assert_eq!(gpus[0].uuid, "GPU-5d5ba0d6-d33d-2b2c-524d-9e3d8d2b8a77");
+    assert_eq!(gpus[1].uuid, "GPU-0e5c6a3b-9a1f-4f5b-8c55-a1b2c3d4e5f6");
+
+    // MIG devices belong to their parent
+    assert_eq!(
+        gpus[0].mig_devices,
+        vec![
+            MigDevice {
+                profile: String::from("3g.20gb"),
+                index: 0,
+                uuid: String::from("MIG-c6d4f1ef-42e4-5de3-91c7-45d71c87eb3f"),
+            },
+            MigDevice {
+                profile: String::from("1g.5gb"),
+                index: 1,
+                uuid: String::from("MIG-cba663e8-9bed-5b26-b243-5deb0e78dbbd"),
+            },
+        ]
+    );
+    assert!(gpus[1].mig_devices.is_empty());
+}
+
+#[test]
+fn test_parse_legacy_mig_uuid() {
+    // Older drivers use `MIG-GPU-<uuid>/<gi>/<ci>` style uuids
+    let gpus: Vec<GpuListing> = parse_gpu_list(
+        "GPU 0: A100-SXM4-40GB (UUID: GPU-5d5ba0d6-d33d-2b2c-524d-9e3d8d2b8a77)
+  MIG 7g.40gb Device 0: (UUID: MIG-GPU-5d5ba0d6-d33d-2b2c-524d-9e3d8d2b8a77/0/0)",
+    );
+
+    assert_eq!(
+        gpus[0].mig_devices[0].uuid,
+        "MIG-GPU-5d5ba0d6-d33d-2b2c-524d-9e3d8d2b8a77/0/0"
+    );
+    assert_eq!(gpus[0].mig_devices[0].profile, "7g.40gb");
+}
+
+#[test]
+fn test_parse_ignores_unknown_lines() {
+    let gpus: Vec<GpuListing> = parse_gpu_list(
+        "No devices were found
+  MIG 1g.5gb Device 0: (UUID: MIG-orphan)
+GPU 0: Broken line without uuid
+",
+    );
+
+    assert!(gpus.is_empty());
+    assert!(parse_gpu_list("").is_empty());
+}
+
+#[test]
+fn test_find_mig_devices() {
+    let gpus: Vec<GpuListing> = parse_gpu_list(MIG_OUTPUT);
+
+    assert_eq!(
+        find_mig_devices(&gpus, "GPU-5d5ba0d6-d33d-2b2c-524d-9e3d8d2b8a77").len(),
+        2
+    );
+    assert!(find_mig_devices(&gpus, "GPU-0e5c6a3b-9a1f-4f5b-8c55-a1b2c3d4e5f6").is_empty());
+    assert!(find_mig_devices(&gpus, "GPU-unknown").is_empty());
+}
+
+#[test]
+fn test_parse_mig_details() {
+    let devices: Vec<MigDetails> = parse_mig_details(MIG_DETAILS_OUTPUT);
+
+    assert_eq!(devices.len(), 2);
+    assert_eq!(devices[0].index, Some(0));
+    assert_eq!(
+        devices[1].readings,
+        vec![
+            (String::from("Index"), String::from("1")),
+            (
+                String::from("FB Memory Usage/Total"),
+                String::from("4864 MiB")
+            ),
+            (String::from("FB Memory Usage/Used"), String::from("3 MiB")),
+        ]
+    );
+    assert!(parse_mig_details("    FB Memory Usage\n        Used : 14 MiB\n").is_empty());
+}
+
+#[test]
+fn test_find_mig_reading() {
+    let devices: Vec<MigDetails> = parse_mig_details(MIG_DETAILS_OUTPUT);
+    let reading = |index: u32, property: &str| -> Option<String> {
+        find_mig_reading(&devices, index, mig_reading_name(property)?)
+    };
+
+    assert_eq!(reading(0, "memory_usage"), Some(String::from("11 MiB")));
+    assert_eq!(reading(1, "memory_total"), Some(String::from("4864 MiB")));
+    assert_eq!(reading(0, "ecc_dbe_volatile"), Some(String::from("0")));
+    // Not listed for this device, unknown device, not supported by MIG devices
+    assert_eq!(reading(1, "ecc_dbe_volatile"), None);
+    assert_eq!(reading(2, "memory_usage"), None);
+    assert_eq!(reading(0, "gr_clock"), None);
+}
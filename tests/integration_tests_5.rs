@@ -0,0 +1,215 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_5.rs
+ *
+ * Description:
+ * Integration tests for fan curves and fan curve control
+ *
+ * Made:
+ * 21/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Commands are captured by a fake runner rather than run
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{create_runner, FakeRunner};
+use gtk4_nvidia_monitor_rust::{
+    fan_curve::{
+        create_fan_speed_command, parse_points, parse_temperature, CurvePoint, FanCurve,
+        FanCurveConfig, FanCurveController,
+    },
+    fan_override::FanControlState,
+};
+
+fn create_curve() -> FanCurve {
+    FanCurve::new(
+        vec![
+            CurvePoint {
+                temperature: 80,
+                speed: 100,
+            },
+            CurvePoint {
+                temperature: 40,
+                speed: 30,
+            },
+            CurvePoint {
+                temperature: 60,
+                speed: 50,
+            },
+        ],
+        5,
+        3,
+    )
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_curve_interpolation() {
+    let curve: FanCurve = create_curve();
+
+    // Points are sorted
+    assert_eq!(curve.points[0].temperature, 40);
+
+    // Outside of curve
+    assert_eq!(curve.speed_for(20.0), Some(30));
+    assert_eq!(curve.speed_for(95.0), Some(100));
+
+    // On and between points
+    assert_eq!(curve.speed_for(60.0), Some(50));
+    assert_eq!(curve.speed_for(50.0), Some(40));
+    assert_eq!(curve.speed_for(70.0), Some(75));
+
+    // No points
+    assert_eq!(FanCurve::new(vec![], 0, 0).speed_for(50.0), None);
+}
+
+#[test]
+fn test_curve_speed_capped() {
+    let curve: FanCurve = FanCurve::new(
+        vec![CurvePoint {
+            temperature: 50,
+            speed: 150,
+        }],
+        0,
+        0,
+    );
+
+    assert_eq!(curve.speed_for(50.0), Some(100));
+}
+
+#[test]
+fn test_parse_points() {
+    assert_eq!(
+        parse_points("40/30,60/50").unwrap(),
+        vec![
+            CurvePoint {
+                temperature: 40,
+                speed: 30
+            },
+            CurvePoint {
+                temperature: 60,
+                speed: 50
+            },
+        ]
+    );
+    assert!(parse_points("").unwrap().is_empty());
+    assert!(parse_points("40-30").is_err());
+    assert!(parse_points("40/abc").is_err());
+}
+
+#[test]
+fn test_config_round_trip() {
+    let config: FanCurveConfig = FanCurveConfig {
+        uuid: String::from("GPU-fb231809-72f7-79fd-eb6c-178b24827aa9"),
+        fan: 1,
+        enabled: true,
+        curve: create_curve(),
+    };
+    let setting: String = config.to_setting();
+
+    assert_eq!(
+        setting,
+        "GPU-fb231809-72f7-79fd-eb6c-178b24827aa9:1:true:5:3:40/30,60/50,80/100"
+    );
+    assert_eq!(FanCurveConfig::from_setting(&setting), Ok(config));
+    assert!(FanCurveConfig::from_setting("GPU-1234:1:true:5").is_err());
+}
+
+#[test]
+fn test_hysteresis() {
+    let mut controller: FanCurveController = FanCurveController::new(0, 0, create_curve());
+
+    // First update always sets speed
+    assert_eq!(controller.update(70.0), Some(75));
+
+    // Small drop in temperature is ignored
+    assert_eq!(controller.update(67.0), None);
+    assert_eq!(controller.last_speed(), Some(75));
+
+    // Large enough drop lowers speed
+    assert_eq!(controller.update(64.0), Some(60));
+
+    // Rising temperature is applied straight away
+    assert_eq!(controller.update(66.0), Some(65));
+}
+
+#[test]
+fn test_min_change() {
+    let mut controller: FanCurveController = FanCurveController::new(0, 0, create_curve());
+    assert_eq!(controller.update(50.0), Some(40));
+
+    // 1% change is ignored
+    assert_eq!(controller.update(51.0), None);
+
+    // 4% change is applied
+    assert_eq!(controller.update(54.0), Some(44));
+
+    // Reaching the top of the curve is always applied
+    let mut controller: FanCurveController = FanCurveController::new(0, 0, create_curve());
+    assert_eq!(controller.update(79.0), Some(98));
+    assert_eq!(controller.update(80.0), Some(100));
+}
+
+#[test]
+fn test_apply_runs_command() {
+    let runner: FakeRunner = create_runner(false);
+    let mut state: FanControlState = FanControlState::new();
+    let mut controller: FanCurveController = FanCurveController::new(1, 2, create_curve());
+
+    assert_eq!(controller.apply(&runner, &mut state, 60.0), Ok(Some(50)));
+    assert_eq!(controller.apply(&runner, &mut state, 60.0), Ok(None));
+    assert!(state.is_controlled(1));
+    assert_eq!(
+        *runner.commands.borrow(),
+        vec![vec![
+            String::from("nvidia-settings"),
+            String::from("-a"),
+            String::from("[gpu:1]/GPUFanControlState=1"),
+            String::from("-a"),
+            String::from("[fan:2]/GPUTargetFanSpeed=50"),
+        ]]
+    );
+}
+
+#[test]
+fn test_apply_retries_after_failure() {
+    let runner: FakeRunner = create_runner(true);
+    let mut state: FanControlState = FanControlState::new();
+    let mut controller: FanCurveController = FanCurveController::new(0, 0, create_curve());
+
+    assert!(controller.apply(&runner, &mut state, 60.0).is_err());
+    assert_eq!(controller.last_speed(), None);
+    assert!(controller.apply(&runner, &mut state, 60.0).is_err());
+    assert_eq!(runner.commands.borrow().len(), 2);
+
+    // Still restored on exit, control may have been partly taken
+    assert!(state.is_controlled(0));
+}
+
+#[test]
+fn test_fan_speed_command() {
+    assert_eq!(
+        create_fan_speed_command(0, 1, 45).join(" "),
+        "nvidia-settings -a [gpu:0]/GPUFanControlState=1 -a [fan:1]/GPUTargetFanSpeed=45"
+    );
+}
+
+#[test]
+fn test_parse_temperature() {
+    assert_eq!(parse_temperature("45°C"), Some(45.0));
+    assert_eq!(parse_temperature("113°F"), Some(45.0));
+    assert_eq!(parse_temperature("N/A"), None);
+}
@@ -0,0 +1,200 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_6.rs
+ *
+ * Description:
+ * Integration tests for manual fan overrides and restoring fan control
+ *
+ * Made:
+ * 22/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Commands are captured by a fake runner rather than run
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{create_runner, FakeRunner};
+use gtk4_nvidia_monitor_rust::{
+    command::CommandRunner,
+    fan_curve::{CurvePoint, FanCurve, FanCurveController},
+    fan_override::{
+        apply_fixed_speed, create_fixed_speed_command, create_restore_command, lock_fan_control,
+        release_fan_control, restore_all, FanControlState, FanOverride, DEFAULT_OVERRIDE_SPEED,
+        FAN_CONTROL,
+    },
+};
+
+/// Command runner that fails if the fan control state is locked while running
+struct UnlockedRunner;
+
+impl CommandRunner for UnlockedRunner {
+    fn run(&self, _argv: &[String]) -> Result<(), String> {
+        match FAN_CONTROL.try_lock() {
+            Ok(_) => Ok(()),
+            Err(_) => Err(String::from("locked")),
+        }
+    }
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_fixed_speed_command() {
+    assert_eq!(
+        create_fixed_speed_command(0, &[0, 1], 65),
+        vec![
+            "nvidia-settings",
+            "-a",
+            "[gpu:0]/GPUFanControlState=1",
+            "-a",
+            "[fan:0]/GPUTargetFanSpeed=65",
+            "-a",
+            "[fan:1]/GPUTargetFanSpeed=65",
+        ]
+    );
+
+    // Speed is capped
+    assert_eq!(
+        create_fixed_speed_command(1, &[2], 120)[4],
+        "[fan:2]/GPUTargetFanSpeed=100"
+    );
+}
+
+#[test]
+fn test_restore_command() {
+    assert_eq!(
+        create_restore_command(1),
+        vec!["nvidia-settings", "-a", "[gpu:1]/GPUFanControlState=0"]
+    );
+}
+
+#[test]
+fn test_override_default() {
+    let fan_override: FanOverride = FanOverride::default();
+
+    assert!(!fan_override.active);
+    assert_eq!(fan_override.speed, DEFAULT_OVERRIDE_SPEED);
+}
+
+#[test]
+fn test_set_and_release() {
+    let runner: FakeRunner = create_runner(false);
+    let mut state: FanControlState = FanControlState::new();
+
+    state.set_fixed_speed(&runner, 0, &[0], 40).unwrap();
+    assert!(state.is_controlled(0));
+    assert!(state.is_fixed(0));
+
+    state.release(&runner, 0).unwrap();
+    assert!(!state.is_controlled(0));
+    assert!(!state.is_fixed(0));
+
+    let commands = runner.commands.borrow();
+    assert_eq!(commands.len(), 2);
+    assert_eq!(commands[1], create_restore_command(0));
+}
+
+#[test]
+fn test_failed_override_still_restored() {
+    let mut state: FanControlState = FanControlState::new();
+
+    // Control may have been partly taken
+    assert!(state
+        .set_fixed_speed(&create_runner(true), 0, &[0], 40)
+        .is_err());
+    assert!(state.is_controlled(0));
+
+    // A failed restore is kept for retrying later
+    assert!(state.release(&create_runner(true), 0).is_err());
+    assert!(state.is_controlled(0));
+}
+
+#[test]
+fn test_release_all() {
+    let runner: FakeRunner = create_runner(false);
+    let mut state: FanControlState = FanControlState::new();
+
+    state.set_fixed_speed(&runner, 0, &[0], 40).unwrap();
+    state.mark_controlled(1);
+    state.mark_controlled(1);
+    assert!(!state.is_fixed(1));
+
+    assert!(state.release_all(&runner).is_empty());
+    assert!(!state.is_controlled(0));
+    assert!(!state.is_controlled(1));
+
+    // One override, then one restore per gpu
+    let commands = runner.commands.borrow();
+    assert_eq!(commands.len(), 3);
+    assert_eq!(commands[1], create_restore_command(0));
+    assert_eq!(commands[2], create_restore_command(1));
+}
+
+#[test]
+fn test_release_all_errors() {
+    let mut state: FanControlState = FanControlState::new();
+    state.mark_controlled(0);
+    state.mark_controlled(1);
+
+    assert_eq!(state.release_all(&create_runner(true)).len(), 2);
+    assert!(state.is_controlled(0));
+}
+
+#[test]
+fn test_restore_all() {
+    // Busy lock reported, not skipped
+    {
+        let _state = lock_fan_control();
+        assert_eq!(restore_all(&create_runner(false)).len(), 1);
+    }
+
+    lock_fan_control().mark_controlled(3);
+    assert_eq!(restore_all(&create_runner(true)).len(), 1);
+    assert!(lock_fan_control().is_controlled(3));
+
+    assert!(restore_all(&create_runner(false)).is_empty());
+    assert!(!lock_fan_control().is_controlled(3));
+
+    // Commands are run without holding the lock (shares the global state, so kept in this test)
+    assert!(apply_fixed_speed(&UnlockedRunner, 5, &[0], 30).is_ok());
+    assert!(lock_fan_control().is_fixed(5));
+    assert!(release_fan_control(&create_runner(true), 5).is_err());
+    assert!(lock_fan_control().is_controlled(5));
+    assert!(release_fan_control(&UnlockedRunner, 5).is_ok());
+    assert!(!lock_fan_control().is_controlled(5));
+}
+
+#[test]
+fn test_curve_reset() {
+    let mut controller: FanCurveController = FanCurveController::new(
+        0,
+        0,
+        FanCurve::new(
+            vec![CurvePoint {
+                temperature: 50,
+                speed: 60,
+            }],
+            0,
+            0,
+        ),
+    );
+
+    assert_eq!(controller.update(50.0), Some(60));
+    assert_eq!(controller.update(50.0), None);
+
+    // Applied again after a reset
+    controller.reset();
+    assert_eq!(controller.last_speed(), None);
+    assert_eq!(controller.update(50.0), Some(60));
+}
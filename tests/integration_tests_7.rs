@@ -0,0 +1,158 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_7.rs
+ *
+ * Description:
+ * Integration tests for clock offsets (overclocking)
+ *
+ * Made:
+ * 23/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Commands are captured by a fake runner rather than run
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{create_runner, FakeRunner};
+use gtk4_nvidia_monitor_rust::overclock::{
+    apply_commands, create_offset_command, create_offset_query, format_command, parse_offset_range,
+    parse_offset_value, plan_offsets, ClockOffset, OffsetRange, OffsetRequest,
+};
+
+const QUERY_OUTPUT: &str = "
+  Attribute 'GPUGraphicsClockOffsetAllPerformanceLevels' (host:1[gpu:0]): 150.
+    The valid values for 'GPUGraphicsClockOffsetAllPerformanceLevels' are in the range -200 - 1000 (inclusive).
+    'GPUGraphicsClockOffsetAllPerformanceLevels' can use the following target types: GPU.
+";
+
+fn create_requests(graphics: i32, memory: i32) -> Vec<OffsetRequest> {
+    vec![
+        OffsetRequest {
+            offset: ClockOffset::Graphics,
+            value: graphics,
+            range: OffsetRange {
+                min: -200,
+                max: 1000,
+            },
+        },
+        OffsetRequest {
+            offset: ClockOffset::Memory,
+            value: memory,
+            range: OffsetRange {
+                min: -2000,
+                max: 6000,
+            },
+        },
+    ]
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_parse_range() {
+    assert_eq!(
+        parse_offset_range(QUERY_OUTPUT).unwrap(),
+        OffsetRange {
+            min: -200,
+            max: 1000
+        }
+    );
+
+    // Both negative
+    assert_eq!(
+        parse_offset_range("are in the range -500 - -100 (inclusive).").unwrap(),
+        OffsetRange {
+            min: -500,
+            max: -100
+        }
+    );
+}
+
+#[test]
+fn test_parse_range_invalid() {
+    assert!(parse_offset_range("").is_err());
+    assert!(parse_offset_range("are in the range 100 - -100 (inclusive).").is_err());
+    assert!(parse_offset_range("are in the range a - b (inclusive).").is_err());
+}
+
+#[test]
+fn test_parse_value() {
+    assert_eq!(parse_offset_value(QUERY_OUTPUT).unwrap(), 150);
+    assert_eq!(
+        parse_offset_value("Attribute 'X' (host:1[gpu:0]): -50.").unwrap(),
+        -50
+    );
+    assert!(parse_offset_value("ERROR: Error querying attribute").is_err());
+}
+
+#[test]
+fn test_commands() {
+    assert_eq!(
+        create_offset_query(0, ClockOffset::Memory),
+        vec![
+            "nvidia-settings",
+            "-q",
+            "[gpu:0]/GPUMemoryTransferRateOffsetAllPerformanceLevels",
+            "-d"
+        ]
+    );
+    assert_eq!(
+        format_command(&create_offset_command(1, ClockOffset::Graphics, -100)),
+        "nvidia-settings -a [gpu:1]/GPUGraphicsClockOffsetAllPerformanceLevels=-100"
+    );
+}
+
+#[test]
+fn test_range_contains() {
+    let range: OffsetRange = OffsetRange { min: -200, max: 0 };
+
+    assert!(range.contains(-200));
+    assert!(range.contains(0));
+    assert!(!range.contains(1));
+    assert!(!range.contains(-201));
+}
+
+#[test]
+fn test_plan_offsets() {
+    let commands: Vec<Vec<String>> = plan_offsets(0, &create_requests(100, 500)).unwrap();
+
+    assert_eq!(
+        commands,
+        vec![
+            create_offset_command(0, ClockOffset::Graphics, 100),
+            create_offset_command(0, ClockOffset::Memory, 500),
+        ]
+    );
+}
+
+#[test]
+fn test_plan_offsets_out_of_range() {
+    // Nothing planned if any value is out of range
+    assert!(plan_offsets(0, &create_requests(100, 7000)).is_err());
+    assert!(plan_offsets(0, &create_requests(-201, 0)).is_err());
+}
+
+#[test]
+fn test_apply_commands() {
+    let commands: Vec<Vec<String>> = plan_offsets(0, &create_requests(0, 0)).unwrap();
+
+    let runner: FakeRunner = create_runner(false);
+    assert!(apply_commands(&runner, &commands).is_ok());
+    assert_eq!(*runner.commands.borrow(), commands);
+
+    // Stops at the first failure
+    let failing_runner: FakeRunner = create_runner(true);
+    assert!(apply_commands(&failing_runner, &commands).is_err());
+    assert_eq!(failing_runner.commands.borrow().len(), 1);
+}
@@ -0,0 +1,193 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_8.rs
+ *
+ * Description:
+ * Integration tests for the privileged helper operations
+ *
+ * Made:
+ * 24/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ *
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+
+// Imports
+use gtk4_nvidia_monitor_rust::helper::{
+    create_helper_command, parse_power_limit, validate_uuid, ComputeMode, HelperOperation,
+    HELPER_PATH,
+};
+
+const UUID: &str = "GPU-1a2b3c4d-0000-1111-2222-333344445555";
+
+fn to_args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| String::from(*arg)).collect()
+}
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_validate_uuid() {
+    assert!(validate_uuid(UUID).is_ok());
+    assert!(validate_uuid("GPU-").is_err());
+    assert!(validate_uuid("MIG-1a2b").is_err());
+    assert!(validate_uuid("GPU-1a2b; rm -rf /").is_err());
+    assert!(validate_uuid("GPU-1a2b --reset").is_err());
+}
+
+#[test]
+fn test_round_trip() {
+    let operations: Vec<HelperOperation> = vec![
+        HelperOperation::SetPowerLimit {
+            uuid: String::from(UUID),
+            watts: 250,
+        },
+        HelperOperation::LockGpuClocks {
+            uuid: String::from(UUID),
+            min: 300,
+            max: 1800,
+        },
+        HelperOperation::ResetGpuClocks {
+            uuid: String::from(UUID),
+        },
+        HelperOperation::LockMemoryClocks {
+            uuid: String::from(UUID),
+            min: 405,
+            max: 9501,
+        },
+        HelperOperation::ResetMemoryClocks {
+            uuid: String::from(UUID),
+        },
+        HelperOperation::SetPersistenceMode {
+            uuid: String::from(UUID),
+            enabled: true,
+        },
+        HelperOperation::SetComputeMode {
+            uuid: String::from(UUID),
+            mode: ComputeMode::ExclusiveProcess,
+        },
+    ];
+
+    for operation in operations {
+        assert_eq!(
+            HelperOperation::from_args(&operation.to_args()).unwrap(),
+            operation
+        );
+    }
+}
+
+#[test]
+fn test_from_args_rejects() {
+    // Unknown operations or wrong number of arguments
+    assert!(HelperOperation::from_args(&to_args(&["reboot", UUID])).is_err());
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID])).is_err());
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID, "250", "-r"])).is_err());
+    assert!(HelperOperation::from_args(&[]).is_err());
+
+    // Invalid numbers
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID, "+250"])).is_err());
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID, " 250"])).is_err());
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID, "-1"])).is_err());
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID, "99999999999"])).is_err());
+
+    // Invalid values
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID, "0"])).is_err());
+    assert!(HelperOperation::from_args(&to_args(&["power-limit", UUID, "5000"])).is_err());
+    assert!(
+        HelperOperation::from_args(&to_args(&["lock-gpu-clocks", UUID, "1800", "300"])).is_err()
+    );
+    assert!(HelperOperation::from_args(&to_args(&["persistence-mode", UUID, "1"])).is_err());
+    assert!(HelperOperation::from_args(&to_args(&["compute-mode", UUID, "1"])).is_err());
+
+    // Invalid uuid
+    assert!(HelperOperation::from_args(&to_args(&["reset-gpu-clocks", "0"])).is_err());
+}
+
+#[test]
+fn test_nvidia_smi_args() {
+    assert_eq!(
+        HelperOperation::SetPowerLimit {
+            uuid: String::from(UUID),
+            watts: 250
+        }
+        .to_nvidia_smi_args(),
+        to_args(&["-i", UUID, "-pl", "250"])
+    );
+    assert_eq!(
+        HelperOperation::LockMemoryClocks {
+            uuid: String::from(UUID),
+            min: 405,
+            max: 9501
+        }
+        .to_nvidia_smi_args(),
+        to_args(&["-i", UUID, "-lmc", "405,9501"])
+    );
+    assert_eq!(
+        HelperOperation::SetPersistenceMode {
+            uuid: String::from(UUID),
+            enabled: false
+        }
+        .to_nvidia_smi_args(),
+        to_args(&["-i", UUID, "-pm", "0"])
+    );
+    assert_eq!(
+        HelperOperation::SetComputeMode {
+            uuid: String::from(UUID),
+            mode: ComputeMode::Prohibited
+        }
+        .to_nvidia_smi_args(),
+        to_args(&["-i", UUID, "-c", "2"])
+    );
+
+    // Resetting the power limit looks up the default first
+    assert_eq!(
+        HelperOperation::ResetPowerLimit {
+            uuid: String::from(UUID)
+        }
+        .to_nvidia_smi_args(),
+        to_args(&[
+            "-i",
+            UUID,
+            "--query-gpu=power.default_limit",
+            "--format=csv,noheader,nounits"
+        ])
+    );
+    assert_eq!(parse_power_limit("249.60\n"), Ok(250));
+    assert!(parse_power_limit("[N/A]").is_err());
+    assert!(parse_power_limit("0.00").is_err());
+    assert!(parse_power_limit("5000.00").is_err());
+}
+
+#[test]
+fn test_helper_command() {
+    assert_eq!(
+        create_helper_command(&HelperOperation::ResetGpuClocks {
+            uuid: String::from(UUID)
+        })
+        .unwrap(),
+        to_args(&["pkexec", HELPER_PATH, "reset-gpu-clocks", UUID])
+    );
+    assert_eq!(
+        HelperOperation::from_args(&to_args(&["reset-power-limit", UUID])),
+        Ok(HelperOperation::ResetPowerLimit {
+            uuid: String::from(UUID)
+        })
+    );
+
+    // Nothing invalid is run
+    assert!(create_helper_command(&HelperOperation::SetPowerLimit {
+        uuid: String::from(UUID),
+        watts: 0
+    })
+    .is_err());
+}
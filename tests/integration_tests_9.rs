@@ -0,0 +1,225 @@
+// SPDX-FileCopyrightText: 2022 Deren Vural
+// SPDX-License-Identifier: GPL-3.0-or-later
+
+/* *
+ * Name:
+ * integration_tests_9.rs
+ *
+ * Description:
+ * Integration tests for tuning profiles
+ *
+ * Made:
+ * 25/01/2023
+ *
+ * Made by:
+ * Deren Vural
+ *
+ * Notes:
+ * Commands are captured by a fake runner rather than run
+ */
+
+// Declare module
+extern crate gtk4_nvidia_monitor_rust;
+mod common;
+
+// Imports
+use common::{create_runner, FakeRunner};
+use gtk4_nvidia_monitor_rust::{
+    fan_curve::FanCurveConfig,
+    helper::HELPER_PATH,
+    overclock::{create_offset_command, ClockOffset, OffsetRange},
+    profile::{
+        apply_fan_curves, apply_plan, load_profiles, plan_profile, profile_names, replace_profile,
+        validate_profile_name, Profile, ProfilePlan,
+    },
+};
+
+const UUID: &str = "GPU-1a2b3c4d";
+const PROFILE: &str = "Quiet:GPU-1a2b3c4d:150:0:-500:0;2;5;40/30,80/70|1;0;0;50/50";
+
+const RANGE: OffsetRange = OffsetRange {
+    min: -1000,
+    max: 1000,
+};
+
+/*
+ * Integration tests
+ */
+#[test]
+fn test_profile_round_trip() {
+    let profile: Profile = Profile::from_setting(PROFILE).unwrap();
+
+    assert_eq!(profile.name, "Quiet");
+    assert_eq!(profile.uuid, UUID);
+    assert_eq!(profile.power_limit, Some(150));
+    assert_eq!(profile.graphics_offset, Some(0));
+    assert_eq!(profile.memory_offset, Some(-500));
+    assert_eq!(profile.fan_curves.len(), 2);
+    assert_eq!(profile.fan_curves[0].1.hysteresis, 2);
+    assert_eq!(profile.fan_curves[1].1.points.len(), 1);
+
+    assert_eq!(profile.to_setting(), PROFILE);
+}
+
+#[test]
+fn test_profile_unset_values() {
+    let profile: Profile = Profile::from_setting("Render:GPU-1a2b3c4d::::").unwrap();
+
+    assert_eq!(profile.power_limit, None);
+    assert_eq!(profile.graphics_offset, None);
+    assert!(profile.fan_curves.is_empty());
+    assert_eq!(profile.to_setting(), "Render:GPU-1a2b3c4d::::");
+}
+
+#[test]
+fn test_profile_malformed() {
+    assert!(Profile::from_setting("Quiet:GPU-1a2b3c4d:150").is_err());
+    assert!(Profile::from_setting("Quiet:GPU-1a2b3c4d:abc:::").is_err());
+    assert!(Profile::from_setting(":GPU-1a2b3c4d::::").is_err());
+    assert!(Profile::from_setting("Quiet:GPU-1a2b3c4d:::: 0;2;5").is_err());
+
+    // Malformed entries are skipped when loading
+    let profiles: Vec<Profile> = load_profiles(&[String::from("broken"), String::from(PROFILE)]);
+    assert_eq!(profiles.len(), 1);
+}
+
+#[test]
+fn test_validate_profile_name() {
+    assert!(validate_profile_name("Training").is_ok());
+    assert!(validate_profile_name("").is_err());
+    assert!(validate_profile_name("  ").is_err());
+    assert!(validate_profile_name("a:b").is_err());
+    assert!(validate_profile_name("a|b").is_err());
+}
+
+#[test]
+fn test_profile_names() {
+    let profiles: Vec<Profile> = load_profiles(&[
+        String::from(PROFILE),
+        String::from("Render:GPU-1a2b3c4d::::"),
+        String::from("Quiet:GPU-5e6f::::"),
+    ]);
+
+    assert_eq!(profile_names(&profiles), vec!["Quiet", "Render"]);
+}
+
+#[test]
+fn test_replace_profile() {
+    let profile: Profile = Profile::from_setting("Quiet:GPU-1a2b3c4d:200:::").unwrap();
+    let entries: Vec<String> = replace_profile(
+        vec![String::from(PROFILE), String::from("Quiet:GPU-5e6f::::")],
+        &profile,
+    );
+
+    assert_eq!(
+        entries,
+        vec!["Quiet:GPU-5e6f::::", "Quiet:GPU-1a2b3c4d:200:::"]
+    );
+}
+
+#[test]
+fn test_capture() {
+    let fan_curves: Vec<String> = vec![
+        String::from("GPU-1a2b3c4d:0:true:2:5:40/30,80/70"),
+        String::from("GPU-1a2b3c4d:1:false:0:0:50/50"),
+        String::from("GPU-5e6f:0:true:0:0:50/50"),
+    ];
+    let profile: Profile = Profile::capture("Quiet", UUID, Some(150), None, None, &fan_curves);
+
+    assert_eq!(profile.fan_curves.len(), 1);
+    assert_eq!(profile.fan_curves[0].0, 0);
+    assert_eq!(
+        profile.to_setting(),
+        "Quiet:GPU-1a2b3c4d:150:::0;2;5;40/30,80/70"
+    );
+}
+
+#[test]
+fn test_apply_fan_curves() {
+    let profile: Profile = Profile::from_setting("Quiet:GPU-1a2b3c4d::::0;0;0;50/50").unwrap();
+    let fan_curves: Vec<String> = apply_fan_curves(
+        vec![
+            String::from("GPU-1a2b3c4d:0:false:2:5:40/30"),
+            String::from("GPU-1a2b3c4d:1:true:0:0:60/60"),
+            String::from("GPU-5e6f:0:true:0:0:50/50"),
+        ],
+        &profile,
+    );
+
+    let configs: Vec<FanCurveConfig> = fan_curves
+        .iter()
+        .map(|entry| FanCurveConfig::from_setting(entry).unwrap())
+        .collect();
+    assert_eq!(configs.len(), 3);
+
+    // Other curve of this gpu disabled, other gpus untouched
+    assert_eq!((configs[0].fan, configs[0].enabled), (1, false));
+    assert_eq!(
+        (configs[1].uuid.as_str(), configs[1].enabled),
+        ("GPU-5e6f", true)
+    );
+
+    // Profile curve enabled
+    assert_eq!((configs[2].fan, configs[2].enabled), (0, true));
+    assert_eq!(configs[2].curve.points[0].speed, 50);
+}
+
+#[test]
+fn test_plan_profile() {
+    let profile: Profile = Profile::from_setting(PROFILE).unwrap();
+    let plan: ProfilePlan =
+        plan_profile(&profile, Some(0), Some(RANGE), Some(RANGE), vec![]).unwrap();
+
+    assert_eq!(
+        plan.commands,
+        vec![
+            create_offset_command(0, ClockOffset::Graphics, 0),
+            create_offset_command(0, ClockOffset::Memory, -500),
+        ]
+    );
+    assert_eq!(
+        plan.privileged_commands,
+        vec![vec!["pkexec", HELPER_PATH, "power-limit", UUID, "150"]]
+    );
+    assert_eq!(plan.fan_curves.len(), 2);
+}
+
+#[test]
+fn test_plan_profile_invalid() {
+    let profile: Profile = Profile::from_setting(PROFILE).unwrap();
+
+    // Offsets without support
+    assert!(plan_profile(&profile, None, Some(RANGE), Some(RANGE), vec![]).is_err());
+    assert!(plan_profile(&profile, Some(0), Some(RANGE), None, vec![]).is_err());
+
+    // Out of range offset
+    let small_range: OffsetRange = OffsetRange {
+        min: -100,
+        max: 100,
+    };
+    assert!(plan_profile(&profile, Some(0), Some(RANGE), Some(small_range), vec![]).is_err());
+
+    // Only fan curves, nothing else needed
+    let profile: Profile = Profile::from_setting("Quiet:GPU-1a2b3c4d::::0;0;0;50/50").unwrap();
+    assert!(plan_profile(&profile, None, None, None, vec![]).is_ok());
+}
+
+#[test]
+fn test_apply_plan() {
+    let profile: Profile = Profile::from_setting(PROFILE).unwrap();
+    let plan: ProfilePlan =
+        plan_profile(&profile, Some(0), Some(RANGE), Some(RANGE), vec![]).unwrap();
+
+    let runner: FakeRunner = create_runner(false);
+    let privileged_runner: FakeRunner = create_runner(false);
+    assert!(apply_plan(&plan, &runner, &privileged_runner).is_ok());
+    assert_eq!(runner.commands.borrow().len(), 2);
+    assert_eq!(privileged_runner.commands.borrow().len(), 1);
+
+    // Stops at the first failure
+    let failing_runner: FakeRunner = create_runner(true);
+    let privileged_runner: FakeRunner = create_runner(false);
+    assert!(apply_plan(&plan, &failing_runner, &privileged_runner).is_err());
+    assert_eq!(failing_runner.commands.borrow().len(), 1);
+    assert!(privileged_runner.commands.borrow().is_empty());
+}